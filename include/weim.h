@@ -0,0 +1,50 @@
+/* weim C 바인딩 (`ffi` 기능)
+ *
+ * 라이브러리 만들기 (target/release/에 libweim.so/.dylib/weim.dll과 libweim.a가 생긴다):
+ *     cargo build --release --features ffi
+ */
+#ifndef WEIM_H
+#define WEIM_H
+
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef enum WeimStatus {
+    WEIM_STATUS_OK = 0,
+    WEIM_STATUS_TIMEOUT = 1,
+    WEIM_STATUS_PERMISSION_DENIED = 2,
+    WEIM_STATUS_UNSUPPORTED = 3,
+    WEIM_STATUS_POSITION_UNAVAILABLE = 4,
+    WEIM_STATUS_FAILED = 5
+} WeimStatus;
+
+/* 값이 없는 항목은 NaN. 실패하면 status가 0이 아니고 error에 UTF-8 메시지가 있다. */
+typedef struct WeimLocation {
+    WeimStatus status;
+    double latitude;
+    double longitude;
+    double accuracy;
+    double altitude;
+    double altitude_accuracy;
+    double speed;
+    double heading;
+    int64_t timestamp; /* Unix epoch (ms) */
+    char *error;
+} WeimLocation;
+
+/* 브라우저에서 위치를 받을 때까지 기다린다. 결과는 weim_location_free()로 놓아준다. */
+WeimLocation *weim_locate(void);
+
+/* timeout_ms 안에 받지 못하면 WEIM_STATUS_TIMEOUT. 0이면 weim_locate()와 같다. */
+WeimLocation *weim_locate_with_timeout(uint64_t timeout_ms);
+
+void weim_location_free(WeimLocation *location);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
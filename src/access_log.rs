@@ -0,0 +1,94 @@
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::net::IpAddr;
+use std::path::PathBuf;
+use std::time::Duration;
+use chrono::Local;
+
+use crate::console::{self, warning};
+use crate::i18n::tr;
+
+// 로거에서 접근 기록만 골라 받을 수 있게 따로 둔 `log` 대상
+const TARGET: &str = "weim::access";
+
+/// 내장 서버가 답한 요청 하나
+pub(crate) struct Access<'a> {
+    pub(crate) client: Option<IpAddr>,
+    pub(crate) method: &'a str,
+    pub(crate) path: &'a str,
+    // `HTTP/1.1`
+    pub(crate) version: &'a str,
+    pub(crate) status: u16,
+    pub(crate) bytes: usize,
+    pub(crate) latency: Duration,
+    pub(crate) referer: Option<&'a str>,
+    pub(crate) user_agent: Option<&'a str>,
+}
+
+/// 요청마다 `weim::access` 대상의 `log` 이벤트를 남기고, 파일을 정했으면 combined 형식으로 한 줄씩 덧붙인다.
+///
+/// 이벤트에는 `method`, `path`, `status`, `latency_ms`, `client` 필드가 붙는다. 콘솔에는 `Verbosity::Debug`일 때만 나온다.
+/// 파일은 쓸 때마다 열고 닫으므로 logrotate 같은 도구로 옮겨도 된다.
+pub(crate) struct AccessLog {
+    file: Option<PathBuf>,
+}
+
+impl AccessLog {
+    pub(crate) fn new(file: Option<PathBuf>) -> Self {
+        AccessLog { file }
+    }
+
+    pub(crate) fn record(&self, access: &Access) {
+        let client = access.client.map(|ip| ip.to_string());
+        let client = client.as_deref().unwrap_or("-");
+        let latency_ms = access.latency.as_secs_f64() * 1000.0;
+        let line = format!("{} {} {} {} {:.1}ms", client, access.method, access.path, access.status, latency_ms);
+        log::info!(
+            target: TARGET,
+            method = access.method,
+            path = access.path,
+            status = access.status,
+            latency_ms = latency_ms,
+            client = client;
+            "{}", line
+        );
+        console::print(log::Level::Debug, format_args!("{}", line));
+
+        if let Some(path) = &self.file {
+            let result = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .and_then(|mut file| file.write_all(combined(access, client).as_bytes()));
+            if let Err(e) = result {
+                warning!("{}", tr!("⚠️ 접근 기록을 쓰지 못했습니다 ({}): {}", "⚠️ Could not write the access log ({}): {}", path.display(), e));
+            }
+        }
+    }
+}
+
+// Apache/nginx combined 형식
+// 127.0.0.1 - - [14/Oct/2026:09:30:00 +0900] "GET / HTTP/1.1" 200 5120 "-" "Mozilla/5.0 ..."
+fn combined(access: &Access, client: &str) -> String {
+    let bytes = match access.bytes {
+        0 => "-".to_string(),
+        bytes => bytes.to_string(),
+    };
+    format!(
+        "{} - - [{}] \"{} {} {}\" {} {} \"{}\" \"{}\"\n",
+        client,
+        Local::now().format("%d/%b/%Y:%H:%M:%S %z"),
+        access.method,
+        quoted(access.path),
+        access.version,
+        access.status,
+        bytes,
+        quoted(access.referer.unwrap_or("-")),
+        quoted(access.user_agent.unwrap_or("-")),
+    )
+}
+
+// 따옴표 안에서 줄이 끊기거나 필드가 어긋나지 않게 한다
+fn quoted(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace(['\r', '\n'], " ")
+}
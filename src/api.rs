@@ -0,0 +1,204 @@
+use std::collections::{BTreeMap, VecDeque};
+#[cfg(feature = "history")]
+use std::path::PathBuf;
+use std::sync::Mutex;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::events::FixEvent;
+use crate::handler::Reply;
+use crate::i18n::{pick, tr};
+use crate::locator::{Config, Mode};
+use crate::query::param;
+#[cfg(feature = "history")]
+use crate::history::{self, HistoryQuery};
+#[cfg(feature = "encryption")]
+use crate::HistoryKey;
+use crate::{DeviceId, Location};
+
+// 기록 파일이 없을 때 `/api/history`가 기억하는 이번 실행의 위치 수
+const SESSION_CAPACITY: usize = 10_000;
+
+/// `/api/devices`의 기기 하나
+#[derive(Serialize)]
+pub(crate) struct DeviceSummary<'a> {
+    pub(crate) device: &'a DeviceId,
+    pub(crate) fixes: usize,
+    pub(crate) location: &'a Location,
+}
+
+#[derive(Default)]
+struct Seen {
+    latest: Option<(DeviceId, Location)>,
+    devices: BTreeMap<DeviceId, (usize, Location)>,
+    session: VecDeque<(DeviceId, Location)>,
+}
+
+/// 실행 중에 다른 프로세스가 읽어 가는 `/api/...` JSON 엔드포인트
+///
+/// - `GET /api/latest[?device=]`: 마지막 위치
+/// - `GET /api/history[?from=&to=&device=&limit=]`: 기간 안의 위치. 시각은 Unix epoch(ms)나 RFC 3339
+/// - `GET /api/devices`: 기기별 위치 수와 마지막 위치
+///
+/// `history()`로 기록 파일을 정했으면 기록은 그 파일에서 읽고, 아니면 이번 실행에서 받은 것만 돌려준다.
+pub(crate) struct Api {
+    enabled: bool,
+    #[cfg(feature = "history")]
+    history: Option<PathBuf>,
+    #[cfg(feature = "encryption")]
+    history_key: Option<HistoryKey>,
+    seen: Mutex<Seen>,
+}
+
+impl Api {
+    pub(crate) fn new(config: &Config, mode: Mode) -> Self {
+        Api {
+            enabled: config.api_enabled(mode),
+            #[cfg(feature = "history")]
+            history: config.history.clone(),
+            #[cfg(feature = "encryption")]
+            history_key: config.history_key.clone(),
+            seen: Mutex::default(),
+        }
+    }
+
+    pub(crate) fn record(&self, device: &DeviceId, location: &Location) {
+        let mut seen = self.seen.lock().unwrap();
+        seen.latest = Some((device.clone(), location.clone()));
+        let entry = seen.devices.entry(device.clone()).or_insert_with(|| (0, location.clone()));
+        entry.0 += 1;
+        entry.1 = location.clone();
+        if !self.persistent() {
+            if seen.session.len() >= SESSION_CAPACITY {
+                seen.session.pop_front();
+            }
+            seen.session.push_back((device.clone(), location.clone()));
+        }
+    }
+
+    /// 경로가 API가 아니거나 API를 껐으면 None
+    pub(crate) fn handle(&self, path: &str, query: &str) -> Option<Reply> {
+        if !self.enabled {
+            return None;
+        }
+        let reply = match path {
+            "/api/latest" => self.latest(query),
+            "/api/history" => self.history(query),
+            "/api/devices" => self.devices(),
+            _ => return None,
+        };
+        Some(reply)
+    }
+
+    fn latest(&self, query: &str) -> Reply {
+        let seen = self.seen.lock().unwrap();
+        let latest = match param(query, "device") {
+            Some(name) => {
+                let device = DeviceId(name);
+                seen.devices.get(&device).map(|(_, location)| (device.clone(), location.clone()))
+            }
+            None => seen.latest.clone(),
+        };
+        match latest {
+            Some((device, location)) => json(200, &FixEvent { device: &device, location: &location }),
+            None => error(404, pick("아직 받은 위치가 없습니다", "no location received yet")),
+        }
+    }
+
+    fn history(&self, query: &str) -> Reply {
+        let mut range = [None, None];
+        for (slot, name) in range.iter_mut().zip(["from", "to"]) {
+            if let Some(value) = param(query, name) {
+                match parse_time(&value) {
+                    Some(timestamp) => *slot = Some(timestamp),
+                    None => return error(400, &tr!("{} 시각을 읽을 수 없습니다: {}", "cannot read the {} time: {}", name, value)),
+                }
+            }
+        }
+        let [from, to] = range;
+        let device = param(query, "device").map(DeviceId);
+        let limit = match param(query, "limit").map(|value| value.parse::<u32>()) {
+            Some(Ok(limit)) => Some(limit),
+            Some(Err(_)) => return error(400, pick("limit은 0 이상의 정수여야 합니다", "limit must be a non-negative integer")),
+            None => None,
+        };
+
+        #[cfg(feature = "history")]
+        if let Some(path) = &self.history {
+            let mut filter = HistoryQuery::new();
+            if let Some(from) = from {
+                filter = filter.since(from);
+            }
+            if let Some(to) = to {
+                filter = filter.until(to);
+            }
+            if let Some(device) = device {
+                filter = filter.device(device);
+            }
+            if let Some(limit) = limit {
+                filter = filter.limit(limit);
+            }
+            return match history::open(path, #[cfg(feature = "encryption")] self.history_key.as_ref()).and_then(|history| history.query(&filter)) {
+                Ok(entries) => {
+                    let fixes: Vec<_> = entries
+                        .iter()
+                        .map(|entry| FixEvent { device: &entry.device, location: &entry.location })
+                        .collect();
+                    json(200, &fixes)
+                }
+                Err(e) => error(500, &e.to_string()),
+            };
+        }
+
+        let seen = self.seen.lock().unwrap();
+        let fixes: Vec<_> = seen
+            .session
+            .iter()
+            .filter(|(_, location)| from.is_none_or(|from| location.timestamp >= from))
+            .filter(|(_, location)| to.is_none_or(|to| location.timestamp < to))
+            .filter(|(name, _)| device.as_ref().is_none_or(|device| name == device))
+            .take(limit.map_or(usize::MAX, |limit| limit as usize))
+            .map(|(device, location)| FixEvent { device, location })
+            .collect();
+        json(200, &fixes)
+    }
+
+    fn devices(&self) -> Reply {
+        let seen = self.seen.lock().unwrap();
+        let devices: Vec<_> = seen
+            .devices
+            .iter()
+            .map(|(device, (fixes, location))| DeviceSummary { device, fixes: *fixes, location })
+            .collect();
+        json(200, &devices)
+    }
+
+    // 기록 파일에서 읽으면 이번 실행의 위치를 따로 모아 둘 필요가 없다
+    fn persistent(&self) -> bool {
+        #[cfg(feature = "history")]
+        if self.history.is_some() {
+            return true;
+        }
+        false
+    }
+}
+
+fn json(status: u16, value: &impl Serialize) -> Reply {
+    match serde_json::to_string(value) {
+        Ok(body) => Reply::new(status, body).header("Content-Type", "application/json"),
+        Err(e) => error(500, &e.to_string()),
+    }
+}
+
+fn error(status: u16, message: &str) -> Reply {
+    let body = serde_json::json!({ "error": message }).to_string();
+    Reply::new(status, body).header("Content-Type", "application/json")
+}
+
+// Unix epoch(ms) 정수나 RFC 3339 시각
+fn parse_time(value: &str) -> Option<DateTime<Utc>> {
+    match value.parse() {
+        Ok(millis) => DateTime::from_timestamp_millis(millis),
+        Err(_) => DateTime::parse_from_rfc3339(value).ok().map(|time| time.to_utc()),
+    }
+}
@@ -0,0 +1,276 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+use tokio::time::{self, Instant};
+
+use crate::access_log::Access;
+use crate::console::{self, warning};
+use crate::enrich::Enricher;
+use crate::events;
+use crate::handler::{self, Handler, Incoming, Outcome, Reply};
+use crate::i18n::{self, tr};
+use crate::locator::{print_banner, CloseStrategy, Mode, POLL_INTERVAL};
+use crate::provider::{LocationProvider, MockProvider};
+use crate::recorder::Recorder;
+use crate::{CancellationToken, DeviceId, Location, Weim, WeimError};
+
+const MAX_HEADER_SIZE: usize = 16 * 1024;
+const MAX_BODY_SIZE: usize = 1024 * 1024;
+
+impl Weim {
+    /// `locate()`의 비동기 버전. tokio 런타임 안에서 실행해야 한다.
+    pub async fn locate_async(&self) -> Result<Location, WeimError> {
+        self.locate_async_cancellable(&CancellationToken::new()).await
+    }
+
+    /// `locate_async()`와 같지만 `token`이 취소되면 `WeimError::Cancelled`로 끝난다.
+    pub async fn locate_async_cancellable(&self, token: &CancellationToken) -> Result<Location, WeimError> {
+        // WEIM_MOCK이 설정되어 있으면 브라우저 없이 바로 돌려준다
+        if let Some(mock) = MockProvider::from_env() {
+            return mock.and_then(|mut mock| mock.locate());
+        }
+
+        // HTTPS는 tiny_http 서버를 블로킹 스레드에서 돌려서 처리한다
+        #[cfg(feature = "tls")]
+        if self.config.tls.is_some() {
+            let weim = self.clone();
+            let token = token.clone();
+            return tokio::task::spawn_blocking(move || weim.locate_cancellable(&token))
+                .await
+                .map_err(|_| WeimError::ServerClosed)?;
+        }
+
+        console::set(self.config.output, self.config.verbosity);
+        i18n::set(self.config.language);
+        print_banner();
+
+        let config = &self.config;
+        let listener = self.bind_async().await?;
+        let addr = listener.local_addr().map_err(|_| WeimError::ServerClosed)?;
+
+        let _browser = self.launch(addr)?;
+
+        let handler = Arc::new(Handler::new(config, Mode::Single));
+        let opened = std::time::Instant::now();
+        // 연결 태스크가 핸들러를 붙잡고 있으므로 끝날 때 스트림을 직접 닫는다
+        let _close = CloseEvents(Arc::clone(&handler));
+        let recorder = Recorder::open(config)?;
+        let enricher = Enricher::new(config);
+        #[cfg(feature = "notifications")]
+        let mut notifier = crate::notify::Notifier::new(config.notifications.as_ref());
+        let (tx, mut rx) = mpsc::unbounded_channel();
+
+        let deadline = config.timeout.map(|timeout| Instant::now() + timeout);
+        let expired = async move {
+            match deadline {
+                Some(deadline) => time::sleep_until(deadline).await,
+                None => std::future::pending().await,
+            }
+        };
+        tokio::pin!(expired);
+
+        let mut poll = time::interval(POLL_INTERVAL);
+
+        loop {
+            tokio::select! {
+                accepted = listener.accept() => {
+                    // 연결마다 태스크를 띄워서 느린 연결이 다른 요청을 막지 않게 한다
+                    if let Ok((stream, client)) = accepted {
+                        let handler = Arc::clone(&handler);
+                        let tx = tx.clone();
+                        tokio::spawn(async move {
+                            if let Some(result) = serve_connection(stream, client, &handler).await {
+                                tx.send(result).ok();
+                            }
+                        });
+                    }
+                }
+                Some(result) = rx.recv() => {
+                    let (device, mut location) = result?;
+                    // 주소, 고도, 날씨 조회는 블로킹 요청이라 별도 스레드에서 한다
+                    if !enricher.is_empty() {
+                        let enricher = enricher.clone();
+                        location = tokio::task::spawn_blocking(move || {
+                            enricher.apply(&mut location);
+                            location
+                        })
+                        .await
+                        .map_err(|_| WeimError::ServerClosed)?;
+                    }
+                    recorder.record(&device, &location);
+                    handler.publish(&device, &location);
+                    #[cfg(feature = "notifications")]
+                    if let Some(notifier) = notifier.as_mut() {
+                        notifier.on_fix(&location);
+                    }
+                    if config.close_strategy == CloseStrategy::KillProcess {
+                        handler::kill_browser();
+                    }
+                    return Ok(location);
+                }
+                () = &mut expired => return Err(WeimError::Timeout),
+                _ = poll.tick() => {
+                    if token.is_cancelled() {
+                        return Err(WeimError::Cancelled);
+                    }
+                    if let Some(e) = handler.abandoned(opened) {
+                        return Err(e);
+                    }
+                }
+            }
+        }
+    }
+
+    async fn bind_async(&self) -> Result<TcpListener, WeimError> {
+        let addr = SocketAddr::new(self.config.bind_address, self.config.port);
+        let bind_error = |e: std::io::Error| WeimError::Bind {
+            addr: addr.to_string(),
+            source: Box::new(e),
+        };
+        match TcpListener::bind(addr).await {
+            Ok(listener) => Ok(listener),
+            Err(_) if self.config.port_fallback && self.config.port != 0 => {
+                warning!("{}", tr!("⚠️ {} 포트를 사용할 수 없어 빈 포트로 엽니다.", "⚠️ Port {} is unavailable, opening a free port instead.", self.config.port));
+                TcpListener::bind(SocketAddr::new(self.config.bind_address, 0))
+                    .await
+                    .map_err(bind_error)
+            }
+            Err(e) => Err(bind_error(e)),
+        }
+    }
+}
+
+struct CloseEvents(Arc<Handler>);
+
+impl Drop for CloseEvents {
+    fn drop(&mut self) {
+        self.0.events().close();
+    }
+}
+
+// 요청 하나를 읽고 응답한 뒤 연결을 닫는다
+async fn serve_connection(mut stream: TcpStream, client: SocketAddr, handler: &Handler) -> Option<Result<(DeviceId, Location), WeimError>> {
+    let started = std::time::Instant::now();
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+
+    let header_end = loop {
+        let n = stream.read(&mut chunk).await.ok()?;
+        if n == 0 {
+            return None;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+        if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+        if buf.len() > MAX_HEADER_SIZE {
+            return None;
+        }
+    };
+
+    let head = std::str::from_utf8(&buf[..header_end]).ok()?;
+    let mut lines = head.lines();
+    let mut request_line = lines.next()?.split_whitespace();
+    let method = request_line.next()?;
+    let path = request_line.next()?;
+    let version = request_line.next().unwrap_or("HTTP/1.0");
+
+    let headers: Vec<(&str, &str)> = lines.filter_map(|line| line.split_once(':')).map(|(name, value)| (name.trim(), value.trim())).collect();
+    let header = |name: &str| headers.iter().find(|(key, _)| key.eq_ignore_ascii_case(name)).map(|(_, value)| *value);
+    let content_length = header("Content-Length")
+        .and_then(|value| value.parse::<usize>().ok())
+        .unwrap_or(0)
+        .min(MAX_BODY_SIZE);
+
+    let mut body = buf[header_end..].to_vec();
+    while body.len() < content_length {
+        let n = stream.read(&mut chunk).await.ok()?;
+        if n == 0 {
+            break;
+        }
+        body.extend_from_slice(&chunk[..n]);
+    }
+    body.truncate(content_length);
+
+    let (reply, outcome) = handler.handle(&Incoming {
+        client: Some(client.ip()),
+        origin: header("Origin"),
+        method,
+        path,
+        body: &String::from_utf8_lossy(&body),
+    });
+    handler.access_log().record(&Access {
+        client: Some(client.ip()),
+        method,
+        path,
+        version,
+        status: reply.status,
+        bytes: reply.body.len(),
+        latency: started.elapsed(),
+        referer: header("Referer"),
+        user_agent: header("User-Agent"),
+    });
+    if let Outcome::Subscribe = outcome {
+        let (tx, rx) = mpsc::unbounded_channel::<String>();
+        handler.events().subscribe(move |frame| tx.send(frame.to_string()).is_ok());
+        stream_events(stream, &reply, rx).await;
+        return None;
+    }
+    write_reply(&mut stream, reply).await;
+
+    match outcome {
+        Outcome::Fix(device, location) => Some(Ok((device, location))),
+        Outcome::Failed(e) => Some(Err(e)),
+        Outcome::Continue | Outcome::Subscribe => None,
+        // 웹소켓은 tiny_http 서버에서만 받는다
+        #[cfg(feature = "websocket")]
+        Outcome::Upgrade => None,
+    }
+}
+
+// 구독이 끝나거나 연결이 끊길 때까지 `/events` 스트림을 쓴다
+async fn stream_events(mut stream: TcpStream, reply: &Reply, mut rx: mpsc::UnboundedReceiver<String>) {
+    if stream.write_all(events::stream_head(reply).as_bytes()).await.is_err() {
+        return;
+    }
+    loop {
+        let frame = match time::timeout(events::KEEPALIVE_INTERVAL, rx.recv()).await {
+            Ok(Some(frame)) => frame,
+            Ok(None) => return,
+            Err(_) => events::KEEPALIVE.to_string(),
+        };
+        if stream.write_all(frame.as_bytes()).await.is_err() {
+            return;
+        }
+    }
+}
+
+async fn write_reply(stream: &mut TcpStream, reply: Reply) {
+    let mut head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Length: {}\r\nConnection: close\r\n",
+        reply.status,
+        status_text(reply.status),
+        reply.body.len()
+    );
+    for (name, value) in reply.headers {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    head.push_str("\r\n");
+
+    stream.write_all(head.as_bytes()).await.ok();
+    stream.write_all(reply.body.as_bytes()).await.ok();
+    stream.shutdown().await.ok();
+}
+
+fn status_text(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        403 => "Forbidden",
+        404 => "Not Found",
+        429 => "Too Many Requests",
+        _ => "",
+    }
+}
@@ -0,0 +1,335 @@
+use std::convert::Infallible;
+use std::net::{SocketAddr, TcpListener as StdListener};
+#[cfg(feature = "websocket")]
+use std::net::IpAddr;
+use std::sync::Arc;
+#[cfg(feature = "websocket")]
+use std::sync::Weak;
+use std::time::Instant;
+#[cfg(feature = "websocket")]
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+#[cfg(feature = "websocket")]
+use axum::extract::FromRequestParts;
+use axum::body::Body;
+use axum::extract::{Request, State};
+use axum::http::{HeaderValue, StatusCode};
+use axum::response::Response;
+use axum::{Extension, Router};
+use hyper_util::rt::{TokioExecutor, TokioIo};
+use hyper_util::server::conn::auto::Builder;
+use hyper_util::service::TowerToHyperService;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc::{self, UnboundedSender};
+use tokio::time;
+#[cfg(feature = "tls")]
+use tokio_rustls::rustls::pki_types::pem::PemObject;
+#[cfg(feature = "tls")]
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer};
+#[cfg(feature = "tls")]
+use tokio_rustls::rustls::ServerConfig;
+#[cfg(feature = "tls")]
+use tokio_rustls::TlsAcceptor;
+
+use crate::access_log::Access;
+use crate::console::{self, warning};
+use crate::enrich::Enricher;
+use crate::events;
+use crate::handler::{Handler, Incoming, Outcome, Reply};
+use crate::i18n::{self, tr};
+use crate::locator::{print_banner, Browser, Mode, POLL_INTERVAL};
+#[cfg(feature = "tls")]
+use crate::locator::lan_ip;
+use crate::recorder::Recorder;
+#[cfg(feature = "websocket")]
+use crate::ws;
+use crate::{CancellationToken, DeviceId, Location, Weim, WeimError};
+
+const MAX_BODY_SIZE: usize = 1024 * 1024;
+
+// 연결 태스크들이 함께 쓰는 것
+#[derive(Clone)]
+struct Shared {
+    handler: Arc<Handler>,
+    // 위치와 실패는 서버 루프가 처리한다
+    outcomes: UnboundedSender<Outcome>,
+}
+
+impl Weim {
+    /// `open()`의 axum 버전. 배너를 출력하고 주소를 연 뒤 브라우저를 연다.
+    pub(crate) fn open_axum(&self) -> Result<(StdListener, Browser), WeimError> {
+        console::set(self.config.output, self.config.verbosity);
+        i18n::set(self.config.language);
+        print_banner();
+
+        let listener = self.bind_std()?;
+        let addr = listener.local_addr()?;
+        let browser = self.launch(addr)?;
+        Ok((listener, browser))
+    }
+
+    /// `run()`의 axum 버전. 자체 tokio 런타임에서 `Mode::Watch`로 돌고, 호출한 스레드를 막는다.
+    ///
+    /// 연결을 재사용하고, 평문에서는 HTTP/2 prior knowledge(h2c), HTTPS에서는 ALPN으로 HTTP/2를 받는다.
+    pub(crate) fn run_axum(
+        &self,
+        listener: StdListener,
+        token: &CancellationToken,
+        on_fix: impl FnMut(DeviceId, Location) -> bool,
+    ) -> Result<(), WeimError> {
+        let runtime = tokio::runtime::Builder::new_multi_thread().enable_all().build()?;
+        runtime.block_on(self.serve_axum(listener, token, on_fix))
+    }
+
+    async fn serve_axum(
+        &self,
+        listener: StdListener,
+        token: &CancellationToken,
+        mut on_fix: impl FnMut(DeviceId, Location) -> bool,
+    ) -> Result<(), WeimError> {
+        let config = &self.config;
+        let listener = TcpListener::from_std(listener)?;
+        #[cfg(feature = "tls")]
+        let acceptor = match &config.tls {
+            Some(tls) => {
+                let ip = listener.local_addr()?.ip();
+                let host = if ip.is_unspecified() { lan_ip() } else { Some(ip) };
+                let ssl = tls.ssl_config(host)?;
+                Some(acceptor(&ssl.certificate, &ssl.private_key)?)
+            }
+            None => None,
+        };
+
+        let handler = Arc::new(Handler::new(config, Mode::Watch));
+        let recorder = Recorder::open(config)?;
+        let enricher = Enricher::new(config);
+        #[cfg(feature = "notifications")]
+        let mut notifier = crate::notify::Notifier::new(config.notifications.as_ref());
+        let (outcomes, mut finished) = mpsc::unbounded_channel();
+        let app = Router::new()
+            .fallback(respond)
+            .with_state(Shared { handler: Arc::clone(&handler), outcomes });
+        let opened = Instant::now();
+        // 첫 위치를 받은 뒤에는 브라우저가 떠났는지 따지지 않는다
+        let mut received = false;
+        let mut poll = time::interval(POLL_INTERVAL);
+
+        loop {
+            tokio::select! {
+                accepted = listener.accept() => {
+                    if let Ok((stream, client)) = accepted {
+                        tokio::spawn(connection(
+                            stream,
+                            client,
+                            app.clone(),
+                            #[cfg(feature = "tls")]
+                            acceptor.clone(),
+                        ));
+                    }
+                }
+                Some(outcome) = finished.recv() => match outcome {
+                    // 한 기기의 오류로 세션을 끝내지 않는다
+                    Outcome::Failed(e) => warning!("{}", tr!("⚠️ 요청을 처리하지 못했습니다: {}", "⚠️ Could not handle a request: {}", e)),
+                    Outcome::Fix(device, mut location) => {
+                        received = true;
+                        // 주소, 고도, 날씨 조회는 블로킹 요청이라 별도 스레드에서 한다
+                        if !enricher.is_empty() {
+                            let enricher = enricher.clone();
+                            location = tokio::task::spawn_blocking(move || {
+                                enricher.apply(&mut location);
+                                location
+                            })
+                            .await
+                            .map_err(|_| WeimError::ServerClosed)?;
+                        }
+                        recorder.record(&device, &location);
+                        handler.publish(&device, &location);
+                        #[cfg(feature = "notifications")]
+                        if let Some(notifier) = notifier.as_mut() {
+                            notifier.on_fix(&location);
+                        }
+                        if !on_fix(device, location) {
+                            return Ok(());
+                        }
+                    }
+                    _ => {}
+                },
+                _ = poll.tick() => {
+                    if token.is_cancelled() {
+                        return Err(WeimError::Cancelled);
+                    }
+                    if !received && let Some(e) = handler.abandoned(opened) {
+                        return Err(e);
+                    }
+                }
+            }
+        }
+    }
+
+    // 설정한 포트로 열고, 실패하면 허용된 경우 빈 포트로 다시 시도한다
+    fn bind_std(&self) -> Result<StdListener, WeimError> {
+        let addr = SocketAddr::new(self.config.bind_address, self.config.port);
+        let bind_error = |e: std::io::Error| WeimError::Bind {
+            addr: addr.to_string(),
+            source: Box::new(e),
+        };
+        let listener = match StdListener::bind(addr) {
+            Ok(listener) => listener,
+            Err(_) if self.config.port_fallback && self.config.port != 0 => {
+                warning!("{}", tr!("⚠️ {} 포트를 사용할 수 없어 빈 포트로 엽니다.", "⚠️ Port {} is unavailable, opening a free port instead.", self.config.port));
+                StdListener::bind(SocketAddr::new(self.config.bind_address, 0)).map_err(bind_error)?
+            }
+            Err(e) => return Err(bind_error(e)),
+        };
+        // tokio로 넘기려면 기다리지 않고 받아야 한다
+        listener.set_nonblocking(true)?;
+        Ok(listener)
+    }
+}
+
+// 연결 하나를 끝날 때까지 처리한다. 요청마다 `respond`가 불린다
+async fn connection(
+    stream: TcpStream,
+    client: SocketAddr,
+    app: Router,
+    #[cfg(feature = "tls")] acceptor: Option<TlsAcceptor>,
+) {
+    let service = TowerToHyperService::new(app.layer(Extension(client)));
+    let builder = Builder::new(TokioExecutor::new());
+    #[cfg(feature = "tls")]
+    if let Some(acceptor) = acceptor {
+        if let Ok(stream) = acceptor.accept(stream).await {
+            builder.serve_connection_with_upgrades(TokioIo::new(stream), service).await.ok();
+        }
+        return;
+    }
+    builder.serve_connection_with_upgrades(TokioIo::new(stream), service).await.ok();
+}
+
+// 모든 경로를 `Handler`에 넘기고 답을 axum 응답으로 바꾼다
+async fn respond(State(shared): State<Shared>, Extension(client): Extension<SocketAddr>, request: Request) -> Response {
+    let started = Instant::now();
+    #[cfg_attr(not(feature = "websocket"), allow(unused_mut))]
+    let (mut parts, body) = request.into_parts();
+    let Ok(body) = axum::body::to_bytes(body, MAX_BODY_SIZE).await else {
+        return into_response(Reply::new(413, "Payload Too Large"));
+    };
+    // 업그레이드할 연결은 `Handler`가 허락하기 전에 떼어 둔다
+    #[cfg(feature = "websocket")]
+    let upgrade = WebSocketUpgrade::from_request_parts(&mut parts, &()).await.ok();
+    let path = parts.uri.path_and_query().map_or("/", |path| path.as_str());
+    let header = |name: &str| parts.headers.get(name).and_then(|value| value.to_str().ok());
+
+    let handler = &shared.handler;
+    let (reply, outcome) = handler.handle(&Incoming {
+        client: Some(client.ip()),
+        origin: header("Origin"),
+        method: parts.method.as_str(),
+        path,
+        body: &String::from_utf8_lossy(&body),
+    });
+
+    #[cfg(feature = "websocket")]
+    let upgrade = upgrade.filter(|_| matches!(outcome, Outcome::Upgrade));
+    #[cfg(feature = "websocket")]
+    let status = if upgrade.is_some() { 101 } else { reply.status };
+    #[cfg(not(feature = "websocket"))]
+    let status = reply.status;
+    handler.access_log().record(&Access {
+        client: Some(client.ip()),
+        method: parts.method.as_str(),
+        path,
+        version: &format!("{:?}", parts.version),
+        status,
+        bytes: reply.body.len(),
+        latency: started.elapsed(),
+        referer: header("Referer"),
+        user_agent: header("User-Agent"),
+    });
+
+    #[cfg(feature = "websocket")]
+    if let Some(upgrade) = upgrade {
+        let (handler, outcomes) = (Arc::downgrade(handler), shared.outcomes.clone());
+        return upgrade.on_upgrade(move |socket| serve_socket(socket, client.ip(), handler, outcomes));
+    }
+    match outcome {
+        Outcome::Subscribe => {
+            let (tx, rx) = mpsc::unbounded_channel::<String>();
+            handler.events().subscribe(move |frame| tx.send(frame.to_string()).is_ok());
+            let mut response = into_response(reply);
+            *response.body_mut() = Body::from_stream(frames(rx));
+            response
+        }
+        Outcome::Fix(..) | Outcome::Failed(_) => {
+            shared.outcomes.send(outcome).ok();
+            into_response(reply)
+        }
+        _ => into_response(reply),
+    }
+}
+
+fn into_response(reply: Reply) -> Response {
+    let mut response = Response::new(Body::from(reply.body));
+    *response.status_mut() = StatusCode::from_u16(reply.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+    for (name, value) in reply.headers {
+        if let Ok(value) = HeaderValue::try_from(value) {
+            response.headers_mut().append(name, value);
+        }
+    }
+    response
+}
+
+// 구독이 끝나거나 연결이 끊길 때까지 이어지는 `/events` 본문
+fn frames(rx: mpsc::UnboundedReceiver<String>) -> impl futures_util::Stream<Item = Result<String, Infallible>> {
+    futures_util::stream::unfold(rx, |mut rx| async move {
+        let frame = match time::timeout(events::KEEPALIVE_INTERVAL, rx.recv()).await {
+            Ok(Some(frame)) => frame,
+            Ok(None) => return None,
+            Err(_) => events::KEEPALIVE.to_string(),
+        };
+        Some((Ok(frame), rx))
+    })
+}
+
+// `ws::serve()`의 비동기 버전
+#[cfg(feature = "websocket")]
+async fn serve_socket(mut socket: WebSocket, client: IpAddr, handler: Weak<Handler>, outcomes: UnboundedSender<Outcome>) {
+    while let Some(Ok(message)) = socket.recv().await {
+        let Some(handler) = handler.upgrade() else {
+            break;
+        };
+        let Message::Text(text) = message else {
+            continue;
+        };
+        if text.as_str() == ws::PING {
+            if socket.send(Message::text(ws::PONG)).await.is_err() {
+                return;
+            }
+            continue;
+        }
+
+        let (reply, outcome) = handler.handle(&Incoming { client: Some(client), origin: None, method: "POST", path: "/update", body: text.as_str() });
+        if socket.send(Message::text(reply.body)).await.is_err() {
+            return;
+        }
+        if !matches!(outcome, Outcome::Continue) && outcomes.send(outcome).is_err() {
+            break;
+        }
+    }
+    socket.send(Message::Close(None)).await.ok();
+}
+
+// PEM 인증서와 키로 h2와 http/1.1을 알리는 TLS 받개를 만든다
+#[cfg(feature = "tls")]
+fn acceptor(certificate: &[u8], private_key: &[u8]) -> Result<TlsAcceptor, WeimError> {
+    let tls_error = |e: &dyn std::fmt::Display| WeimError::Tls(e.to_string());
+    let certificates = CertificateDer::pem_slice_iter(certificate)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| tls_error(&e))?;
+    let key = PrivateKeyDer::from_pem_slice(private_key).map_err(|e| tls_error(&e))?;
+    let mut config = ServerConfig::builder()
+        .with_no_client_auth()
+        .with_single_cert(certificates, key)
+        .map_err(|e| tls_error(&e))?;
+    config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+    Ok(TlsAcceptor::from(Arc::new(config)))
+}
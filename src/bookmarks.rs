@@ -0,0 +1,147 @@
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+
+use crate::geo::{haversine, Coordinate};
+use crate::i18n::tr;
+use crate::WeimError;
+
+// `add_place()`로 넣은 곳의 반경 (m)
+const DEFAULT_RADIUS: f64 = 100.0;
+
+/// 이름을 붙여 둔 곳
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Bookmark {
+    pub name: String,
+    pub latitude: f64,
+    pub longitude: f64,
+    /// 이 반경(m) 안이면 이곳에 있는 것으로 본다.
+    pub radius: f64,
+}
+
+impl Bookmark {
+    pub fn new(name: impl Into<String>, at: impl Coordinate, radius: f64) -> Self {
+        Bookmark { name: name.into(), latitude: at.latitude(), longitude: at.longitude(), radius }
+    }
+
+    /// 이곳의 가운데까지의 거리 (m)
+    pub fn distance(&self, from: impl Coordinate) -> f64 {
+        haversine((self.latitude, self.longitude), from)
+    }
+
+    pub fn contains(&self, point: impl Coordinate) -> bool {
+        self.distance(point) <= self.radius
+    }
+}
+
+impl Coordinate for Bookmark {
+    fn latitude(&self) -> f64 {
+        self.latitude
+    }
+
+    fn longitude(&self) -> f64 {
+        self.longitude
+    }
+}
+
+/// 집, 회사처럼 이름을 붙여 둔 곳들
+///
+/// `open()`으로 연 목록은 바꿀 때마다 그 JSON 파일에 저장한다. `new()`로 만든 목록은 메모리에만 둔다.
+#[derive(Debug, Clone, Default)]
+pub struct Bookmarks {
+    path: Option<PathBuf>,
+    places: Vec<Bookmark>,
+}
+
+impl Bookmarks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// JSON 파일에서 읽는다. 파일이 없으면 빈 목록으로 시작하고 처음 바꿀 때 만든다.
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self, WeimError> {
+        let path = path.into();
+        let places = match fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json)
+                .map_err(|e| WeimError::Config(tr!("{}을(를) 읽을 수 없습니다: {}", "cannot read {}: {}", path.display(), e)))?,
+            Err(e) if e.kind() == ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Bookmarks { path: Some(path), places })
+    }
+
+    /// 저장하는 파일. `new()`로 만들었으면 None
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// `at`을 반경 100m의 `name`으로 저장한다. 같은 이름이 있으면 바꾼다.
+    pub fn add_place(&mut self, name: impl Into<String>, at: impl Coordinate) -> Result<(), WeimError> {
+        self.add(Bookmark::new(name, at, DEFAULT_RADIUS))
+    }
+
+    /// 반경까지 정한 곳을 저장한다. 같은 이름이 있으면 바꾼다.
+    pub fn add(&mut self, place: Bookmark) -> Result<(), WeimError> {
+        match self.places.iter_mut().find(|existing| existing.name == place.name) {
+            Some(existing) => *existing = place,
+            None => self.places.push(place),
+        }
+        self.save()
+    }
+
+    /// 지웠으면 true
+    pub fn remove(&mut self, name: &str) -> Result<bool, WeimError> {
+        let before = self.places.len();
+        self.places.retain(|place| place.name != name);
+        if self.places.len() == before {
+            return Ok(false);
+        }
+        self.save()?;
+        Ok(true)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Bookmark> {
+        self.places.iter().find(|place| place.name == name)
+    }
+
+    /// 저장한 순서대로
+    pub fn places(&self) -> &[Bookmark] {
+        &self.places
+    }
+
+    /// 가운데가 가장 가까운 곳과 그곳까지의 거리 (m). 목록이 비었으면 None
+    pub fn nearest(&self, point: impl Coordinate) -> Option<(&Bookmark, f64)> {
+        self.places
+            .iter()
+            .map(|place| (place, place.distance(&point)))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+    }
+
+    /// `point`가 반경 안에 드는 곳. 여럿이면 가운데가 가장 가까운 곳이다.
+    pub fn place_at(&self, point: impl Coordinate) -> Option<&Bookmark> {
+        self.places
+            .iter()
+            .map(|place| (place, place.distance(&point)))
+            .filter(|(place, distance)| *distance <= place.radius)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(place, _)| place)
+    }
+
+    /// `point`가 `name`의 반경 안인지. 그런 이름이 없으면 false
+    pub fn is_at(&self, name: &str, point: impl Coordinate) -> bool {
+        self.get(name).is_some_and(|place| place.contains(point))
+    }
+
+    // 읽는 쪽이 반쯤 쓴 파일을 보지 않도록 임시 파일에 쓴 뒤 바꿔 넣는다
+    fn save(&self) -> Result<(), WeimError> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let json = serde_json::to_string_pretty(&self.places).map_err(std::io::Error::from)?;
+        let temporary = path.with_extension("tmp");
+        fs::write(&temporary, json)?;
+        fs::rename(&temporary, path)?;
+        Ok(())
+    }
+}
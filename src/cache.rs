@@ -0,0 +1,60 @@
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
+use chrono::Utc;
+
+use crate::console::warning;
+use crate::i18n::tr;
+use crate::{Location, Weim};
+
+// 프로세스 전체에서 마지막으로 받은 위치. 어느 `Weim`, `Locator`, 데몬이 받았든 같이 쓴다.
+static LAST_KNOWN: Mutex<Option<Location>> = Mutex::new(None);
+
+impl Weim {
+    /// 받은 지 `max_age`가 지나지 않은 마지막 위치. 없으면 None
+    ///
+    /// 이 프로세스에서 받은 위치를 먼저 보고, 없거나 오래됐으면 `cache_file()`에 저장된 위치를 읽는다.
+    /// 나이는 위치의 `timestamp`로 잰다. 브라우저를 다시 열지 않아도 되는지 확인할 때 쓴다.
+    pub fn last_known(&self, max_age: Duration) -> Option<Location> {
+        let fresh = |location: &Location| age(location) <= max_age;
+        if let Some(location) = LAST_KNOWN.lock().unwrap().clone().filter(fresh) {
+            return Some(location);
+        }
+        let path = self.config.cache_file.as_ref()?;
+        let location = serde_json::from_str::<Location>(&fs::read_to_string(path).ok()?).ok()?;
+        fresh(&location).then_some(location)
+    }
+}
+
+/// 마지막 위치를 기억하고, `path`가 있으면 파일에도 남긴다.
+pub(crate) fn store(location: &Location, path: Option<&Path>) {
+    let newer = {
+        let mut last = LAST_KNOWN.lock().unwrap();
+        // 늦게 도착한 오래된 위치로 덮어쓰지 않는다. 파일도 마찬가지다
+        let newer = last.as_ref().is_none_or(|last| last.timestamp <= location.timestamp);
+        if newer {
+            *last = Some(location.clone());
+        }
+        newer
+    };
+    if newer
+        && let Some(path) = path
+        && let Err(e) = save(location, path)
+    {
+        warning!("{}", tr!("⚠️ 마지막 위치를 {}에 저장하지 못했습니다: {}", "⚠️ Could not save the last location to {}: {}", path.display(), e));
+    }
+}
+
+// 읽는 쪽이 반쯤 쓴 파일을 보지 않도록 임시 파일에 쓴 뒤 바꿔 넣는다
+fn save(location: &Location, path: &Path) -> std::io::Result<()> {
+    let json = serde_json::to_string(location)?;
+    let temporary = path.with_extension("tmp");
+    fs::write(&temporary, json)?;
+    fs::rename(&temporary, path)
+}
+
+// 시계가 어긋나 미래의 위치로 보이면 방금 받은 것으로 친다
+fn age(location: &Location) -> Duration {
+    (Utc::now() - location.timestamp).to_std().unwrap_or_default()
+}
@@ -0,0 +1,165 @@
+use std::env;
+use std::fs;
+use std::io::{self, BufRead, BufReader, ErrorKind};
+use std::net::TcpStream;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, SystemTime};
+use serde_json::{json, Value};
+use tungstenite::stream::MaybeTlsStream;
+use tungstenite::{Message, WebSocket};
+
+use crate::WeimError;
+
+/// 실행할 Chrome을 직접 정하는 환경 변수
+pub(crate) const CHROME_ENV: &str = "WEIM_CHROME";
+
+// Chrome이 뜨고 DevTools 명령에 답할 때까지 기다리는 시간
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(10);
+
+// PATH에서 찾을 실행 파일 이름
+const CHROME_NAMES: &[&str] = &["google-chrome", "google-chrome-stable", "chromium", "chromium-browser", "chrome", "chrome.exe"];
+
+#[cfg(target_os = "macos")]
+const CHROME_PATHS: &[&str] = &[
+    "/Applications/Google Chrome.app/Contents/MacOS/Google Chrome",
+    "/Applications/Chromium.app/Contents/MacOS/Chromium",
+];
+#[cfg(target_os = "windows")]
+const CHROME_PATHS: &[&str] = &[
+    r"C:\Program Files\Google\Chrome\Application\chrome.exe",
+    r"C:\Program Files (x86)\Google\Chrome\Application\chrome.exe",
+];
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+const CHROME_PATHS: &[&str] = &[];
+
+/// 창 없이 띄운 Chrome. 버리면 프로세스를 끝내고 임시 프로필을 지운다.
+pub(crate) struct Headless {
+    child: Child,
+    profile: PathBuf,
+}
+
+impl Headless {
+    /// Chrome을 헤드리스로 띄우고 페이지 출처에 위치 권한을 준 뒤 `url`을 연다.
+    ///
+    /// `insecure`면 자체 서명 인증서를 그대로 믿는다. 이 페이지만 여는 임시 프로필이다.
+    pub(crate) fn launch(url: &str, insecure: bool) -> Result<Self, WeimError> {
+        let binary = find_chrome().ok_or_else(|| launch_error(ErrorKind::NotFound, "Chrome/Chromium"))?;
+        let nanos = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap_or_default().as_nanos();
+        let profile = env::temp_dir().join(format!("weim-chrome-{}-{}", std::process::id(), nanos));
+
+        let mut command = Command::new(binary);
+        command
+            .arg("--headless=new")
+            .arg("--remote-debugging-port=0")
+            .arg(format!("--user-data-dir={}", profile.display()))
+            .arg("--no-first-run")
+            .arg("--no-default-browser-check");
+        if insecure {
+            command.arg("--ignore-certificate-errors");
+        }
+        let child = command
+            .arg("about:blank")
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(WeimError::BrowserLaunch)?;
+        // 여기서부터 실패하면 Drop이 프로세스를 정리한다
+        let mut headless = Headless { child, profile };
+
+        let mut cdp = Cdp::connect(&headless.endpoint()?)?;
+        let origin = url.trim_end_matches('/');
+        cdp.call("Browser.grantPermissions", json!({ "origin": origin, "permissions": ["geolocation"] }))?;
+        cdp.call("Target.createTarget", json!({ "url": url }))?;
+        Ok(headless)
+    }
+
+    // Chrome이 stderr에 알려 주는 `ws://.../devtools/browser/...` 주소
+    fn endpoint(&mut self) -> Result<String, WeimError> {
+        let stderr = self.child.stderr.take().ok_or_else(|| launch_error(ErrorKind::BrokenPipe, "stderr"))?;
+        let (tx, rx) = mpsc::channel();
+        // 주소를 찾은 뒤에도 파이프가 차서 Chrome이 멈추지 않도록 끝까지 읽어 버린다
+        thread::spawn(move || {
+            for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                if let Some(start) = line.find("ws://") {
+                    tx.send(line[start..].trim().to_string()).ok();
+                }
+            }
+        });
+        rx.recv_timeout(STARTUP_TIMEOUT)
+            .map_err(|_| launch_error(ErrorKind::TimedOut, "Chrome DevTools"))
+    }
+}
+
+impl Drop for Headless {
+    fn drop(&mut self) {
+        self.child.kill().ok();
+        self.child.wait().ok();
+        fs::remove_dir_all(&self.profile).ok();
+    }
+}
+
+// 브라우저 전체를 다루는 DevTools 연결. 답이 올 때까지 기다리는 명령만 쓴다.
+struct Cdp {
+    socket: WebSocket<MaybeTlsStream<TcpStream>>,
+    next_id: u64,
+}
+
+impl Cdp {
+    fn connect(endpoint: &str) -> Result<Self, WeimError> {
+        let (socket, _) = tungstenite::connect(endpoint).map_err(|e| launch_error(ErrorKind::ConnectionRefused, &e.to_string()))?;
+        if let MaybeTlsStream::Plain(stream) = socket.get_ref() {
+            stream.set_read_timeout(Some(STARTUP_TIMEOUT)).ok();
+        }
+        Ok(Cdp { socket, next_id: 0 })
+    }
+
+    fn call(&mut self, method: &str, params: Value) -> Result<Value, WeimError> {
+        self.next_id += 1;
+        let request = json!({ "id": self.next_id, "method": method, "params": params });
+        self.socket.send(Message::text(request.to_string())).map_err(|e| cdp_error(method, &e.to_string()))?;
+        loop {
+            let message = self.socket.read().map_err(|e| cdp_error(method, &e.to_string()))?;
+            let Message::Text(text) = message else {
+                continue;
+            };
+            // 명령의 답이 아닌 이벤트는 건너뛴다
+            let Ok(reply) = serde_json::from_str::<Value>(text.as_str()) else {
+                continue;
+            };
+            if reply["id"].as_u64() != Some(self.next_id) {
+                continue;
+            }
+            if let Some(error) = reply.get("error") {
+                return Err(cdp_error(method, error["message"].as_str().unwrap_or_default()));
+            }
+            return Ok(reply["result"].clone());
+        }
+    }
+}
+
+fn find_chrome() -> Option<PathBuf> {
+    if let Some(path) = env::var_os(CHROME_ENV) {
+        return Some(PathBuf::from(path));
+    }
+    let paths = env::var_os("PATH").unwrap_or_default();
+    env::split_paths(&paths)
+        .flat_map(|dir| CHROME_NAMES.iter().map(move |name| dir.join(name)))
+        .chain(CHROME_PATHS.iter().map(PathBuf::from))
+        .find(|path| is_file(path))
+}
+
+fn is_file(path: &Path) -> bool {
+    path.metadata().is_ok_and(|metadata| metadata.is_file())
+}
+
+fn launch_error(kind: ErrorKind, message: &str) -> WeimError {
+    WeimError::BrowserLaunch(io::Error::new(kind, message.to_string()))
+}
+
+fn cdp_error(method: &str, message: &str) -> WeimError {
+    launch_error(ErrorKind::Other, &format!("{}: {}", method, message))
+}
@@ -0,0 +1,48 @@
+use std::collections::{HashMap, VecDeque};
+use std::net::IpAddr;
+use std::sync::Mutex;
+use chrono::{DateTime, TimeDelta, Utc};
+
+use crate::DeviceId;
+
+// 기기마다 기억해 둘 최근 표본 수. 기기 시계가 조금씩 흘러가도 따라간다
+const WINDOW: usize = 32;
+// 기억해 둘 기기 수. 넘으면 아무 기기나 하나 잊는다
+const MAX_DEVICES: usize = 1_000;
+
+// 이름 있는 기기는 이름만, 이름 없는 기기는 보낸 주소까지
+type Key = (DeviceId, Option<IpAddr>);
+
+/// 기기마다 서버 시계와의 차이를 어림한다.
+///
+/// 표본은 서버가 받은 시각에서 페이지가 보낸 기기 시각을 뺀 값이다. 전송 지연만큼 늘 크게 나오므로
+/// 최근 표본 중 가장 작은 값을 시계 차이로 본다.
+///
+/// 이름을 보내지 않은 기기들은 모두 `DeviceId::default()`라서, 보낸 주소로 나눠 따로 어림한다.
+#[derive(Default)]
+pub(crate) struct Clocks {
+    devices: Mutex<HashMap<Key, VecDeque<TimeDelta>>>,
+}
+
+impl Clocks {
+    /// `sent`는 페이지가 요청을 보낸 기기 시각, `received`는 서버가 받은 시각
+    ///
+    /// 이름 없는 기기의 주소를 모르면 다른 기기와 섞일 수 있어 고치지 않는다 (0).
+    pub(crate) fn offset(&self, device: &DeviceId, client: Option<IpAddr>, sent: DateTime<Utc>, received: DateTime<Utc>) -> TimeDelta {
+        let key = match client {
+            _ if *device != DeviceId::default() => (device.clone(), None),
+            Some(ip) => (device.clone(), Some(ip)),
+            None => return TimeDelta::zero(),
+        };
+        let mut devices = self.devices.lock().unwrap();
+        if devices.len() >= MAX_DEVICES && !devices.contains_key(&key) && let Some(forgotten) = devices.keys().next().cloned() {
+            devices.remove(&forgotten);
+        }
+        let samples = devices.entry(key).or_default();
+        if samples.len() >= WINDOW {
+            samples.pop_front();
+        }
+        samples.push_back(received - sent);
+        samples.iter().min().copied().unwrap_or_default()
+    }
+}
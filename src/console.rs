@@ -0,0 +1,104 @@
+// wasm32에서는 서버와 함께 안내 메시지도 쓰지 않는다
+#![cfg_attr(target_arch = "wasm32", allow(unused))]
+
+use std::fmt;
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::events::FixEvent;
+use crate::{DeviceId, Location};
+
+// 프로세스 전체에 하나. 백그라운드 전송 스레드도 같은 설정을 따른다.
+static JSON: AtomicBool = AtomicBool::new(false);
+static VERBOSITY: AtomicU8 = AtomicU8::new(Verbosity::Info as u8);
+
+/// 콘솔에 무엇을 어디로 출력할지
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Output {
+    /// 사람이 읽는 안내를 stdout에 출력 (기본값)
+    #[default]
+    Text,
+    /// 받은 위치마다 JSON 객체 하나를 한 줄로 stdout에 출력하고, 안내는 stderr로 보낸다
+    Json,
+}
+
+/// 콘솔에 직접 출력할 안내의 양
+///
+/// 안내는 설정과 상관없이 모두 `log` 이벤트로도 나가므로, 로거를 설치한 라이브러리 사용자는
+/// `Verbosity::Quiet`로 콘솔 출력을 끄고 로거에서 받으면 된다.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum Verbosity {
+    /// 아무것도 출력하지 않음
+    Quiet,
+    /// 오류만
+    Errors,
+    /// 오류와 경고만
+    Warnings,
+    /// 받은 위치와 진행 안내까지 (기본값)
+    #[default]
+    Info,
+    /// 요청 하나하나까지
+    Debug,
+}
+
+pub(crate) fn set(output: Output, verbosity: Verbosity) {
+    JSON.store(output == Output::Json, Ordering::Relaxed);
+    VERBOSITY.store(verbosity as u8, Ordering::Relaxed);
+}
+
+pub(crate) fn is_json() -> bool {
+    JSON.load(Ordering::Relaxed)
+}
+
+/// JSON 모드에서 처리를 마친 위치를 stdout에 한 줄로 쓴다. 안내가 아니라 결과라서 `Verbosity`를 따르지 않는다.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn fix(device: &DeviceId, location: &Location) {
+    if !is_json() {
+        return;
+    }
+    if let Ok(json) = serde_json::to_string(&FixEvent { device, location }) {
+        let mut out = std::io::stdout().lock();
+        writeln!(out, "{}", json).and_then(|_| out.flush()).ok();
+    }
+}
+
+/// 설정한 양만큼 콘솔에 쓴다. JSON 모드에서는 stdout을 비워 두도록 stderr로 보낸다.
+pub(crate) fn print(level: log::Level, args: fmt::Arguments) {
+    let verbosity = match level {
+        log::Level::Error => Verbosity::Errors,
+        log::Level::Warn => Verbosity::Warnings,
+        log::Level::Info => Verbosity::Info,
+        log::Level::Debug | log::Level::Trace => Verbosity::Debug,
+    };
+    if (verbosity as u8) > VERBOSITY.load(Ordering::Relaxed) {
+        return;
+    }
+    if is_json() {
+        eprintln!("{}", args);
+    } else {
+        println!("{}", args);
+    }
+}
+
+// 부른 모듈 경로가 `log` 대상(target)이 되도록 매크로에서 바로 `log!`을 부른다
+macro_rules! emit {
+    ($level:expr, $($arg:tt)*) => {{
+        log::log!($level, $($arg)*);
+        $crate::console::print($level, format_args!($($arg)*));
+    }};
+}
+
+macro_rules! error {
+    ($($arg:tt)*) => { $crate::console::emit!(log::Level::Error, $($arg)*) };
+}
+
+macro_rules! warning {
+    ($($arg:tt)*) => { $crate::console::emit!(log::Level::Warn, $($arg)*) };
+}
+
+macro_rules! info {
+    ($($arg:tt)*) => { $crate::console::emit!(log::Level::Info, $($arg)*) };
+}
+
+pub(crate) use {emit, error, info, warning};
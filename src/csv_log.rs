@@ -0,0 +1,113 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use chrono::{DateTime, Local, Utc};
+
+use crate::export::rfc3339;
+use crate::{Location, WeimError};
+
+const HEADER: &str = "timestamp,latitude,longitude,accuracy,source\n";
+
+/// 새 파일로 넘어가는 기준
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Rotation {
+    /// 한 파일에 계속 쓴다 (기본값)
+    #[default]
+    Never,
+    /// 파일이 이 크기(바이트)를 넘으면 시각을 붙인 이름으로 옮기고 새로 쓴다
+    Size(u64),
+    /// 위치 시각의 날짜(현지 시간)마다 `이름-YYYY-MM-DD.csv` 파일에 쓴다
+    Daily,
+}
+
+/// 받은 위치를 CSV 파일 끝에 한 줄씩 덧붙이는 기록기
+///
+/// 열은 `timestamp,latitude,longitude,accuracy,source`이고, 새 파일에는 머리글을 먼저 쓴다.
+/// 쓸 때마다 파일을 열고 닫으므로 다른 프로그램에서 읽어도 된다.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CsvLog {
+    path: PathBuf,
+    rotation: Rotation,
+}
+
+impl CsvLog {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        CsvLog { path: path.into(), rotation: Rotation::default() }
+    }
+
+    /// 파일을 나누는 기준 (기본값 `Rotation::Never`)
+    pub fn rotation(mut self, rotation: Rotation) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    /// 한 줄을 덧붙인다. `source`는 위치를 보낸 기기나 공급자 이름이다.
+    pub fn append(&self, source: &str, location: &Location) -> Result<(), WeimError> {
+        let path = match self.rotation {
+            Rotation::Daily => self.dated_path(location.timestamp),
+            _ => self.path.clone(),
+        };
+        if let Rotation::Size(limit) = self.rotation {
+            rotate_if_full(&path, limit)?;
+        }
+
+        let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+        if file.metadata()?.len() == 0 {
+            file.write_all(HEADER.as_bytes())?;
+        }
+        writeln!(
+            file,
+            "{},{},{},{},{}",
+            rfc3339(location.timestamp),
+            location.latitude,
+            location.longitude,
+            location.accuracy,
+            escape_csv(source)
+        )?;
+        Ok(())
+    }
+
+    // fixes.csv -> fixes-2026-10-14.csv
+    fn dated_path(&self, timestamp: DateTime<Utc>) -> PathBuf {
+        let date = timestamp.with_timezone(&Local).format("%Y-%m-%d");
+        with_suffix(&self.path, &date.to_string())
+    }
+}
+
+// 가득 찬 파일은 fixes-20261014-053300.csv처럼 옮긴다
+fn rotate_if_full(path: &Path, limit: u64) -> Result<(), WeimError> {
+    let Ok(metadata) = fs::metadata(path) else {
+        return Ok(());
+    };
+    if metadata.len() < limit {
+        return Ok(());
+    }
+    let stamp = Local::now().format("%Y%m%d-%H%M%S").to_string();
+    let mut target = with_suffix(path, &stamp);
+    // 같은 초에 또 넘치면 번호를 붙인다
+    let mut n = 1;
+    while target.exists() {
+        target = with_suffix(path, &format!("{}-{}", stamp, n));
+        n += 1;
+    }
+    fs::rename(path, target)?;
+    File::create(path)?;
+    Ok(())
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let stem = path.file_stem().map(|stem| stem.to_string_lossy()).unwrap_or_default();
+    let name = match path.extension() {
+        Some(extension) => format!("{}-{}.{}", stem, suffix, extension.to_string_lossy()),
+        None => format!("{}-{}", stem, suffix),
+    };
+    path.with_file_name(name)
+}
+
+pub(crate) fn escape_csv(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
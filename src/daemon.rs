@@ -0,0 +1,546 @@
+use std::collections::{BTreeMap, HashMap};
+#[cfg(windows)]
+use std::fs::{File, OpenOptions};
+#[cfg(any(unix, windows))]
+use std::io::{BufRead, BufReader};
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpStream};
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+#[cfg(windows)]
+use std::os::windows::ffi::OsStrExt;
+#[cfg(windows)]
+use std::os::windows::io::{AsRawHandle, FromRawHandle, OwnedHandle};
+#[cfg(any(unix, windows))]
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+use chrono::Local;
+use serde::Deserialize;
+#[cfg(windows)]
+use windows_sys::Win32::Foundation::{ERROR_ACCESS_DENIED, ERROR_PIPE_BUSY, ERROR_PIPE_CONNECTED, GetLastError, INVALID_HANDLE_VALUE};
+#[cfg(windows)]
+use windows_sys::Win32::Storage::FileSystem::{FILE_FLAG_FIRST_PIPE_INSTANCE, PIPE_ACCESS_DUPLEX};
+#[cfg(windows)]
+use windows_sys::Win32::System::Pipes::{
+    ConnectNamedPipe, CreateNamedPipeW, PIPE_READMODE_BYTE, PIPE_REJECT_REMOTE_CLIENTS, PIPE_TYPE_BYTE, PIPE_UNLIMITED_INSTANCES, PIPE_WAIT,
+};
+
+use crate::api::DeviceSummary;
+use crate::console::{info, warning};
+use crate::devices::encode_query;
+use crate::events::FixEvent;
+use crate::i18n::{pick, tr};
+use crate::locator::{page_url, Mode, POLL_INTERVAL};
+use crate::{CancellationToken, DeviceId, Location, Schedule, Weim, WeimError};
+
+// 질의 하나에 답을 기다리는 최대 시간
+const QUERY_TIMEOUT: Duration = Duration::from_secs(5);
+// `daemon_socket("weim")`처럼 이름만 주면 붙이는 앞부분
+#[cfg(windows)]
+const PIPE_PREFIX: &str = r"\\.\pipe\";
+
+#[derive(Debug, Default)]
+struct Cache {
+    latest: Option<(DeviceId, Location)>,
+    devices: BTreeMap<DeviceId, (usize, Location)>,
+}
+
+impl Cache {
+    fn latest_json(&self, device: Option<&str>) -> String {
+        let latest = match device {
+            Some(name) => self.devices.get_key_value(&DeviceId::new(name)).map(|(device, (_, location))| (device, location)),
+            None => self.latest.as_ref().map(|(device, location)| (device, location)),
+        };
+        match latest {
+            Some((device, location)) => serde_json::to_string(&FixEvent { device, location }).unwrap_or_default(),
+            None => serde_json::json!({ "error": pick("아직 받은 위치가 없습니다", "no location received yet") }).to_string(),
+        }
+    }
+
+    // 지금까지 받은 위치 수
+    fn fixes(&self) -> usize {
+        self.devices.values().map(|(fixes, _)| fixes).sum()
+    }
+
+    fn devices_json(&self) -> String {
+        let devices: Vec<_> = self
+            .devices
+            .iter()
+            .map(|(device, (fixes, location))| DeviceSummary { device, fixes: *fixes, location })
+            .collect();
+        serde_json::to_string(&devices).unwrap_or_default()
+    }
+}
+
+impl Weim {
+    /// 서버를 닫지 않고 계속 띄워 두는 데몬 모드로 시작한다.
+    ///
+    /// 페이지를 한 번 열어 두면 위치가 들어올 때마다 마지막 위치를 기억하고, 다른 프로세스는
+    /// 매번 브라우저를 여는 대신 `DaemonClient`로 `/api/latest`나 `daemon_socket()`의 Unix 소켓(Windows에서는 이름 있는 파이프)에 물어본다.
+    /// 감시 도구는 `/healthz`와 `/version`으로 데몬이 살아 있는지, 어떤 빌드인지 확인한다.
+    /// `schedule()`을 정했으면 처음에는 페이지를 열지 않고, 정해 둔 때마다 한 번만 보내는 페이지를 연다.
+    /// `timeout`은 쓰지 않으며, 돌려받은 `Daemon`을 버리면 멈춘다.
+    pub fn serve(&self) -> Result<Daemon, WeimError> {
+        let mut weim = self.clone();
+        weim.config.timeout = None;
+        let schedule = weim.config.schedule.take();
+        if schedule.is_some() {
+            weim.config.open_browser = false;
+        }
+
+        let token = CancellationToken::new();
+        let cache = Arc::new(Mutex::new(Cache::default()));
+
+        let shared = Arc::clone(&cache);
+        let (addr, thread) = weim.spawn_server(token.clone(), move |device, location| {
+            let mut cache = shared.lock().unwrap();
+            let entry = cache.devices.entry(device.clone()).or_insert_with(|| (0, location.clone()));
+            entry.0 += 1;
+            entry.1 = location.clone();
+            cache.latest = Some((device, location));
+            true
+        })?;
+
+        #[cfg(any(unix, windows))]
+        let socket = match &self.config.daemon_socket {
+            Some(path) => match listen(path, Arc::clone(&cache), token.clone()) {
+                Ok(path) => Some(path),
+                Err(e) => {
+                    token.cancel();
+                    return Err(e);
+                }
+            },
+            None => None,
+        };
+
+        info!("{}", tr!("🛰️ 데몬 모드로 {}에서 계속 위치를 받습니다.", "🛰️ Daemon mode: receiving locations at {}.", addr));
+        if self.config.api_enabled(Mode::Watch) {
+            info!("{}", tr!("📊 대시보드: {}/dashboard", "📊 Dashboard: {}/dashboard", self.base_url(addr)));
+        }
+        let scheduler = schedule.map(|schedule| {
+            let (weim, cache, token) = (self.clone(), Arc::clone(&cache), token.clone());
+            thread::spawn(move || scheduled(&weim, &schedule, addr, &cache, &token))
+        });
+        Ok(Daemon {
+            addr,
+            token,
+            cache,
+            thread: Some(thread),
+            scheduler,
+            #[cfg(any(unix, windows))]
+            socket,
+        })
+    }
+
+    // 서버를 열고 요청을 처리할 스레드를 띄운다. `axum()`을 켰으면 axum 서버로 연다
+    fn spawn_server(
+        self,
+        token: CancellationToken,
+        on_fix: impl FnMut(DeviceId, Location) -> bool + Send + 'static,
+    ) -> Result<(SocketAddr, JoinHandle<Result<(), WeimError>>), WeimError> {
+        #[cfg(feature = "axum")]
+        if self.config.axum {
+            let (listener, browser) = self.open_axum()?;
+            let addr = listener.local_addr()?;
+            let thread = thread::spawn(move || {
+                let _browser = browser;
+                self.run_axum(listener, &token, on_fix)
+            });
+            return Ok((addr, thread));
+        }
+
+        let (server, browser) = self.open()?;
+        let addr = server.server_addr().to_ip().ok_or(WeimError::ServerClosed)?;
+        let thread = thread::spawn(move || {
+            let _browser = browser;
+            self.run(&server, &token, Mode::Watch, on_fix)
+        });
+        Ok((addr, thread))
+    }
+}
+
+/// `Weim::serve()`가 돌려주는 데몬
+///
+/// 버리면 서버를 멈추고 Unix 소켓 파일을 지운다.
+#[derive(Debug)]
+pub struct Daemon {
+    addr: SocketAddr,
+    token: CancellationToken,
+    cache: Arc<Mutex<Cache>>,
+    thread: Option<JoinHandle<Result<(), WeimError>>>,
+    scheduler: Option<JoinHandle<()>>,
+    #[cfg(any(unix, windows))]
+    socket: Option<PathBuf>,
+}
+
+impl Daemon {
+    /// 서버가 열린 주소. `DaemonClient::http()`에 넘긴다.
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// 어느 기기든 마지막으로 받은 위치
+    pub fn last_known(&self) -> Option<Location> {
+        self.cache.lock().unwrap().latest.as_ref().map(|(_, location)| location.clone())
+    }
+
+    /// 기기별 마지막 위치
+    pub fn latest(&self) -> HashMap<DeviceId, Location> {
+        let cache = self.cache.lock().unwrap();
+        cache.devices.iter().map(|(device, (_, location))| (device.clone(), location.clone())).collect()
+    }
+
+    /// 데몬을 멈추는 토큰. 신호 처리기 같은 다른 스레드에서 쓴다.
+    pub fn cancellation_token(&self) -> CancellationToken {
+        self.token.clone()
+    }
+
+    /// 데몬이 멈출 때까지 이 스레드를 막는다. 토큰으로 멈췄으면 Ok
+    pub fn wait(mut self) -> Result<(), WeimError> {
+        let result = match self.thread.take().map(JoinHandle::join) {
+            Some(Ok(result)) => result,
+            _ => Err(WeimError::ServerClosed),
+        };
+        match result {
+            Err(WeimError::Cancelled) => Ok(()),
+            other => other,
+        }
+    }
+}
+
+impl Drop for Daemon {
+    fn drop(&mut self) {
+        self.token.cancel();
+        // 열어 둔 창 없는 Chrome을 끝내고 돌아가게 기다린다
+        if let Some(scheduler) = self.scheduler.take() {
+            scheduler.join().ok();
+        }
+        #[cfg(unix)]
+        if let Some(path) = &self.socket {
+            std::fs::remove_file(path).ok();
+        }
+        // 연결을 기다리며 막혀 있는 파이프 스레드를 깨워서 끝나게 한다
+        #[cfg(windows)]
+        if let Some(path) = &self.socket {
+            OpenOptions::new().read(true).write(true).open(path).ok();
+        }
+    }
+}
+
+// 정해 둔 때마다 한 번만 보내는 페이지를 열고, 위치가 오거나 기다릴 시간이 지나면 닫는다
+fn scheduled(weim: &Weim, schedule: &Schedule, addr: SocketAddr, cache: &Mutex<Cache>, token: &CancellationToken) {
+    let patience = weim.config.timeout.unwrap_or(weim.config.response_grace);
+    let mut due = schedule.first(Local::now());
+    while let Some(time) = due {
+        info!("{}", tr!("⏰ 다음 위치는 {}에 받습니다.", "⏰ Next scheduled fix at {}.", time.format("%Y-%m-%d %H:%M:%S")));
+        // 잠자기에서 깨어나도 맞게 벽시계로 잰다
+        while Local::now() < time {
+            if token.is_cancelled() {
+                return;
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+
+        let before = cache.lock().unwrap().fixes();
+        let url = format!("{}/?once", weim.base_url(addr));
+        info!("{}", tr!("📍 정해 둔 시각이라 위치를 받습니다: {}", "📍 Taking the scheduled fix: {}", url));
+        // 페이지는 이 안에서만 열어 둔다
+        {
+            let _browser = if weim.config.open_browser {
+                weim.open_page(&format!("{}/?once", page_url(weim.scheme(), addr)))
+                    .inspect_err(|e| warning!("{}", tr!("⚠️ 페이지를 열지 못했습니다: {}", "⚠️ Could not open the page: {}", e)))
+                    .ok()
+            } else {
+                None
+            };
+            // `Duration::MAX`면 올 때까지 기다린다
+            let deadline = Instant::now().checked_add(patience);
+            while cache.lock().unwrap().fixes() == before {
+                if token.is_cancelled() {
+                    return;
+                }
+                if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                    warning!("{}", tr!("⚠️ 정해 둔 위치를 {}초 안에 받지 못했습니다.", "⚠️ No scheduled fix arrived within {} s.", patience.as_secs()));
+                    break;
+                }
+                thread::sleep(POLL_INTERVAL);
+            }
+        }
+
+        // 늦어져서 지나 버린 때는 건너뛴다
+        let now = Local::now();
+        due = schedule.next_after(time).filter(|next| *next > now).or_else(|| schedule.next_after(now));
+    }
+}
+
+// 남아 있는 소켓 파일을 지우고 연 뒤, 한 줄짜리 질의에 한 줄짜리 JSON으로 답한다
+#[cfg(unix)]
+fn listen(path: &Path, cache: Arc<Mutex<Cache>>, token: CancellationToken) -> Result<PathBuf, WeimError> {
+    if UnixStream::connect(path).is_ok() {
+        return Err(WeimError::Bind { addr: path.display().to_string(), source: pick("다른 데몬이 이미 쓰고 있습니다", "another daemon is already using it").into() });
+    }
+    std::fs::remove_file(path).ok();
+    let listener = UnixListener::bind(path).map_err(|source| WeimError::Bind { addr: path.display().to_string(), source: source.into() })?;
+    // 토큰을 확인할 수 있게 기다리지 않고 받는다
+    listener.set_nonblocking(true)?;
+
+    thread::spawn(move || {
+        while !token.is_cancelled() {
+            match listener.accept() {
+                Ok((stream, _)) => {
+                    answer(stream, &cache).ok();
+                }
+                Err(_) => thread::sleep(POLL_INTERVAL),
+            }
+        }
+    });
+    info!("{}", tr!("🔌 {} 소켓에서 질의를 받습니다.", "🔌 Answering queries on socket {}.", path.display()));
+    Ok(path.to_path_buf())
+}
+
+#[cfg(unix)]
+fn answer(stream: UnixStream, cache: &Mutex<Cache>) -> std::io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(QUERY_TIMEOUT))?;
+    let mut line = String::new();
+    BufReader::new(&stream).read_line(&mut line)?;
+    (&stream).write_all(format!("{}\n", reply(&line, cache)).as_bytes())
+}
+
+// `latest [기기]`나 `devices` 한 줄에 답할 JSON
+#[cfg(any(unix, windows))]
+fn reply(line: &str, cache: &Mutex<Cache>) -> String {
+    // 기기 이름에는 공백이 들어갈 수 있다
+    let line = line.trim();
+    let (command, device) = match line.split_once(' ') {
+        Some((command, device)) => (command, Some(device.trim())),
+        None => (line, None),
+    };
+    match (command, device) {
+        ("latest", device) => cache.lock().unwrap().latest_json(device),
+        ("devices", None) => cache.lock().unwrap().devices_json(),
+        _ => serde_json::json!({ "error": pick("알 수 없는 질의입니다 (latest [기기] | devices)", "unknown query (latest [device] | devices)") }).to_string(),
+    }
+}
+
+// 이름 있는 파이프를 열고, 연결마다 스레드를 띄워 한 줄짜리 질의에 한 줄짜리 JSON으로 답한다.
+// 다른 컴퓨터에서 오는 연결은 받지 않는다
+#[cfg(windows)]
+fn listen(path: &Path, cache: Arc<Mutex<Cache>>, token: CancellationToken) -> Result<PathBuf, WeimError> {
+    let path = pipe_name(path);
+    let first = create_pipe(&path, true).map_err(|source| {
+        let source = match source.raw_os_error() {
+            // 같은 이름의 파이프가 이미 있다
+            Some(code) if code == ERROR_ACCESS_DENIED as i32 => pick("다른 데몬이 이미 쓰고 있습니다", "another daemon is already using it").into(),
+            _ => source.into(),
+        };
+        WeimError::Bind { addr: path.display().to_string(), source }
+    })?;
+
+    let name = path.clone();
+    thread::spawn(move || {
+        let mut next = Some(first);
+        while !token.is_cancelled() {
+            let pipe = match next.take().map_or_else(|| create_pipe(&name, false), Ok) {
+                Ok(pipe) => pipe,
+                Err(_) => {
+                    thread::sleep(POLL_INTERVAL);
+                    continue;
+                }
+            };
+            // 클라이언트가 연결할 때까지 막힌다. 멈출 때는 `Daemon`이 직접 연결해서 깨운다
+            let connected = unsafe { ConnectNamedPipe(pipe.as_raw_handle(), std::ptr::null_mut()) } != 0
+                || unsafe { GetLastError() } == ERROR_PIPE_CONNECTED;
+            if !connected || token.is_cancelled() {
+                continue;
+            }
+            let cache = Arc::clone(&cache);
+            thread::spawn(move || answer(pipe, &cache).ok());
+        }
+    });
+    info!("{}", tr!("🔌 {} 파이프에서 질의를 받습니다.", "🔌 Answering queries on pipe {}.", path.display()));
+    Ok(path)
+}
+
+#[cfg(windows)]
+fn answer(mut pipe: File, cache: &Mutex<Cache>) -> std::io::Result<()> {
+    let mut line = String::new();
+    BufReader::new(&mut pipe).read_line(&mut line)?;
+    pipe.write_all(format!("{}\n", reply(&line, cache)).as_bytes())?;
+    // 클라이언트가 다 읽을 때까지 기다린 뒤 닫는다
+    pipe.sync_all()
+}
+
+#[cfg(windows)]
+fn create_pipe(path: &Path, first: bool) -> std::io::Result<File> {
+    let name: Vec<u16> = path.as_os_str().encode_wide().chain([0]).collect();
+    let mut mode = PIPE_ACCESS_DUPLEX;
+    if first {
+        mode |= FILE_FLAG_FIRST_PIPE_INSTANCE;
+    }
+    let handle = unsafe {
+        CreateNamedPipeW(
+            name.as_ptr(),
+            mode,
+            PIPE_TYPE_BYTE | PIPE_READMODE_BYTE | PIPE_WAIT | PIPE_REJECT_REMOTE_CLIENTS,
+            PIPE_UNLIMITED_INSTANCES,
+            4096,
+            4096,
+            0,
+            std::ptr::null(),
+        )
+    };
+    if handle == INVALID_HANDLE_VALUE {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(File::from(unsafe { OwnedHandle::from_raw_handle(handle) }))
+}
+
+// `weim` -> `\\.\pipe\weim`
+#[cfg(windows)]
+fn pipe_name(path: &Path) -> PathBuf {
+    if path.to_string_lossy().starts_with(PIPE_PREFIX) {
+        path.to_path_buf()
+    } else {
+        Path::new(PIPE_PREFIX).join(path)
+    }
+}
+
+// 기기 이름 같은 나머지 필드는 무시한다
+#[derive(Deserialize)]
+#[serde(untagged)]
+#[allow(clippy::large_enum_variant)]
+enum Latest {
+    Fix(Location),
+    Missing {
+        #[allow(dead_code)]
+        error: String,
+    },
+}
+
+#[derive(Deserialize)]
+struct Summary {
+    device: DeviceId,
+    location: Location,
+}
+
+#[derive(Debug, Clone)]
+enum Target {
+    Http(SocketAddr),
+    #[cfg(any(unix, windows))]
+    Socket(PathBuf),
+}
+
+/// 다른 프로세스에서 실행 중인 데몬(`Weim::serve()`)에 마지막 위치를 묻는 클라이언트
+#[derive(Debug, Clone)]
+pub struct DaemonClient {
+    target: Target,
+}
+
+impl DaemonClient {
+    /// 데몬의 HTTP 주소 (예: 127.0.0.1:3030)로 `/api`에 묻는다. 데몬이 `api(false)`면 쓸 수 없다.
+    pub fn http(addr: SocketAddr) -> Self {
+        DaemonClient { target: Target::Http(addr) }
+    }
+
+    /// `daemon_socket()`으로 연 Unix 소켓이나 Windows의 이름 있는 파이프로 묻는다. (Unix, Windows 전용)
+    #[cfg(any(unix, windows))]
+    pub fn socket(path: impl Into<PathBuf>) -> Self {
+        DaemonClient { target: Target::Socket(path.into()) }
+    }
+
+    /// 어느 기기든 마지막 위치. 아직 받은 위치가 없으면 None
+    pub fn latest(&self) -> Result<Option<Location>, WeimError> {
+        self.latest_json(None)
+    }
+
+    /// 이 기기의 마지막 위치
+    pub fn latest_for(&self, device: &DeviceId) -> Result<Option<Location>, WeimError> {
+        self.latest_json(Some(device))
+    }
+
+    /// 기기별 마지막 위치
+    pub fn devices(&self) -> Result<HashMap<DeviceId, Location>, WeimError> {
+        let body = match &self.target {
+            Target::Http(addr) => http_get(*addr, "/api/devices")?.1,
+            #[cfg(any(unix, windows))]
+            Target::Socket(path) => socket_query(path, "devices")?,
+        };
+        let summaries: Vec<Summary> = serde_json::from_str(&body).map_err(|e| WeimError::InvalidPayload(e.to_string()))?;
+        Ok(summaries.into_iter().map(|summary| (summary.device, summary.location)).collect())
+    }
+
+    fn latest_json(&self, device: Option<&DeviceId>) -> Result<Option<Location>, WeimError> {
+        let body = match &self.target {
+            Target::Http(addr) => {
+                let path = match device {
+                    Some(device) => format!("/api/latest?device={}", encode_query(device.as_str())),
+                    None => "/api/latest".to_string(),
+                };
+                match http_get(*addr, &path)? {
+                    (404, _) => return Ok(None),
+                    (_, body) => body,
+                }
+            }
+            #[cfg(any(unix, windows))]
+            Target::Socket(path) => match device {
+                Some(device) => socket_query(path, &format!("latest {}", device))?,
+                None => socket_query(path, "latest")?,
+            },
+        };
+        match serde_json::from_str(&body) {
+            Ok(Latest::Fix(location)) => Ok(Some(location)),
+            Ok(Latest::Missing { .. }) => Ok(None),
+            Err(e) => Err(WeimError::InvalidPayload(e.to_string())),
+        }
+    }
+}
+
+// 라이브러리 없이 보내는 최소한의 HTTP/1.0 GET. (상태 코드, 본문)
+fn http_get(addr: SocketAddr, path: &str) -> Result<(u16, String), WeimError> {
+    let mut stream = TcpStream::connect_timeout(&addr, QUERY_TIMEOUT)?;
+    stream.set_read_timeout(Some(QUERY_TIMEOUT))?;
+    write!(stream, "GET {} HTTP/1.0\r\nHost: {}\r\nConnection: close\r\n\r\n", path, addr)?;
+    let mut response = String::new();
+    stream.read_to_string(&mut response)?;
+
+    let (head, body) = response.split_once("\r\n\r\n").ok_or_else(|| WeimError::InvalidPayload(pick("HTTP 응답이 잘렸습니다", "truncated HTTP response").to_string()))?;
+    let status = head
+        .split_whitespace()
+        .nth(1)
+        .and_then(|code| code.parse().ok())
+        .ok_or_else(|| WeimError::InvalidPayload(pick("HTTP 상태 줄을 읽을 수 없습니다", "cannot read the HTTP status line").to_string()))?;
+    match status {
+        200 | 404 => Ok((status, body.to_string())),
+        _ => Err(WeimError::Network(tr!("데몬이 {}으로 답했습니다: {}", "the daemon answered {}: {}", status, body))),
+    }
+}
+
+#[cfg(unix)]
+fn socket_query(path: &Path, query: &str) -> Result<String, WeimError> {
+    let stream = UnixStream::connect(path)?;
+    stream.set_read_timeout(Some(QUERY_TIMEOUT))?;
+    (&stream).write_all(format!("{}\n", query).as_bytes())?;
+    let mut line = String::new();
+    BufReader::new(&stream).read_line(&mut line)?;
+    Ok(line)
+}
+
+// 다른 질의에 답하는 중이면 파이프가 빌 때까지 잠깐씩 다시 연다
+#[cfg(windows)]
+fn socket_query(path: &Path, query: &str) -> Result<String, WeimError> {
+    let (path, deadline) = (pipe_name(path), Instant::now() + QUERY_TIMEOUT);
+    let mut pipe = loop {
+        match OpenOptions::new().read(true).write(true).open(&path) {
+            Ok(pipe) => break pipe,
+            Err(e) if e.raw_os_error() == Some(ERROR_PIPE_BUSY as i32) && Instant::now() < deadline => thread::sleep(Duration::from_millis(10)),
+            Err(e) => return Err(e.into()),
+        }
+    };
+    pipe.write_all(format!("{}\n", query).as_bytes())?;
+    let mut line = String::new();
+    BufReader::new(pipe).read_line(&mut line)?;
+    Ok(line)
+}
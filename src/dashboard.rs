@@ -0,0 +1,156 @@
+use crate::i18n;
+
+const DASHBOARD_TEMPLATE: &str = r#"<!DOCTYPE html>
+<html lang="{{LANG}}">
+<head>
+    <meta charset="UTF-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1.0">
+    <title>{{TITLE}}</title>
+    <link rel="stylesheet" href="https://unpkg.com/leaflet@1.9.4/dist/leaflet.css"
+        integrity="sha256-p4NxAoJBhIIN+hmNHrzRCf9tD/miZyoHS5obTRR9BMY=" crossorigin="">
+    <script src="https://unpkg.com/leaflet@1.9.4/dist/leaflet.js"
+        integrity="sha256-20nQCchB9co0qIjJZRGuk2/Z9VM+kNiyxNV1lvTlZBo=" crossorigin=""></script>
+    <style>
+        body {
+            margin: 0;
+            padding: 20px;
+            font-family: monospace;
+            background: #000;
+            color: #0f0;
+        }
+        #map { height: 60vh; margin: 12px 0; }
+        table { border-collapse: collapse; }
+        th, td { padding: 2px 12px 2px 0; text-align: left; }
+    </style>
+</head>
+<body>
+    <div id="status">{{WAITING}}</div>
+    <div id="map"></div>
+    <div id="stats"></div>
+    <table>
+        <thead><tr><th>{{DEVICE}}</th><th>{{FIXES}}</th><th>{{POSITION}}</th><th>{{ACCURACY}}</th><th>{{TIME}}</th></tr></thead>
+        <tbody id="devices"></tbody>
+    </table>
+    <script>
+        const text = {{TEXT}};
+        const say = (template, values = {}) => template.replace(/\{(\w+)\}/g, (_, name) => values[name]);
+        // 지도에 남길 최근 위치 수
+        const TRAIL = 1000;
+        const trail = [];
+        const devices = new Map();
+        let view = null;
+
+        const draw = (fix) => {
+            if (!window.L) return;
+            const point = [fix.latitude, fix.longitude];
+            if (!view) {
+                const map = L.map('map');
+                L.tileLayer('https://tile.openstreetmap.org/{z}/{x}/{y}.png', {
+                    maxZoom: 19,
+                    attribution: '&copy; OpenStreetMap contributors'
+                }).addTo(map);
+                view = {
+                    map,
+                    line: L.polyline([], { color: '#0f0' }).addTo(map),
+                    marker: L.marker(point).addTo(map),
+                    circle: L.circle(point, { radius: fix.accuracy }).addTo(map)
+                };
+                map.fitBounds(view.circle.getBounds(), { maxZoom: 17 });
+            }
+            view.line.setLatLngs(trail.map((fix) => [fix.latitude, fix.longitude]));
+            view.marker.setLatLng(point);
+            view.circle.setLatLng(point).setRadius(fix.accuracy);
+            view.map.panTo(point);
+        };
+
+        const stats = () => {
+            if (!trail.length) return;
+            const accuracies = trail.map((fix) => fix.accuracy);
+            const mean = accuracies.reduce((sum, value) => sum + value, 0) / accuracies.length;
+            document.getElementById('stats').textContent = say(text.stats, {
+                count: trail.length,
+                last: accuracies[accuracies.length - 1].toFixed(1),
+                mean: mean.toFixed(1),
+                best: Math.min(...accuracies).toFixed(1)
+            });
+        };
+
+        // 기기 이름은 textContent로만 넣는다
+        const table = () => {
+            const body = document.getElementById('devices');
+            body.innerHTML = '';
+            for (const [name, { fixes, location }] of devices) {
+                const row = document.createElement('tr');
+                const cells = [
+                    name,
+                    fixes,
+                    `${location.latitude.toFixed(6)}, ${location.longitude.toFixed(6)}`,
+                    `±${location.accuracy.toFixed(1)}m`,
+                    new Date(location.timestamp).toLocaleTimeString()
+                ];
+                for (const value of cells) {
+                    const cell = document.createElement('td');
+                    cell.textContent = value;
+                    row.appendChild(cell);
+                }
+                body.appendChild(row);
+            }
+        };
+
+        const add = (fix) => {
+            trail.push(fix);
+            if (trail.length > TRAIL) trail.shift();
+            const seen = devices.get(fix.device);
+            devices.set(fix.device, { fixes: (seen ? seen.fixes : 0) + 1, location: fix });
+        };
+
+        const refresh = () => {
+            if (!trail.length) return;
+            draw(trail[trail.length - 1]);
+            stats();
+            table();
+        };
+
+        window.addEventListener('DOMContentLoaded', async () => {
+            // 지난 한 시간의 위치와 기기 목록으로 시작한다. API를 껐으면 스트림만 쓴다
+            try {
+                const history = await fetch('/api/history?from=' + (Date.now() - 3600000));
+                if (history.ok) (await history.json()).slice(-TRAIL).forEach(add);
+                const list = await fetch('/api/devices');
+                if (list.ok) {
+                    for (const { device, fixes, location } of await list.json()) {
+                        devices.set(device, { fixes, location: { ...location, device } });
+                    }
+                }
+            } catch (e) {}
+            refresh();
+
+            const events = new EventSource('/events');
+            events.onopen = () => document.getElementById('status').textContent = text.live;
+            // EventSource는 끊기면 스스로 다시 연결한다
+            events.onerror = () => document.getElementById('status').textContent = text.reconnecting;
+            events.addEventListener('fix', (event) => {
+                add(JSON.parse(event.data));
+                refresh();
+            });
+        });
+    </script>
+</body>
+</html>
+"#;
+
+/// 현재 언어로 `/dashboard` 페이지를 만든다.
+pub(crate) fn render() -> String {
+    let text = i18n::dashboard_text();
+    let json = serde_json::to_string(text).unwrap_or_else(|_| "{}".to_string());
+    DASHBOARD_TEMPLATE
+        .replace("{{LANG}}", text.lang)
+        .replace("{{TITLE}}", text.title)
+        .replace("{{WAITING}}", text.waiting)
+        .replace("{{DEVICE}}", text.device)
+        .replace("{{FIXES}}", text.fixes)
+        .replace("{{POSITION}}", text.position)
+        .replace("{{ACCURACY}}", text.accuracy)
+        .replace("{{TIME}}", text.time)
+        .replace("{{TEXT}}", &json)
+}
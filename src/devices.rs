@@ -0,0 +1,123 @@
+#[cfg(not(target_arch = "wasm32"))]
+use std::collections::HashMap;
+use std::fmt;
+#[cfg(not(target_arch = "wasm32"))]
+use std::net::SocketAddr;
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::mpsc::Receiver;
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::{Arc, Mutex};
+use serde::{Deserialize, Serialize};
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::{CancellationToken, Location, Weim, WeimError};
+
+/// 위치를 보낸 브라우저나 휴대폰의 이름
+///
+/// 페이지 주소의 `?device=이름`으로 정하고, 없으면 브라우저마다 임의의 ID를 만들어 기억한다.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct DeviceId(pub String);
+
+impl DeviceId {
+    pub fn new(name: impl Into<String>) -> Self {
+        DeviceId(name.into())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+/// 기기 이름을 보내지 않는 클라이언트에 붙는 이름
+impl Default for DeviceId {
+    fn default() -> Self {
+        DeviceId("default".to_string())
+    }
+}
+
+impl fmt::Display for DeviceId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Weim {
+    /// 여러 기기가 같은 서버로 위치를 보내는 세션을 연다.
+    ///
+    /// `watch()`처럼 백그라운드 스레드에서 돌고, 돌려받은 `Devices`를 버리면 멈춘다.
+    pub fn devices(&self) -> Result<Devices, WeimError> {
+        let latest = Arc::new(Mutex::new(HashMap::new()));
+        let shared = Arc::clone(&latest);
+        let (rx, token, addr) = self.spawn_watch(move |device: DeviceId, location: Location| {
+            shared.lock().unwrap().insert(device.clone(), location.clone());
+            (device, location)
+        })?;
+        Ok(Devices { rx, token, addr, latest, base_url: self.base_url(addr) })
+    }
+}
+
+/// `Weim::devices()`가 돌려주는 다중 기기 세션
+///
+/// 반복자로 쓰면 위치가 들어오는 대로 `(기기, 위치)`를 돌려준다.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug)]
+pub struct Devices {
+    rx: Receiver<Result<(DeviceId, Location), WeimError>>,
+    token: CancellationToken,
+    addr: SocketAddr,
+    latest: Arc<Mutex<HashMap<DeviceId, Location>>>,
+    base_url: String,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Devices {
+    /// 서버가 열린 주소
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// 기기 이름을 정해서 접속하는 페이지 주소
+    pub fn url_for(&self, device: &str) -> String {
+        format!("{}/?device={}", self.base_url, encode_query(device))
+    }
+
+    /// 지금까지 위치를 보낸 기기별 마지막 위치
+    pub fn latest(&self) -> HashMap<DeviceId, Location> {
+        self.latest.lock().unwrap().clone()
+    }
+
+    /// 서버를 멈추는 토큰
+    pub fn cancellation_token(&self) -> CancellationToken {
+        self.token.clone()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Iterator for Devices {
+    type Item = Result<(DeviceId, Location), WeimError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.rx.recv().ok()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Drop for Devices {
+    fn drop(&mut self) {
+        self.token.cancel();
+    }
+}
+
+// 영문, 숫자와 일부 기호 말고는 %XX로 바꾼다
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn encode_query(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
@@ -0,0 +1,34 @@
+//! 좌표의 지형 고도를 찾아 채우는 기능 (`elevation` 기능 필요)
+
+mod open_meteo;
+mod srtm;
+
+pub use open_meteo::OpenMeteo;
+pub use srtm::Srtm;
+
+use std::fmt;
+
+use crate::console::{info, warning};
+use crate::i18n::tr;
+use crate::{Location, WeimError};
+
+/// 지형 고도 자료. 다른 서비스를 쓰려면 이 트레이트를 구현한다.
+pub trait ElevationSource: Send + Sync + fmt::Debug {
+    /// 해발 고도 (m). 자료가 없는 곳이면 `None`
+    fn elevation(&self, latitude: f64, longitude: f64) -> Result<Option<f64>, WeimError>;
+}
+
+/// 브라우저가 고도를 주지 않았으면 지형 고도로 채운다. 실패해도 위치는 그대로 둔다.
+pub(crate) fn annotate(source: &dyn ElevationSource, location: &mut Location) {
+    if location.altitude.is_some() {
+        return;
+    }
+    match source.elevation(location.latitude, location.longitude) {
+        Ok(Some(elevation)) => {
+            info!("{}", tr!("⛰️ 지형 고도: {:.1}m", "⛰️ Terrain elevation: {:.1}m", elevation));
+            location.altitude = Some(elevation);
+        }
+        Ok(None) => {}
+        Err(e) => warning!("{}", tr!("⚠️ 고도를 찾지 못했습니다: {}", "⚠️ Could not find the elevation: {}", e)),
+    }
+}
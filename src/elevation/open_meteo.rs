@@ -0,0 +1,44 @@
+use serde::Deserialize;
+
+use super::ElevationSource;
+use crate::{http, WeimError};
+
+const DEFAULT_ENDPOINT: &str = "https://api.open-meteo.com/v1/elevation";
+
+#[derive(Debug, Deserialize)]
+struct ElevationResponse {
+    #[serde(default)]
+    elevation: Vec<Option<f64>>,
+}
+
+/// Open-Meteo 고도 API (Copernicus DEM, 약 90m 격자)
+#[derive(Debug, Clone)]
+pub struct OpenMeteo {
+    endpoint: String,
+}
+
+impl Default for OpenMeteo {
+    fn default() -> Self {
+        OpenMeteo { endpoint: DEFAULT_ENDPOINT.to_string() }
+    }
+}
+
+impl OpenMeteo {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// API 주소 (기본값 api.open-meteo.com/v1/elevation)
+    pub fn endpoint(mut self, url: impl Into<String>) -> Self {
+        self.endpoint = url.into();
+        self
+    }
+}
+
+impl ElevationSource for OpenMeteo {
+    fn elevation(&self, latitude: f64, longitude: f64) -> Result<Option<f64>, WeimError> {
+        let query = [("latitude", latitude.to_string()), ("longitude", longitude.to_string())];
+        let response: ElevationResponse = http::get_json(&self.endpoint, &query)?;
+        Ok(response.elevation.first().copied().flatten().filter(|elevation| elevation.is_finite()))
+    }
+}
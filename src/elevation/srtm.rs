@@ -0,0 +1,100 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use super::ElevationSource;
+use crate::WeimError;
+use crate::i18n::tr;
+
+// 자료가 없는 칸
+const VOID: i16 = -32768;
+
+// 1°×1° 타일 하나. 북서쪽 모서리부터 행 순서로 저장된다.
+#[derive(Debug)]
+struct Tile {
+    size: usize,
+    samples: Vec<i16>,
+}
+
+impl Tile {
+    fn parse(bytes: &[u8]) -> Result<Self, WeimError> {
+        let count = bytes.len() / 2;
+        let size = (count as f64).sqrt() as usize;
+        if size < 2 || size * size != count || !bytes.len().is_multiple_of(2) {
+            return Err(WeimError::InvalidPayload(tr!("HGT 타일 크기가 올바르지 않습니다: {}바이트", "invalid HGT tile size: {} bytes", bytes.len())));
+        }
+        let samples = bytes.chunks_exact(2).map(|pair| i16::from_be_bytes([pair[0], pair[1]])).collect();
+        Ok(Tile { size, samples })
+    }
+
+    fn sample(&self, row: usize, column: usize) -> Option<f64> {
+        let value = self.samples[row * self.size + column];
+        (value != VOID).then_some(value as f64)
+    }
+
+    // 타일 안의 위치 (0.0..=1.0, 북쪽과 서쪽이 0) 주변 네 칸을 선형 보간한다
+    fn interpolate(&self, y: f64, x: f64) -> Option<f64> {
+        let last = (self.size - 1) as f64;
+        let (y, x) = (y.clamp(0.0, 1.0) * last, x.clamp(0.0, 1.0) * last);
+        let (row, column) = ((y as usize).min(self.size - 2), (x as usize).min(self.size - 2));
+        let (dy, dx) = (y - row as f64, x - column as f64);
+
+        let north = self.sample(row, column)? * (1.0 - dx) + self.sample(row, column + 1)? * dx;
+        let south = self.sample(row + 1, column)? * (1.0 - dx) + self.sample(row + 1, column + 1)? * dx;
+        Some(north * (1.0 - dy) + south * dy)
+    }
+}
+
+// 파일이 없는 타일은 `None`으로 기억한다
+type Tiles = HashMap<(i32, i32), Option<Arc<Tile>>>;
+
+/// 내려받아 둔 SRTM `.hgt` 타일에서 고도를 읽는다. 네트워크를 쓰지 않는다.
+///
+/// 타일 이름은 남서쪽 모서리 기준 `N37E126.hgt` 형식이고, 1″(3601²)와 3″(1201²) 타일을 모두 읽는다.
+/// 읽은 타일은 메모리에 남겨 두며, 복제한 값은 타일을 함께 쓴다.
+#[derive(Debug, Clone)]
+pub struct Srtm {
+    directory: PathBuf,
+    tiles: Arc<Mutex<Tiles>>,
+}
+
+impl Srtm {
+    /// `.hgt` 파일이 있는 폴더
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        Srtm { directory: directory.into(), tiles: Arc::default() }
+    }
+
+    fn tile(&self, south: i32, west: i32) -> Result<Option<Arc<Tile>>, WeimError> {
+        let mut tiles = self.tiles.lock().unwrap();
+        if let Some(tile) = tiles.get(&(south, west)) {
+            return Ok(tile.clone());
+        }
+
+        let name = format!(
+            "{}{:02}{}{:03}.hgt",
+            if south < 0 { 'S' } else { 'N' },
+            south.unsigned_abs(),
+            if west < 0 { 'W' } else { 'E' },
+            west.unsigned_abs()
+        );
+        let tile = match fs::read(self.directory.join(name)) {
+            Ok(bytes) => Some(Arc::new(Tile::parse(&bytes)?)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e.into()),
+        };
+        tiles.insert((south, west), tile.clone());
+        Ok(tile)
+    }
+}
+
+impl ElevationSource for Srtm {
+    fn elevation(&self, latitude: f64, longitude: f64) -> Result<Option<f64>, WeimError> {
+        let (south, west) = (latitude.floor(), longitude.floor());
+        let Some(tile) = self.tile(south as i32, west as i32)? else {
+            return Ok(None);
+        };
+        Ok(tile.interpolate(south + 1.0 - latitude, longitude - west))
+    }
+}
@@ -0,0 +1,73 @@
+#[cfg(any(feature = "geocoding", feature = "elevation", feature = "weather"))]
+use std::sync::Arc;
+
+#[cfg(feature = "elevation")]
+use crate::elevation::{self, ElevationSource};
+#[cfg(feature = "geocoding")]
+use crate::geocode::{self, Geocoder};
+use crate::locator::Config;
+#[cfg(feature = "weather")]
+use crate::weather::{self, WeatherSource};
+use crate::Location;
+
+/// 받은 위치를 기록하기 전에 외부 자료(주소, 고도, 날씨)로 채운다.
+#[derive(Clone)]
+pub(crate) struct Enricher {
+    #[cfg(feature = "geocoding")]
+    geocoder: Option<Arc<dyn Geocoder>>,
+    #[cfg(feature = "elevation")]
+    elevation: Option<Arc<dyn ElevationSource>>,
+    #[cfg(feature = "weather")]
+    weather: Option<Arc<dyn WeatherSource>>,
+}
+
+impl Enricher {
+    #[cfg_attr(not(any(feature = "geocoding", feature = "elevation", feature = "weather")), allow(unused_variables))]
+    pub(crate) fn new(config: &Config) -> Self {
+        Enricher {
+            #[cfg(feature = "geocoding")]
+            geocoder: config.geocoder.clone(),
+            #[cfg(feature = "elevation")]
+            elevation: config.elevation.clone(),
+            #[cfg(feature = "weather")]
+            weather: config.weather.clone(),
+        }
+    }
+
+    /// 채울 것이 없으면 블로킹 스레드를 띄울 필요가 없다.
+    #[cfg(feature = "tokio")]
+    pub(crate) fn is_empty(&self) -> bool {
+        #[allow(unused_mut)]
+        let mut empty = true;
+        #[cfg(feature = "geocoding")]
+        {
+            empty &= self.geocoder.is_none();
+        }
+        #[cfg(feature = "elevation")]
+        {
+            empty &= self.elevation.is_none();
+        }
+        #[cfg(feature = "weather")]
+        {
+            empty &= self.weather.is_none();
+        }
+        empty
+    }
+
+    /// 외부 요청을 할 수 있으므로 블로킹된다. 실패한 항목은 비워 둔다.
+    #[cfg_attr(not(any(feature = "geocoding", feature = "elevation", feature = "weather")), allow(unused_variables))]
+    pub(crate) fn apply(&self, location: &mut Location) {
+        #[cfg(feature = "geocoding")]
+        if let Some(geocoder) = &self.geocoder {
+            geocode::annotate(geocoder.as_ref(), location);
+        }
+        #[cfg(feature = "elevation")]
+        if let Some(source) = &self.elevation {
+            elevation::annotate(source.as_ref(), location);
+        }
+        #[cfg(feature = "weather")]
+        if let Some(source) = &self.weather {
+            weather::annotate(source.as_ref(), location);
+        }
+    }
+}
@@ -0,0 +1,109 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+use crate::i18n::{pick, tr};
+
+/// weim 실행 중 발생할 수 있는 오류
+#[derive(Debug)]
+pub enum WeimError {
+    /// 로컬 서버 포트를 열지 못함
+    Bind {
+        addr: String,
+        source: Box<dyn Error + Send + Sync + 'static>,
+    },
+    /// 브라우저 실행 실패
+    BrowserLaunch(io::Error),
+    /// 열어 둔 브라우저가 정해진 시간 안에 페이지를 받아 가지 않음
+    BrowserNotReached,
+    /// 페이지를 연 브라우저가 위치를 보내지 않고 탭을 닫았거나 정해진 시간 안에 답하지 않음
+    NoResponse,
+    /// 사용자가 위치 권한을 거부함
+    PermissionDenied,
+    /// 제한 시간 안에 위치를 받지 못함 (브라우저의 측정 시간 초과 포함)
+    Timeout,
+    /// 브라우저가 위치를 측정하지 못함 (브라우저가 알려 준 메시지)
+    PositionUnavailable(String),
+    /// 브라우저가 Geolocation API를 지원하지 않음
+    Unsupported,
+    /// `CancellationToken`으로 요청이 중단됨
+    Cancelled,
+    /// 브라우저가 보낸 데이터를 해석할 수 없음
+    InvalidPayload(String),
+    /// 위치를 받기 전에 서버가 종료됨
+    ServerClosed,
+    /// 재생할 위치가 더 없음 (가짜 위치 목록, 경로 재생 등)
+    Exhausted,
+    /// 운영체제 위치 API 오류
+    Platform(String),
+    /// 권한을 물어볼 위치 에이전트가 없음 (GeoClue2 등)
+    AgentUnavailable(String),
+    /// 파일이나 장치 입출력 오류
+    Io(io::Error),
+    /// 외부 서비스와 통신 실패
+    Network(String),
+    /// HTTPS 인증서를 준비하지 못함
+    Tls(String),
+    /// 위치 기록 저장소 오류
+    Storage(String),
+    /// 설정 파일이나 `WEIM_*` 환경 변수를 해석할 수 없음
+    Config(String),
+    /// 대체 체인의 모든 공급자가 실패함 (공급자 이름, 오류)
+    AllProvidersFailed(Vec<(String, WeimError)>),
+}
+
+impl fmt::Display for WeimError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WeimError::Bind { addr, source } => write!(f, "{}", tr!("{} 주소를 열 수 없습니다: {}", "cannot open {}: {}", addr, source)),
+            WeimError::BrowserLaunch(e) => write!(f, "{}", tr!("브라우저를 실행할 수 없습니다: {}", "cannot launch the browser: {}", e)),
+            WeimError::BrowserNotReached => {
+                f.write_str(pick("브라우저가 위치 페이지를 열지 않았습니다", "the browser never opened the location page"))
+            }
+            WeimError::NoResponse => {
+                f.write_str(pick("브라우저가 위치를 보내지 않고 페이지를 떠났습니다", "the browser left the page without sending a location"))
+            }
+            WeimError::PermissionDenied => f.write_str(pick("위치 권한이 거부되었습니다", "location permission was denied")),
+            WeimError::Timeout => f.write_str(pick("위치 응답 시간이 초과되었습니다", "timed out waiting for a location")),
+            WeimError::PositionUnavailable(msg) => {
+                write!(f, "{}", tr!("브라우저가 위치를 측정하지 못했습니다: {}", "the browser could not determine the position: {}", msg))
+            }
+            WeimError::Unsupported => f.write_str(pick("브라우저가 위치 정보를 지원하지 않습니다", "the browser does not support geolocation")),
+            WeimError::Cancelled => f.write_str(pick("위치 요청이 취소되었습니다", "the location request was cancelled")),
+            WeimError::InvalidPayload(msg) => write!(f, "{}", tr!("잘못된 위치 데이터: {}", "invalid location data: {}", msg)),
+            WeimError::ServerClosed => f.write_str(pick("위치를 받기 전에 서버가 종료되었습니다", "the server closed before a location arrived")),
+            WeimError::Exhausted => f.write_str(pick("더 이상 돌려줄 위치가 없습니다", "no more locations to return")),
+            WeimError::Platform(msg) => write!(f, "{}", tr!("운영체제 위치 API 오류: {}", "OS location API error: {}", msg)),
+            WeimError::AgentUnavailable(msg) => write!(f, "{}", tr!("위치 에이전트를 사용할 수 없습니다: {}", "location agent unavailable: {}", msg)),
+            WeimError::Io(e) => write!(f, "{}", tr!("입출력 오류: {}", "I/O error: {}", e)),
+            WeimError::Network(msg) => write!(f, "{}", tr!("네트워크 오류: {}", "network error: {}", msg)),
+            WeimError::Tls(msg) => write!(f, "{}", tr!("HTTPS 인증서를 준비할 수 없습니다: {}", "cannot prepare the HTTPS certificate: {}", msg)),
+            WeimError::Storage(msg) => write!(f, "{}", tr!("위치 기록 저장소 오류: {}", "location history storage error: {}", msg)),
+            WeimError::Config(msg) => write!(f, "{}", tr!("설정 오류: {}", "configuration error: {}", msg)),
+            WeimError::AllProvidersFailed(errors) => {
+                f.write_str(pick("모든 위치 공급자가 실패했습니다", "all location providers failed"))?;
+                for (name, e) in errors {
+                    write!(f, "\n  - {}: {}", name, e)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Error for WeimError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            WeimError::Bind { source, .. } => Some(source.as_ref()),
+            WeimError::BrowserLaunch(e) => Some(e),
+            WeimError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for WeimError {
+    fn from(e: io::Error) -> Self {
+        WeimError::Io(e)
+    }
+}
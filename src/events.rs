@@ -0,0 +1,83 @@
+use std::io::Write;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::Mutex;
+use std::time::Duration;
+use serde::Serialize;
+
+use crate::handler::Reply;
+use crate::{DeviceId, Location};
+
+/// 연결이 살아 있는지 확인하려고 보내는 주석의 간격
+pub(crate) const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(15);
+pub(crate) const KEEPALIVE: &str = ": keepalive\n\n";
+
+type Subscriber = Box<dyn Fn(&str) -> bool + Send>;
+
+/// 기기 이름을 붙인 위치 JSON
+#[derive(Serialize)]
+pub(crate) struct FixEvent<'a> {
+    pub(crate) device: &'a DeviceId,
+    #[serde(flatten)]
+    pub(crate) location: &'a Location,
+}
+
+/// `/events` (Server-Sent Events) 구독자들에게 받은 위치를 나눠 준다.
+#[derive(Default)]
+pub(crate) struct EventHub {
+    subscribers: Mutex<Vec<Subscriber>>,
+}
+
+impl EventHub {
+    /// `send`가 false를 돌려주면 연결이 끊긴 것으로 보고 뺀다.
+    pub(crate) fn subscribe(&self, send: impl Fn(&str) -> bool + Send + 'static) {
+        self.subscribers.lock().unwrap().push(Box::new(send));
+    }
+
+    pub(crate) fn publish(&self, device: &DeviceId, location: &Location) {
+        let Ok(json) = serde_json::to_string(&FixEvent { device, location }) else {
+            return;
+        };
+        let frame = format!("event: fix\ndata: {}\n\n", json);
+        self.subscribers.lock().unwrap().retain(|send| send(&frame));
+    }
+
+    /// 새 구독자의 이벤트를 받을 채널
+    pub(crate) fn channel(&self) -> Receiver<String> {
+        let (tx, rx) = mpsc::channel();
+        self.subscribe(move |frame| tx.send(frame.to_string()).is_ok());
+        rx
+    }
+
+    /// 서버를 닫을 때 모든 스트림을 끝낸다.
+    #[cfg(feature = "tokio")]
+    pub(crate) fn close(&self) {
+        self.subscribers.lock().unwrap().clear();
+    }
+}
+
+/// 본문 길이 없이 연결이 끊길 때까지 이어지는 응답 머리
+pub(crate) fn stream_head(reply: &Reply) -> String {
+    let mut head = format!("HTTP/1.1 {} OK\r\nConnection: keep-alive\r\n", reply.status);
+    for (name, value) in &reply.headers {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    head.push_str("\r\n");
+    head
+}
+
+/// tiny_http 연결을 넘겨받아 구독이 끝날 때까지 이벤트를 쓴다. 호출한 스레드를 막는다.
+pub(crate) fn stream(rx: Receiver<String>, head: String, mut writer: impl Write) {
+    if writer.write_all(head.as_bytes()).and_then(|_| writer.flush()).is_err() {
+        return;
+    }
+    loop {
+        let frame = match rx.recv_timeout(KEEPALIVE_INTERVAL) {
+            Ok(frame) => frame,
+            Err(RecvTimeoutError::Timeout) => KEEPALIVE.to_string(),
+            Err(RecvTimeoutError::Disconnected) => return,
+        };
+        if writer.write_all(frame.as_bytes()).and_then(|_| writer.flush()).is_err() {
+            return;
+        }
+    }
+}
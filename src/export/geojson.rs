@@ -0,0 +1,64 @@
+use geojson::{Feature, FeatureCollection, Geometry, Position};
+
+use super::{rfc3339, Track};
+use crate::Location;
+
+impl Location {
+    /// 위치를 GeoJSON Point Feature로 바꾼다. (`geojson` 기능 필요)
+    ///
+    /// 정확도, 시각과 함께 값이 있는 고도, 속도, 방향이 속성으로 들어간다.
+    pub fn to_geojson(&self) -> Feature {
+        let mut feature = Feature::from(Geometry::new_point(position(self)));
+        feature.set_property("accuracy", self.accuracy);
+        feature.set_property("timestamp", self.timestamp.timestamp_millis());
+        feature.set_property("time", rfc3339(self.timestamp));
+        let optional = [
+            ("altitude", self.altitude),
+            ("altitude_accuracy", self.altitude_accuracy),
+            ("speed", self.speed),
+            ("heading", self.heading),
+        ];
+        for (key, value) in optional {
+            if let Some(value) = value {
+                feature.set_property(key, value);
+            }
+        }
+        if let Some(address) = &self.address {
+            feature.set_property("address", address.clone());
+        }
+        if let Some(weather) = self.weather.as_ref().and_then(|weather| serde_json::to_value(weather).ok()) {
+            feature.set_property("weather", weather);
+        }
+        feature
+    }
+}
+
+/// 트랙마다 LineString Feature 하나로 된 FeatureCollection을 만든다. (`geojson` 기능 필요)
+///
+/// 위치가 하나뿐인 트랙은 Point가 된다.
+pub fn geojson_tracks(tracks: &[Track]) -> FeatureCollection {
+    tracks
+        .iter()
+        .filter_map(|track| {
+            let (first, last) = (track.points.first()?, track.points.last()?);
+            let mut feature = if track.points.len() == 1 {
+                first.to_geojson()
+            } else {
+                Feature::from(Geometry::new_line_string(track.points.iter().map(position)))
+            };
+            feature.set_property("name", track.name.clone());
+            feature.set_property("start", rfc3339(first.timestamp));
+            feature.set_property("end", rfc3339(last.timestamp));
+            feature.set_property("points", track.points.len());
+            Some(feature)
+        })
+        .collect()
+}
+
+// GeoJSON 좌표 순서는 경도, 위도, 고도
+fn position(location: &Location) -> Position {
+    match location.altitude {
+        Some(altitude) => Position::from(vec![location.longitude, location.latitude, altitude]),
+        None => Position::from([location.longitude, location.latitude]),
+    }
+}
@@ -0,0 +1,36 @@
+use std::io::{self, Write};
+use std::time::Duration;
+
+use super::{escape_xml, rfc3339, segments, simplify, Track};
+
+// GPX 읽기와 같은 HDOP 1당 오차 (m)
+const METERS_PER_HDOP: f64 = 5.0;
+
+/// 트랙을 GPX 1.1 문서로 쓴다. 위치 사이가 `split_gap`보다 벌어지면 구간(`<trkseg>`)을 나눈다.
+///
+/// `tolerance`(m)가 0보다 크면 구간마다 `simplify()`로 위치를 줄인다. 구간을 먼저 나누므로 공백 양쪽 위치는 남는다.
+pub fn write_gpx<W: Write>(mut out: W, tracks: &[Track], split_gap: Duration, tolerance: f64) -> io::Result<()> {
+    let gap = split_gap.as_millis().min(i64::MAX as u128) as i64;
+
+    writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(out, r#"<gpx version="1.1" creator="weim" xmlns="http://www.topografix.com/GPX/1/1">"#)?;
+    for track in tracks.iter().filter(|track| !track.points.is_empty()) {
+        writeln!(out, "  <trk>")?;
+        writeln!(out, "    <name>{}</name>", escape_xml(&track.name))?;
+        for segment in segments(&track.points, gap) {
+            writeln!(out, "    <trkseg>")?;
+            for point in &simplify(segment, tolerance) {
+                write!(out, r#"      <trkpt lat="{}" lon="{}">"#, point.latitude, point.longitude)?;
+                if let Some(altitude) = point.altitude {
+                    write!(out, "<ele>{}</ele>", altitude)?;
+                }
+                write!(out, "<time>{}</time>", rfc3339(point.timestamp))?;
+                write!(out, "<hdop>{:.2}</hdop>", point.accuracy / METERS_PER_HDOP)?;
+                writeln!(out, "</trkpt>")?;
+            }
+            writeln!(out, "    </trkseg>")?;
+        }
+        writeln!(out, "  </trk>")?;
+    }
+    writeln!(out, "</gpx>")
+}
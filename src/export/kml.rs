@@ -0,0 +1,59 @@
+use std::io::{self, Write};
+
+use super::{escape_xml, rfc3339, Track};
+use crate::Location;
+use crate::i18n::tr;
+
+const HEADER: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<kml xmlns="http://www.opengis.net/kml/2.2" xmlns:gx="http://www.google.com/kml/ext/2.2">
+  <Document>
+    <name>weim</name>
+"#;
+const FOOTER: &str = "  </Document>\n</kml>\n";
+
+impl Location {
+    /// 위치 하나를 Google Earth에서 열 수 있는 KML 문서(Placemark)로 만든다.
+    pub fn to_kml(&self) -> String {
+        let coordinates = match self.altitude {
+            Some(altitude) => format!("{},{},{}", self.longitude, self.latitude, altitude),
+            None => format!("{},{}", self.longitude, self.latitude),
+        };
+        let mut kml = String::from(HEADER);
+        kml.push_str("    <Placemark>\n      <name>weim</name>\n");
+        kml.push_str(&tr!("      <description>정확도 {:.1}m</description>\n", "      <description>Accuracy {:.1}m</description>\n", self.accuracy));
+        kml.push_str(&format!("      <TimeStamp><when>{}</when></TimeStamp>\n", rfc3339(self.timestamp)));
+        kml.push_str(&format!("      <Point><coordinates>{}</coordinates></Point>\n", coordinates));
+        kml.push_str("    </Placemark>\n");
+        kml.push_str(FOOTER);
+        kml
+    }
+}
+
+/// 트랙마다 시각이 붙은 `gx:Track` Placemark 하나로 된 KML 문서를 쓴다.
+pub fn write_kml<W: Write>(mut out: W, tracks: &[Track]) -> io::Result<()> {
+    out.write_all(HEADER.as_bytes())?;
+    for track in tracks.iter().filter(|track| !track.points.is_empty()) {
+        // 고도가 하나도 없으면 지면에 붙여서 그린다
+        let has_altitude = track.points.iter().any(|point| point.altitude.is_some());
+        writeln!(out, "    <Placemark>")?;
+        writeln!(out, "      <name>{}</name>", escape_xml(&track.name))?;
+        writeln!(out, "      <gx:Track>")?;
+        writeln!(out, "        <altitudeMode>{}</altitudeMode>", if has_altitude { "absolute" } else { "clampToGround" })?;
+        // gx:Track은 모든 <when> 다음에 같은 순서로 <gx:coord>가 온다
+        for point in &track.points {
+            writeln!(out, "        <when>{}</when>", rfc3339(point.timestamp))?;
+        }
+        for point in &track.points {
+            writeln!(
+                out,
+                "        <gx:coord>{} {} {}</gx:coord>",
+                point.longitude,
+                point.latitude,
+                point.altitude.unwrap_or(0.0)
+            )?;
+        }
+        writeln!(out, "      </gx:Track>")?;
+        writeln!(out, "    </Placemark>")?;
+    }
+    out.write_all(FOOTER.as_bytes())
+}
@@ -0,0 +1,49 @@
+//! 위치 기록을 다른 프로그램에서 열 수 있는 형식으로 내보내기
+
+#[cfg(feature = "geojson")]
+mod geojson;
+mod gpx;
+mod kml;
+mod simplify;
+
+#[cfg(feature = "geojson")]
+pub use geojson::geojson_tracks;
+pub use gpx::write_gpx;
+pub use kml::write_kml;
+pub use simplify::simplify;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+
+use crate::Location;
+
+/// 이름이 붙은 위치 목록. 내보낼 때 기기 하나가 트랙 하나가 된다.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Track {
+    pub name: String,
+    /// 시간순 위치
+    pub points: Vec<Location>,
+}
+
+impl Track {
+    pub fn new(name: impl Into<String>, points: Vec<Location>) -> Self {
+        Track { name: name.into(), points }
+    }
+}
+
+// 위치 사이의 간격이 `gap`(ms)보다 크면 나눈다
+fn segments(points: &[Location], gap: i64) -> impl Iterator<Item = &[Location]> {
+    points.chunk_by(move |a, b| (b.timestamp - a.timestamp).num_milliseconds() <= gap)
+}
+
+// XML 속성과 본문에 넣을 문자열
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+pub(crate) fn rfc3339(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
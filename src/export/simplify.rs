@@ -0,0 +1,50 @@
+use super::Track;
+use crate::geo::EARTH_RADIUS;
+use crate::Location;
+
+/// Douglas–Peucker로 트랙 모양에서 `tolerance`(m) 안쪽으로만 벗어나는 위치를 뺀다.
+///
+/// 처음과 끝 위치는 늘 남는다. `tolerance`가 0 이하면 그대로 돌려준다.
+pub fn simplify(points: &[Location], tolerance: f64) -> Vec<Location> {
+    if points.len() < 3 || tolerance.is_nan() || tolerance <= 0.0 {
+        return points.to_vec();
+    }
+    let mut keep = vec![false; points.len()];
+    keep[0] = true;
+    keep[points.len() - 1] = true;
+    // 재귀 대신 남은 구간을 쌓아 둔다. 긴 기록에서도 스택이 넘치지 않는다.
+    let mut ranges = vec![(0, points.len() - 1)];
+    while let Some((first, last)) = ranges.pop() {
+        let farthest = (first + 1..last)
+            .map(|i| (i, offset(&points[i], &points[first], &points[last])))
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+        if let Some((i, distance)) = farthest
+            && distance > tolerance
+        {
+            keep[i] = true;
+            ranges.push((first, i));
+            ranges.push((i, last));
+        }
+    }
+    points.iter().zip(keep).filter(|(_, keep)| *keep).map(|(point, _)| point.clone()).collect()
+}
+
+impl Track {
+    /// `simplify()`로 위치를 줄인다.
+    pub fn simplify(&mut self, tolerance: f64) {
+        self.points = simplify(&self.points, tolerance);
+    }
+}
+
+// `point`에서 `from`–`to` 선분까지의 거리 (m). 짧은 거리라 `from` 둘레를 평면으로 본다.
+fn offset(point: &Location, from: &Location, to: &Location) -> f64 {
+    let scale = from.latitude.to_radians().cos();
+    let plane = |location: &Location| {
+        let longitude = (location.longitude - from.longitude + 540.0).rem_euclid(360.0) - 180.0;
+        (longitude.to_radians() * scale * EARTH_RADIUS, (location.latitude - from.latitude).to_radians() * EARTH_RADIUS)
+    };
+    let ((px, py), (dx, dy)) = (plane(point), plane(to));
+    let length = dx * dx + dy * dy;
+    let t = if length > 0.0 { ((px * dx + py * dy) / length).clamp(0.0, 1.0) } else { 0.0 };
+    (px - t * dx).hypot(py - t * dy)
+}
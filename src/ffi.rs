@@ -0,0 +1,135 @@
+//! C, C++, C# 같은 다른 언어에서 부르는 함수 (`ffi` 기능 필요)
+//!
+//! `cargo build --release --features ffi`로 만든 `target/release/libweim.so`(macOS는 `.dylib`, Windows는 `weim.dll`)나
+//! 정적 라이브러리 `libweim.a`(`weim.lib`)를 링크하고, 선언은 `include/weim.h`에 있다. 모든 함수는 위치를 받거나 실패할 때까지 부른 스레드를 막는다.
+
+use std::ffi::{c_char, CString};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+use std::time::Duration;
+
+use crate::{Location, Weim, WeimError};
+
+/// `WeimLocation::status`
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WeimStatus {
+    Ok = 0,
+    Timeout = 1,
+    PermissionDenied = 2,
+    Unsupported = 3,
+    PositionUnavailable = 4,
+    /// 그 밖의 오류. `error`에 까닭이 있다
+    Failed = 5,
+}
+
+/// `weim_locate()`가 돌려주는 위치. 다 쓰면 `weim_location_free()`로 놓아준다.
+///
+/// 실패하면 `status`가 0이 아니고 좌표는 0이다. 값이 없는 항목은 NaN이다.
+#[repr(C)]
+#[derive(Debug)]
+pub struct WeimLocation {
+    pub status: WeimStatus,
+    pub latitude: f64,
+    pub longitude: f64,
+    /// 정확도 (m)
+    pub accuracy: f64,
+    pub altitude: f64,
+    pub altitude_accuracy: f64,
+    /// 속도 (m/s)
+    pub speed: f64,
+    /// 진행 방향 (°, 북쪽 기준 시계 방향)
+    pub heading: f64,
+    /// 측정 시각 (Unix epoch, ms)
+    pub timestamp: i64,
+    /// 실패했을 때 현재 언어로 쓴 UTF-8 오류 메시지. 성공하면 NULL
+    pub error: *mut c_char,
+}
+
+impl WeimLocation {
+    fn new(result: Result<Location, WeimError>) -> Self {
+        match result {
+            Ok(location) => WeimLocation {
+                status: WeimStatus::Ok,
+                latitude: location.latitude,
+                longitude: location.longitude,
+                accuracy: location.accuracy,
+                altitude: location.altitude.unwrap_or(f64::NAN),
+                altitude_accuracy: location.altitude_accuracy.unwrap_or(f64::NAN),
+                speed: location.speed.unwrap_or(f64::NAN),
+                heading: location.heading.unwrap_or(f64::NAN),
+                timestamp: location.timestamp.timestamp_millis(),
+                error: ptr::null_mut(),
+            },
+            Err(e) => {
+                let status = match e {
+                    WeimError::Timeout => WeimStatus::Timeout,
+                    WeimError::PermissionDenied => WeimStatus::PermissionDenied,
+                    WeimError::Unsupported => WeimStatus::Unsupported,
+                    WeimError::PositionUnavailable(_) => WeimStatus::PositionUnavailable,
+                    _ => WeimStatus::Failed,
+                };
+                WeimLocation::failed(status, &e.to_string())
+            }
+        }
+    }
+
+    fn failed(status: WeimStatus, message: &str) -> Self {
+        // C 문자열 안에는 NUL이 들어갈 수 없다
+        let message = CString::new(message.replace('\0', "")).unwrap_or_default();
+        WeimLocation {
+            status,
+            latitude: 0.0,
+            longitude: 0.0,
+            accuracy: 0.0,
+            altitude: f64::NAN,
+            altitude_accuracy: f64::NAN,
+            speed: f64::NAN,
+            heading: f64::NAN,
+            timestamp: 0,
+            error: message.into_raw(),
+        }
+    }
+}
+
+// 패닉이 C 쪽으로 넘어가지 않게 막고 결과를 힙에 올린다
+fn locate(weim: Weim) -> *mut WeimLocation {
+    let location = match panic::catch_unwind(AssertUnwindSafe(|| weim.locate())) {
+        Ok(result) => WeimLocation::new(result),
+        Err(_) => WeimLocation::failed(WeimStatus::Failed, "panic"),
+    };
+    Box::into_raw(Box::new(location))
+}
+
+/// 기본 설정으로 브라우저에서 위치를 받는다. 위치가 올 때까지 기다린다.
+#[unsafe(no_mangle)]
+pub extern "C" fn weim_locate() -> *mut WeimLocation {
+    locate(Weim::default())
+}
+
+/// `timeout_ms` 밀리초 안에 위치를 받지 못하면 `WEIM_STATUS_TIMEOUT`으로 끝난다. 0이면 `weim_locate()`와 같다.
+#[unsafe(no_mangle)]
+pub extern "C" fn weim_locate_with_timeout(timeout_ms: u64) -> *mut WeimLocation {
+    match timeout_ms {
+        0 => locate(Weim::default()),
+        ms => locate(Weim::builder().timeout(Duration::from_millis(ms)).build()),
+    }
+}
+
+/// `weim_locate()`가 돌려준 위치와 오류 메시지를 놓아준다. NULL이면 아무것도 하지 않는다.
+///
+/// # Safety
+///
+/// `location`은 NULL이거나 `weim_locate()`, `weim_locate_with_timeout()`이 돌려준 뒤 아직 놓아주지 않은 포인터여야 한다.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn weim_location_free(location: *mut WeimLocation) {
+    if location.is_null() {
+        return;
+    }
+    // SAFETY: 위 약속대로 이 모듈이 Box::into_raw로 만든 포인터다
+    let location = unsafe { Box::from_raw(location) };
+    if !location.error.is_null() {
+        // SAFETY: error는 CString::into_raw로 만든 것이다
+        drop(unsafe { CString::from_raw(location.error) });
+    }
+}
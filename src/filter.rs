@@ -0,0 +1,222 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::geo::{haversine, initial_bearing, METERS_PER_DEGREE};
+use crate::Location;
+
+// 이보다 느리면 방향을 믿을 수 없다 (m/s)
+const MIN_HEADING_SPEED: f64 = 0.5;
+// 정확도가 0으로 들어와도 측정을 완전히 믿지 않도록 하는 최솟값 (m)
+const MIN_ACCURACY: f64 = 0.1;
+
+/// 칼만 필터를 거친 위치와 추정 속도
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FilteredLocation {
+    pub latitude: f64,
+    pub longitude: f64,
+    /// 추정 위치의 오차 (m)
+    pub accuracy: f64,
+    /// 추정 속력 (m/s)
+    pub speed: f64,
+    /// 추정 진행 방향 (도, 북쪽 0 시계 방향). 거의 멈춰 있으면 `None`
+    pub heading: Option<f64>,
+    /// 잰 속력 (m/s). 브라우저가 알려 준 값, 없으면 앞 위치들에서 움직인 거리와 시간으로 잰 값이다.
+    /// 첫 위치처럼 잴 수 없으면 `None`
+    pub measured_speed: Option<f64>,
+    /// 잰 진행 방향 (도). 브라우저가 알려 준 값, 없으면 앞 위치에서 온 방향이다. 거의 멈춰 있으면 `None`
+    pub measured_heading: Option<f64>,
+    pub timestamp: DateTime<Utc>,
+    /// 필터에 넣은 원래 위치
+    pub raw: Location,
+}
+
+impl From<FilteredLocation> for Location {
+    fn from(filtered: FilteredLocation) -> Self {
+        Location {
+            latitude: filtered.latitude,
+            longitude: filtered.longitude,
+            accuracy: filtered.accuracy,
+            speed: Some(filtered.speed),
+            heading: filtered.heading,
+            timestamp: filtered.timestamp,
+            ..filtered.raw
+        }
+    }
+}
+
+// 한 축의 위치와 속도 상태
+#[derive(Debug, Clone, Copy)]
+struct Axis {
+    position: f64,
+    velocity: f64,
+    // 공분산 [[pp, pv], [pv, vv]]
+    pp: f64,
+    pv: f64,
+    vv: f64,
+}
+
+impl Axis {
+    fn new(position: f64, variance: f64) -> Self {
+        // 처음에는 속도를 전혀 모른다
+        Axis { position, velocity: 0.0, pp: variance, pv: 0.0, vv: 1e3 }
+    }
+
+    fn predict(&mut self, dt: f64, noise: f64) {
+        self.position += self.velocity * dt;
+        self.pp += dt * (2.0 * self.pv + dt * self.vv) + noise * dt.powi(3) / 3.0;
+        self.pv += dt * self.vv + noise * dt.powi(2) / 2.0;
+        self.vv += noise * dt;
+    }
+
+    fn correct(&mut self, measured: f64, variance: f64) {
+        let s = self.pp + variance;
+        let (kp, kv) = (self.pp / s, self.pv / s);
+        let residual = measured - self.position;
+        self.position += kp * residual;
+        self.velocity += kv * residual;
+        self.vv -= kv * self.pv;
+        self.pv -= kv * self.pp;
+        self.pp -= kp * self.pp;
+    }
+}
+
+/// 등속 운동 모델로 위도/경도를 다듬고 속도를 추정하는 칼만 필터
+///
+/// 첫 위치를 원점으로 하는 평면(m)에서 동서, 남북 축을 따로 계산한다.
+#[derive(Debug, Clone)]
+pub struct KalmanFilter {
+    acceleration_noise: f64,
+    state: Option<State>,
+    measurement: Option<Measurement>,
+}
+
+// 속력과 방향을 잰 기준 위치와 그때 잰 값
+#[derive(Debug, Clone)]
+struct Measurement {
+    anchor: Location,
+    speed: f64,
+    heading: Option<f64>,
+}
+
+#[derive(Debug, Clone)]
+struct State {
+    origin: (f64, f64),
+    east: Axis,
+    north: Axis,
+    timestamp: DateTime<Utc>,
+}
+
+impl Default for KalmanFilter {
+    fn default() -> Self {
+        KalmanFilter { acceleration_noise: 1.0, state: None, measurement: None }
+    }
+}
+
+impl KalmanFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 예상하는 가속도의 분산 (m²/s⁴, 기본값 1.0). 클수록 새 측정값을 빨리 따라간다.
+    pub fn acceleration_noise(mut self, variance: f64) -> Self {
+        self.acceleration_noise = variance;
+        self
+    }
+
+    /// 지금까지의 상태를 지우고 다음 위치부터 새로 시작한다.
+    pub fn reset(&mut self) {
+        self.state = None;
+        self.measurement = None;
+    }
+
+    /// 위치 하나를 넣고 다듬은 결과를 돌려준다.
+    pub fn update(&mut self, location: &Location) -> FilteredLocation {
+        let variance = location.accuracy.max(MIN_ACCURACY).powi(2);
+
+        let state = match &mut self.state {
+            Some(state) => {
+                let (east, north) = to_plane(state.origin, location);
+                // 시각이 거꾸로 들어오면 예측 없이 측정만 반영한다
+                let dt = (location.timestamp - state.timestamp).as_seconds_f64().max(0.0);
+                for (axis, measured) in [(&mut state.east, east), (&mut state.north, north)] {
+                    axis.predict(dt, self.acceleration_noise);
+                    axis.correct(measured, variance);
+                }
+                state.timestamp = state.timestamp.max(location.timestamp);
+                state
+            }
+            None => self.state.insert(State {
+                origin: (location.latitude, location.longitude),
+                east: Axis::new(0.0, variance),
+                north: Axis::new(0.0, variance),
+                timestamp: location.timestamp,
+            }),
+        };
+
+        let (latitude, longitude) = from_plane(state.origin, state.east.position, state.north.position);
+        let speed = state.east.velocity.hypot(state.north.velocity);
+        let heading = (speed >= MIN_HEADING_SPEED)
+            .then(|| state.east.velocity.atan2(state.north.velocity).to_degrees().rem_euclid(360.0));
+
+        let timestamp = state.timestamp;
+        let accuracy = ((state.east.pp + state.north.pp) / 2.0).sqrt();
+        let (measured_speed, measured_heading) = self.measure(location);
+
+        FilteredLocation {
+            latitude,
+            longitude,
+            accuracy,
+            speed,
+            heading,
+            measured_speed,
+            measured_heading,
+            timestamp,
+            raw: location.clone(),
+        }
+    }
+
+    // 브라우저가 알려 준 값이 없으면 기준 위치에서 잰다.
+    // 두 위치의 오차를 합친 것보다 멀어져야 새로 재고 기준을 옮긴다. 그 전에는 지난 값을 쓰되,
+    // 그렇게 빨랐다면 벌써 벗어났을 것이므로 `오차 / 지난 시간`보다 크지 않게 줄여서 멈추면 0으로 내려가게 한다.
+    fn measure(&mut self, location: &Location) -> (Option<f64>, Option<f64>) {
+        let Some(measurement) = &mut self.measurement else {
+            self.measurement = Some(Measurement { anchor: location.clone(), speed: 0.0, heading: None });
+            return (location.speed, location.heading);
+        };
+        let anchor = &measurement.anchor;
+        let seconds = (location.timestamp - anchor.timestamp).as_seconds_f64();
+        if seconds <= 0.0 {
+            return (location.speed, location.heading);
+        }
+        let distance = haversine(anchor, location);
+        let noise = anchor.accuracy.max(0.0).hypot(location.accuracy.max(0.0));
+        if distance > noise {
+            let speed = distance / seconds;
+            let heading = (speed >= MIN_HEADING_SPEED).then(|| initial_bearing(anchor, location));
+            *measurement = Measurement { anchor: location.clone(), speed, heading };
+        } else if noise / seconds < MIN_HEADING_SPEED {
+            // 움직였어도 거의 멈춘 것이다. 기준을 옮겨 다시 떠날 때 속력이 낮게 잡히지 않게 한다.
+            *measurement = Measurement { anchor: location.clone(), speed: 0.0, heading: None };
+        } else {
+            measurement.speed = measurement.speed.min(noise / seconds);
+        }
+        let speed = location.speed.unwrap_or(measurement.speed);
+        // 방향은 속력이 있어야 믿을 수 있다
+        let heading = location.heading.or(measurement.heading).filter(|_| speed >= MIN_HEADING_SPEED);
+        (Some(speed), heading)
+    }
+}
+
+// 원점 기준 동쪽, 북쪽 거리 (m)
+fn to_plane(origin: (f64, f64), location: &Location) -> (f64, f64) {
+    let scale = origin.0.to_radians().cos();
+    (
+        (location.longitude - origin.1) * METERS_PER_DEGREE * scale,
+        (location.latitude - origin.0) * METERS_PER_DEGREE,
+    )
+}
+
+fn from_plane(origin: (f64, f64), east: f64, north: f64) -> (f64, f64) {
+    let scale = origin.0.to_radians().cos();
+    (origin.0 + north / METERS_PER_DEGREE, origin.1 + east / (METERS_PER_DEGREE * scale))
+}
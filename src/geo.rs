@@ -0,0 +1,212 @@
+//! 좌표 사이의 거리, 방위각, 중간점 같은 측지 계산
+//!
+//! 함수들은 `Location`이나 `(위도, 경도)` 튜플을 받는다. 각도는 모두 도(°) 단위다.
+//! `geo` 기능을 켜면 `geo_types::Point`와 `geo_types::Coord`(x = 경도, y = 위도)도 받는다.
+
+use crate::Location;
+
+/// 지구 평균 반지름 (m)
+pub const EARTH_RADIUS: f64 = 6_371_008.8;
+
+// 위도 1도의 길이 (m). 짧은 거리를 평면으로 어림할 때 쓴다
+pub(crate) const METERS_PER_DEGREE: f64 = 111_320.0;
+
+// WGS84 타원체
+const WGS84_A: f64 = 6_378_137.0;
+const WGS84_F: f64 = 1.0 / 298.257_223_563;
+const WGS84_B: f64 = WGS84_A * (1.0 - WGS84_F);
+const VINCENTY_ITERATIONS: usize = 200;
+
+/// 위도와 경도를 가진 값
+pub trait Coordinate {
+    /// 위도 (°)
+    fn latitude(&self) -> f64;
+    /// 경도 (°)
+    fn longitude(&self) -> f64;
+}
+
+impl Coordinate for Location {
+    fn latitude(&self) -> f64 {
+        self.latitude
+    }
+
+    fn longitude(&self) -> f64 {
+        self.longitude
+    }
+}
+
+/// `(위도, 경도)`
+impl Coordinate for (f64, f64) {
+    fn latitude(&self) -> f64 {
+        self.0
+    }
+
+    fn longitude(&self) -> f64 {
+        self.1
+    }
+}
+
+/// x = 경도, y = 위도 (`geo` 기능 필요)
+#[cfg(feature = "geo")]
+impl Coordinate for geo_types::Coord<f64> {
+    fn latitude(&self) -> f64 {
+        self.y
+    }
+
+    fn longitude(&self) -> f64 {
+        self.x
+    }
+}
+
+/// x = 경도, y = 위도 (`geo` 기능 필요)
+#[cfg(feature = "geo")]
+impl Coordinate for geo_types::Point<f64> {
+    fn latitude(&self) -> f64 {
+        self.y()
+    }
+
+    fn longitude(&self) -> f64 {
+        self.x()
+    }
+}
+
+/// 위치 목록은 `collect::<geo_types::LineString>()`으로 궤적 선이 된다. (`geo` 기능 필요)
+#[cfg(feature = "geo")]
+impl From<&Location> for geo_types::Coord<f64> {
+    fn from(location: &Location) -> Self {
+        geo_types::coord! { x: location.longitude, y: location.latitude }
+    }
+}
+
+#[cfg(feature = "geo")]
+impl From<Location> for geo_types::Coord<f64> {
+    fn from(location: Location) -> Self {
+        (&location).into()
+    }
+}
+
+#[cfg(feature = "geo")]
+impl From<&Location> for geo_types::Point<f64> {
+    fn from(location: &Location) -> Self {
+        geo_types::Point::new(location.longitude, location.latitude)
+    }
+}
+
+#[cfg(feature = "geo")]
+impl From<Location> for geo_types::Point<f64> {
+    fn from(location: Location) -> Self {
+        (&location).into()
+    }
+}
+
+impl<T: Coordinate + ?Sized> Coordinate for &T {
+    fn latitude(&self) -> f64 {
+        (**self).latitude()
+    }
+
+    fn longitude(&self) -> f64 {
+        (**self).longitude()
+    }
+}
+
+/// 구면 위의 대원 거리 (m). 오차는 0.5% 안쪽이다.
+pub fn haversine(from: impl Coordinate, to: impl Coordinate) -> f64 {
+    let (phi1, phi2) = (from.latitude().to_radians(), to.latitude().to_radians());
+    let d_phi = (to.latitude() - from.latitude()).to_radians();
+    let d_lambda = (to.longitude() - from.longitude()).to_radians();
+
+    let a = (d_phi / 2.0).sin().powi(2) + phi1.cos() * phi2.cos() * (d_lambda / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS * a.sqrt().asin()
+}
+
+/// WGS84 타원체 위의 측지 거리 (m, Vincenty 공식)
+///
+/// 거의 정반대편에 있는 두 점처럼 계산이 수렴하지 않으면 `None`이다.
+pub fn vincenty(from: impl Coordinate, to: impl Coordinate) -> Option<f64> {
+    let l = (to.longitude() - from.longitude()).to_radians();
+    let u1 = ((1.0 - WGS84_F) * from.latitude().to_radians().tan()).atan();
+    let u2 = ((1.0 - WGS84_F) * to.latitude().to_radians().tan()).atan();
+    let (sin_u1, cos_u1) = u1.sin_cos();
+    let (sin_u2, cos_u2) = u2.sin_cos();
+
+    let mut lambda = l;
+    for _ in 0..VINCENTY_ITERATIONS {
+        let (sin_lambda, cos_lambda) = lambda.sin_cos();
+        let sin_sigma = ((cos_u2 * sin_lambda).powi(2)
+            + (cos_u1 * sin_u2 - sin_u1 * cos_u2 * cos_lambda).powi(2))
+        .sqrt();
+        if sin_sigma == 0.0 {
+            // 같은 점
+            return Some(0.0);
+        }
+        let cos_sigma = sin_u1 * sin_u2 + cos_u1 * cos_u2 * cos_lambda;
+        let sigma = sin_sigma.atan2(cos_sigma);
+        let sin_alpha = cos_u1 * cos_u2 * sin_lambda / sin_sigma;
+        let cos_sq_alpha = 1.0 - sin_alpha * sin_alpha;
+        // 적도 위의 두 점이면 cos²α = 0
+        let cos_2sigma_m = if cos_sq_alpha == 0.0 { 0.0 } else { cos_sigma - 2.0 * sin_u1 * sin_u2 / cos_sq_alpha };
+        let c = WGS84_F / 16.0 * cos_sq_alpha * (4.0 + WGS84_F * (4.0 - 3.0 * cos_sq_alpha));
+
+        let previous = lambda;
+        lambda = l + (1.0 - c) * WGS84_F * sin_alpha
+            * (sigma + c * sin_sigma * (cos_2sigma_m + c * cos_sigma * (-1.0 + 2.0 * cos_2sigma_m * cos_2sigma_m)));
+
+        if (lambda - previous).abs() < 1e-12 {
+            let u_sq = cos_sq_alpha * (WGS84_A * WGS84_A - WGS84_B * WGS84_B) / (WGS84_B * WGS84_B);
+            let a = 1.0 + u_sq / 16384.0 * (4096.0 + u_sq * (-768.0 + u_sq * (320.0 - 175.0 * u_sq)));
+            let b = u_sq / 1024.0 * (256.0 + u_sq * (-128.0 + u_sq * (74.0 - 47.0 * u_sq)));
+            let delta_sigma = b * sin_sigma
+                * (cos_2sigma_m
+                    + b / 4.0
+                        * (cos_sigma * (-1.0 + 2.0 * cos_2sigma_m * cos_2sigma_m)
+                            - b / 6.0 * cos_2sigma_m * (-3.0 + 4.0 * sin_sigma * sin_sigma) * (-3.0 + 4.0 * cos_2sigma_m * cos_2sigma_m)));
+            return Some(WGS84_B * a * (sigma - delta_sigma));
+        }
+    }
+    None
+}
+
+/// `from`에서 `to`를 바라보고 출발할 때의 방위각 (°, 북쪽 기준 시계 방향, 0..360)
+pub fn initial_bearing(from: impl Coordinate, to: impl Coordinate) -> f64 {
+    let (phi1, phi2) = (from.latitude().to_radians(), to.latitude().to_radians());
+    let d_lambda = (to.longitude() - from.longitude()).to_radians();
+
+    let y = d_lambda.sin() * phi2.cos();
+    let x = phi1.cos() * phi2.sin() - phi1.sin() * phi2.cos() * d_lambda.cos();
+    y.atan2(x).to_degrees().rem_euclid(360.0)
+}
+
+/// 대원을 따라 `to`에 도착할 때의 방위각 (°, 0..360)
+pub fn final_bearing(from: impl Coordinate, to: impl Coordinate) -> f64 {
+    (initial_bearing(to, from) + 180.0).rem_euclid(360.0)
+}
+
+/// `start`에서 `bearing` 방향으로 `distance` 미터 간 지점 `(위도, 경도)`
+pub fn destination(start: impl Coordinate, bearing: f64, distance: f64) -> (f64, f64) {
+    let phi1 = start.latitude().to_radians();
+    let lambda1 = start.longitude().to_radians();
+    let theta = bearing.to_radians();
+    let delta = distance / EARTH_RADIUS;
+
+    let phi2 = (phi1.sin() * delta.cos() + phi1.cos() * delta.sin() * theta.cos()).asin();
+    let lambda2 = lambda1 + (theta.sin() * delta.sin() * phi1.cos()).atan2(delta.cos() - phi1.sin() * phi2.sin());
+    (phi2.to_degrees(), normalize_longitude(lambda2.to_degrees()))
+}
+
+/// 두 점을 잇는 대원의 중간점 `(위도, 경도)`
+pub fn midpoint(from: impl Coordinate, to: impl Coordinate) -> (f64, f64) {
+    let (phi1, phi2) = (from.latitude().to_radians(), to.latitude().to_radians());
+    let lambda1 = from.longitude().to_radians();
+    let d_lambda = (to.longitude() - from.longitude()).to_radians();
+
+    let bx = phi2.cos() * d_lambda.cos();
+    let by = phi2.cos() * d_lambda.sin();
+    let phi = (phi1.sin() + phi2.sin()).atan2(((phi1.cos() + bx).powi(2) + by * by).sqrt());
+    let lambda = lambda1 + by.atan2(phi1.cos() + bx);
+    (phi.to_degrees(), normalize_longitude(lambda.to_degrees()))
+}
+
+// -180..180 범위로 맞춘다
+pub(crate) fn normalize_longitude(longitude: f64) -> f64 {
+    (longitude + 540.0).rem_euclid(360.0) - 180.0
+}
@@ -0,0 +1,33 @@
+//! 좌표와 주소를 서로 바꾸는 지오코딩 (`geocoding` 기능 필요)
+
+mod nominatim;
+
+pub use nominatim::Nominatim;
+
+use std::fmt;
+
+use crate::console::{info, warning};
+use crate::i18n::tr;
+use crate::{Location, WeimError};
+
+/// 지오코딩 서비스. 다른 서비스를 쓰려면 이 트레이트를 구현한다.
+pub trait Geocoder: Send + Sync + fmt::Debug {
+    /// 장소 이름이나 주소로 찾은 위치들. 잘 맞는 순서이고 `address`에 찾은 이름이 들어간다.
+    fn geocode(&self, query: &str) -> Result<Vec<Location>, WeimError>;
+
+    /// 좌표를 사람이 읽을 수 있는 주소로 바꾼다. 주소가 없는 곳(바다 등)이면 `None`
+    fn reverse(&self, location: &Location) -> Result<Option<String>, WeimError>;
+}
+
+/// 받은 위치에 주소를 채우고 출력한다. 실패해도 위치는 그대로 둔다.
+pub(crate) fn annotate(geocoder: &dyn Geocoder, location: &mut Location) {
+    match geocoder.reverse(location) {
+        Ok(address) => {
+            if let Some(address) = &address {
+                info!("{}", tr!("📫 주소: {}", "📫 Address: {}", address));
+            }
+            location.address = address;
+        }
+        Err(e) => warning!("{}", tr!("⚠️ 주소를 찾지 못했습니다: {}", "⚠️ Could not find the address: {}", e)),
+    }
+}
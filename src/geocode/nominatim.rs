@@ -0,0 +1,205 @@
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use serde::Deserialize;
+
+use super::Geocoder;
+use crate::geo::haversine;
+use crate::{http, Location, WeimError};
+
+const DEFAULT_ENDPOINT: &str = "https://nominatim.openstreetmap.org";
+// 공개 Nominatim 서버의 이용 정책은 초당 1회
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(1);
+const CACHE_FILE: &str = "weim-nominatim.json";
+const SEARCH_LIMIT: usize = 5;
+
+#[derive(Debug, Deserialize)]
+struct ReverseResponse {
+    display_name: Option<String>,
+}
+
+// Nominatim은 숫자를 문자열로 준다
+#[derive(Debug, Deserialize)]
+struct SearchResult {
+    lat: String,
+    lon: String,
+    display_name: Option<String>,
+    // [남, 북, 서, 동]
+    #[serde(default)]
+    boundingbox: Vec<String>,
+}
+
+impl SearchResult {
+    fn into_location(self) -> Option<Location> {
+        let latitude: f64 = self.lat.parse().ok()?;
+        let longitude: f64 = self.lon.parse().ok()?;
+        // 장소 범위의 중심에서 모서리까지를 정확도로 쓴다
+        let bounds: Vec<f64> = self.boundingbox.iter().filter_map(|value| value.parse().ok()).collect();
+        let accuracy = match bounds.as_slice() {
+            [south, north, west, east] => haversine((*south, *west), (*north, *east)) / 2.0,
+            _ => 0.0,
+        };
+        Some(Location {
+            latitude,
+            longitude,
+            accuracy,
+            timestamp: chrono::Utc::now(),
+            address: self.display_name,
+            ..Location::default()
+        })
+    }
+}
+
+#[derive(Debug, Default)]
+struct State {
+    last_request: Option<Instant>,
+    // 처음 쓸 때 파일에서 읽는다
+    cache: Option<HashMap<String, String>>,
+}
+
+/// OpenStreetMap Nominatim 지오코더
+///
+/// 요청 사이에 최소 간격을 두고, 결과를 디스크에 저장해 같은 곳은 다시 묻지 않는다.
+/// 복제한 값은 캐시와 요청 간격을 함께 쓴다.
+#[derive(Debug, Clone)]
+pub struct Nominatim {
+    endpoint: String,
+    language: Option<String>,
+    min_interval: Duration,
+    cache_path: Option<PathBuf>,
+    state: Arc<Mutex<State>>,
+}
+
+impl Default for Nominatim {
+    fn default() -> Self {
+        Nominatim {
+            endpoint: DEFAULT_ENDPOINT.to_string(),
+            language: None,
+            min_interval: DEFAULT_INTERVAL,
+            cache_path: Some(env::temp_dir().join(CACHE_FILE)),
+            state: Arc::default(),
+        }
+    }
+}
+
+impl Nominatim {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Nominatim 서버 주소 (기본값 nominatim.openstreetmap.org). 직접 띄운 서버를 쓸 때 바꾼다.
+    pub fn endpoint(mut self, url: impl Into<String>) -> Self {
+        self.endpoint = url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// 주소 언어 (예: "ko", 기본값 서버 기본)
+    pub fn language(mut self, language: impl Into<String>) -> Self {
+        self.language = Some(language.into());
+        self
+    }
+
+    /// 요청 사이의 최소 간격 (기본값 1초)
+    pub fn min_interval(mut self, interval: Duration) -> Self {
+        self.min_interval = interval;
+        self
+    }
+
+    /// 결과를 저장할 파일 (기본값 임시 폴더의 weim-nominatim.json). `None`이면 메모리에만 둔다.
+    pub fn cache(mut self, path: Option<PathBuf>) -> Self {
+        self.cache_path = path;
+        self
+    }
+
+    // 약 11m 안의 위치는 같은 주소로 본다
+    fn cache_key(&self, location: &Location) -> String {
+        format!(
+            "{:.4},{:.4},{}",
+            location.latitude,
+            location.longitude,
+            self.language.as_deref().unwrap_or("")
+        )
+    }
+
+    // `min_interval`이 지나도록 기다린 뒤 요청한다
+    fn throttled<T>(&self, request: impl FnOnce() -> Result<T, WeimError>) -> Result<T, WeimError> {
+        let mut state = self.state.lock().unwrap();
+        if let Some(last) = state.last_request {
+            thread::sleep(self.min_interval.saturating_sub(last.elapsed()));
+        }
+        let result = request();
+        state.last_request = Some(Instant::now());
+        result
+    }
+
+    fn cached(&self, key: &str) -> Option<String> {
+        let mut state = self.state.lock().unwrap();
+        let cache = state.cache.get_or_insert_with(|| self.load_cache());
+        cache.get(key).cloned()
+    }
+
+    fn store(&self, key: String, address: String) {
+        let mut state = self.state.lock().unwrap();
+        let cache = state.cache.get_or_insert_with(|| self.load_cache());
+        cache.insert(key, address);
+        if let Some(path) = &self.cache_path {
+            // 캐시를 못 써도 결과는 돌려준다
+            if let Ok(json) = serde_json::to_string(cache) {
+                fs::write(path, json).ok();
+            }
+        }
+    }
+
+    fn load_cache(&self) -> HashMap<String, String> {
+        self.cache_path
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}/{}", self.endpoint, path)
+    }
+
+    fn language_query(&self) -> Vec<(&str, String)> {
+        self.language.iter().map(|language| ("accept-language", language.clone())).collect()
+    }
+}
+
+impl Geocoder for Nominatim {
+    fn geocode(&self, query: &str) -> Result<Vec<Location>, WeimError> {
+        let mut params = vec![
+            ("format", "jsonv2".to_string()),
+            ("q", query.to_string()),
+            ("limit", SEARCH_LIMIT.to_string()),
+        ];
+        params.extend(self.language_query());
+        let results: Vec<SearchResult> = self.throttled(|| http::get_json(&self.url("search"), &params))?;
+        Ok(results.into_iter().filter_map(SearchResult::into_location).collect())
+    }
+
+    fn reverse(&self, location: &Location) -> Result<Option<String>, WeimError> {
+        let key = self.cache_key(location);
+        if let Some(address) = self.cached(&key) {
+            return Ok(Some(address));
+        }
+
+        let mut query = vec![
+            ("format", "jsonv2".to_string()),
+            ("lat", location.latitude.to_string()),
+            ("lon", location.longitude.to_string()),
+        ];
+        query.extend(self.language_query());
+        let response: ReverseResponse = self.throttled(|| http::get_json(&self.url("reverse"), &query))?;
+
+        if let Some(address) = &response.display_name {
+            self.store(key, address.clone());
+        }
+        Ok(response.display_name)
+    }
+}
@@ -0,0 +1,366 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::geo::{haversine, Coordinate, METERS_PER_DEGREE};
+use crate::i18n::pick;
+use crate::{Location, WeimError};
+
+const DEFAULT_HYSTERESIS: f64 = 5.0;
+const DEFAULT_ACCURACY_WEIGHT: f64 = 0.5;
+
+/// 지오펜스 모양
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Shape {
+    /// 중심 `(위도, 경도)`와 반지름 (m)
+    Circle { center: (f64, f64), radius: f64 },
+    /// 꼭짓점 `(위도, 경도)` 목록. 마지막 점은 첫 점과 자동으로 이어진다. 3개보다 적으면 어떤 위치도 안에 들지 않는다.
+    Polygon(Vec<(f64, f64)>),
+}
+
+impl Shape {
+    // 경계까지의 거리 (m). 안쪽이면 음수
+    fn signed_distance(&self, point: (f64, f64)) -> f64 {
+        match self {
+            Shape::Circle { center, radius } => haversine(*center, point) - radius,
+            Shape::Polygon(vertices) => {
+                let edge = polygon_edge_distance(vertices, point);
+                if contains(vertices, point) { -edge } else { edge }
+            }
+        }
+    }
+}
+
+/// 이름이 붙은 영역 하나
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Geofence {
+    pub id: String,
+    pub shape: Shape,
+    /// 안에 이만큼 머무르면 `Dwell`을 한 번 보낸다.
+    pub dwell: Option<Duration>,
+}
+
+impl Geofence {
+    /// `center` 주변 `radius` 미터 원
+    pub fn circle(id: impl Into<String>, center: impl Coordinate, radius: f64) -> Self {
+        Geofence {
+            id: id.into(),
+            shape: Shape::Circle { center: (center.latitude(), center.longitude()), radius },
+            dwell: None,
+        }
+    }
+
+    /// 꼭짓점 `(위도, 경도)`로 만든 다각형. 꼭짓점이 3개보다 적으면 `WeimError::InvalidPayload`
+    pub fn polygon(id: impl Into<String>, vertices: Vec<(f64, f64)>) -> Result<Self, WeimError> {
+        if vertices.len() < 3 {
+            return Err(WeimError::InvalidPayload(pick("다각형 지오펜스에는 꼭짓점이 3개 이상 필요합니다", "a polygon geofence needs at least 3 vertices").to_string()));
+        }
+        Ok(Geofence { id: id.into(), shape: Shape::Polygon(vertices), dwell: None })
+    }
+
+    /// `geo_types` 도형으로 만든 지오펜스 (`geo` 기능 필요)
+    ///
+    /// `Polygon`, `Rect`, `Triangle`과 닫힌 `LineString`을 받고, x는 경도, y는 위도로 읽는다.
+    /// 구멍이 있는 다각형이나 영역이 아닌 도형은 `WeimError::InvalidPayload`
+    #[cfg(feature = "geo")]
+    pub fn geometry(id: impl Into<String>, geometry: impl Into<geo_types::Geometry<f64>>) -> Result<Self, WeimError> {
+        use geo_types::Geometry;
+
+        let ring = match geometry.into() {
+            Geometry::Polygon(polygon) if polygon.interiors().is_empty() => polygon.into_inner().0,
+            Geometry::Polygon(_) => {
+                return Err(WeimError::InvalidPayload(pick("구멍이 있는 다각형은 지오펜스로 쓸 수 없습니다", "polygons with holes cannot be geofences").to_string()));
+            }
+            Geometry::Rect(rect) => rect.to_polygon().into_inner().0,
+            Geometry::Triangle(triangle) => triangle.to_polygon().into_inner().0,
+            Geometry::LineString(line) if line.is_closed() => line,
+            _ => {
+                return Err(WeimError::InvalidPayload(pick("영역을 나타내는 도형이 아닙니다", "the geometry does not describe an area").to_string()));
+            }
+        };
+        // geo_types는 고리를 첫 점으로 닫아 두지만, 여기서는 마지막 점이 첫 점과 자동으로 이어진다
+        let mut vertices: Vec<(f64, f64)> = ring.coords().map(|coord| (coord.y, coord.x)).collect();
+        if vertices.len() > 1 && vertices.first() == vertices.last() {
+            vertices.pop();
+        }
+        Geofence::polygon(id, vertices)
+    }
+
+    /// 머무름(`Dwell`)으로 볼 시간 (기본값 없음 = 보내지 않음)
+    pub fn dwell(mut self, duration: Duration) -> Self {
+        self.dwell = Some(duration);
+        self
+    }
+}
+
+/// 지오펜스 이벤트 종류
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum GeofenceEventKind {
+    Enter,
+    Exit,
+    Dwell,
+}
+
+impl fmt::Display for GeofenceEventKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GeofenceEventKind::Enter => f.write_str(pick("진입", "enter")),
+            GeofenceEventKind::Exit => f.write_str(pick("이탈", "exit")),
+            GeofenceEventKind::Dwell => f.write_str(pick("머무름", "dwell")),
+        }
+    }
+}
+
+/// 지오펜스 경계를 넘거나 안에 머무른 사건
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GeofenceEvent {
+    pub kind: GeofenceEventKind,
+    /// 지오펜스 `id`
+    pub fence: String,
+    /// 이벤트를 일으킨 위치
+    pub location: Location,
+}
+
+// 지오펜스 하나의 현재 판단
+#[derive(Debug, Clone, Copy, Default)]
+struct State {
+    // 아직 확실한 위치를 받지 못했으면 `None`
+    inside: Option<bool>,
+    entered_at: Option<DateTime<Utc>>,
+    dwelled: bool,
+}
+
+type Callback = Box<dyn FnMut(&GeofenceEvent) + Send>;
+
+/// 등록한 지오펜스들에 위치를 넣어 이벤트를 만든다.
+///
+/// 경계 근처의 흔들리는 위치로 진입과 이탈이 번갈아 나오지 않도록 위치가 경계에서
+/// `hysteresis + 정확도 × accuracy_weight` 미터 넘게 떨어졌을 때만 상태를 바꾼다.
+pub struct Geofences {
+    fences: Vec<Geofence>,
+    states: HashMap<String, State>,
+    hysteresis: f64,
+    accuracy_weight: f64,
+    on_event: Option<Callback>,
+    #[cfg(feature = "notifications")]
+    desktop_notifications: bool,
+}
+
+impl fmt::Debug for Geofences {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Geofences")
+            .field("fences", &self.fences)
+            .field("hysteresis", &self.hysteresis)
+            .field("accuracy_weight", &self.accuracy_weight)
+            .finish()
+    }
+}
+
+impl Default for Geofences {
+    fn default() -> Self {
+        Geofences {
+            fences: Vec::new(),
+            states: HashMap::new(),
+            hysteresis: DEFAULT_HYSTERESIS,
+            accuracy_weight: DEFAULT_ACCURACY_WEIGHT,
+            on_event: None,
+            #[cfg(feature = "notifications")]
+            desktop_notifications: false,
+        }
+    }
+}
+
+impl Geofences {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 지오펜스를 등록한다. 같은 `id`가 있으면 바꾸고 상태를 처음부터 다시 판단한다.
+    pub fn add(&mut self, fence: Geofence) {
+        self.remove(&fence.id);
+        self.fences.push(fence);
+    }
+
+    /// `add()`의 빌더 형태
+    pub fn with(mut self, fence: Geofence) -> Self {
+        self.add(fence);
+        self
+    }
+
+    /// 지오펜스를 지운다. 있었으면 true
+    pub fn remove(&mut self, id: &str) -> bool {
+        self.states.remove(id);
+        let before = self.fences.len();
+        self.fences.retain(|fence| fence.id != id);
+        self.fences.len() != before
+    }
+
+    pub fn fences(&self) -> &[Geofence] {
+        &self.fences
+    }
+
+    /// 지금 안에 있다고 판단한 지오펜스인지. 아직 모르면 false
+    pub fn is_inside(&self, id: &str) -> bool {
+        self.states.get(id).and_then(|state| state.inside).unwrap_or(false)
+    }
+
+    /// 상태를 바꾸기 전에 경계에서 떨어져야 하는 최소 거리 (기본값 5m)
+    pub fn hysteresis(mut self, meters: f64) -> Self {
+        self.hysteresis = meters.max(0.0);
+        self
+    }
+
+    /// 위치 정확도를 여유 거리에 얼마나 더할지 (기본값 0.5)
+    ///
+    /// 1.0이면 정확도 원 전체가 경계 한쪽에 있어야 상태가 바뀐다.
+    pub fn accuracy_weight(mut self, weight: f64) -> Self {
+        self.accuracy_weight = weight.max(0.0);
+        self
+    }
+
+    /// 이벤트가 생길 때마다 부를 함수 (기본값 없음)
+    pub fn on_event(mut self, callback: impl FnMut(&GeofenceEvent) + Send + 'static) -> Self {
+        self.on_event = Some(Box::new(callback));
+        self
+    }
+
+    /// 이벤트마다 데스크톱 알림을 띄울지 여부 (기본값 false, `notifications` 기능 필요)
+    #[cfg(feature = "notifications")]
+    pub fn desktop_notifications(mut self, enabled: bool) -> Self {
+        self.desktop_notifications = enabled;
+        self
+    }
+
+    /// 새 위치를 넣고 생긴 이벤트를 돌려준다.
+    pub fn update(&mut self, location: &Location) -> Vec<GeofenceEvent> {
+        let point = (location.latitude, location.longitude);
+        let margin = self.hysteresis + location.accuracy.max(0.0) * self.accuracy_weight;
+        let mut events = Vec::new();
+
+        for fence in &self.fences {
+            let state = self.states.entry(fence.id.clone()).or_default();
+            let distance = fence.shape.signed_distance(point);
+            let mut event = |kind| events.push(GeofenceEvent { kind, fence: fence.id.clone(), location: location.clone() });
+
+            // 여유 거리 안쪽에서는 이전 판단을 유지한다
+            if distance < -margin && state.inside != Some(true) {
+                *state = State { inside: Some(true), entered_at: Some(location.timestamp), dwelled: false };
+                event(GeofenceEventKind::Enter);
+            } else if distance > margin && state.inside != Some(false) {
+                // 처음 받은 위치가 바깥이면 이탈로 보지 않는다
+                if state.inside.is_some() {
+                    event(GeofenceEventKind::Exit);
+                }
+                *state = State { inside: Some(false), ..State::default() };
+            }
+
+            if let (Some(true), Some(entered_at), Some(dwell)) = (state.inside, state.entered_at, fence.dwell) {
+                let stayed = (location.timestamp - entered_at).to_std().unwrap_or_default();
+                if !state.dwelled && stayed >= dwell {
+                    state.dwelled = true;
+                    event(GeofenceEventKind::Dwell);
+                }
+            }
+        }
+
+        if let Some(callback) = self.on_event.as_mut() {
+            for event in &events {
+                callback(event);
+            }
+        }
+        #[cfg(feature = "notifications")]
+        if self.desktop_notifications {
+            events.iter().for_each(crate::notify::geofence);
+        }
+        events
+    }
+}
+
+/// 위치 스트림에서 지오펜스 이벤트만 꺼내는 반복자
+#[derive(Debug)]
+pub struct Geofenced<I> {
+    inner: I,
+    fences: Geofences,
+    pending: std::vec::IntoIter<GeofenceEvent>,
+}
+
+impl<I> Geofenced<I> {
+    /// 어떤 위치 스트림에든 지오펜스를 붙인다.
+    pub fn new(inner: I, fences: Geofences) -> Self {
+        Geofenced { inner, fences, pending: Vec::new().into_iter() }
+    }
+
+    pub fn geofences(&self) -> &Geofences {
+        &self.fences
+    }
+
+    /// 돌고 있는 중에 지오펜스를 더하거나 지울 때 쓴다.
+    pub fn geofences_mut(&mut self) -> &mut Geofences {
+        &mut self.fences
+    }
+}
+
+impl<I> Iterator for Geofenced<I>
+where
+    I: Iterator<Item = Result<Location, WeimError>>,
+{
+    type Item = Result<GeofenceEvent, WeimError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(event) = self.pending.next() {
+                return Some(Ok(event));
+            }
+            match self.inner.next()? {
+                Ok(location) => self.pending = self.fences.update(&location).into_iter(),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+// 짝수-홀수 규칙. 작은 영역이라 위경도를 평면으로 본다.
+// `Shape::Polygon`은 직접 만들거나 설정 파일에서 읽을 수 있어 꼭짓점이 모자랄 수 있다. 그런 다각형은 늘 밖이다
+fn contains(vertices: &[(f64, f64)], (lat, lon): (f64, f64)) -> bool {
+    if vertices.len() < 3 {
+        return false;
+    }
+    let mut inside = false;
+    let mut previous = vertices[vertices.len() - 1];
+    for &current in vertices {
+        let ((lat1, lon1), (lat2, lon2)) = (previous, current);
+        if (lat1 > lat) != (lat2 > lat) && lon < (lon2 - lon1) * (lat - lat1) / (lat2 - lat1) + lon1 {
+            inside = !inside;
+        }
+        previous = current;
+    }
+    inside
+}
+
+// 가장 가까운 변까지의 거리 (m). 점 주변을 평면으로 펴서 잰다.
+fn polygon_edge_distance(vertices: &[(f64, f64)], (lat, lon): (f64, f64)) -> f64 {
+    let scale = lat.to_radians().cos();
+    let project = |(vlat, vlon): (f64, f64)| ((vlon - lon) * METERS_PER_DEGREE * scale, (vlat - lat) * METERS_PER_DEGREE);
+    if vertices.len() < 3 {
+        return f64::INFINITY;
+    }
+
+    let mut previous = project(vertices[vertices.len() - 1]);
+    let mut nearest = f64::INFINITY;
+    for &vertex in vertices {
+        let current = project(vertex);
+        nearest = nearest.min(origin_to_segment(previous, current));
+        previous = current;
+    }
+    nearest
+}
+
+// 원점에서 선분 (a, b)까지의 거리
+fn origin_to_segment((ax, ay): (f64, f64), (bx, by): (f64, f64)) -> f64 {
+    let (dx, dy) = (bx - ax, by - ay);
+    let length = dx * dx + dy * dy;
+    let t = if length == 0.0 { 0.0 } else { (-(ax * dx + ay * dy) / length).clamp(0.0, 1.0) };
+    (ax + t * dx).hypot(ay + t * dy)
+}
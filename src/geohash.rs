@@ -0,0 +1,159 @@
+//! Geohash 인코딩과 디코딩
+//!
+//! 같은 접두사를 가진 geohash는 같은 칸 안에 있으므로, 위치를 짧은 문자열로 저장하거나
+//! 칸 단위로 묶고 비교할 때 쓴다. 자릿수가 적을수록 칸이 커져 정확한 위치를 덜 드러낸다.
+
+use crate::geo::{haversine, Coordinate};
+use crate::i18n::tr;
+use crate::{Location, WeimError};
+
+/// 가장 긴 자릿수. 12자리 칸은 수 cm 크기다.
+pub const MAX_PRECISION: usize = 12;
+
+const ALPHABET: &[u8; 32] = b"0123456789bcdefghjkmnpqrstuvwxyz";
+
+/// geohash 하나가 가리키는 칸 (°)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cell {
+    pub south: f64,
+    pub west: f64,
+    pub north: f64,
+    pub east: f64,
+}
+
+impl Cell {
+    /// 칸의 중심 `(위도, 경도)`
+    pub fn center(&self) -> (f64, f64) {
+        ((self.south + self.north) / 2.0, (self.west + self.east) / 2.0)
+    }
+
+    /// 중심에서 모서리까지의 거리 (m). 칸 안의 어느 점이든 중심에서 이만큼 안에 있다.
+    pub fn radius(&self) -> f64 {
+        haversine(self.center(), (self.north, self.east))
+    }
+
+    /// 좌표가 칸 안에 있는지 여부
+    pub fn contains(&self, coordinate: impl Coordinate) -> bool {
+        (self.south..=self.north).contains(&coordinate.latitude()) && (self.west..=self.east).contains(&coordinate.longitude())
+    }
+}
+
+/// 좌표를 `precision`자리 geohash로 바꾼다. 자릿수는 1~12로 맞춘다.
+pub fn encode(coordinate: impl Coordinate, precision: usize) -> String {
+    let (mut latitude, mut longitude) = ((-90.0, 90.0), (-180.0, 180.0));
+    let (lat, lon) = (coordinate.latitude().clamp(-90.0, 90.0), coordinate.longitude().clamp(-180.0, 180.0));
+    let mut hash = String::with_capacity(precision);
+    // 비트는 경도부터 번갈아 나온다
+    let mut even = true;
+    for _ in 0..precision.clamp(1, MAX_PRECISION) {
+        let mut index = 0;
+        for _ in 0..5 {
+            let (range, value) = if even { (&mut longitude, lon) } else { (&mut latitude, lat) };
+            let middle = (range.0 + range.1) / 2.0;
+            index <<= 1;
+            if value >= middle {
+                index |= 1;
+                range.0 = middle;
+            } else {
+                range.1 = middle;
+            }
+            even = !even;
+        }
+        hash.push(ALPHABET[index] as char);
+    }
+    hash
+}
+
+/// geohash가 가리키는 칸. 대소문자를 가리지 않는다. 비었거나 쓸 수 없는 글자가 있으면 `WeimError::InvalidPayload`
+pub fn decode(hash: &str) -> Result<Cell, WeimError> {
+    if hash.is_empty() || hash.len() > MAX_PRECISION {
+        return Err(WeimError::InvalidPayload(tr!("geohash는 1~{}자여야 합니다: {:?}", "a geohash must be 1 to {} characters: {:?}", MAX_PRECISION, hash)));
+    }
+    let (mut latitude, mut longitude) = ((-90.0, 90.0), (-180.0, 180.0));
+    let mut even = true;
+    for c in hash.chars() {
+        let Some(index) = ALPHABET.iter().position(|&b| b as char == c.to_ascii_lowercase()) else {
+            return Err(WeimError::InvalidPayload(tr!("geohash에 쓸 수 없는 글자 {:?}", "invalid geohash character {:?}", c)));
+        };
+        for bit in (0..5).rev() {
+            let range: &mut (f64, f64) = if even { &mut longitude } else { &mut latitude };
+            let middle = (range.0 + range.1) / 2.0;
+            if index >> bit & 1 == 1 {
+                range.0 = middle;
+            } else {
+                range.1 = middle;
+            }
+            even = !even;
+        }
+    }
+    Ok(Cell { south: latitude.0, west: longitude.0, north: latitude.1, east: longitude.1 })
+}
+
+/// 같은 자릿수로 둘러싼 이웃 칸들 (북쪽부터 시계 방향). 극지방 너머의 칸은 빠진다.
+///
+/// 가까운지 볼 때 경계에 걸친 위치를 놓치지 않도록 자기 칸과 함께 쓴다.
+pub fn neighbors(hash: &str) -> Result<Vec<String>, WeimError> {
+    let cell = decode(hash)?;
+    let (latitude, longitude) = cell.center();
+    let (height, width) = (cell.north - cell.south, cell.east - cell.west);
+    let steps = [(1.0, 0.0), (1.0, 1.0), (0.0, 1.0), (-1.0, 1.0), (-1.0, 0.0), (-1.0, -1.0), (0.0, -1.0), (1.0, -1.0)];
+    Ok(steps
+        .iter()
+        .map(|(up, right)| (latitude + up * height, longitude + right * width))
+        .filter(|(latitude, _)| latitude.abs() < 90.0)
+        // 날짜 변경선을 넘으면 반대편으로 돈다
+        .map(|(latitude, longitude)| encode((latitude, (longitude + 540.0) % 360.0 - 180.0), hash.len()))
+        .collect())
+}
+
+impl Location {
+    /// 이 위치의 `precision`자리 geohash. 5자리는 약 5km, 7자리는 약 150m, 9자리는 약 5m 칸이다.
+    pub fn geohash(&self, precision: usize) -> String {
+        encode(self, precision)
+    }
+
+    /// geohash 칸의 중심 위치. 정확도는 중심에서 칸 모서리까지의 거리다.
+    pub fn from_geohash(hash: &str) -> Result<Location, WeimError> {
+        let cell = decode(hash)?;
+        let (latitude, longitude) = cell.center();
+        Ok(Location { latitude, longitude, accuracy: cell.radius(), ..Location::default() })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // https://en.wikipedia.org/wiki/Geohash 의 예
+    const HASH: &str = "u4pruydqqvj";
+    const POINT: (f64, f64) = (57.64911, 10.40744);
+
+    #[test]
+    fn encodes_reference_point() {
+        assert_eq!(encode(POINT, 11), HASH);
+        assert_eq!(encode(POINT, 5), "u4pru");
+    }
+
+    #[test]
+    fn decodes_reference_hash() {
+        let cell = decode(HASH).unwrap();
+        assert!(cell.contains(POINT));
+        let (lat, lon) = cell.center();
+        assert!((lat - POINT.0).abs() < 1e-5 && (lon - POINT.1).abs() < 1e-5);
+        assert_eq!(decode("U4PRUYDQQVJ").unwrap(), cell);
+    }
+
+    #[test]
+    fn rejects_invalid_hashes() {
+        assert!(decode("").is_err());
+        assert!(decode("u4pa").is_err());
+        assert!(decode("u4pruydqqvjuu").is_err());
+    }
+
+    #[test]
+    fn neighbors_surround_the_cell() {
+        let neighbors = neighbors("u4pru").unwrap();
+        assert_eq!(neighbors.len(), 8);
+        assert!(neighbors.iter().all(|hash| hash.len() == 5 && hash != "u4pru"));
+    }
+}
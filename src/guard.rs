@@ -0,0 +1,211 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::net::IpAddr;
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::Instant;
+use serde::{Deserialize, Deserializer};
+
+use crate::console::warning;
+use crate::i18n::tr;
+use crate::locator::Config;
+use crate::WeimError;
+
+// 기억해 둘 클라이언트 수. 넘으면 가득 찬 양동이부터 잊는다
+const MAX_CLIENTS: usize = 10_000;
+
+/// IP 주소 하나(`192.168.0.7`) 또는 CIDR 대역(`192.168.0.0/24`, `fd00::/8`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct IpRange {
+    network: IpAddr,
+    prefix: u8,
+}
+
+impl IpRange {
+    /// `prefix`보다 뒤의 비트는 지운다.
+    pub fn new(addr: IpAddr, prefix: u8) -> Result<Self, WeimError> {
+        // `::ffff:192.168.0.0/120`은 `192.168.0.0/24`와 같다
+        let (addr, prefix) = match addr {
+            IpAddr::V6(v6) if prefix >= 96 && v6.to_ipv4_mapped().is_some() => (addr.to_canonical(), prefix - 96),
+            _ => (addr, prefix),
+        };
+        let bits = if addr.is_ipv4() { 32 } else { 128 };
+        if prefix > bits {
+            return Err(WeimError::Config(tr!("{}비트보다 긴 접두사입니다: /{}", "prefix longer than {} bits: /{}", bits, prefix)));
+        }
+        let network = match addr {
+            IpAddr::V4(v4) => IpAddr::from((u32::from(v4) & mask(prefix, 32) as u32).to_be_bytes()),
+            IpAddr::V6(v6) => IpAddr::from((u128::from(v6) & mask(prefix, 128)).to_be_bytes()),
+        };
+        Ok(IpRange { network, prefix })
+    }
+
+    /// IPv6로 감싼 IPv4 주소(`::ffff:192.168.0.7`)도 IPv4 대역과 맞춰 본다.
+    pub fn contains(&self, addr: IpAddr) -> bool {
+        match (self.network, addr.to_canonical()) {
+            (IpAddr::V4(network), IpAddr::V4(addr)) => u32::from(addr) & mask(self.prefix, 32) as u32 == u32::from(network),
+            (IpAddr::V6(network), IpAddr::V6(addr)) => u128::from(addr) & mask(self.prefix, 128) == u128::from(network),
+            _ => false,
+        }
+    }
+}
+
+// 앞에서부터 `prefix`개 비트가 1인 마스크
+fn mask(prefix: u8, bits: u32) -> u128 {
+    match prefix {
+        0 => 0,
+        prefix => (u128::MAX << (128 - u32::from(prefix))) >> (128 - bits),
+    }
+}
+
+impl From<IpAddr> for IpRange {
+    fn from(addr: IpAddr) -> Self {
+        let addr = addr.to_canonical();
+        IpRange { network: addr, prefix: if addr.is_ipv4() { 32 } else { 128 } }
+    }
+}
+
+impl FromStr for IpRange {
+    type Err = WeimError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || WeimError::Config(tr!("IP 주소나 CIDR 대역을 읽을 수 없습니다: {}", "cannot read the IP address or CIDR range: {}", s));
+        let (addr, prefix) = match s.trim().split_once('/') {
+            Some((addr, prefix)) => (addr, Some(prefix.parse::<u8>().map_err(|_| invalid())?)),
+            None => (s.trim(), None),
+        };
+        let addr: IpAddr = addr.parse().map_err(|_| invalid())?;
+        match prefix {
+            Some(prefix) => IpRange::new(addr, prefix),
+            None => Ok(IpRange::from(addr)),
+        }
+    }
+}
+
+impl fmt::Display for IpRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.network, self.prefix)
+    }
+}
+
+impl<'de> Deserialize<'de> for IpRange {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// 클라이언트 IP마다 따로 두는 토큰 양동이
+///
+/// 초마다 `per_second`개씩 차고 최대 `burst`개까지 모인다. 위치를 보낼 때마다 하나씩 쓴다.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimit {
+    per_second: f64,
+    burst: u32,
+}
+
+impl Default for RateLimit {
+    fn default() -> Self {
+        RateLimit { per_second: 5.0, burst: 20 }
+    }
+}
+
+impl RateLimit {
+    /// 초당 평균 요청 수 (기본값 5)
+    pub fn new(per_second: f64) -> Self {
+        RateLimit { per_second: per_second.max(0.0), ..Self::default() }
+    }
+
+    /// 한꺼번에 받을 수 있는 요청 수 (기본값 20)
+    pub fn burst(mut self, burst: u32) -> Self {
+        self.burst = burst.max(1);
+        self
+    }
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+    // 막힌 동안에는 한 번만 경고한다
+    limited: bool,
+}
+
+/// 요청을 받기 전에 클라이언트가 막혔는지 본 결과
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Verdict {
+    Allowed,
+    /// 허용 목록에 없거나 차단 목록에 있다
+    Denied,
+    /// 너무 자주 보냈다
+    Limited,
+}
+
+/// 허용/차단 목록과 요청 속도 제한
+///
+/// 차단 목록이 먼저다. 허용 목록이 있으면 루프백이 아닌 클라이언트는 그 안에 있어야 한다.
+/// 속도 제한은 루프백이 아닌 클라이언트, 즉 LAN으로 들어온 요청에만 건다.
+pub(crate) struct Guard {
+    allow: Vec<IpRange>,
+    deny: Vec<IpRange>,
+    rate_limit: Option<RateLimit>,
+    buckets: Mutex<HashMap<IpAddr, Bucket>>,
+    // 경고를 이미 남긴 주소
+    denied: Mutex<HashSet<IpAddr>>,
+}
+
+impl Guard {
+    pub(crate) fn new(config: &Config) -> Self {
+        Guard {
+            allow: config.allow.clone(),
+            deny: config.deny.clone(),
+            rate_limit: config.rate_limit.filter(|limit| limit.per_second > 0.0),
+            buckets: Mutex::default(),
+            denied: Mutex::default(),
+        }
+    }
+
+    /// 목록에 걸리는지. 주소를 모르면 막지 않는다
+    pub(crate) fn admit(&self, client: Option<IpAddr>) -> Verdict {
+        let Some(client) = client.map(|addr| addr.to_canonical()) else {
+            return Verdict::Allowed;
+        };
+        let denied = self.deny.iter().any(|range| range.contains(client))
+            || (!self.allow.is_empty() && !client.is_loopback() && !self.allow.iter().any(|range| range.contains(client)));
+        if !denied {
+            return Verdict::Allowed;
+        }
+        let mut warned = self.denied.lock().unwrap();
+        if warned.len() < MAX_CLIENTS && warned.insert(client) {
+            warning!("{}", tr!("🚫 허용하지 않은 주소 {}의 요청을 거부했습니다.", "🚫 Rejected a request from {}, which is not allowed.", client));
+        }
+        Verdict::Denied
+    }
+
+    /// 위치를 보내는 요청 하나를 센다. 양동이가 비었으면 `Limited`
+    pub(crate) fn throttle(&self, client: Option<IpAddr>) -> Verdict {
+        let (Some(limit), Some(client)) = (self.rate_limit, client.map(|addr| addr.to_canonical())) else {
+            return Verdict::Allowed;
+        };
+        if client.is_loopback() {
+            return Verdict::Allowed;
+        }
+        let now = Instant::now();
+        let burst = f64::from(limit.burst);
+        let mut buckets = self.buckets.lock().unwrap();
+        if buckets.len() >= MAX_CLIENTS && !buckets.contains_key(&client) {
+            buckets.retain(|_, bucket| bucket.tokens + now.duration_since(bucket.updated).as_secs_f64() * limit.per_second < burst);
+        }
+        let bucket = buckets.entry(client).or_insert(Bucket { tokens: burst, updated: now, limited: false });
+        bucket.tokens = (bucket.tokens + now.duration_since(bucket.updated).as_secs_f64() * limit.per_second).min(burst);
+        bucket.updated = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            bucket.limited = false;
+            return Verdict::Allowed;
+        }
+        if !bucket.limited {
+            bucket.limited = true;
+            warning!("{}", tr!("🚦 {}이(가) 위치를 너무 자주 보내서 잠시 거부합니다.", "🚦 {} is sending locations too fast, rejecting for a while.", client));
+        }
+        Verdict::Limited
+    }
+}
@@ -0,0 +1,113 @@
+//! H3 육각형 칸으로 위치 묶기 (`h3` 기능 필요)
+//!
+//! 위치를 해상도별 H3 칸으로 바꾸고, 칸마다 몇 번 찍혔고 얼마나 머물렀는지 센다.
+//! 해상도는 0(대륙 크기)~15(약 1m²)이다. 7은 약 5km², 9는 약 0.1km², 11은 약 2000m² 칸이다.
+
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+
+pub use h3o::{CellIndex, LatLng, Resolution};
+
+use crate::geo::Coordinate;
+use crate::i18n::tr;
+use crate::{Location, WeimError};
+
+/// 칸 하나에 찍힌 위치들
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CellVisit {
+    pub cell: CellIndex,
+    /// 칸 안에서 찍힌 위치 수
+    pub fixes: usize,
+    /// 칸 안의 위치에서 다음 위치까지 걸린 시간의 합
+    pub duration: Duration,
+}
+
+impl Coordinate for LatLng {
+    fn latitude(&self) -> f64 {
+        self.lat()
+    }
+
+    fn longitude(&self) -> f64 {
+        self.lng()
+    }
+}
+
+/// 좌표가 들어 있는 `resolution` 해상도의 칸. 위도나 경도가 유한한 수가 아니면 `WeimError::InvalidPayload`
+pub fn cell(coordinate: impl Coordinate, resolution: Resolution) -> Result<CellIndex, WeimError> {
+    let (latitude, longitude) = (coordinate.latitude(), coordinate.longitude());
+    LatLng::new(latitude, longitude)
+        .map(|point| point.to_cell(resolution))
+        .map_err(|_| WeimError::InvalidPayload(tr!("H3 칸으로 바꿀 수 없는 좌표: {}, {}", "cannot index coordinate as an H3 cell: {}, {}", latitude, longitude)))
+}
+
+/// `center`에서 `k`칸 안(자기 칸 포함)에 있는 위치만 남긴다. 해상도는 `center`의 해상도를 쓴다.
+pub fn within_disk<'a>(locations: impl IntoIterator<Item = &'a Location>, center: CellIndex, k: u32) -> Vec<&'a Location> {
+    let disk: HashSet<CellIndex> = center.grid_disk(k);
+    locations
+        .into_iter()
+        .filter(|location| cell(*location, center.resolution()).is_ok_and(|cell| disk.contains(&cell)))
+        .collect()
+}
+
+/// 한 기기의 시간순 위치를 칸별로 묶는다. 오래 머문 칸부터 돌려준다.
+///
+/// 위치에서 다음 위치까지의 시간은 앞 위치의 칸에 더한다. `max_gap`보다 긴 공백은
+/// 기록이 끊긴 것으로 보고 더하지 않는다.
+pub fn visits<'a>(locations: impl IntoIterator<Item = &'a Location>, resolution: Resolution, max_gap: Duration) -> Vec<CellVisit> {
+    let mut visits = HashMap::new();
+    tally(&mut visits, locations, resolution, max_gap);
+    sorted(visits)
+}
+
+fn tally<'a>(visits: &mut HashMap<CellIndex, CellVisit>, locations: impl IntoIterator<Item = &'a Location>, resolution: Resolution, max_gap: Duration) {
+    let mut previous: Option<(CellIndex, DateTime<Utc>)> = None;
+    for location in locations {
+        let Ok(cell) = cell(location, resolution) else {
+            continue;
+        };
+        if let Some((previous, timestamp)) = previous {
+            let gap = (location.timestamp - timestamp).to_std().unwrap_or_default();
+            if gap <= max_gap
+                && let Some(visit) = visits.get_mut(&previous)
+            {
+                visit.duration += gap;
+            }
+        }
+        visits.entry(cell).or_insert(CellVisit { cell, fixes: 0, duration: Duration::ZERO }).fixes += 1;
+        previous = Some((cell, location.timestamp));
+    }
+}
+
+fn sorted(visits: HashMap<CellIndex, CellVisit>) -> Vec<CellVisit> {
+    let mut visits: Vec<CellVisit> = visits.into_values().collect();
+    visits.sort_by(|a, b| b.duration.cmp(&a.duration).then(b.fixes.cmp(&a.fixes)).then(a.cell.cmp(&b.cell)));
+    visits
+}
+
+impl Location {
+    /// 이 위치가 들어 있는 `resolution` 해상도의 H3 칸 (`h3` 기능 필요)
+    pub fn h3_cell(&self, resolution: Resolution) -> Result<CellIndex, WeimError> {
+        cell(self, resolution)
+    }
+}
+
+#[cfg(feature = "history")]
+impl crate::History {
+    /// 조건에 맞는 위치를 칸별로 묶는다. 기기마다 따로 시간을 잰 뒤 합친다. (`h3` 기능 필요)
+    pub fn visits(&self, query: &crate::HistoryQuery, resolution: Resolution, max_gap: Duration) -> Result<Vec<CellVisit>, WeimError> {
+        let mut visits = HashMap::new();
+        for track in self.tracks(query)? {
+            tally(&mut visits, &track.points, resolution, max_gap);
+        }
+        Ok(sorted(visits))
+    }
+
+    /// 조건에 맞는 위치 중 `center`에서 `k`칸 안에 있는 것만 시간순으로 돌려준다. (`h3` 기능 필요)
+    pub fn within_disk(&self, query: &crate::HistoryQuery, center: CellIndex, k: u32) -> Result<Vec<crate::HistoryEntry>, WeimError> {
+        let disk: HashSet<CellIndex> = center.grid_disk(k);
+        let mut entries = self.query(query)?;
+        entries.retain(|entry| cell(&entry.location, center.resolution()).is_ok_and(|cell| disk.contains(&cell)));
+        Ok(entries)
+    }
+}
@@ -0,0 +1,492 @@
+use std::fmt::Write;
+use std::net::IpAddr;
+use std::process::Command;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::access_log::AccessLog;
+use crate::api::Api;
+use crate::clock::Clocks;
+use crate::console::{self, info, warning};
+use crate::events::EventHub;
+use crate::guard::{Guard, Verdict};
+use crate::health::Health;
+use crate::i18n::{pick, tr};
+use crate::locator::{Config, Mode};
+use crate::map::MapProvider;
+use crate::metrics::Metrics;
+use crate::notation::{CoordinateFormat, Style};
+use crate::privacy::Coarsening;
+use crate::query::param;
+use crate::security::Headers;
+use crate::{dashboard, page, DeviceId, DeviceInfo, Location, Source, WeimError};
+
+// 탭을 닫았다는 알림 뒤 새로 고침으로 페이지를 다시 받아 가기를 기다리는 시간
+const RELOAD_GRACE: Duration = Duration::from_secs(3);
+
+#[derive(Debug, Deserialize, Serialize)]
+struct LocationData {
+    latitude: f64,
+    longitude: f64,
+    accuracy: f64,
+    // 기기가 지원하지 않으면 브라우저가 null을 보낸다
+    #[serde(default)]
+    altitude: Option<f64>,
+    #[serde(default, rename = "altitudeAccuracy")]
+    altitude_accuracy: Option<f64>,
+    #[serde(default)]
+    heading: Option<f64>,
+    #[serde(default)]
+    speed: Option<f64>,
+    #[serde(default)]
+    compass: Option<f64>,
+    // 측정한 기기 시각 (ms)
+    timestamp: i64,
+    // 페이지가 요청을 보낸 기기 시각 (ms). 없으면 `timestamp`로 어림한다
+    #[serde(default, rename = "sentAt")]
+    sent_at: Option<i64>,
+    #[serde(default)]
+    device: Option<String>,
+    #[serde(default)]
+    source: Source,
+    #[serde(default, rename = "deviceInfo")]
+    device_info: Option<DeviceInfo>,
+}
+
+// 위치보다 먼저 확인하는 세션 토큰
+#[derive(Deserialize)]
+struct Credentials {
+    token: Option<String>,
+}
+
+// 페이지가 `/error`로 보내는 GeolocationPositionError. 0은 Geolocation API가 없는 브라우저
+#[derive(Deserialize)]
+struct BrowserError {
+    code: u16,
+    #[serde(default)]
+    message: String,
+}
+
+impl From<BrowserError> for WeimError {
+    fn from(error: BrowserError) -> Self {
+        match error.code {
+            0 => WeimError::Unsupported,
+            1 => WeimError::PermissionDenied,
+            3 => WeimError::Timeout,
+            _ => WeimError::PositionUnavailable(error.message),
+        }
+    }
+}
+
+impl From<LocationData> for Location {
+    fn from(data: LocationData) -> Self {
+        Location {
+            latitude: data.latitude,
+            longitude: data.longitude,
+            accuracy: data.accuracy,
+            altitude: data.altitude,
+            altitude_accuracy: data.altitude_accuracy,
+            heading: data.heading,
+            speed: data.speed,
+            timestamp: DateTime::from_timestamp_millis(data.timestamp).unwrap_or_default(),
+            device_timestamp: DateTime::from_timestamp_millis(data.timestamp),
+            address: None,
+            compass: data.compass.filter(|degrees| (0.0..360.0).contains(degrees)),
+            weather: None,
+            device_info: data.device_info,
+            source: data.source,
+        }
+    }
+}
+
+/// 서버 구현과 상관없는 HTTP 응답
+pub(crate) struct Reply {
+    pub(crate) status: u16,
+    pub(crate) headers: Vec<(&'static str, String)>,
+    pub(crate) body: String,
+}
+
+impl Reply {
+    pub(crate) fn new(status: u16, body: impl Into<String>) -> Self {
+        Reply { status, headers: Vec::new(), body: body.into() }
+    }
+
+    pub(crate) fn header(mut self, name: &'static str, value: impl Into<String>) -> Self {
+        self.headers.push((name, value.into()));
+        self
+    }
+}
+
+/// 서버 구현과 상관없는 HTTP 요청
+pub(crate) struct Incoming<'a> {
+    /// 보낸 주소. 서버가 알려 주지 않으면 None이고, 그때는 목록과 속도 제한을 보지 않는다.
+    pub(crate) client: Option<IpAddr>,
+    /// `Origin` 헤더
+    pub(crate) origin: Option<&'a str>,
+    pub(crate) method: &'a str,
+    pub(crate) path: &'a str,
+    pub(crate) body: &'a str,
+}
+
+/// 요청 하나를 처리한 뒤 서버 루프가 할 일
+///
+/// 받자마자 넘기고 마는 값이라 위치를 상자에 넣지 않는다.
+#[allow(clippy::large_enum_variant)]
+pub(crate) enum Outcome {
+    Continue,
+    Fix(DeviceId, Location),
+    Failed(WeimError),
+    /// 응답 머리만 보내고 연결을 `/events` 스트림으로 넘긴다
+    Subscribe,
+    /// 연결을 `/ws` 웹소켓으로 바꾼다. 업그레이드할 수 없는 서버는 응답을 그대로 보낸다.
+    #[cfg(feature = "websocket")]
+    Upgrade,
+}
+
+/// tiny_http와 tokio 서버가 함께 쓰는 라우터
+pub(crate) struct Handler {
+    html: String,
+    token: String,
+    events: EventHub,
+    api: Api,
+    health: Health,
+    access_log: AccessLog,
+    guard: Guard,
+    headers: Headers,
+    // API를 끄면 대시보드도 열지 않는다
+    dashboard: Option<String>,
+    // 계속 받는 모드에서 켰을 때만 있다
+    metrics: Option<Metrics>,
+    manual_entry: bool,
+    map_links: Vec<MapProvider>,
+    coarse_location: Option<Coarsening>,
+    clocks: Clocks,
+    // 계속 받는 모드에서는 한 기기의 오류로 세션을 끝내지 않는다
+    watching: bool,
+    visit: Mutex<Visit>,
+    // 브라우저를 기다리는 시간. 직접 열지 않았거나 계속 받는 모드면 없다
+    browser_grace: Option<Duration>,
+    response_grace: Option<Duration>,
+}
+
+// 페이지를 마지막으로 받아 간 시각과 위치 없이 탭을 닫은 시각
+#[derive(Default)]
+struct Visit {
+    served: Option<Instant>,
+    left: Option<Instant>,
+}
+
+impl Handler {
+    /// 서버를 열 때마다 새 세션 토큰을 만들어 페이지에 넣는다.
+    pub(crate) fn new(config: &Config, mode: Mode) -> Self {
+        let token = session_token();
+        Handler {
+            html: page::render(config, mode, &token),
+            token,
+            events: EventHub::default(),
+            api: Api::new(config, mode),
+            health: Health::new(mode),
+            access_log: AccessLog::new(config.access_log.clone()),
+            guard: Guard::new(config),
+            headers: Headers::new(config),
+            dashboard: config.api_enabled(mode).then(dashboard::render),
+            metrics: (config.metrics && matches!(mode, Mode::Watch | Mode::Session)).then(|| Metrics::new(config)),
+            manual_entry: config.manual_entry,
+            map_links: config.map_links.clone(),
+            coarse_location: config.coarse_location,
+            clocks: Clocks::default(),
+            watching: mode == Mode::Watch,
+            visit: Mutex::default(),
+            browser_grace: (config.open_browser && mode != Mode::Watch).then_some(config.browser_grace),
+            response_grace: (mode != Mode::Watch).then_some(config.response_grace),
+        }
+    }
+
+    /// 브라우저가 페이지를 받아 가지 않았거나 위치 없이 떠났으면 끝낼 까닭을 돌려준다. 서버를 연 시각 `opened`부터 잰다.
+    ///
+    /// 첫 위치를 받기 전까지만 부른다.
+    pub(crate) fn abandoned(&self, opened: Instant) -> Option<WeimError> {
+        let visit = self.visit.lock().unwrap();
+        let expired = |since: Instant, grace: Option<Duration>| grace.is_some_and(|grace| since.elapsed() >= grace);
+        match visit.served {
+            None if expired(opened, self.browser_grace) => Some(WeimError::BrowserNotReached),
+            None => None,
+            Some(_) if visit.left.is_some_and(|left| left.elapsed() >= RELOAD_GRACE) => Some(WeimError::NoResponse),
+            Some(served) if expired(served, self.response_grace) => Some(WeimError::NoResponse),
+            Some(_) => None,
+        }
+    }
+
+    /// 서버가 답한 요청을 남길 곳
+    pub(crate) fn access_log(&self) -> &AccessLog {
+        &self.access_log
+    }
+
+    /// `/events` 구독자들
+    pub(crate) fn events(&self) -> &EventHub {
+        &self.events
+    }
+
+    /// 처리를 마친 위치를 콘솔(JSON 모드), `/events` 구독자, `/api`, `/metrics`에 알린다.
+    pub(crate) fn publish(&self, device: &DeviceId, location: &Location) {
+        console::fix(device, location);
+        self.api.record(device, location);
+        self.health.fix();
+        if let Some(metrics) = &self.metrics {
+            metrics.fix(device, location);
+        }
+        self.events.publish(device, location);
+    }
+
+    // 페이지를 받아 간 브라우저만 위치를 보낼 수 있다
+    fn authorized(&self, body: &str) -> bool {
+        serde_json::from_str::<Credentials>(body)
+            .ok()
+            .and_then(|credentials| credentials.token)
+            .is_some_and(|token| constant_time_eq(token.as_bytes(), self.token.as_bytes()))
+    }
+
+    // 브라우저가 알려 온 실패. 계속 받는 모드에서는 경고만 남긴다
+    fn fail(&self, error: WeimError) -> (Reply, Outcome) {
+        self.count_error();
+        let reply = Reply::new(200, r#"{"status":"ok"}"#).header("Content-Type", "application/json");
+        if self.watching {
+            warning!("{}", tr!("⚠️ 브라우저가 위치를 보내지 못했습니다: {}", "⚠️ A browser could not send its location: {}", error));
+            return (reply, Outcome::Continue);
+        }
+        (reply, Outcome::Failed(error))
+    }
+
+    // 읽을 수 없는 요청 본문. 계속 받는 모드에서는 그 요청만 거절한다
+    fn invalid(&self, e: serde_json::Error) -> (Reply, Outcome) {
+        self.count_error();
+        let reply = Reply::new(400, "Invalid JSON");
+        let error = WeimError::InvalidPayload(e.to_string());
+        if self.watching {
+            warning!("{}", tr!("⚠️ 읽을 수 없는 요청을 거절했습니다: {}", "⚠️ Rejected a request that could not be read: {}", error));
+            return (reply, Outcome::Continue);
+        }
+        (reply, Outcome::Failed(error))
+    }
+
+    fn count_error(&self) {
+        if let Some(metrics) = &self.metrics {
+            metrics.error();
+        }
+    }
+
+    fn reject(&self, reason: &'static str) -> Reply {
+        if let Some(metrics) = &self.metrics {
+            metrics.rejected(reason);
+        }
+        forbidden()
+    }
+
+    /// 모든 응답에 CORS와 보안 헤더를 붙인다.
+    pub(crate) fn handle(&self, request: &Incoming) -> (Reply, Outcome) {
+        let (reply, outcome) = self.route(request.client, request.method, request.path, request.body);
+        (self.headers.apply(reply, request.origin), outcome)
+    }
+
+    fn route(&self, client: Option<IpAddr>, method: &str, path: &str, body: &str) -> (Reply, Outcome) {
+        if self.guard.admit(client) == Verdict::Denied {
+            return (self.reject("ip"), Outcome::Continue);
+        }
+        // 페이지가 보내는 요청은 모두 POST다
+        if method == "POST" && self.guard.throttle(client) == Verdict::Limited {
+            if let Some(metrics) = &self.metrics {
+                metrics.rejected("rate_limit");
+            }
+            let reply = Reply::new(429, r#"{"status":"too many requests"}"#)
+                .header("Content-Type", "application/json")
+                .header("Retry-After", "1");
+            return (reply, Outcome::Continue);
+        }
+        // `?device=` 같은 쿼리는 페이지 스크립트가 읽는다
+        let (path, query) = path.split_once('?').unwrap_or((path, ""));
+        if method == "GET" && let Some(reply) = self.api.handle(path, query) {
+            return (reply, Outcome::Continue);
+        }
+        if method == "GET" && let Some(reply) = self.health.handle(path) {
+            return (reply, Outcome::Continue);
+        }
+        if method == "GET" && path == "/dashboard" && let Some(html) = &self.dashboard {
+            let reply = Reply::new(200, html.as_str()).header("Content-Type", "text/html; charset=utf-8");
+            return (reply, Outcome::Continue);
+        }
+        if method == "GET" && path == "/metrics" && let Some(metrics) = &self.metrics {
+            return (metrics.reply(), Outcome::Continue);
+        }
+        match (method, path) {
+            ("GET", "/") => {
+                *self.visit.lock().unwrap() = Visit { served: Some(Instant::now()), left: None };
+                self.health.served();
+                if let Some(metrics) = &self.metrics {
+                    metrics.served(param(query, "device").map(DeviceId).unwrap_or_default());
+                }
+                let reply = Reply::new(200, self.html.as_str())
+                    .header("Content-Type", "text/html; charset=utf-8");
+                (reply, Outcome::Continue)
+            }
+            ("GET", "/events") => {
+                let reply = Reply::new(200, "")
+                    .header("Content-Type", "text/event-stream")
+                    .header("Cache-Control", "no-cache");
+                (reply, Outcome::Subscribe)
+            }
+            #[cfg(feature = "websocket")]
+            ("GET", "/ws") => {
+                let reply = Reply::new(426, "Upgrade Required").header("Upgrade", "websocket");
+                (reply, Outcome::Upgrade)
+            }
+            ("POST", "/update") => {
+                // 토큰이 틀린 요청은 세션을 끝내지 않고 무시한다
+                if !self.authorized(body) {
+                    warning!("{}", pick("🚫 세션 토큰이 없거나 맞지 않는 위치 요청을 거부했습니다.", "🚫 Rejected a location request with a missing or wrong session token."));
+                    return (self.reject("token"), Outcome::Continue);
+                }
+
+                let (device, location) = match serde_json::from_str::<LocationData>(body) {
+                    // 직접 입력을 켜지 않았으면 받지 않는다
+                    Ok(data) if data.source == Source::Manual && !self.manual_entry => {
+                        return (forbidden(), Outcome::Continue);
+                    }
+                    Ok(mut data) => {
+                        let received = Utc::now();
+                        let device = data.device.take().filter(|name| !name.is_empty()).map(DeviceId).unwrap_or_default();
+                        let sent = data.sent_at.unwrap_or(data.timestamp);
+                        let sent = DateTime::from_timestamp_millis(sent).unwrap_or_default();
+                        let offset = self.clocks.offset(&device, client, sent, received);
+                        let mut location = Location::from(data);
+                        // 기기 시계가 틀린 만큼 서버 시계로 옮긴다
+                        location.timestamp = location.timestamp.checked_add_signed(offset).unwrap_or(received);
+                        // 로그와 내보내기보다 먼저 뭉갠다
+                        if let Some(coarsening) = self.coarse_location {
+                            coarsening.apply(&mut location);
+                        }
+                        (device, location)
+                    }
+                    Err(e) => return self.invalid(e),
+                };
+
+                report(&device, &location, &self.map_links);
+
+                let reply = Reply::new(200, r#"{"status":"ok"}"#).header("Content-Type", "application/json");
+                (reply, Outcome::Fix(device, location))
+            }
+            // 동의 화면에서 거절을 누름
+            ("POST", "/decline") => {
+                if !self.authorized(body) {
+                    return (self.reject("token"), Outcome::Continue);
+                }
+                self.fail(WeimError::PermissionDenied)
+            }
+            // 브라우저가 위치를 주지 못함
+            ("POST", "/error") => {
+                if !self.authorized(body) {
+                    return (self.reject("token"), Outcome::Continue);
+                }
+                match serde_json::from_str::<BrowserError>(body) {
+                    Ok(error) => self.fail(error.into()),
+                    Err(e) => self.invalid(e),
+                }
+            }
+            // 위치를 보내기 전에 탭을 닫음. 계속 받는 모드에서는 다른 기기를 기다린다
+            ("POST", "/leave") => {
+                if !self.authorized(body) {
+                    return (self.reject("token"), Outcome::Continue);
+                }
+                if !self.watching {
+                    self.visit.lock().unwrap().left = Some(Instant::now());
+                }
+                (Reply::new(204, ""), Outcome::Continue)
+            }
+            // 다른 출처의 preflight. 출처를 허용하지 않았으면 `handle()`이 허용 헤더를 붙이지 않아 브라우저가 막는다
+            ("OPTIONS", _) => {
+                let reply = Reply::new(204, "")
+                    .header("Access-Control-Allow-Methods", "GET, POST, OPTIONS")
+                    .header("Access-Control-Allow-Headers", "Content-Type")
+                    .header("Access-Control-Max-Age", "600");
+                (reply, Outcome::Continue)
+            }
+            _ => (Reply::new(404, "Not Found"), Outcome::Continue),
+        }
+    }
+}
+
+// 토큰이 틀린 요청에 대한 답
+fn forbidden() -> Reply {
+    Reply::new(403, r#"{"status":"forbidden"}"#).header("Content-Type", "application/json")
+}
+
+// 운영체제의 난수로 만든, 추측할 수 없는 128비트 토큰
+fn session_token() -> String {
+    let mut bytes = [0u8; 16];
+    getrandom::fill(&mut bytes).expect("운영체제 난수를 읽을 수 없습니다");
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+// 몇 번째 글자에서 틀렸는지 응답 시간으로 알 수 없게 끝까지 비교한다
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+// 로그 한 줄에 위치 하나가 담기도록 모아서 한 번에 남긴다
+fn report(device: &DeviceId, location: &Location, map_links: &[MapProvider]) {
+    let time = location.local_timestamp().format("%Y-%m-%d %H:%M:%S");
+    let mut text = tr!("\n[{}] 📍 새로운 위치 데이터:\n", "\n[{}] 📍 New location:\n", time);
+    let coordinates = CoordinateFormat::new(Style::Hemisphere).precision(8);
+    if *device != DeviceId::default() {
+        writeln!(text, "{}", tr!("  기기: {}", "  Device: {}", device)).ok();
+    }
+    writeln!(text, "{}", tr!("  위도: {}", "  Latitude: {}", coordinates.latitude(location.latitude))).ok();
+    writeln!(text, "{}", tr!("  경도: {}", "  Longitude: {}", coordinates.longitude(location.longitude))).ok();
+    writeln!(text, "{}", tr!("  정확도: {:.2}m", "  Accuracy: {:.2}m", location.accuracy)).ok();
+    if location.source == Source::Manual {
+        writeln!(text, "{}", pick("  ✍️ 사용자가 직접 입력한 좌표", "  ✍️ Entered manually by the user")).ok();
+    }
+    if let Some(altitude) = location.altitude {
+        writeln!(text, "{}", tr!("  고도: {:.1}m", "  Altitude: {:.1}m", altitude)).ok();
+    }
+    if let Some(speed) = location.speed {
+        writeln!(text, "{}", tr!("  속도: {:.1}m/s", "  Speed: {:.1}m/s", speed)).ok();
+    }
+    if let Some(heading) = location.heading {
+        writeln!(text, "{}", tr!("  방향: {:.0}°", "  Heading: {:.0}°", heading)).ok();
+    }
+    if let Some(compass) = location.compass {
+        writeln!(text, "{}", tr!("  나침반: {:.0}°", "  Compass: {:.0}°", compass)).ok();
+    }
+    if let Some(info) = &location.device_info {
+        if let Some(platform) = &info.platform {
+            writeln!(text, "{}", tr!("  플랫폼: {}", "  Platform: {}", platform)).ok();
+        }
+        if let Some(battery) = info.battery {
+            let charging = if info.charging == Some(true) { pick(" (충전 중)", " (charging)") } else { "" };
+            writeln!(text, "{}", tr!("  배터리: {:.0}%{}", "  Battery: {:.0}%{}", battery * 100.0, charging)).ok();
+        }
+    }
+    for provider in map_links {
+        writeln!(text, "  {}: {}", provider.name(), location.map_url(*provider)).ok();
+    }
+    text.push_str(&"=".repeat(60));
+    info!("{}", text);
+}
+
+/// 브라우저 프로세스 강제 종료 (`CloseStrategy::KillProcess`)
+pub(crate) fn kill_browser() {
+    #[cfg(target_os = "windows")]
+    Command::new("cmd").args(["/C", "taskkill /IM chrome.exe /F"]).spawn().ok();
+
+    #[cfg(target_os = "macos")]
+    {
+        // 페이지 제목은 현재 언어를 따른다
+        let script = format!("tell application \"Safari\" to close (every window whose name contains \"{}\")", crate::i18n::page_text().title);
+        Command::new("osascript").args(["-e", &script]).spawn().ok();
+    }
+
+    #[cfg(target_os = "linux")]
+    Command::new("pkill").arg("chrome").spawn().ok();
+}
@@ -0,0 +1,149 @@
+use std::sync::Mutex;
+use std::time::Instant;
+use serde::Serialize;
+
+use crate::handler::Reply;
+use crate::locator::Mode;
+
+// 이 빌드에서 켠 기능. `/version`이 알려 준다
+const FEATURES: &[(&str, bool)] = &[
+    ("tokio", cfg!(feature = "tokio")),
+    ("axum", cfg!(feature = "axum")),
+    ("http-client", cfg!(feature = "http-client")),
+    ("ip-lookup", cfg!(feature = "ip-lookup")),
+    ("geocoding", cfg!(feature = "geocoding")),
+    ("elevation", cfg!(feature = "elevation")),
+    ("weather", cfg!(feature = "weather")),
+    ("poi", cfg!(feature = "poi")),
+    ("static-map", cfg!(feature = "static-map")),
+    ("heatmap", cfg!(feature = "heatmap")),
+    ("webhook", cfg!(feature = "webhook")),
+    ("traccar", cfg!(feature = "traccar")),
+    ("mqtt", cfg!(feature = "mqtt")),
+    ("websocket", cfg!(feature = "websocket")),
+    ("headless", cfg!(feature = "headless")),
+    ("windows-native", cfg!(feature = "windows-native")),
+    ("macos-native", cfg!(feature = "macos-native")),
+    ("geoclue", cfg!(feature = "geoclue")),
+    ("notifications", cfg!(feature = "notifications")),
+    ("nmea", cfg!(feature = "nmea")),
+    ("gpx", cfg!(feature = "gpx")),
+    ("qr", cfg!(feature = "qr")),
+    ("tls", cfg!(feature = "tls")),
+    ("history", cfg!(feature = "history")),
+    ("encryption", cfg!(feature = "encryption")),
+    ("spatial", cfg!(feature = "spatial")),
+    ("geojson", cfg!(feature = "geojson")),
+    ("geo", cfg!(feature = "geo")),
+    ("h3", cfg!(feature = "h3")),
+    ("timezone", cfg!(feature = "timezone")),
+    ("config", cfg!(feature = "config")),
+    ("cli", cfg!(feature = "cli")),
+    ("ffi", cfg!(feature = "ffi")),
+    ("python", cfg!(feature = "python")),
+];
+
+// 브라우저 쪽 위치 공급원의 상태
+#[derive(Serialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+enum Browser {
+    // 아직 아무도 페이지를 받아 가지 않았다
+    Waiting,
+    // 페이지를 받아 갔지만 위치는 아직 오지 않았다
+    Connected,
+    // 위치를 받고 있다
+    Receiving,
+}
+
+#[derive(Serialize)]
+struct Status {
+    status: &'static str,
+    provider: &'static str,
+    browser: Browser,
+    mode: &'static str,
+    uptime_seconds: f64,
+    fixes: u64,
+    // 아직 받은 위치가 없으면 null
+    last_fix_age_seconds: Option<f64>,
+}
+
+#[derive(Serialize)]
+struct Version {
+    name: &'static str,
+    version: &'static str,
+    features: Vec<&'static str>,
+}
+
+#[derive(Default)]
+struct Seen {
+    served: bool,
+    fixes: u64,
+    last_fix: Option<Instant>,
+}
+
+/// 감시 도구와 스크립트가 서버가 살아 있는지, 어떤 빌드인지 확인하는 엔드포인트
+///
+/// - `GET /healthz`: 위치 공급원 상태, 서버를 연 뒤 지난 시간, 마지막 위치를 받은 뒤 지난 시간
+/// - `GET /version`: 크레이트 버전과 켠 기능
+pub(crate) struct Health {
+    started: Instant,
+    mode: &'static str,
+    seen: Mutex<Seen>,
+}
+
+impl Health {
+    pub(crate) fn new(mode: Mode) -> Self {
+        let mode = match mode {
+            Mode::Single => "single",
+            Mode::Samples(_) => "samples",
+            Mode::Watch => "watch",
+            Mode::Session => "session",
+        };
+        Health { started: Instant::now(), mode, seen: Mutex::default() }
+    }
+
+    pub(crate) fn served(&self) {
+        self.seen.lock().unwrap().served = true;
+    }
+
+    pub(crate) fn fix(&self) {
+        let mut seen = self.seen.lock().unwrap();
+        seen.fixes += 1;
+        seen.last_fix = Some(Instant::now());
+    }
+
+    /// 경로가 `/healthz`나 `/version`이 아니면 None
+    pub(crate) fn handle(&self, path: &str) -> Option<Reply> {
+        let body = match path {
+            "/healthz" => serde_json::to_string(&self.status()),
+            "/version" => serde_json::to_string(&Version {
+                name: env!("CARGO_PKG_NAME"),
+                version: env!("CARGO_PKG_VERSION"),
+                features: FEATURES.iter().filter(|(_, enabled)| *enabled).map(|(name, _)| *name).collect(),
+            }),
+            _ => return None,
+        };
+        let reply = Reply::new(200, body.unwrap_or_default())
+            .header("Content-Type", "application/json")
+            .header("Cache-Control", "no-store");
+        Some(reply)
+    }
+
+    fn status(&self) -> Status {
+        let seen = self.seen.lock().unwrap();
+        let browser = match (seen.served, seen.last_fix) {
+            (_, Some(_)) => Browser::Receiving,
+            (true, None) => Browser::Connected,
+            (false, None) => Browser::Waiting,
+        };
+        Status {
+            status: "ok",
+            provider: "browser",
+            browser,
+            mode: self.mode,
+            uptime_seconds: self.started.elapsed().as_secs_f64(),
+            fixes: seen.fixes,
+            last_fix_age_seconds: seen.last_fix.map(|at| at.elapsed().as_secs_f64()),
+        }
+    }
+}
@@ -0,0 +1,313 @@
+//! 기록 열지도 (`heatmap` 기능 필요)
+//!
+//! 위치를 격자에 모아 오래 머문 곳일수록 뜨거운 색으로 칠한다. 격자는 웹 메르카토르 좌표라서
+//! 만든 PNG를 `HeatmapImage`의 남서, 북동 모서리에 맞춰 OpenStreetMap 같은 타일 지도 위에 그대로 겹칠 수 있다(Leaflet의 `L.imageOverlay`).
+//! Leaflet.heat에 넘길 `[위도, 경도, 세기]` 배열도 만든다.
+
+use std::f64::consts::PI;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::geo::EARTH_RADIUS;
+use crate::i18n::{pick, tr};
+use crate::{Location, WeimError};
+
+// 웹 메르카토르가 다루는 위도 끝
+const MAX_LATITUDE: f64 = 85.051_128_78;
+const MAX_SIZE: usize = 4096;
+
+/// 세기 0~1을 색으로 바꾸는 색 띠
+///
+/// 구간 끝마다 색을 두고 그 사이는 섞는다. 세기가 0인 칸은 투명하고, 세기가 낮을수록 흐리게 칠한다.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColorRamp {
+    stops: Vec<(f64, [u8; 3])>,
+}
+
+impl Default for ColorRamp {
+    /// Leaflet.heat의 기본 색 띠 (파랑 → 하늘 → 연두 → 노랑 → 빨강)
+    fn default() -> Self {
+        ColorRamp {
+            stops: vec![
+                (0.0, [0x00, 0x00, 0xff]),
+                (0.4, [0x00, 0x00, 0xff]),
+                (0.6, [0x00, 0xff, 0xff]),
+                (0.7, [0x00, 0xff, 0x00]),
+                (0.8, [0xff, 0xff, 0x00]),
+                (1.0, [0xff, 0x00, 0x00]),
+            ],
+        }
+    }
+}
+
+impl ColorRamp {
+    /// `(세기, 색)` 목록. 세기는 0~1이고 순서대로 정렬한다. 비어 있으면 기본 색 띠를 쓴다.
+    pub fn new(mut stops: Vec<(f64, [u8; 3])>) -> Self {
+        stops.retain(|(position, _)| position.is_finite());
+        if stops.is_empty() {
+            return Self::default();
+        }
+        stops.sort_by(|a, b| a.0.total_cmp(&b.0));
+        ColorRamp { stops }
+    }
+
+    /// 세기 `t`(0~1)의 색
+    pub fn color(&self, t: f64) -> [u8; 3] {
+        let t = t.clamp(0.0, 1.0);
+        let upper = self.stops.iter().position(|(position, _)| *position >= t).unwrap_or(self.stops.len() - 1);
+        if upper == 0 {
+            return self.stops[0].1;
+        }
+        let ((from, low), (to, high)) = (self.stops[upper - 1], self.stops[upper]);
+        let mix = if to > from { (t - from) / (to - from) } else { 1.0 };
+        std::array::from_fn(|i| (f64::from(low[i]) + (f64::from(high[i]) - f64::from(low[i])) * mix).round() as u8)
+    }
+}
+
+/// 쉼표로 나눈 `#rrggbb` 색들. 0부터 1까지 같은 간격으로 놓는다. 예: `#000080,#ff8000,#ffff00`
+impl FromStr for ColorRamp {
+    type Err = WeimError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let colors = value
+            .split(',')
+            .map(|color| {
+                let hex = color.trim().trim_start_matches('#');
+                let channel = |i: usize| hex.get(i..i + 2).and_then(|pair| u8::from_str_radix(pair, 16).ok());
+                match (hex.len(), channel(0), channel(2), channel(4)) {
+                    (6, Some(r), Some(g), Some(b)) => Ok([r, g, b]),
+                    _ => Err(WeimError::InvalidPayload(tr!("#rrggbb 색이 아닙니다: {}", "not a #rrggbb color: {}", color.trim()))),
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let last = (colors.len() - 1).max(1) as f64;
+        Ok(ColorRamp::new(colors.into_iter().enumerate().map(|(i, color)| (i as f64 / last, color)).collect()))
+    }
+}
+
+/// 열지도 설정
+#[derive(Debug, Clone, PartialEq)]
+pub struct Heatmap {
+    cell: f64,
+    blur: u32,
+    ramp: ColorRamp,
+    time_weighted: bool,
+    max_weight: Duration,
+}
+
+impl Default for Heatmap {
+    fn default() -> Self {
+        Heatmap { cell: 50.0, blur: 1, ramp: ColorRamp::default(), time_weighted: true, max_weight: Duration::from_secs(10 * 60) }
+    }
+}
+
+/// 만든 열지도 PNG와 그것이 덮는 범위
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeatmapImage {
+    pub png: Vec<u8>,
+    pub width: u32,
+    pub height: u32,
+    /// 이미지 가장자리 위도/경도 (°)
+    pub south: f64,
+    pub west: f64,
+    pub north: f64,
+    pub east: f64,
+}
+
+// 값을 모은 격자. 칸 `(x, y)`는 `cells[y * width + x]`이고 y는 북쪽이 0이다.
+struct Grid {
+    width: usize,
+    height: usize,
+    // 왼쪽 위 모서리의 메르카토르 좌표와 칸 크기
+    left: f64,
+    top: f64,
+    step: f64,
+    cells: Vec<f64>,
+}
+
+impl Heatmap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 격자 한 칸의 크기 (m, 기본값 50). 이미지가 4096 픽셀을 넘으면 칸을 키운다.
+    pub fn cell_size(mut self, meters: f64) -> Self {
+        self.cell = meters;
+        self
+    }
+
+    /// 이웃한 이만큼의 칸까지 번지게 그린다 (기본값 1, 0이면 번지지 않음)
+    pub fn blur(mut self, cells: u32) -> Self {
+        self.blur = cells.min(32);
+        self
+    }
+
+    pub fn ramp(mut self, ramp: ColorRamp) -> Self {
+        self.ramp = ramp;
+        self
+    }
+
+    /// 위치마다 다음 위치까지 머문 시간만큼 무게를 줄지 (기본값 true). false면 위치 수를 센다.
+    ///
+    /// 다음 위치까지 `max_weight`보다 비면 그만큼만 센다. 기록이 끊긴 동안 머문 것으로 치지 않으려는 것이다.
+    pub fn time_weighted(mut self, enabled: bool) -> Self {
+        self.time_weighted = enabled;
+        self
+    }
+
+    /// 위치 하나가 가질 수 있는 가장 긴 시간 (기본값 10분)
+    pub fn max_weight(mut self, duration: Duration) -> Self {
+        self.max_weight = duration;
+        self
+    }
+
+    /// 기기별 시간순 위치로 열지도 PNG를 그린다. 위치가 없으면 `WeimError::InvalidPayload`
+    pub fn render(&self, tracks: &[&[Location]]) -> Result<HeatmapImage, WeimError> {
+        let mut grid = self.grid(tracks)?;
+        // 오래 머문 몇 곳만 보이지 않도록 로그로 줄인 뒤에 번지게 한다
+        grid.cells.iter_mut().for_each(|value| *value = value.ln_1p());
+        grid.blur(self.blur as usize);
+        let max = grid.cells.iter().copied().fold(0.0, f64::max);
+        let mut pixels = Vec::with_capacity(grid.cells.len() * 4);
+        for &value in &grid.cells {
+            let t = if max > 0.0 { value / max } else { 0.0 };
+            let [r, g, b] = self.ramp.color(t);
+            pixels.extend([r, g, b, (t.sqrt() * 255.0).round() as u8]);
+        }
+
+        let (width, height) = (grid.width as u32, grid.height as u32);
+        let mut png = Vec::new();
+        let mut encoder = png::Encoder::new(&mut png, width, height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder
+            .write_header()
+            .and_then(|mut writer| writer.write_image_data(&pixels))
+            .map_err(|e| WeimError::Io(std::io::Error::other(e)))?;
+
+        let right = grid.left + grid.step * grid.width as f64;
+        let bottom = grid.top - grid.step * grid.height as f64;
+        Ok(HeatmapImage {
+            png,
+            width,
+            height,
+            south: latitude(bottom),
+            west: grid.left.to_degrees(),
+            north: latitude(grid.top),
+            east: right.to_degrees(),
+        })
+    }
+
+    /// Leaflet.heat(`L.heatLayer(points, { max: 1 })`)에 넘길 `[위도, 경도, 세기]` 배열. 세기는 가장 뜨거운 칸이 1이다.
+    ///
+    /// 번지는 것은 Leaflet.heat가 그리므로 여기서는 칸의 가운데만 돌려준다.
+    pub fn leaflet(&self, tracks: &[&[Location]]) -> Result<Vec<[f64; 3]>, WeimError> {
+        let grid = self.grid(tracks)?;
+        let max = grid.cells.iter().copied().fold(0.0, f64::max);
+        let mut points = Vec::new();
+        for (i, &value) in grid.cells.iter().enumerate() {
+            if value > 0.0 {
+                let (x, y) = ((i % grid.width) as f64 + 0.5, (i / grid.width) as f64 + 0.5);
+                let intensity = value / max;
+                points.push([latitude(grid.top - y * grid.step), (grid.left + x * grid.step).to_degrees(), intensity]);
+            }
+        }
+        Ok(points)
+    }
+
+    fn grid(&self, tracks: &[&[Location]]) -> Result<Grid, WeimError> {
+        let points = || tracks.iter().flat_map(|track| track.iter());
+        let (mut west, mut east, mut south, mut north) = (f64::INFINITY, f64::NEG_INFINITY, f64::INFINITY, f64::NEG_INFINITY);
+        for point in points() {
+            let (x, y) = mercator(point);
+            (west, east, south, north) = (west.min(x), east.max(x), south.min(y), north.max(y));
+        }
+        if !west.is_finite() {
+            return Err(WeimError::InvalidPayload(pick("열지도를 그릴 위치가 없습니다", "no locations to draw a heatmap from").to_string()));
+        }
+
+        // 가운데 위도에서 `cell` 미터가 되는 메르카토르 간격. 번지는 칸만큼 둘레를 비워 둔다
+        let center = latitude((south + north) / 2.0).to_radians();
+        let margin = self.blur as f64 + 1.0;
+        let mut step = self.cell.max(1.0) / (EARTH_RADIUS * center.cos());
+        let span = (east - west).max(north - south);
+        if span / step + 2.0 * margin > MAX_SIZE as f64 {
+            step = span / (MAX_SIZE as f64 - 2.0 * margin);
+        }
+        let (left, top) = (west - margin * step, north + margin * step);
+        let width = (((east - west) / step).floor() as usize + 1 + 2 * margin as usize).min(MAX_SIZE);
+        let height = (((north - south) / step).floor() as usize + 1 + 2 * margin as usize).min(MAX_SIZE);
+        let mut grid = Grid { width, height, left, top, step, cells: vec![0.0; width * height] };
+
+        let max_weight = self.max_weight.as_secs_f64();
+        for track in tracks {
+            for (i, point) in track.iter().enumerate() {
+                let weight = match track.get(i + 1) {
+                    _ if !self.time_weighted => 1.0,
+                    Some(next) => (next.timestamp - point.timestamp).as_seconds_f64().clamp(0.0, max_weight),
+                    // 마지막 위치는 바로 앞 간격만큼 머문 것으로 친다
+                    None => i.checked_sub(1).map_or(1.0, |previous| (point.timestamp - track[previous].timestamp).as_seconds_f64().clamp(0.0, max_weight)),
+                };
+                let (x, y) = mercator(point);
+                let column = (((x - left) / step) as usize).min(width - 1);
+                let row = (((top - y) / step) as usize).min(height - 1);
+                grid.cells[row * width + column] += weight;
+            }
+        }
+        Ok(grid)
+    }
+}
+
+impl Grid {
+    // 가로, 세로로 한 번씩 가우스 커널을 건다. 두 번 나눠 걸어도 둥글게 번진다.
+    fn blur(&mut self, radius: usize) {
+        if radius == 0 {
+            return;
+        }
+        let sigma = radius as f64 / 3.0;
+        let kernel: Vec<f64> = (0..=2 * radius).map(|i| (-(i.abs_diff(radius).pow(2) as f64) / (2.0 * sigma * sigma)).exp()).collect();
+        let (width, height) = (self.width, self.height);
+        let pass = |cells: &[f64], at: &dyn Fn(usize, usize) -> usize, length: usize, lines: usize| {
+            let mut out = vec![0.0; cells.len()];
+            for line in 0..lines {
+                for i in 0..length {
+                    let mut sum = 0.0;
+                    for (k, weight) in kernel.iter().enumerate() {
+                        if let Some(j) = (i + k).checked_sub(radius).filter(|&j| j < length) {
+                            sum += cells[at(line, j)] * weight;
+                        }
+                    }
+                    out[at(line, i)] = sum;
+                }
+            }
+            out
+        };
+        let horizontal = pass(&self.cells, &|row, column| row * width + column, width, height);
+        self.cells = pass(&horizontal, &|column, row| row * width + column, height, width);
+    }
+}
+
+// 단위 구 위의 웹 메르카토르 좌표 (라디안)
+fn mercator(location: &Location) -> (f64, f64) {
+    let phi = location.latitude.clamp(-MAX_LATITUDE, MAX_LATITUDE).to_radians();
+    (location.longitude.to_radians(), (PI / 4.0 + phi / 2.0).tan().ln())
+}
+
+fn latitude(y: f64) -> f64 {
+    (2.0 * y.exp().atan() - PI / 2.0).to_degrees()
+}
+
+#[cfg(feature = "history")]
+impl crate::History {
+    /// 조건에 맞는 위치로 열지도 PNG를 그린다. (`heatmap` 기능 필요)
+    pub fn heatmap(&self, query: &crate::HistoryQuery, heatmap: &Heatmap) -> Result<HeatmapImage, WeimError> {
+        let tracks = self.tracks(query)?;
+        heatmap.render(&tracks.iter().map(|track| track.points.as_slice()).collect::<Vec<_>>())
+    }
+
+    /// 조건에 맞는 위치로 Leaflet.heat 배열을 만든다. (`heatmap` 기능 필요)
+    pub fn heatmap_points(&self, query: &crate::HistoryQuery, heatmap: &Heatmap) -> Result<Vec<[f64; 3]>, WeimError> {
+        let tracks = self.tracks(query)?;
+        heatmap.leaflet(&tracks.iter().map(|track| track.points.as_slice()).collect::<Vec<_>>())
+    }
+}
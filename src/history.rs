@@ -0,0 +1,705 @@
+#[cfg(feature = "spatial")]
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+#[cfg(feature = "encryption")]
+use std::fmt;
+use std::path::Path;
+#[cfg(feature = "encryption")]
+use std::path::PathBuf;
+use std::time::Duration;
+use chrono::{DateTime, TimeDelta, Utc};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+
+use crate::export::{self, Track};
+use crate::i18n::pick;
+#[cfg(feature = "encryption")]
+use crate::i18n::tr;
+#[cfg(feature = "encryption")]
+use crate::seal::{Seal, SALT_LEN};
+#[cfg(feature = "spatial")]
+use crate::spatial::SpatialIndex;
+use crate::{DeviceId, Location, Source, WeimError};
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS fixes (
+        id INTEGER PRIMARY KEY,
+        device TEXT NOT NULL,
+        latitude REAL NOT NULL,
+        longitude REAL NOT NULL,
+        accuracy REAL NOT NULL,
+        altitude REAL,
+        altitude_accuracy REAL,
+        speed REAL,
+        heading REAL,
+        timestamp INTEGER NOT NULL,
+        source TEXT
+    );
+    CREATE INDEX IF NOT EXISTS fixes_time ON fixes (timestamp);
+    CREATE INDEX IF NOT EXISTS fixes_device_time ON fixes (device, timestamp);
+";
+
+// 암호화한 저장소. 기기와 시각만 평문으로 두어 색인을 쓰고, 나머지는 `sealed`에 봉한다.
+// `encryption`에는 소금과, 열쇠가 맞는지 확인할 봉한 값 하나가 들어 있다.
+#[cfg(feature = "encryption")]
+const SEALED_SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS encryption (
+        salt BLOB NOT NULL,
+        verifier BLOB NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS sealed_fixes (
+        id INTEGER PRIMARY KEY,
+        device TEXT NOT NULL,
+        timestamp INTEGER NOT NULL,
+        sealed BLOB NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS sealed_fixes_time ON sealed_fixes (timestamp);
+    CREATE INDEX IF NOT EXISTS sealed_fixes_device_time ON sealed_fixes (device, timestamp);
+";
+
+#[cfg(feature = "encryption")]
+const SELECT_SEALED: &str = "
+    SELECT id, device, timestamp, sealed
+    FROM sealed_fixes
+    WHERE (?1 IS NULL OR timestamp >= ?1)
+      AND (?2 IS NULL OR timestamp < ?2)
+      AND (?3 IS NULL OR device = ?3)
+    ORDER BY timestamp, id
+    LIMIT ?4
+";
+
+#[cfg(feature = "encryption")]
+const DELETE_SEALED: &str = "
+    DELETE FROM sealed_fixes WHERE id IN (
+        SELECT id
+        FROM sealed_fixes
+        WHERE (?1 IS NULL OR timestamp >= ?1)
+          AND (?2 IS NULL OR timestamp < ?2)
+          AND (?3 IS NULL OR device = ?3)
+        ORDER BY timestamp, id
+        LIMIT ?4
+    )
+";
+
+// 열쇠를 확인하려고 봉해 두는 글자
+#[cfg(feature = "encryption")]
+const VERIFIER: &[u8] = b"weim history";
+
+// 비어 있는 조건은 NULL로 넘겨서 건너뛴다. LIMIT -1은 제한 없음.
+const SELECT: &str = "
+    SELECT device, latitude, longitude, accuracy, altitude, altitude_accuracy, speed, heading, timestamp, source
+    FROM fixes
+    WHERE (?1 IS NULL OR timestamp >= ?1)
+      AND (?2 IS NULL OR timestamp < ?2)
+      AND (?3 IS NULL OR device = ?3)
+      AND (?4 IS NULL OR latitude BETWEEN ?4 AND ?5)
+      AND (?6 IS NULL OR longitude BETWEEN ?6 AND ?7)
+    ORDER BY timestamp, id
+    LIMIT ?8
+";
+
+// `SELECT`와 같은 조건으로 고른 위치를 지운다
+const DELETE: &str = "
+    DELETE FROM fixes WHERE id IN (
+        SELECT id
+        FROM fixes
+        WHERE (?1 IS NULL OR timestamp >= ?1)
+          AND (?2 IS NULL OR timestamp < ?2)
+          AND (?3 IS NULL OR device = ?3)
+          AND (?4 IS NULL OR latitude BETWEEN ?4 AND ?5)
+          AND (?6 IS NULL OR longitude BETWEEN ?6 AND ?7)
+        ORDER BY timestamp, id
+        LIMIT ?8
+    )
+";
+
+/// 위도/경도 범위
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingBox {
+    pub min_latitude: f64,
+    pub min_longitude: f64,
+    pub max_latitude: f64,
+    pub max_longitude: f64,
+}
+
+impl BoundingBox {
+    pub fn new(min_latitude: f64, min_longitude: f64, max_latitude: f64, max_longitude: f64) -> Self {
+        BoundingBox { min_latitude, min_longitude, max_latitude, max_longitude }
+    }
+}
+
+/// 저장된 위치 하나
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryEntry {
+    pub device: DeviceId,
+    pub location: Location,
+}
+
+/// `History::query()`의 검색 조건. 아무것도 정하지 않으면 전부 돌려준다.
+#[derive(Debug, Clone, Default)]
+pub struct HistoryQuery {
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+    device: Option<DeviceId>,
+    bounds: Option<BoundingBox>,
+    limit: Option<u32>,
+}
+
+impl HistoryQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 이 시각 이후의 위치만
+    pub fn since(mut self, timestamp: DateTime<Utc>) -> Self {
+        self.since = Some(timestamp);
+        self
+    }
+
+    /// 이 시각 이전의 위치만 (해당 시각은 빠진다)
+    pub fn until(mut self, timestamp: DateTime<Utc>) -> Self {
+        self.until = Some(timestamp);
+        self
+    }
+
+    /// 이 기기가 보낸 위치만
+    pub fn device(mut self, device: DeviceId) -> Self {
+        self.device = Some(device);
+        self
+    }
+
+    /// 범위 안의 위치만
+    pub fn within(mut self, bounds: BoundingBox) -> Self {
+        self.bounds = Some(bounds);
+        self
+    }
+
+    /// 최대 개수 (기본값 무제한). 오래된 것부터 센다.
+    pub fn limit(mut self, count: u32) -> Self {
+        self.limit = Some(count);
+        self
+    }
+
+    // 개수 제한을 뺀 조건에 맞는지. 데이터베이스 밖에서 고를 때 쓴다.
+    #[cfg(feature = "spatial")]
+    pub(crate) fn matches(&self, entry: &HistoryEntry) -> bool {
+        let location = &entry.location;
+        self.since.is_none_or(|since| location.timestamp >= since)
+            && self.until.is_none_or(|until| location.timestamp < until)
+            && self.device.as_ref().is_none_or(|device| *device == entry.device)
+            && self.bounds.is_none_or(|b| {
+                (b.min_latitude..=b.max_latitude).contains(&location.latitude) && (b.min_longitude..=b.max_longitude).contains(&location.longitude)
+            })
+    }
+
+    // 데이터베이스에는 Unix epoch(ms)로 들어 있다
+    fn range(&self) -> (Option<i64>, Option<i64>) {
+        (self.since.map(|since| since.timestamp_millis()), self.until.map(|until| until.timestamp_millis()))
+    }
+
+    #[cfg(feature = "spatial")]
+    pub(crate) fn max_count(&self) -> usize {
+        self.limit.map_or(usize::MAX, |limit| limit as usize)
+    }
+}
+
+/// 기록을 얼마나 남길지. 정하지 않은 기준은 보지 않는다.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Retention {
+    max_age: Option<Duration>,
+    max_rows: Option<u64>,
+}
+
+impl Retention {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 이보다 오래된 위치는 지운다 (기본값 없음)
+    pub fn max_age(mut self, age: Duration) -> Self {
+        self.max_age = Some(age);
+        self
+    }
+
+    /// 최근 이 개수만 남긴다 (기본값 없음)
+    pub fn max_rows(mut self, rows: u64) -> Self {
+        self.max_rows = Some(rows);
+        self
+    }
+}
+
+/// 암호화한 기록을 여는 열쇠 (`encryption` 기능 필요)
+///
+/// 어느 쪽이든 데이터베이스마다 다른 소금과 함께 Argon2id를 거쳐 실제 열쇠가 된다.
+#[cfg(feature = "encryption")]
+#[derive(Clone, PartialEq, Eq)]
+pub enum HistoryKey {
+    /// 사람이 외우는 암호
+    Passphrase(String),
+    /// 이 파일의 내용 전체. `head -c 32 /dev/urandom > weim.key`처럼 만들어 둔다.
+    File(PathBuf),
+}
+
+#[cfg(feature = "encryption")]
+impl HistoryKey {
+    fn secret(&self) -> Result<Vec<u8>, WeimError> {
+        let secret = match self {
+            HistoryKey::Passphrase(passphrase) => passphrase.as_bytes().to_vec(),
+            HistoryKey::File(path) => std::fs::read(path)?,
+        };
+        if secret.is_empty() {
+            return Err(WeimError::Config(pick("기록 암호나 열쇠 파일이 비어 있습니다.", "The history passphrase or key file is empty.").into()));
+        }
+        Ok(secret)
+    }
+}
+
+// 암호가 로그에 찍히지 않게 한다
+#[cfg(feature = "encryption")]
+impl fmt::Debug for HistoryKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HistoryKey::Passphrase(_) => f.write_str("Passphrase(..)"),
+            HistoryKey::File(path) => f.debug_tuple("File").field(path).finish(),
+        }
+    }
+}
+
+/// 받은 위치를 쌓아 두는 SQLite 저장소 (`history` 기능 필요)
+#[derive(Debug)]
+pub struct History {
+    conn: Connection,
+    // 암호화한 저장소면 행을 봉하고 여는 열쇠
+    #[cfg(feature = "encryption")]
+    seal: Option<Seal>,
+    // `within()`과 `nearest()`가 처음 쓸 때 만든다
+    #[cfg(feature = "spatial")]
+    pub(crate) index: RefCell<Option<SpatialIndex>>,
+}
+
+impl History {
+    /// 데이터베이스 파일을 열고, 없으면 만든다. 암호화한 파일은 `open_encrypted()`로 연다.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, WeimError> {
+        Self::init(Connection::open(path).map_err(storage_error)?)
+    }
+
+    /// 암호화한 데이터베이스 파일을 열고, 없으면 만든다. (`encryption` 기능 필요)
+    ///
+    /// 위치(위도, 경도, 정확도, 고도, 속도, 방향)는 행마다 XChaCha20-Poly1305로 봉하고, 기기 이름과 시각만 평문으로 남는다.
+    /// 암호화하지 않은 기록이 들어 있으면 처음 열 때 모두 봉하고 평문은 지운다. 열쇠가 틀리면 오류를 돌려준다.
+    #[cfg(feature = "encryption")]
+    pub fn open_encrypted(path: impl AsRef<Path>, key: &HistoryKey) -> Result<Self, WeimError> {
+        Self::init_encrypted(Connection::open(path).map_err(storage_error)?, key)
+    }
+
+    /// 메모리에만 두는 저장소
+    pub fn in_memory() -> Result<Self, WeimError> {
+        Self::init(Connection::open_in_memory().map_err(storage_error)?)
+    }
+
+    fn init(conn: Connection) -> Result<Self, WeimError> {
+        conn.execute_batch(SCHEMA).map_err(storage_error)?;
+        migrate(&conn)?;
+        conn.pragma_update(None, "secure_delete", true).map_err(storage_error)?;
+        let encrypted: bool = conn
+            .query_row("SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE name = 'encryption')", [], |row| row.get(0))
+            .map_err(storage_error)?;
+        if encrypted {
+            return Err(WeimError::Storage(pick("암호화한 기록이라 열쇠가 있어야 열 수 있습니다.", "this history is encrypted and needs a key to open").into()));
+        }
+        Ok(History {
+            conn,
+            #[cfg(feature = "encryption")]
+            seal: None,
+            #[cfg(feature = "spatial")]
+            index: RefCell::default(),
+        })
+    }
+
+    #[cfg(feature = "encryption")]
+    fn init_encrypted(mut conn: Connection, key: &HistoryKey) -> Result<Self, WeimError> {
+        conn.execute_batch(SCHEMA).map_err(storage_error)?;
+        migrate(&conn)?;
+        conn.pragma_update(None, "secure_delete", true).map_err(storage_error)?;
+        conn.execute_batch(SEALED_SCHEMA).map_err(storage_error)?;
+        let secret = key.secret()?;
+        let derive = |salt: &[u8]| Seal::derive(&secret, salt).ok_or_else(|| WeimError::Storage(pick("열쇠를 만들지 못했습니다.", "could not derive the key").into()));
+
+        let stored: Option<(Vec<u8>, Vec<u8>)> = conn
+            .query_row("SELECT salt, verifier FROM encryption", [], |row| Ok((row.get(0)?, row.get(1)?)))
+            .optional()
+            .map_err(storage_error)?;
+        let seal = match stored {
+            Some((salt, verifier)) => {
+                let seal = derive(&salt)?;
+                if seal.open(&verifier, &[]).as_deref() != Some(VERIFIER) {
+                    return Err(WeimError::Storage(pick("기록 열쇠가 맞지 않습니다.", "wrong history key").into()));
+                }
+                seal
+            }
+            None => {
+                let salt: [u8; SALT_LEN] = Seal::salt();
+                let seal = derive(&salt)?;
+                conn.execute("INSERT INTO encryption (salt, verifier) VALUES (?1, ?2)", params![salt, seal.seal(VERIFIER, &[])])
+                    .map_err(storage_error)?;
+                seal
+            }
+        };
+
+        // 전에 평문으로 남긴 기록은 봉해서 옮기고, 지운 자리가 파일에 남지 않게 VACUUM한다
+        let plain: Vec<HistoryEntry> = {
+            let mut statement = conn.prepare(SELECT).map_err(storage_error)?;
+            let none = None::<i64>;
+            let rows = statement.query_map(params![none, none, none, none, none, none, none, -1], entry).map_err(storage_error)?;
+            rows.collect::<Result<_, _>>().map_err(storage_error)?
+        };
+        if !plain.is_empty() {
+            let transaction = conn.transaction().map_err(storage_error)?;
+            for entry in &plain {
+                insert_sealed(&transaction, &seal, &entry.device, &entry.location)?;
+            }
+            transaction.execute("DELETE FROM fixes", []).map_err(storage_error)?;
+            transaction.commit().map_err(storage_error)?;
+            conn.execute_batch("VACUUM").map_err(storage_error)?;
+        }
+        Ok(History {
+            conn,
+            seal: Some(seal),
+            #[cfg(feature = "spatial")]
+            index: RefCell::default(),
+        })
+    }
+
+    /// 위치 하나를 덧붙인다.
+    pub fn append(&self, device: &DeviceId, location: &Location) -> Result<(), WeimError> {
+        self.insert(device, location)?;
+        #[cfg(feature = "spatial")]
+        if let Some(index) = self.index.borrow_mut().as_mut() {
+            index.insert(HistoryEntry { device: device.clone(), location: location.clone() });
+        }
+        Ok(())
+    }
+
+    fn insert(&self, device: &DeviceId, location: &Location) -> Result<(), WeimError> {
+        #[cfg(feature = "encryption")]
+        if let Some(seal) = &self.seal {
+            return insert_sealed(&self.conn, seal, device, location);
+        }
+        self.conn
+            .execute(
+                "INSERT INTO fixes (device, latitude, longitude, accuracy, altitude, altitude_accuracy, speed, heading, timestamp, source)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                params![
+                    device.as_str(),
+                    location.latitude,
+                    location.longitude,
+                    location.accuracy,
+                    location.altitude,
+                    location.altitude_accuracy,
+                    location.speed,
+                    location.heading,
+                    location.timestamp.timestamp_millis(),
+                    source_name(location.source),
+                ],
+            )
+            .map_err(storage_error)?;
+        Ok(())
+    }
+
+    /// 조건에 맞는 위치를 시간순으로 돌려준다.
+    pub fn query(&self, query: &HistoryQuery) -> Result<Vec<HistoryEntry>, WeimError> {
+        #[cfg(feature = "encryption")]
+        if let Some(seal) = &self.seal {
+            return Ok(self.query_sealed(seal, query)?.into_iter().map(|(_, entry)| entry).collect());
+        }
+        let bounds = query.bounds;
+        let (since, until) = query.range();
+        let mut statement = self.conn.prepare_cached(SELECT).map_err(storage_error)?;
+        let rows = statement
+            .query_map(
+                params![
+                    since,
+                    until,
+                    query.device.as_ref().map(DeviceId::as_str),
+                    bounds.map(|b| b.min_latitude),
+                    bounds.map(|b| b.max_latitude),
+                    bounds.map(|b| b.min_longitude),
+                    bounds.map(|b| b.max_longitude),
+                    query.limit.map_or(-1, i64::from),
+                ],
+                entry,
+            )
+            .map_err(storage_error)?;
+        rows.collect::<Result<_, _>>().map_err(storage_error)
+    }
+
+    // 범위 조건은 봉한 값을 연 뒤에야 볼 수 있어서, 범위가 있으면 개수 제한도 연 뒤에 건다
+    #[cfg(feature = "encryption")]
+    fn query_sealed(&self, seal: &Seal, query: &HistoryQuery) -> Result<Vec<(i64, HistoryEntry)>, WeimError> {
+        let bounds = query.bounds;
+        let limit = query.limit.filter(|_| bounds.is_none());
+        let (since, until) = query.range();
+        let mut statement = self.conn.prepare_cached(SELECT_SEALED).map_err(storage_error)?;
+        let rows = statement
+            .query_map(
+                params![since, until, query.device.as_ref().map(DeviceId::as_str), limit.map_or(-1, i64::from)],
+                |row| Ok((row.get::<_, i64>(0)?, DeviceId(row.get(1)?), row.get::<_, i64>(2)?, row.get::<_, Vec<u8>>(3)?)),
+            )
+            .map_err(storage_error)?;
+        let mut entries = Vec::new();
+        for row in rows {
+            let (id, device, timestamp, sealed) = row.map_err(storage_error)?;
+            let location = seal
+                .open(&sealed, &context(&device, timestamp))
+                .and_then(|plain| serde_json::from_slice::<SealedFix>(&plain).ok())
+                .map(|fix| fix.location(timestamp))
+                .ok_or_else(|| WeimError::Storage(tr!("{}번 기록을 열 수 없습니다 (손상됨)", "record {} cannot be opened (damaged)", id)))?;
+            if bounds.is_none_or(|b| {
+                (b.min_latitude..=b.max_latitude).contains(&location.latitude) && (b.min_longitude..=b.max_longitude).contains(&location.longitude)
+            }) {
+                entries.push((id, HistoryEntry { device, location }));
+            }
+            if query.limit.is_some_and(|limit| entries.len() >= limit as usize) {
+                break;
+            }
+        }
+        Ok(entries)
+    }
+
+    /// 조건에 맞는 위치를 지우고 지운 개수를 돌려준다. 지운 내용은 파일에서도 0으로 덮는다.
+    pub fn delete(&self, query: &HistoryQuery) -> Result<usize, WeimError> {
+        // 공간 색인은 다음에 찾을 때 다시 만든다
+        #[cfg(feature = "spatial")]
+        self.index.replace(None);
+        let bounds = query.bounds;
+        let (since, until) = query.range();
+        #[cfg(feature = "encryption")]
+        if let Some(seal) = &self.seal {
+            // 범위 조건이 있으면 열어 본 뒤 하나씩 지운다
+            if bounds.is_some() {
+                let transaction = self.conn.unchecked_transaction().map_err(storage_error)?;
+                let rows = self.query_sealed(seal, query)?;
+                for (id, _) in &rows {
+                    transaction.execute("DELETE FROM sealed_fixes WHERE id = ?1", [id]).map_err(storage_error)?;
+                }
+                transaction.commit().map_err(storage_error)?;
+                return Ok(rows.len());
+            }
+            return self
+                .conn
+                .execute(
+                    DELETE_SEALED,
+                    params![since, until, query.device.as_ref().map(DeviceId::as_str), query.limit.map_or(-1, i64::from)],
+                )
+                .map_err(storage_error);
+        }
+        self.conn
+            .execute(
+                DELETE,
+                params![
+                    since,
+                    until,
+                    query.device.as_ref().map(DeviceId::as_str),
+                    bounds.map(|b| b.min_latitude),
+                    bounds.map(|b| b.max_latitude),
+                    bounds.map(|b| b.min_longitude),
+                    bounds.map(|b| b.max_longitude),
+                    query.limit.map_or(-1, i64::from),
+                ],
+            )
+            .map_err(storage_error)
+    }
+
+    /// 이 시각 이전의 위치가 `retention`을 벗어났다. 지울 것이 없으면 None
+    ///
+    /// 지우기 전에 내보내려면 `HistoryQuery::new().until(cutoff)`로 `export_*()`를 부른 뒤 같은 조건으로 `delete()`한다.
+    /// 시각으로 고르므로 그사이에 새로 들어온 위치는 섞이지 않는다.
+    pub fn retention_cutoff(&self, retention: &Retention) -> Result<Option<DateTime<Utc>>, WeimError> {
+        let by_age = retention.max_age.map(|age| {
+            TimeDelta::from_std(age).ok().and_then(|age| Utc::now().checked_sub_signed(age)).unwrap_or(DateTime::<Utc>::MIN_UTC)
+        });
+        // 최근 `max_rows`개 가운데 가장 오래된 위치보다 앞선 것. 같은 시각의 위치는 함께 남긴다
+        let by_rows = match retention.max_rows {
+            Some(0) => Some(DateTime::<Utc>::MAX_UTC),
+            Some(rows) => self
+                .conn
+                .query_row(
+                    &format!("SELECT timestamp FROM {} ORDER BY timestamp DESC, id DESC LIMIT 1 OFFSET ?1", self.table()),
+                    [rows.saturating_sub(1).min(i64::MAX as u64) as i64],
+                    |row| row.get(0),
+                )
+                .optional()
+                .map_err(storage_error)?
+                .and_then(DateTime::from_timestamp_millis),
+            None => None,
+        };
+        Ok(by_age.max(by_rows))
+    }
+
+    /// `retention`을 벗어난 위치를 지우고 지운 개수를 돌려준다.
+    pub fn purge(&self, retention: &Retention) -> Result<usize, WeimError> {
+        match self.retention_cutoff(retention)? {
+            Some(cutoff) => self.delete(&HistoryQuery::new().until(cutoff)),
+            None => Ok(0),
+        }
+    }
+
+    // 위치가 들어 있는 표
+    fn table(&self) -> &'static str {
+        #[cfg(feature = "encryption")]
+        if self.seal.is_some() {
+            return "sealed_fixes";
+        }
+        "fixes"
+    }
+
+    /// 조건에 맞는 위치를 기기별 트랙으로 묶는다.
+    pub fn tracks(&self, query: &HistoryQuery) -> Result<Vec<Track>, WeimError> {
+        let mut tracks: BTreeMap<DeviceId, Vec<Location>> = BTreeMap::new();
+        for entry in self.query(query)? {
+            tracks.entry(entry.device).or_default().push(entry.location);
+        }
+        Ok(tracks.into_iter().map(|(device, points)| Track::new(device.0, points)).collect())
+    }
+
+    fn simplified_tracks(&self, query: &HistoryQuery, tolerance: f64) -> Result<Vec<Track>, WeimError> {
+        let mut tracks = self.tracks(query)?;
+        for track in &mut tracks {
+            track.simplify(tolerance);
+        }
+        Ok(tracks)
+    }
+
+    /// 조건에 맞는 위치를 GPX 1.1 파일로 쓴다. 기기마다 트랙 하나, `split_gap`보다 긴 공백에서 구간을 나눈다.
+    ///
+    /// `tolerance`(m)가 0보다 크면 트랙 모양에서 그만큼 안쪽으로만 벗어나는 위치는 뺀다 (`export::simplify()`).
+    pub fn export_gpx(&self, path: impl AsRef<Path>, query: &HistoryQuery, split_gap: Duration, tolerance: f64) -> Result<(), WeimError> {
+        let tracks = self.tracks(query)?;
+        let mut out = BufWriter::new(File::create(path)?);
+        export::write_gpx(&mut out, &tracks, split_gap, tolerance)?;
+        out.flush()?;
+        Ok(())
+    }
+
+    /// 조건에 맞는 위치를 기기별 `gx:Track`으로 된 KML 파일로 쓴다. `tolerance`는 `export_gpx()`와 같다.
+    pub fn export_kml(&self, path: impl AsRef<Path>, query: &HistoryQuery, tolerance: f64) -> Result<(), WeimError> {
+        let tracks = self.simplified_tracks(query, tolerance)?;
+        let mut out = BufWriter::new(File::create(path)?);
+        export::write_kml(&mut out, &tracks)?;
+        out.flush()?;
+        Ok(())
+    }
+
+    /// 조건에 맞는 위치를 기기별 LineString으로 된 GeoJSON 파일로 쓴다. `tolerance`는 `export_gpx()`와 같다. (`geojson` 기능 필요)
+    #[cfg(feature = "geojson")]
+    pub fn export_geojson(&self, path: impl AsRef<Path>, query: &HistoryQuery, tolerance: f64) -> Result<(), WeimError> {
+        let collection = export::geojson_tracks(&self.simplified_tracks(query, tolerance)?);
+        let mut out = BufWriter::new(File::create(path)?);
+        serde_json::to_writer(&mut out, &collection).map_err(std::io::Error::from)?;
+        out.flush()?;
+        Ok(())
+    }
+}
+
+fn entry(row: &Row) -> rusqlite::Result<HistoryEntry> {
+    Ok(HistoryEntry {
+        device: DeviceId(row.get(0)?),
+        location: Location {
+            latitude: row.get(1)?,
+            longitude: row.get(2)?,
+            accuracy: row.get(3)?,
+            altitude: row.get(4)?,
+            altitude_accuracy: row.get(5)?,
+            speed: row.get(6)?,
+            heading: row.get(7)?,
+            timestamp: DateTime::from_timestamp_millis(row.get(8)?).unwrap_or_default(),
+            device_timestamp: None,
+            address: None,
+            compass: None,
+            weather: None,
+            device_info: None,
+            source: row.get::<_, Option<String>>(9)?.as_deref().map(source).unwrap_or_default(),
+        },
+    })
+}
+
+// `source` 열이 생기기 전에 만든 파일에 열을 더한다. 그때 남긴 위치는 측정한 위치로 읽는다
+fn migrate(conn: &Connection) -> Result<(), WeimError> {
+    let has_source: bool = conn
+        .query_row("SELECT EXISTS (SELECT 1 FROM pragma_table_info('fixes') WHERE name = 'source')", [], |row| row.get(0))
+        .map_err(storage_error)?;
+    if !has_source {
+        conn.execute_batch("ALTER TABLE fixes ADD COLUMN source TEXT").map_err(storage_error)?;
+    }
+    Ok(())
+}
+
+fn source_name(source: Source) -> &'static str {
+    match source {
+        Source::Measured => "measured",
+        Source::Manual => "manual",
+    }
+}
+
+// 모르는 값은 측정한 위치로 본다
+fn source(name: &str) -> Source {
+    match name {
+        "manual" => Source::Manual,
+        _ => Source::Measured,
+    }
+}
+
+// `Weim` 설정대로 연다. 열쇠를 정했으면 암호화한 저장소로 연다.
+pub(crate) fn open(path: &Path, #[cfg(feature = "encryption")] key: Option<&HistoryKey>) -> Result<History, WeimError> {
+    #[cfg(feature = "encryption")]
+    if let Some(key) = key {
+        return History::open_encrypted(path, key);
+    }
+    History::open(path)
+}
+
+// 봉하는 위치 값. 시각은 평문 열과 `context`에 있다. 출처가 없는 예전 값은 측정한 위치로 읽는다
+#[cfg(feature = "encryption")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct SealedFix(f64, f64, f64, Option<f64>, Option<f64>, Option<f64>, Option<f64>, #[serde(default)] Source);
+
+#[cfg(feature = "encryption")]
+impl SealedFix {
+    fn location(self, timestamp: i64) -> Location {
+        let SealedFix(latitude, longitude, accuracy, altitude, altitude_accuracy, speed, heading, source) = self;
+        let timestamp = DateTime::from_timestamp_millis(timestamp).unwrap_or_default();
+        Location { latitude, longitude, accuracy, altitude, altitude_accuracy, speed, heading, timestamp, source, ..Location::default() }
+    }
+}
+
+// 봉한 값을 다른 행의 기기나 시각으로 옮겨 붙이면 열리지 않게 한다
+#[cfg(feature = "encryption")]
+fn context(device: &DeviceId, timestamp: i64) -> Vec<u8> {
+    [device.as_str().as_bytes(), &[0], &timestamp.to_le_bytes()].concat()
+}
+
+#[cfg(feature = "encryption")]
+fn insert_sealed(conn: &Connection, seal: &Seal, device: &DeviceId, location: &Location) -> Result<(), WeimError> {
+    let fix = SealedFix(
+        location.latitude,
+        location.longitude,
+        location.accuracy,
+        location.altitude,
+        location.altitude_accuracy,
+        location.speed,
+        location.heading,
+        location.source,
+    );
+    let plain = serde_json::to_vec(&fix).map_err(|e| WeimError::Storage(e.to_string()))?;
+    conn.execute(
+        "INSERT INTO sealed_fixes (device, timestamp, sealed) VALUES (?1, ?2, ?3)",
+        params![device.as_str(), location.timestamp.timestamp_millis(), seal.seal(&plain, &context(device, location.timestamp.timestamp_millis()))],
+    )
+    .map_err(storage_error)?;
+    Ok(())
+}
+
+fn storage_error(e: rusqlite::Error) -> WeimError {
+    WeimError::Storage(e.to_string())
+}
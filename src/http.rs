@@ -0,0 +1,38 @@
+use std::sync::OnceLock;
+use std::time::Duration;
+use serde::de::DeserializeOwned;
+
+use crate::WeimError;
+
+const USER_AGENT: &str = concat!("weim/", env!("CARGO_PKG_VERSION"));
+
+// 외부 서비스 요청에 함께 쓰는 에이전트
+pub(crate) fn agent() -> &'static ureq::Agent {
+    static AGENT: OnceLock<ureq::Agent> = OnceLock::new();
+    AGENT.get_or_init(|| {
+        ureq::Agent::config_builder()
+            .timeout_global(Some(Duration::from_secs(10)))
+            .user_agent(USER_AGENT)
+            .build()
+            .into()
+    })
+}
+
+/// 쿼리 값은 알아서 인코딩된다.
+// 켠 기능에 따라 에이전트만 쓰기도 한다
+#[allow(dead_code)]
+pub(crate) fn get_json<T: DeserializeOwned>(url: &str, query: &[(&str, String)]) -> Result<T, WeimError> {
+    agent()
+        .get(url)
+        .query_pairs(query.iter().map(|(key, value)| (*key, value.as_str())))
+        .call()
+        .map_err(network_error)?
+        .body_mut()
+        .read_json()
+        .map_err(network_error)
+}
+
+#[allow(dead_code)]
+pub(crate) fn network_error(e: ureq::Error) -> WeimError {
+    WeimError::Network(e.to_string())
+}
@@ -0,0 +1,214 @@
+use std::sync::atomic::{AtomicU8, Ordering};
+#[cfg(not(target_arch = "wasm32"))]
+use serde::Serialize;
+
+// 프로세스 전체에 하나. 아직 정하지 않았으면(0) 처음 쓸 때 시스템 로케일에서 고른다.
+static LANGUAGE: AtomicU8 = AtomicU8::new(0);
+const KOREAN: u8 = 1;
+const ENGLISH: u8 = 2;
+
+/// 콘솔 안내, 오류 메시지, 브라우저 페이지에 쓸 언어
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Language {
+    /// 한국어
+    Korean,
+    /// 영어
+    English,
+    /// 시스템 로케일(`LC_ALL`, `LC_MESSAGES`, `LANG`)이 한국어면 한국어, 다른 언어면 영어,
+    /// 로케일이 없으면 한국어 (기본값)
+    #[default]
+    Auto,
+}
+
+impl Language {
+    /// `Auto`면 시스템 로케일로 고른 언어를, 아니면 그대로 돌려준다.
+    pub fn resolve(self) -> Language {
+        match self {
+            Language::Auto => detect(),
+            language => language,
+        }
+    }
+}
+
+fn detect() -> Language {
+    let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.is_empty());
+    match locale {
+        Some(locale) if locale.starts_with("ko") => Language::Korean,
+        // "C"와 "POSIX"는 로케일을 정하지 않은 것과 같다
+        Some(locale) if locale != "C" && locale != "POSIX" => Language::English,
+        _ => Language::Korean,
+    }
+}
+
+pub(crate) fn set(language: Language) {
+    let value = match language.resolve() {
+        Language::English => ENGLISH,
+        _ => KOREAN,
+    };
+    LANGUAGE.store(value, Ordering::Relaxed);
+}
+
+pub(crate) fn current() -> Language {
+    match LANGUAGE.load(Ordering::Relaxed) {
+        KOREAN => Language::Korean,
+        ENGLISH => Language::English,
+        _ => {
+            set(Language::Auto);
+            current()
+        }
+    }
+}
+
+pub(crate) fn is_english() -> bool {
+    current() == Language::English
+}
+
+/// 현재 언어에 맞는 문자열을 고른다
+pub(crate) fn pick(korean: &'static str, english: &'static str) -> &'static str {
+    if is_english() { english } else { korean }
+}
+
+/// 브라우저 페이지의 문구. `{count}` 같은 자리는 페이지 스크립트에서 채운다.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Serialize)]
+pub(crate) struct PageText {
+    pub(crate) lang: &'static str,
+    pub(crate) title: &'static str,
+    pub(crate) tracking: &'static str,
+    pub(crate) done: &'static str,
+    pub(crate) unsupported: &'static str,
+    pub(crate) position: &'static str,
+    pub(crate) streaming: &'static str,
+    pub(crate) sampling: &'static str,
+    pub(crate) improving: &'static str,
+    pub(crate) error: &'static str,
+    pub(crate) consent: &'static str,
+    pub(crate) share: &'static str,
+    pub(crate) decline: &'static str,
+    pub(crate) declined: &'static str,
+    pub(crate) manual: &'static str,
+    pub(crate) latitude: &'static str,
+    pub(crate) longitude: &'static str,
+    pub(crate) accuracy: &'static str,
+    pub(crate) submit: &'static str,
+    pub(crate) invalid: &'static str,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+const KOREAN_PAGE: PageText = PageText {
+    lang: "ko",
+    title: "위치 추적",
+    tracking: "위치 추적 중...",
+    done: "위치 전송 완료. 이 탭은 닫아도 됩니다.",
+    unsupported: "위치 정보 지원 안 됨",
+    position: "위도: {latitude}, 경도: {longitude}, 정확도: {accuracy}m",
+    streaming: "실시간 전송 중 ({count})",
+    sampling: "샘플 수집 중 ({count}/{samples})",
+    improving: "정확도 개선 중... ({attempts}/{maxAttempts}, 현재 {accuracy}m)",
+    error: "위치 오류: ",
+    consent: "이 페이지는 기기의 위치(위도, 경도, 정확도와 가능하면 고도, 속도, 방향)를 이 페이지를 연 프로그램에 보냅니다. \
+              공유를 누르면 브라우저가 위치 권한을 묻습니다.",
+    share: "내 위치 공유",
+    decline: "거절",
+    declined: "위치 공유를 거절했습니다. 이 탭은 닫아도 됩니다.",
+    manual: "위치를 받지 못했습니다. 좌표를 직접 입력할 수 있습니다.",
+    latitude: "위도",
+    longitude: "경도",
+    accuracy: "정확도 (m, 선택)",
+    submit: "보내기",
+    invalid: "위도는 -90~90, 경도는 -180~180 사이의 숫자여야 합니다.",
+};
+
+#[cfg(not(target_arch = "wasm32"))]
+const ENGLISH_PAGE: PageText = PageText {
+    lang: "en",
+    title: "Location tracking",
+    tracking: "Locating...",
+    done: "Location sent. You can close this tab.",
+    unsupported: "Geolocation is not supported",
+    position: "Latitude: {latitude}, longitude: {longitude}, accuracy: {accuracy}m",
+    streaming: "Streaming live ({count})",
+    sampling: "Collecting samples ({count}/{samples})",
+    improving: "Improving accuracy... ({attempts}/{maxAttempts}, now {accuracy}m)",
+    error: "Location error: ",
+    consent: "This page sends your device's location (latitude, longitude, accuracy and, when available, altitude, \
+              speed and heading) to the program that opened it. The browser asks for permission after you press Share.",
+    share: "Share my location",
+    decline: "Decline",
+    declined: "You declined to share your location. You can close this tab.",
+    manual: "Could not get your location. You can enter coordinates instead.",
+    latitude: "Latitude",
+    longitude: "Longitude",
+    accuracy: "Accuracy (m, optional)",
+    submit: "Send",
+    invalid: "Latitude must be between -90 and 90 and longitude between -180 and 180.",
+};
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn page_text() -> &'static PageText {
+    if is_english() { &ENGLISH_PAGE } else { &KOREAN_PAGE }
+}
+
+/// `/dashboard` 페이지의 문구
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Serialize)]
+pub(crate) struct DashboardText {
+    pub(crate) lang: &'static str,
+    pub(crate) title: &'static str,
+    pub(crate) waiting: &'static str,
+    pub(crate) live: &'static str,
+    pub(crate) reconnecting: &'static str,
+    pub(crate) stats: &'static str,
+    pub(crate) device: &'static str,
+    pub(crate) fixes: &'static str,
+    pub(crate) position: &'static str,
+    pub(crate) accuracy: &'static str,
+    pub(crate) time: &'static str,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+const KOREAN_DASHBOARD: DashboardText = DashboardText {
+    lang: "ko",
+    title: "weim 대시보드",
+    waiting: "위치를 기다리는 중...",
+    live: "실시간 수신 중",
+    reconnecting: "연결이 끊겨 다시 연결하는 중...",
+    stats: "위치 {count}개, 마지막 정확도 {last}m, 평균 {mean}m, 최고 {best}m",
+    device: "기기",
+    fixes: "위치 수",
+    position: "위치",
+    accuracy: "정확도",
+    time: "시각",
+};
+
+#[cfg(not(target_arch = "wasm32"))]
+const ENGLISH_DASHBOARD: DashboardText = DashboardText {
+    lang: "en",
+    title: "weim dashboard",
+    waiting: "Waiting for a location...",
+    live: "Receiving live",
+    reconnecting: "Disconnected, reconnecting...",
+    stats: "{count} fixes, last accuracy {last}m, mean {mean}m, best {best}m",
+    device: "Device",
+    fixes: "Fixes",
+    position: "Position",
+    accuracy: "Accuracy",
+    time: "Time",
+};
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn dashboard_text() -> &'static DashboardText {
+    if is_english() { &ENGLISH_DASHBOARD } else { &KOREAN_DASHBOARD }
+}
+
+// 현재 언어로 `format!`한다. 첫 문자열이 한국어, 둘째가 영어.
+macro_rules! tr {
+    ($korean:literal, $english:literal $(, $arg:expr)* $(,)?) => {
+        if $crate::i18n::is_english() { format!($english $(, $arg)*) } else { format!($korean $(, $arg)*) }
+    };
+}
+
+pub(crate) use tr;
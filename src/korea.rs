@@ -0,0 +1,154 @@
+//! 한국 좌표계 변환 (KATEC/TM128, UTM-K, 중부원점 등)
+//!
+//! 네이버, 카카오 지도 API나 공공 데이터는 위도/경도 대신 평면 좌표 `(x, y)`를 쓸 때가 많다.
+//! x는 동향, y는 북향(m)이다. KATEC은 베셀 타원체라 WGS84와의 측지계 차이(약 300m)도 함께 옮긴다.
+
+use crate::geo::Coordinate;
+use crate::projection::{Ellipsoid, TransverseMercator, BESSEL, GRS80, WGS84};
+use crate::Location;
+
+// 베셀(한국 측지계)에서 WGS84로 옮기는 7변수 (이동 m, 회전 ″, 축척 ppm, Position Vector)
+const BESSEL_TO_WGS84: [f64; 7] = [-115.80, 474.99, 674.11, 1.16, -2.31, -1.63, 6.43];
+
+/// 한국에서 쓰는 평면 좌표계
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KoreanGrid {
+    /// KATEC(TM128). 네이버, 카카오의 예전 API와 공공 데이터가 쓴다.
+    Katec,
+    /// UTM-K (EPSG:5179). 네이버 지도와 도로명주소가 쓴다.
+    UtmK,
+    /// 서부원점 (EPSG:5185)
+    West,
+    /// 중부원점 (EPSG:5186)
+    Central,
+    /// 동부원점 (EPSG:5187)
+    East,
+    /// 동해원점 (EPSG:5188)
+    EastSea,
+}
+
+impl KoreanGrid {
+    /// EPSG 코드. KATEC은 EPSG 코드가 없어 None
+    pub fn epsg(&self) -> Option<u32> {
+        match self {
+            KoreanGrid::Katec => None,
+            KoreanGrid::UtmK => Some(5179),
+            KoreanGrid::West => Some(5185),
+            KoreanGrid::Central => Some(5186),
+            KoreanGrid::East => Some(5187),
+            KoreanGrid::EastSea => Some(5188),
+        }
+    }
+
+    /// WGS84 좌표를 이 좌표계의 `(x, y)`로
+    pub fn project(&self, coordinate: impl Coordinate) -> (f64, f64) {
+        let (mut latitude, mut longitude) = (coordinate.latitude(), coordinate.longitude());
+        if *self == KoreanGrid::Katec {
+            (latitude, longitude) = shift(WGS84, BESSEL, latitude, longitude, -1.0);
+        }
+        self.projection().forward(latitude, longitude)
+    }
+
+    /// 이 좌표계의 `(x, y)`를 WGS84 `(위도, 경도)`로
+    pub fn unproject(&self, x: f64, y: f64) -> (f64, f64) {
+        let (latitude, longitude) = self.projection().inverse(x, y);
+        match self {
+            KoreanGrid::Katec => shift(BESSEL, WGS84, latitude, longitude, 1.0),
+            _ => (latitude, longitude),
+        }
+    }
+
+    fn projection(&self) -> TransverseMercator {
+        // 2010년 이후의 네 원점은 GRS80, 축척 1, 가산값 (200km, 600km)를 같이 쓴다
+        let belt = |central_meridian| TransverseMercator {
+            ellipsoid: GRS80,
+            origin_latitude: 38.0,
+            central_meridian,
+            scale: 1.0,
+            false_easting: 200_000.0,
+            false_northing: 600_000.0,
+        };
+        match self {
+            KoreanGrid::Katec => TransverseMercator {
+                ellipsoid: BESSEL,
+                origin_latitude: 38.0,
+                central_meridian: 128.0,
+                scale: 0.9999,
+                false_easting: 400_000.0,
+                false_northing: 600_000.0,
+            },
+            KoreanGrid::UtmK => TransverseMercator {
+                ellipsoid: GRS80,
+                origin_latitude: 38.0,
+                central_meridian: 127.5,
+                scale: 0.9996,
+                false_easting: 1_000_000.0,
+                false_northing: 2_000_000.0,
+            },
+            KoreanGrid::West => belt(125.0),
+            KoreanGrid::Central => belt(127.0),
+            KoreanGrid::East => belt(129.0),
+            KoreanGrid::EastSea => belt(131.0),
+        }
+    }
+}
+
+// 측지계를 옮긴다. `direction`이 1이면 베셀에서 WGS84로, -1이면 그 반대 (회전이 작아 근사한 역변환)
+fn shift(from: Ellipsoid, to: Ellipsoid, latitude: f64, longitude: f64, direction: f64) -> (f64, f64) {
+    let [tx, ty, tz, rx, ry, rz, ppm] = BESSEL_TO_WGS84.map(|value| value * direction);
+    let [rx, ry, rz] = [rx, ry, rz].map(|seconds| (seconds / 3600.0).to_radians());
+    let scale = 1.0 + ppm * 1e-6;
+    let [x, y, z] = from.cartesian(latitude, longitude);
+    to.geodetic([
+        tx + scale * (x - rz * y + ry * z),
+        ty + scale * (rz * x + y - rx * z),
+        tz + scale * (-ry * x + rx * y + z),
+    ])
+}
+
+impl Location {
+    /// 이 위치의 `grid` 좌표 `(x, y)`
+    pub fn korean_grid(&self, grid: KoreanGrid) -> (f64, f64) {
+        grid.project(self)
+    }
+
+    /// `grid` 좌표 `(x, y)`의 위치. 정확도는 알 수 없어 0으로 둔다.
+    pub fn from_korean_grid(grid: KoreanGrid, x: f64, y: f64) -> Location {
+        let (latitude, longitude) = grid.unproject(x, y);
+        Location { latitude, longitude, ..Location::default() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geo::haversine;
+
+    const SEOUL_CITY_HALL: (f64, f64) = (37.5666805, 126.9784147);
+
+    fn assert_near((x, y): (f64, f64), (expected_x, expected_y): (f64, f64)) {
+        assert!((x - expected_x).abs() < 1.0 && (y - expected_y).abs() < 1.0, "({}, {})", x, y);
+    }
+
+    #[test]
+    fn projects_seoul_city_hall() {
+        assert_near(KoreanGrid::Katec.project(SEOUL_CITY_HALL), (309_947.0, 552_093.0));
+        assert_near(KoreanGrid::UtmK.project(SEOUL_CITY_HALL), (953_937.9, 1_952_051.9));
+        assert_near(KoreanGrid::Central.project(SEOUL_CITY_HALL), (198_093.0, 551_905.1));
+    }
+
+    #[test]
+    fn round_trips_every_grid() {
+        for grid in [KoreanGrid::Katec, KoreanGrid::UtmK, KoreanGrid::West, KoreanGrid::Central, KoreanGrid::East, KoreanGrid::EastSea] {
+            let (x, y) = grid.project(SEOUL_CITY_HALL);
+            assert!(haversine(grid.unproject(x, y), SEOUL_CITY_HALL) < 0.05, "{:?}", grid);
+        }
+    }
+
+    #[test]
+    fn reports_epsg_codes() {
+        assert_eq!(KoreanGrid::Katec.epsg(), None);
+        assert_eq!(KoreanGrid::UtmK.epsg(), Some(5179));
+        assert_eq!(KoreanGrid::EastSea.epsg(), Some(5188));
+    }
+}
@@ -1,157 +1,210 @@
-use std::process::Command;
-use std::thread;
-use std::time::Duration;
-use chrono::Local;
-use serde::{Deserialize, Serialize};
-use tiny_http::{Server, Response, Method};
-
-const HTML_CONTENT: &str = r#"
-<!DOCTYPE html> 
-<html lang="ko">
-<head>
-    <meta charset="UTF-8">
-    <meta name="viewport" content="width=device-width, initial-scale=1.0">
-    <title>위치 추적</title>
-    <style>
-        body {
-            margin: 0;
-            padding: 20px;
-            font-family: monospace;
-            background: #000;
-            color: #0f0;
+#[cfg(not(target_arch = "wasm32"))]
+mod access_log;
+#[cfg(not(target_arch = "wasm32"))]
+mod api;
+#[cfg(all(feature = "tokio", not(target_arch = "wasm32")))]
+mod async_locator;
+#[cfg(all(feature = "axum", not(target_arch = "wasm32")))]
+mod axum_backend;
+mod bookmarks;
+#[cfg(not(target_arch = "wasm32"))]
+mod cache;
+mod cancel;
+#[cfg(all(feature = "headless", not(target_arch = "wasm32")))]
+mod cdp;
+#[cfg(not(target_arch = "wasm32"))]
+mod clock;
+mod console;
+mod csv_log;
+#[cfg(not(target_arch = "wasm32"))]
+mod daemon;
+#[cfg(not(target_arch = "wasm32"))]
+mod dashboard;
+mod devices;
+#[cfg(feature = "elevation")]
+pub mod elevation;
+#[cfg(not(target_arch = "wasm32"))]
+mod enrich;
+mod error;
+#[cfg(not(target_arch = "wasm32"))]
+mod events;
+pub mod export;
+#[cfg(all(feature = "ffi", not(target_arch = "wasm32")))]
+pub mod ffi;
+mod filter;
+pub mod geo;
+pub mod geohash;
+mod geofence;
+#[cfg(feature = "geocoding")]
+pub mod geocode;
+#[cfg(not(target_arch = "wasm32"))]
+mod guard;
+#[cfg(feature = "h3")]
+pub mod h3;
+#[cfg(not(target_arch = "wasm32"))]
+mod handler;
+#[cfg(not(target_arch = "wasm32"))]
+mod health;
+#[cfg(feature = "heatmap")]
+pub mod heatmap;
+#[cfg(feature = "history")]
+mod history;
+#[cfg(feature = "http-client")]
+mod http;
+mod i18n;
+pub mod korea;
+mod location;
+#[cfg(not(target_arch = "wasm32"))]
+mod locator;
+pub mod map;
+#[cfg(not(target_arch = "wasm32"))]
+mod metrics;
+mod motion;
+#[cfg(all(feature = "mqtt", not(target_arch = "wasm32")))]
+mod mqtt;
+pub mod notation;
+#[cfg(all(feature = "notifications", not(target_arch = "wasm32")))]
+mod notify;
+#[cfg(not(target_arch = "wasm32"))]
+mod page;
+pub mod places;
+pub mod plus_code;
+#[cfg(feature = "poi")]
+pub mod poi;
+#[cfg(not(target_arch = "wasm32"))]
+mod pool;
+pub mod privacy;
+mod projection;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod provider;
+#[cfg(all(feature = "python", not(target_arch = "wasm32")))]
+mod python;
+#[cfg(all(feature = "qr", not(target_arch = "wasm32")))]
+mod qr;
+mod query;
+#[cfg(not(target_arch = "wasm32"))]
+mod recorder;
+mod samples;
+#[cfg(not(target_arch = "wasm32"))]
+mod schedule;
+#[cfg(feature = "encryption")]
+mod seal;
+#[cfg(not(target_arch = "wasm32"))]
+mod security;
+#[cfg(not(target_arch = "wasm32"))]
+mod session;
+#[cfg(all(feature = "config", not(target_arch = "wasm32")))]
+mod settings;
+#[cfg(feature = "spatial")]
+mod spatial;
+#[cfg(feature = "static-map")]
+pub mod static_map;
+#[cfg(feature = "timezone")]
+mod timezone;
+#[cfg(all(feature = "tls", not(target_arch = "wasm32")))]
+mod tls;
+#[cfg(all(feature = "traccar", not(target_arch = "wasm32")))]
+mod traccar;
+pub mod trips;
+pub mod utm;
+#[cfg(target_arch = "wasm32")]
+mod wasm;
+#[cfg(not(target_arch = "wasm32"))]
+mod watch;
+#[cfg(all(feature = "webhook", not(target_arch = "wasm32")))]
+mod webhook;
+pub mod weather;
+#[cfg(all(feature = "websocket", not(target_arch = "wasm32")))]
+mod ws;
+
+pub use bookmarks::{Bookmark, Bookmarks};
+pub use cancel::CancellationToken;
+pub use console::{Output, Verbosity};
+pub use csv_log::{CsvLog, Rotation};
+#[cfg(not(target_arch = "wasm32"))]
+pub use daemon::{Daemon, DaemonClient};
+pub use devices::DeviceId;
+#[cfg(not(target_arch = "wasm32"))]
+pub use devices::Devices;
+pub use error::WeimError;
+pub use filter::{FilteredLocation, KalmanFilter};
+pub use geofence::{Geofence, GeofenceEvent, GeofenceEventKind, Geofenced, Geofences, Shape};
+#[cfg(not(target_arch = "wasm32"))]
+pub use guard::{IpRange, RateLimit};
+pub use i18n::Language;
+#[cfg(feature = "history")]
+pub use history::{BoundingBox, History, HistoryEntry, HistoryQuery, Retention};
+#[cfg(feature = "encryption")]
+pub use history::HistoryKey;
+pub use location::{DeviceInfo, Location, Source};
+#[cfg(not(target_arch = "wasm32"))]
+pub use locator::{CloseStrategy, Weim, WeimBuilder};
+pub use motion::{MotionChange, MotionDetector, MotionEvent, MotionState, WithMotion};
+#[cfg(all(feature = "mqtt", not(target_arch = "wasm32")))]
+pub use mqtt::Mqtt;
+#[cfg(all(feature = "notifications", not(target_arch = "wasm32")))]
+pub use notify::Notifications;
+#[cfg(not(target_arch = "wasm32"))]
+pub use provider::LocationProvider;
+pub use samples::SampleSet;
+#[cfg(not(target_arch = "wasm32"))]
+pub use schedule::Schedule;
+#[cfg(not(target_arch = "wasm32"))]
+pub use session::Locator;
+#[cfg(all(feature = "config", not(target_arch = "wasm32")))]
+pub use settings::CONFIG_ENV;
+#[cfg(feature = "timezone")]
+pub use chrono_tz::Tz;
+#[cfg(all(feature = "traccar", not(target_arch = "wasm32")))]
+pub use traccar::Traccar;
+#[cfg(target_arch = "wasm32")]
+pub use wasm::Geolocation;
+#[cfg(not(target_arch = "wasm32"))]
+pub use watch::{Filtered, Watch};
+#[cfg(all(feature = "webhook", not(target_arch = "wasm32")))]
+pub use webhook::Webhook;
+
+#[cfg(not(target_arch = "wasm32"))]
+use console::error;
+#[cfg(not(target_arch = "wasm32"))]
+use i18n::tr;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn where_i_am() -> Vec<f64> {
+    match try_where_i_am() {
+        Ok(location) => vec![location.latitude, location.longitude, location.accuracy],
+        Err(e) => {
+            error!("{}", tr!("❌ 위치 데이터를 받지 못했습니다: {}", "❌ Could not get location data: {}", e));
+            vec![]
         }
-        #status { font-size: 14px; }
-    </style>
-</head>
-<body>
-    <div id="status">위치 추적 중...</div>
-    <script>
-        window.addEventListener('DOMContentLoaded', () => {
-            if (!navigator.geolocation) {
-                document.getElementById('status').textContent = '위치 정보 지원 안 됨';
-                return;
-            }
-            navigator.geolocation.getCurrentPosition(
-                async (position) => {
-                    const data = {
-                        latitude: position.coords.latitude,
-                        longitude: position.coords.longitude,
-                        accuracy: position.coords.accuracy,
-                        timestamp: Date.now()
-                    };
-                    document.getElementById('status').textContent = 
-                        `위도: ${data.latitude.toFixed(6)}, 경도: ${data.longitude.toFixed(6)}, 정확도: ${data.accuracy.toFixed(2)}m`;
-
-                    await fetch('/update', {
-                        method: 'POST',
-                        headers: { 'Content-Type': 'application/json' },
-                        body: JSON.stringify(data)
-                    });
-
-                    // 자동으로 창 닫기
-                    setTimeout(() => window.close(), 1500);
-                },
-                (error) => {
-                    document.getElementById('status').textContent = '위치 오류: ' + error.message;
-                },
-                { enableHighAccuracy: true, timeout: 5000 }
-            );
-        });
-    </script>
-</body>
-</html>
-"#;
-
-#[derive(Debug, Deserialize, Serialize)]
-struct LocationData {
-    latitude: f64,
-    longitude: f64,
-    accuracy: f64,
-    timestamp: i64,
+    }
 }
 
-pub fn where_i_am() -> Vec<f64> {
-    println!("\n🚀 위치 추적 시스템 시작!");
-    println!("🔍 위치 정보를 수집합니다...\n");
-    println!("{}", "=".repeat(60));
-
-    // 브라우저 열기
-    thread::spawn(|| {
-        thread::sleep(Duration::from_millis(500));
-        #[cfg(target_os = "windows")]
-        Command::new("cmd").args(&["/C", "start", "http://localhost:3030"]).spawn().ok();
-
-        #[cfg(target_os = "macos")]
-        Command::new("open").arg("http://localhost:3030").spawn().ok();
-
-        #[cfg(target_os = "linux")]
-        Command::new("xdg-open").arg("http://localhost:3030").spawn().ok();
-    });
-
-    let server = Server::http("127.0.0.1:3030").unwrap();
-    let mut result: Option<Vec<f64>> = None;
-
-    for mut request in server.incoming_requests() {
-        match (request.method(), request.url()) {
-            (Method::Get, "/") => {
-                let response = Response::from_string(HTML_CONTENT)
-                    .with_header(tiny_http::Header::from_bytes("Content-Type", "text/html; charset=utf-8").unwrap());
-                request.respond(response).ok();
-            }
-            (Method::Post, "/update") => {
-                let mut content = String::new();
-                request.as_reader().read_to_string(&mut content).ok();
-
-                if let Ok(location) = serde_json::from_str::<LocationData>(&content) {
-                    let time = Local::now().format("%Y-%m-%d %H:%M:%S");
-                    println!("\n[{}] 📍 새로운 위치 데이터:", time);
-                    println!("  위도: {:.8}°", location.latitude);
-                    println!("  경도: {:.8}°", location.longitude);
-                    println!("  정확도: {:.2}m", location.accuracy);
-                    println!(
-                        "  Google Maps: https://www.google.com/maps?q={},{}",
-                        location.latitude, location.longitude
-                    );
-                    println!("{}", "=".repeat(60));
-
-                    result = Some(vec![location.latitude, location.longitude, location.accuracy]);
-
-                    let response = Response::from_string(r#"{"status":"ok"}"#)
-                        .with_header(tiny_http::Header::from_bytes("Content-Type", "application/json").unwrap())
-                        .with_header(tiny_http::Header::from_bytes("Access-Control-Allow-Origin", "*").unwrap());
-                    request.respond(response).ok();
-
-                    // 브라우저 닫기 시도
-                    #[cfg(target_os = "windows")]
-                    Command::new("cmd").args(&["/C", "taskkill /IM chrome.exe /F"]).spawn().ok();
-
-                    #[cfg(target_os = "macos")]
-                    Command::new("osascript").args(&["-e", "tell application \"Safari\" to close (every window whose name contains \"위치 추적\")"]).spawn().ok();
+/// 브라우저에서 위치를 받아오고, 실패하면 원인을 `WeimError`로 돌려준다.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn try_where_i_am() -> Result<Location, WeimError> {
+    Weim::default().locate()
+}
 
-                    #[cfg(target_os = "linux")]
-                    Command::new("pkill").arg("chrome").spawn().ok();
+/// `try_where_i_am()`의 비동기 버전 (`tokio` 기능 필요)
+#[cfg(all(feature = "tokio", not(target_arch = "wasm32")))]
+pub async fn where_i_am_async() -> Result<Location, WeimError> {
+    Weim::default().locate_async().await
+}
 
-                    break;
-                } else {
-                    request.respond(Response::from_string("Invalid JSON").with_status_code(400)).ok();
-                }
-            }
-            (Method::Options, "/update") => {
-                let response = Response::empty(200)
-                    .with_header(tiny_http::Header::from_bytes("Access-Control-Allow-Origin", "*").unwrap())
-                    .with_header(tiny_http::Header::from_bytes("Access-Control-Allow-Methods", "POST, OPTIONS").unwrap())
-                    .with_header(tiny_http::Header::from_bytes("Access-Control-Allow-Headers", "Content-Type").unwrap());
-                request.respond(response).ok();
-            }
-            _ => {
-                request.respond(Response::from_string("Not Found").with_status_code(404)).ok();
-            }
-        }
-    }
+/// wasm32로 빌드해 브라우저 안에서 돌면 서버를 열지 않고 이 페이지의 Geolocation API로 바로 받는다.
+#[cfg(target_arch = "wasm32")]
+pub async fn where_i_am_async() -> Result<Location, WeimError> {
+    Geolocation::default().locate().await
+}
 
-    // 결과 반환
-    result.unwrap_or_else(|| {
-        println!("❌ 위치 데이터를 받지 못했습니다.");
-        vec![]
-    })
+/// 장소 이름이나 주소로 위치를 찾는다. 기본 Nominatim 서버를 쓴다. (`geocoding` 기능 필요)
+///
+/// 다른 서비스를 쓰려면 `geocode::Geocoder`를 직접 구현해서 부른다.
+#[cfg(feature = "geocoding")]
+pub fn geocode(query: &str) -> Result<Vec<Location>, WeimError> {
+    use geocode::{Geocoder, Nominatim};
+    // 요청 간격 제한과 캐시를 호출 사이에 공유한다
+    static NOMINATIM: std::sync::OnceLock<Nominatim> = std::sync::OnceLock::new();
+    NOMINATIM.get_or_init(Nominatim::default).geocode(query)
 }
@@ -0,0 +1,128 @@
+use chrono::{DateTime, Local, TimeDelta, Utc};
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::geo::{haversine, initial_bearing};
+use crate::weather::Weather;
+
+/// 브라우저에서 받아온 위치 정보
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct Location {
+    /// 위도 (°)
+    pub latitude: f64,
+    /// 경도 (°)
+    pub longitude: f64,
+    /// 정확도 (m)
+    pub accuracy: f64,
+    /// 고도 (m)
+    pub altitude: Option<f64>,
+    /// 고도 정확도 (m)
+    pub altitude_accuracy: Option<f64>,
+    /// 속도 (m/s)
+    pub speed: Option<f64>,
+    /// 진행 방향 (°, 북쪽 기준 시계 방향)
+    pub heading: Option<f64>,
+    /// 기기가 향한 나침반 방위 (°, 북쪽 기준 시계 방향). `heading`과 달리 멈춰 있어도 있다
+    #[serde(default)]
+    pub compass: Option<f64>,
+    /// 측정 시각. 브라우저에서 받은 위치는 기기 시계가 틀린 만큼 서버 시계에 맞춘다. JSON에서는 RFC 3339
+    #[serde(deserialize_with = "timestamp")]
+    pub timestamp: DateTime<Utc>,
+    /// 기기가 보낸 시각 그대로. 서버 시계에 맞추지 않았으면 None
+    #[serde(default, skip_serializing_if = "Option::is_none", deserialize_with = "optional_timestamp")]
+    pub device_timestamp: Option<DateTime<Utc>>,
+    /// 역지오코딩으로 찾은 주소
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    /// 받은 곳의 현재 날씨
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weather: Option<Weather>,
+    /// 위치를 보낸 브라우저와 기기. 브라우저에서 받은 위치에만 있다
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device_info: Option<DeviceInfo>,
+    /// 위치를 어떻게 얻었는지
+    #[serde(default)]
+    pub source: Source,
+}
+
+/// 위치를 얻은 방법
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Source {
+    /// 브라우저나 위치 공급자가 측정한 위치 (기본값)
+    #[default]
+    Measured,
+    /// 위치를 얻지 못해 사용자가 페이지에 직접 입력한 좌표. 정확도는 입력한 값이며 없으면 0이다.
+    Manual,
+}
+
+/// 위치를 보낸 브라우저가 알려 준 기기 정보
+///
+/// 브라우저가 알려 주지 않는 값은 None이다. 배터리는 Battery Status API가 있는 브라우저(Chromium 계열)에서만 온다.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct DeviceInfo {
+    /// `navigator.userAgent`
+    #[serde(default, alias = "userAgent")]
+    pub user_agent: Option<String>,
+    /// 운영체제 (예: "Android", "iPhone", "Win32")
+    #[serde(default)]
+    pub platform: Option<String>,
+    /// 화면 너비 (CSS px)
+    #[serde(default, alias = "screenWidth")]
+    pub screen_width: Option<u32>,
+    /// 화면 높이 (CSS px)
+    #[serde(default, alias = "screenHeight")]
+    pub screen_height: Option<u32>,
+    /// 배터리 잔량 (0–1)
+    #[serde(default)]
+    pub battery: Option<f64>,
+    /// 충전 중인지
+    #[serde(default)]
+    pub charging: Option<bool>,
+}
+
+impl Location {
+    /// 다른 위치까지의 대원 거리 (m)
+    pub fn distance_to(&self, other: &Location) -> f64 {
+        haversine(self, other)
+    }
+
+    /// 기기 시계가 서버 시계보다 늦은 만큼 (`timestamp - device_timestamp`). 앞서면 음수
+    pub fn clock_offset(&self) -> Option<TimeDelta> {
+        self.device_timestamp.map(|device| self.timestamp - device)
+    }
+
+    /// 측정 시각을 이 컴퓨터의 시간대로 (그 지점의 시간대는 `timezone` 기능의 `local_time()`)
+    pub fn local_timestamp(&self) -> DateTime<Local> {
+        self.timestamp.with_timezone(&Local)
+    }
+
+    /// 다른 위치를 바라보는 방위각 (°, 북쪽 기준 시계 방향)
+    pub fn bearing_to(&self, other: &Location) -> f64 {
+        initial_bearing(self, other)
+    }
+}
+
+// 예전 버전이 캐시와 기록에 남긴 Unix epoch(ms) 정수도 읽는다
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Timestamp {
+    Rfc3339(DateTime<Utc>),
+    Millis(i64),
+}
+
+impl Timestamp {
+    fn time<E: serde::de::Error>(self) -> Result<DateTime<Utc>, E> {
+        match self {
+            Timestamp::Rfc3339(time) => Ok(time),
+            Timestamp::Millis(millis) => DateTime::from_timestamp_millis(millis).ok_or_else(|| E::custom("timestamp out of range")),
+        }
+    }
+}
+
+pub(crate) fn timestamp<'de, D: Deserializer<'de>>(deserializer: D) -> Result<DateTime<Utc>, D::Error> {
+    Timestamp::deserialize(deserializer)?.time()
+}
+
+fn optional_timestamp<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<DateTime<Utc>>, D::Error> {
+    Option::<Timestamp>::deserialize(deserializer)?.map(Timestamp::time).transpose()
+}
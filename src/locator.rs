@@ -0,0 +1,1164 @@
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, UdpSocket};
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::atomic::AtomicBool;
+use std::sync::mpsc::{self, RecvTimeoutError};
+#[cfg(feature = "websocket")]
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::access_log::Access;
+#[cfg(feature = "headless")]
+use crate::cdp::Headless;
+use crate::console::{self, info, warning};
+#[cfg(feature = "config")]
+use crate::settings::ProviderKind;
+#[cfg(feature = "elevation")]
+use crate::elevation::ElevationSource;
+use crate::enrich::Enricher;
+#[cfg(feature = "geocoding")]
+use crate::geocode::Geocoder;
+use crate::events;
+use crate::handler::{self, Handler, Incoming, Outcome, Reply};
+use crate::i18n::{self, pick, tr};
+use crate::map::MapProvider;
+#[cfg(feature = "notifications")]
+use crate::notify::{Notifications, Notifier};
+use crate::provider::{LocationProvider, MockProvider};
+use crate::page::Template;
+use crate::pool::{self, Reorder, StopOnDrop};
+use crate::privacy::Coarsening;
+use crate::recorder::Recorder;
+use crate::security;
+#[cfg(feature = "tls")]
+use crate::tls::{self, Tls};
+#[cfg(feature = "weather")]
+use crate::weather::WeatherSource;
+#[cfg(feature = "encryption")]
+use crate::HistoryKey;
+#[cfg(feature = "history")]
+use crate::Retention;
+#[cfg(feature = "mqtt")]
+use crate::Mqtt;
+#[cfg(feature = "traccar")]
+use crate::Traccar;
+#[cfg(feature = "webhook")]
+use crate::Webhook;
+#[cfg(feature = "websocket")]
+use crate::ws;
+use crate::{CancellationToken, CsvLog, DeviceId, IpRange, Language, Location, Output, RateLimit, SampleSet, Schedule, Verbosity, WeimError};
+
+// 취소 여부를 확인하는 간격
+pub(crate) const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// 위치를 받은 뒤 브라우저를 어떻게 정리할지
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CloseStrategy {
+    /// 아무것도 하지 않고 "탭을 닫아도 됩니다" 안내만 표시 (기본값)
+    #[default]
+    None,
+    /// 페이지 스크립트에서 `window.close()`를 시도
+    TabCloseScript,
+    /// 브라우저 프로세스를 강제로 종료. 사용자의 다른 창도 모두 닫힌다.
+    KillProcess,
+}
+
+// 한 번의 서버 실행에서 페이지가 보낼 위치의 수
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Mode {
+    // 가장 좋은 위치 하나
+    Single,
+    // 측정값을 이 개수만큼 모두
+    Samples(usize),
+    // 멈출 때까지 계속
+    Watch,
+    // `Locator`가 멈출 때까지 계속. 브라우저의 실패는 기다리는 `locate()`에 넘긴다
+    Session,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Config {
+    pub(crate) port: u16,
+    pub(crate) port_fallback: bool,
+    pub(crate) bind_address: IpAddr,
+    pub(crate) lan: bool,
+    pub(crate) allow: Vec<IpRange>,
+    pub(crate) deny: Vec<IpRange>,
+    pub(crate) rate_limit: Option<RateLimit>,
+    pub(crate) allowed_origins: Vec<String>,
+    pub(crate) content_security_policy: Option<String>,
+    pub(crate) workers: usize,
+    #[cfg(feature = "qr")]
+    pub(crate) print_qr: bool,
+    #[cfg(feature = "tls")]
+    pub(crate) tls: Option<Tls>,
+    #[cfg(feature = "history")]
+    pub(crate) history: Option<PathBuf>,
+    #[cfg(feature = "history")]
+    pub(crate) history_retention: Option<Retention>,
+    #[cfg(feature = "encryption")]
+    pub(crate) history_key: Option<HistoryKey>,
+    pub(crate) csv_log: Option<CsvLog>,
+    pub(crate) cache_file: Option<PathBuf>,
+    pub(crate) access_log: Option<PathBuf>,
+    // 설정 파일의 `providers`. `FallbackChain::recommended()`가 쓴다
+    #[cfg(feature = "config")]
+    pub(crate) providers: Option<Vec<ProviderKind>>,
+    // None이면 계속 받는 모드(`watch()`, `serve()`)에서만 연다
+    pub(crate) api: Option<bool>,
+    pub(crate) metrics: bool,
+    #[cfg(any(unix, windows))]
+    pub(crate) daemon_socket: Option<PathBuf>,
+    pub(crate) schedule: Option<Schedule>,
+    #[cfg(feature = "webhook")]
+    pub(crate) webhooks: Vec<Webhook>,
+    #[cfg(feature = "mqtt")]
+    pub(crate) mqtt: Option<Mqtt>,
+    #[cfg(feature = "traccar")]
+    pub(crate) traccar: Option<Traccar>,
+    #[cfg(feature = "geocoding")]
+    pub(crate) geocoder: Option<Arc<dyn Geocoder>>,
+    #[cfg(feature = "elevation")]
+    pub(crate) elevation: Option<Arc<dyn ElevationSource>>,
+    #[cfg(feature = "weather")]
+    pub(crate) weather: Option<Arc<dyn WeatherSource>>,
+    #[cfg(feature = "notifications")]
+    pub(crate) notifications: Option<Notifications>,
+    pub(crate) timeout: Option<Duration>,
+    pub(crate) high_accuracy: bool,
+    pub(crate) position_timeout: Duration,
+    pub(crate) maximum_age: Duration,
+    pub(crate) accuracy_threshold: Option<f64>,
+    pub(crate) coarse_location: Option<Coarsening>,
+    pub(crate) max_attempts: u32,
+    pub(crate) open_browser: bool,
+    pub(crate) browser_grace: Duration,
+    pub(crate) response_grace: Duration,
+    #[cfg(feature = "headless")]
+    pub(crate) headless: bool,
+    #[cfg(feature = "axum")]
+    pub(crate) axum: bool,
+    pub(crate) close_strategy: CloseStrategy,
+    pub(crate) template: Option<Template>,
+    pub(crate) consent: bool,
+    pub(crate) show_map: bool,
+    pub(crate) map_links: Vec<MapProvider>,
+    pub(crate) manual_entry: bool,
+    pub(crate) output: Output,
+    pub(crate) verbosity: Verbosity,
+    pub(crate) language: Language,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            port: 3030,
+            port_fallback: true,
+            bind_address: IpAddr::V4(Ipv4Addr::LOCALHOST),
+            lan: false,
+            allow: Vec::new(),
+            deny: Vec::new(),
+            rate_limit: Some(RateLimit::default()),
+            allowed_origins: Vec::new(),
+            content_security_policy: Some(security::DEFAULT_CSP.to_string()),
+            workers: 4,
+            #[cfg(feature = "qr")]
+            print_qr: false,
+            #[cfg(feature = "tls")]
+            tls: None,
+            #[cfg(feature = "history")]
+            history: None,
+            #[cfg(feature = "history")]
+            history_retention: None,
+            #[cfg(feature = "encryption")]
+            history_key: None,
+            csv_log: None,
+            cache_file: None,
+            access_log: None,
+            #[cfg(feature = "config")]
+            providers: None,
+            api: None,
+            metrics: false,
+            #[cfg(any(unix, windows))]
+            daemon_socket: None,
+            schedule: None,
+            #[cfg(feature = "webhook")]
+            webhooks: Vec::new(),
+            #[cfg(feature = "mqtt")]
+            mqtt: None,
+            #[cfg(feature = "traccar")]
+            traccar: None,
+            #[cfg(feature = "geocoding")]
+            geocoder: None,
+            #[cfg(feature = "elevation")]
+            elevation: None,
+            #[cfg(feature = "weather")]
+            weather: None,
+            #[cfg(feature = "notifications")]
+            notifications: None,
+            timeout: None,
+            high_accuracy: true,
+            position_timeout: Duration::from_secs(5),
+            maximum_age: Duration::ZERO,
+            accuracy_threshold: None,
+            coarse_location: None,
+            max_attempts: 10,
+            open_browser: true,
+            browser_grace: Duration::from_secs(30),
+            response_grace: Duration::from_secs(180),
+            #[cfg(feature = "headless")]
+            headless: false,
+            #[cfg(feature = "axum")]
+            axum: false,
+            close_strategy: CloseStrategy::default(),
+            template: None,
+            consent: false,
+            show_map: false,
+            map_links: vec![MapProvider::Google],
+            manual_entry: false,
+            output: Output::default(),
+            verbosity: Verbosity::default(),
+            language: Language::default(),
+        }
+    }
+}
+
+impl Config {
+    // `/api`와 `/dashboard`를 열지 여부. 따로 정하지 않았으면 계속 받는 모드에서만 연다
+    pub(crate) fn api_enabled(&self, mode: Mode) -> bool {
+        self.api.unwrap_or(mode == Mode::Watch)
+    }
+}
+
+/// `Weim` 설정을 위한 빌더
+#[derive(Debug, Clone, Default)]
+pub struct WeimBuilder {
+    pub(crate) config: Config,
+}
+
+impl WeimBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 로컬 서버 포트 (기본값 3030). 0이면 빈 포트를 자동으로 고른다.
+    pub fn port(mut self, port: u16) -> Self {
+        self.config.port = port;
+        self
+    }
+
+    /// 운영체제가 고른 빈 포트를 사용한다.
+    pub fn auto_port(self) -> Self {
+        self.port(0)
+    }
+
+    /// 지정한 포트가 사용 중이면 빈 포트로 대신 열지 여부 (기본값 true)
+    pub fn port_fallback(mut self, enabled: bool) -> Self {
+        self.config.port_fallback = enabled;
+        self
+    }
+
+    /// 서버를 열 주소 (기본값 127.0.0.1)
+    pub fn bind_address(mut self, addr: IpAddr) -> Self {
+        self.config.bind_address = addr;
+        self
+    }
+
+    /// 이 주소나 대역에서 온 요청만 받는다. 여러 번 부르면 모두 허용한다. (기본값 없음 = 모두 허용)
+    ///
+    /// 이 컴퓨터(루프백)에서 온 요청은 목록과 상관없이 받는다.
+    pub fn allow(mut self, range: IpRange) -> Self {
+        self.config.allow.push(range);
+        self
+    }
+
+    /// 이 주소나 대역에서 온 요청은 받지 않는다. 허용 목록보다 먼저 본다. (기본값 없음)
+    pub fn deny(mut self, range: IpRange) -> Self {
+        self.config.deny.push(range);
+        self
+    }
+
+    /// LAN으로 들어온 클라이언트가 위치를 보내는 속도 제한 (기본값 초당 5개, 한꺼번에 20개). None이면 끈다
+    ///
+    /// 넘으면 `429 Too Many Requests`로 답한다. 이 컴퓨터(루프백)에서 온 요청에는 걸지 않는다.
+    pub fn rate_limit(mut self, limit: Option<RateLimit>) -> Self {
+        self.config.rate_limit = limit;
+        self
+    }
+
+    /// 다른 출처의 웹 페이지가 `/api`와 `/events`를 읽을 수 있게 허용할 출처 (기본값 빈 목록 = 같은 출처만)
+    ///
+    /// `https://example.com`처럼 스킴과 호스트(와 포트)로 쓴다. `["*"]`를 주면 사용자가 연 아무 웹 페이지나
+    /// 위치 기록을 읽을 수 있으니, 그래도 괜찮을 때만 직접 켠다.
+    pub fn allowed_origins<S: Into<String>>(mut self, origins: impl IntoIterator<Item = S>) -> Self {
+        self.config.allowed_origins = origins.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// 페이지와 대시보드에 붙일 Content-Security-Policy (기본값은 페이지가 쓰는 Leaflet과 OpenStreetMap만 허용). None이면 붙이지 않는다
+    ///
+    /// `template()`으로 바꾼 페이지가 다른 곳의 스크립트나 이미지를 쓰면 여기에 더한다.
+    pub fn content_security_policy(mut self, policy: Option<String>) -> Self {
+        self.config.content_security_policy = policy;
+        self
+    }
+
+    /// 내장 서버가 요청을 동시에 처리할 스레드 수 (기본값 4)
+    ///
+    /// 본문을 늦게 보내는 클라이언트가 하나 있어도 다른 요청은 기다리지 않는다.
+    /// 받은 위치는 요청이 들어온 순서대로 넘긴다. 0을 주면 1로 본다.
+    pub fn workers(mut self, workers: usize) -> Self {
+        self.config.workers = workers.max(1);
+        self
+    }
+
+    /// 모든 인터페이스(0.0.0.0)에서 열고 이 컴퓨터의 LAN 주소를 안내한다. (기본값 false)
+    ///
+    /// 같은 네트워크의 휴대폰 브라우저로 접속해서 그 기기의 위치를 받을 때 쓴다.
+    /// 켜면 이 컴퓨터의 브라우저는 열지 않는다. 필요하면 뒤에 `open_browser(true)`를 부른다.
+    /// 대부분의 휴대폰 브라우저는 HTTPS가 아닌 주소에는 위치를 주지 않는다.
+    pub fn lan(mut self, enabled: bool) -> Self {
+        self.config.lan = enabled;
+        if enabled {
+            self.config.bind_address = IpAddr::V4(Ipv4Addr::UNSPECIFIED);
+            self.config.open_browser = false;
+        }
+        self
+    }
+
+    /// 접속 주소를 터미널에 QR 코드로도 출력할지 여부 (기본값 false, `qr` 기능 필요)
+    ///
+    /// LAN 모드에서는 휴대폰 카메라로 찍어서 바로 열 수 있다.
+    #[cfg(feature = "qr")]
+    pub fn print_qr(mut self, enabled: bool) -> Self {
+        self.config.print_qr = enabled;
+        self
+    }
+
+    /// 자체 서명 인증서로 HTTPS 페이지를 열지 여부 (기본값 false, `tls` 기능 필요)
+    ///
+    /// 브라우저는 localhost가 아닌 HTTP 주소에서 위치를 주지 않으므로 LAN 모드와 함께 쓴다.
+    #[cfg(feature = "tls")]
+    pub fn https(mut self, enabled: bool) -> Self {
+        self.config.tls = enabled.then_some(Tls::SelfSigned);
+        self
+    }
+
+    /// 자체 서명 대신 PEM 인증서와 개인 키 파일로 HTTPS를 연다. (`tls` 기능 필요)
+    #[cfg(feature = "tls")]
+    pub fn tls_certificate(mut self, certificate: impl Into<PathBuf>, private_key: impl Into<PathBuf>) -> Self {
+        self.config.tls = Some(Tls::Files { certificate: certificate.into(), private_key: private_key.into() });
+        self
+    }
+
+    /// 받은 위치를 모두 이 SQLite 파일에 기록한다. (기본값 없음, `history` 기능 필요)
+    #[cfg(feature = "history")]
+    pub fn history(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config.history = Some(path.into());
+        self
+    }
+
+    /// `history` 파일을 열 때마다 이 기준을 벗어난 위치를 지운다. (기본값 없음 = 모두 남김, `history` 기능 필요)
+    #[cfg(feature = "history")]
+    pub fn history_retention(mut self, retention: Retention) -> Self {
+        self.config.history_retention = Some(retention);
+        self
+    }
+
+    /// `history` 파일을 이 열쇠로 암호화한다. 위치 API(`/api/history`)도 같은 열쇠로 읽는다. (기본값 없음, `encryption` 기능 필요)
+    #[cfg(feature = "encryption")]
+    pub fn history_key(mut self, key: HistoryKey) -> Self {
+        self.config.history_key = Some(key);
+        self
+    }
+
+    /// 받은 위치를 모두 CSV 파일에 덧붙인다. (기본값 없음)
+    pub fn csv_log(mut self, log: CsvLog) -> Self {
+        self.config.csv_log = Some(log);
+        self
+    }
+
+    /// 마지막으로 받은 위치를 이 JSON 파일에 저장해 두고, 프로세스를 다시 시작해도 `last_known()`이 읽게 한다. (기본값 없음)
+    pub fn cache_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config.cache_file = Some(path.into());
+        self
+    }
+
+    /// 내장 서버가 답한 요청을 이 파일에 Apache/nginx combined 형식으로 덧붙인다. (기본값 없음)
+    ///
+    /// 파일을 정하지 않아도 요청마다 `weim::access` 대상의 `log` 이벤트는 남고, `Verbosity::Debug`면 콘솔에도 나온다.
+    /// LAN 모드에서 휴대폰이 접속하지 못할 때 요청이 서버까지 왔는지 확인하는 데 쓴다.
+    pub fn access_log(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config.access_log = Some(path.into());
+        self
+    }
+
+    /// 다른 프로세스가 받은 위치를 읽어 갈 `/api/latest`, `/api/history`, `/api/devices`를 열지 여부
+    /// (기본값 `watch()`와 `serve()`에서만 true)
+    ///
+    /// 켜 두면 지도와 기기 목록을 실시간으로 보여 주는 `/dashboard` 페이지도 연다.
+    /// 토큰 없이 읽을 수 있으므로 LAN 모드에서는 같은 네트워크의 누구나 볼 수 있다.
+    pub fn api(mut self, enabled: bool) -> Self {
+        self.config.api = Some(enabled);
+        self
+    }
+
+    /// 계속 받는 모드(`watch()`, `serve()`)에서 Prometheus가 긁어 갈 `/metrics`를 열지 여부 (기본값 false)
+    ///
+    /// 받은 위치 수, 오류 수, 웹훅 실패 수, 마지막 정확도와 지난 시간, 첫 위치까지 걸린 시간을 내보낸다.
+    pub fn metrics(mut self, enabled: bool) -> Self {
+        self.config.metrics = enabled;
+        self
+    }
+
+    /// `serve()`로 띄운 데몬이 질의를 받을 Unix 소켓 경로. Windows에서는 이름 있는 파이프 (기본값 없음, Unix, Windows 전용)
+    ///
+    /// 한 줄에 `latest`, `latest <기기>`, `devices` 중 하나를 보내면 한 줄짜리 JSON으로 답한다.
+    /// 파이프는 `weim`처럼 이름만 주면 `\\.\pipe\weim`으로 열고, 다른 컴퓨터에서 오는 연결은 받지 않는다.
+    /// TCP로 질의를 받지 않으려면 `api(false)`와 함께 쓴다.
+    #[cfg(any(unix, windows))]
+    pub fn daemon_socket(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config.daemon_socket = Some(path.into());
+        self
+    }
+
+    /// `serve()`로 띄운 데몬이 페이지를 계속 열어 두지 않고, 정해 둔 때마다 위치를 하나씩 받는다. (기본값 없음)
+    ///
+    /// 때가 되면 한 번만 보내는 페이지(`/?once`)를 브라우저로 열고(`headless(true)`면 창 없는 Chrome), 위치가 오거나
+    /// `timeout()`(주지 않았으면 `response_grace()`)이 지나면 닫는다. 받은 위치는 평소처럼 기록과 웹훅, MQTT로 간다.
+    /// `open_browser(false)`면 열 주소만 알린다.
+    pub fn schedule(mut self, schedule: Schedule) -> Self {
+        self.config.schedule = Some(schedule);
+        self
+    }
+
+    /// 받은 위치를 웹훅으로도 보낸다. 여러 번 부르면 모두에 보낸다. (기본값 없음, `webhook` 기능 필요)
+    #[cfg(feature = "webhook")]
+    pub fn webhook(mut self, webhook: Webhook) -> Self {
+        self.config.webhooks.push(webhook);
+        self
+    }
+
+    /// 받은 위치를 MQTT 브로커에 OwnTracks 형식으로 발행한다. (기본값 없음, `mqtt` 기능 필요)
+    #[cfg(feature = "mqtt")]
+    pub fn mqtt(mut self, mqtt: Mqtt) -> Self {
+        self.config.mqtt = Some(mqtt);
+        self
+    }
+
+    /// 받은 위치를 Traccar 서버에 보고한다. (기본값 없음, `traccar` 기능 필요)
+    #[cfg(feature = "traccar")]
+    pub fn traccar(mut self, traccar: Traccar) -> Self {
+        self.config.traccar = Some(traccar);
+        self
+    }
+
+    /// 받은 위치의 주소를 찾아 `Location::address`에 채운다. (기본값 없음, `geocoding` 기능 필요)
+    #[cfg(feature = "geocoding")]
+    pub fn reverse_geocode(mut self, geocoder: impl Geocoder + 'static) -> Self {
+        self.config.geocoder = Some(Arc::new(geocoder));
+        self
+    }
+
+    /// 브라우저가 고도를 주지 않으면 지형 고도로 `Location::altitude`를 채운다. (기본값 없음, `elevation` 기능 필요)
+    #[cfg(feature = "elevation")]
+    pub fn elevation(mut self, source: impl ElevationSource + 'static) -> Self {
+        self.config.elevation = Some(Arc::new(source));
+        self
+    }
+
+    /// 받은 위치의 현재 날씨를 `Location::weather`에 붙인다. (기본값 없음, `weather` 기능 필요)
+    #[cfg(feature = "weather")]
+    pub fn weather(mut self, source: impl WeatherSource + 'static) -> Self {
+        self.config.weather = Some(Arc::new(source));
+        self
+    }
+
+    /// 위치를 받거나 정확도가 나빠지면 데스크톱 알림을 띄운다. (기본값 없음, `notifications` 기능 필요)
+    #[cfg(feature = "notifications")]
+    pub fn notifications(mut self, notifications: Notifications) -> Self {
+        self.config.notifications = Some(notifications);
+        self
+    }
+
+    /// 위치를 기다리는 최대 시간 (기본값 무제한)
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.config.timeout = Some(timeout);
+        self
+    }
+
+    /// 브라우저에 고정밀 위치를 요청할지 여부 (기본값 true)
+    pub fn high_accuracy(mut self, enabled: bool) -> Self {
+        self.config.high_accuracy = enabled;
+        self
+    }
+
+    /// 브라우저가 측정 한 번에 쓸 최대 시간, Geolocation API의 `timeout` (기본값 5초)
+    ///
+    /// 넘기면 페이지가 `WeimError::Timeout`을 알린다. 전체를 기다리는 시간은 `timeout()`으로 정한다.
+    pub fn position_timeout(mut self, timeout: Duration) -> Self {
+        self.config.position_timeout = timeout;
+        self
+    }
+
+    /// 브라우저가 이 시간 안에 잰 위치가 있으면 새로 재지 않고 쓴다, Geolocation API의 `maximumAge` (기본값 0 = 항상 새로 잰다)
+    pub fn maximum_age(mut self, age: Duration) -> Self {
+        self.config.maximum_age = age;
+        self
+    }
+
+    /// 정확도가 이 값(m) 이하가 될 때까지 브라우저에서 계속 측정한다. (기본값 없음 = 첫 위치 사용)
+    pub fn accuracy_threshold(mut self, meters: f64) -> Self {
+        self.config.accuracy_threshold = Some(meters);
+        self
+    }
+
+    /// 받은 좌표를 거칠게 만든 뒤에만 돌려주고, 출력하고, 기록하고, 내보낸다. (기본값 없음 = 그대로)
+    ///
+    /// `FallbackChain::recommended()`로 만든 체인은 다른 공급자가 찾은 위치에도 적용한다.
+    pub fn coarse_location(mut self, coarsening: Coarsening) -> Self {
+        self.config.coarse_location = Some(coarsening);
+        self
+    }
+
+    /// `accuracy_threshold`를 쓸 때 최대 측정 횟수. 다 쓰면 그중 가장 정확한 위치를 보낸다. (기본값 10)
+    pub fn max_attempts(mut self, attempts: u32) -> Self {
+        self.config.max_attempts = attempts;
+        self
+    }
+
+    /// 브라우저를 자동으로 열지 여부 (기본값 true)
+    pub fn open_browser(mut self, enabled: bool) -> Self {
+        self.config.open_browser = enabled;
+        self
+    }
+
+    /// 열어 둔 브라우저가 이 시간 안에 페이지를 받아 가지 않으면 `WeimError::BrowserNotReached`로 끝낸다. (기본값 30초)
+    ///
+    /// 브라우저를 직접 열 때(`open_browser(true)`)만 잰다. 멈출 때까지 받는 모드(`watch()`, `devices()`, `serve()`)에서는
+    /// 쓰지 않는다. 끄려면 `Duration::MAX`를 준다.
+    pub fn browser_grace(mut self, grace: Duration) -> Self {
+        self.config.browser_grace = grace;
+        self
+    }
+
+    /// 페이지를 받아 간 뒤 이 시간 안에 위치도 오류도 오지 않으면 `WeimError::NoResponse`로 끝낸다. (기본값 3분)
+    ///
+    /// 동의 화면이나 권한 창에서 머뭇거리는 시간도 포함된다. 위치를 보내기 전에 탭을 닫으면 이 시간과 상관없이
+    /// 곧바로 끝나고, 새로 고침은 다시 기다린다. 멈출 때까지 받는 모드에서는 쓰지 않는다. 끄려면 `Duration::MAX`를 준다.
+    pub fn response_grace(mut self, grace: Duration) -> Self {
+        self.config.response_grace = grace;
+        self
+    }
+
+    /// 브라우저 창 대신 헤드리스 Chrome/Chromium을 DevTools 프로토콜로 띄우고 위치 권한을 미리 준다. (기본값 false, `headless` 기능 필요)
+    ///
+    /// 창이 전혀 뜨지 않으며, 서버가 닫히면 Chrome도 끝낸다. Chrome은 PATH나 `WEIM_CHROME` 환경 변수에서 찾는다.
+    /// 찾지 못하거나 띄우지 못하면 경고를 남기고 평소처럼 브라우저 창을 연다. `open_browser(false)`면 쓰지 않는다.
+    /// 헤드리스 Chrome은 운영체제나 네트워크에서 위치를 얻는데, 얻지 못하면 페이지가 `WeimError::PositionUnavailable`을 알린다.
+    #[cfg(feature = "headless")]
+    pub fn headless(mut self, enabled: bool) -> Self {
+        self.config.headless = enabled;
+        self
+    }
+
+    /// `serve()`의 데몬을 tiny_http 대신 axum(hyper) 서버로 연다. (기본값 false, `axum` 기능 필요)
+    ///
+    /// 연결을 재사용하고 HTTP/2를 받으며, 요청은 tokio 스레드들이 나눠 처리한다.
+    /// `https()`를 켰으면 HTTPS로, `websocket` 기능이 있으면 웹소켓도 받는다. 다른 메서드는 계속 tiny_http를 쓴다.
+    #[cfg(feature = "axum")]
+    pub fn axum(mut self, enabled: bool) -> Self {
+        self.config.axum = enabled;
+        self
+    }
+
+    /// 위치를 받은 뒤 브라우저 정리 방법 (기본값 `CloseStrategy::None`)
+    pub fn close_strategy(mut self, strategy: CloseStrategy) -> Self {
+        self.config.close_strategy = strategy;
+        self
+    }
+
+    /// 위치를 묻기 전에 무엇을 보내는지 설명하고 "내 위치 공유"를 눌러야 시작하는 화면을 보여 줄지 여부 (기본값 false)
+    ///
+    /// 사용자가 거절을 누르면 `WeimError::PermissionDenied`로 끝난다. 브라우저가 위치를 주지 못했을 때처럼
+    /// 멈출 때까지 받는 모드(`watch()`, `devices()`, `serve()`)에서는 경고만 남기고 다른 기기를 계속 기다린다.
+    pub fn consent_screen(mut self, enabled: bool) -> Self {
+        self.config.consent = enabled;
+        self
+    }
+
+    /// 받은 위치를 페이지의 Leaflet 지도에 표시와 정확도 원으로 그릴지 여부 (기본값 false)
+    ///
+    /// `watch()`에서는 위치를 받을 때마다 지도가 따라 움직인다. 지도를 켜면 `CloseStrategy::TabCloseScript`여도 탭을 닫지 않는다.
+    /// Leaflet과 OpenStreetMap 타일은 브라우저가 인터넷에서 받아 온다.
+    pub fn show_map(mut self, enabled: bool) -> Self {
+        self.config.show_map = enabled;
+        self
+    }
+
+    /// 위치를 받았을 때 요약에 찍을 지도 링크들 (기본값 Google Maps). 비우면 링크를 찍지 않는다.
+    pub fn map_links(mut self, providers: impl IntoIterator<Item = MapProvider>) -> Self {
+        self.config.map_links = providers.into_iter().collect();
+        self
+    }
+
+    /// 브라우저가 위치를 주지 않으면(권한 거부, 미지원 등) 좌표를 직접 입력하는 양식을 보여 줄지 여부 (기본값 false)
+    ///
+    /// 입력한 위치는 `Location::source`가 `Source::Manual`이다. 끄면 서버도 직접 입력한 위치를 받지 않는다.
+    pub fn manual_entry(mut self, enabled: bool) -> Self {
+        self.config.manual_entry = enabled;
+        self
+    }
+
+    /// 기본 페이지 대신 이 HTML을 브라우저에 보낸다. (기본값 없음 = 내장 페이지)
+    ///
+    /// 템플릿 안의 자리 표시자는 설정값으로 바뀐다.
+    /// - `{{UPDATE_URL}}`: 위치를 JSON으로 POST할 주소. 본문에 `token`을 함께 보내야 받는다.
+    /// - `{{TOKEN}}`: 이번 실행의 세션 토큰
+    /// - `{{SAMPLES}}`: 보낼 위치 수 (`Infinity` = 멈출 때까지)
+    /// - `{{HIGH_ACCURACY}}`, `{{POSITION_TIMEOUT}}`, `{{MAXIMUM_AGE}}` (ms), `{{ACCURACY_THRESHOLD}}` (`null` = 없음),
+    ///   `{{MAX_ATTEMPTS}}`, `{{CLOSE_TAB}}`, `{{WEBSOCKET}}`
+    /// - `{{CONSENT}}`, `{{MAP}}`, `{{MANUAL}}`: 동의 화면, 지도, 직접 입력을 켰는지 여부
+    /// - `{{LANG}}`, `{{TITLE}}`, `{{TRACKING}}`, `{{DONE}}`, `{{CONSENT_TEXT}}`, `{{SHARE}}`, `{{DECLINE}}`, `{{TEXT}}`:
+    ///   현재 언어의 문구 (`{{TEXT}}`는 JSON 객체)
+    ///
+    /// 거절을 알리려면 `{"token": ...}`을 `/decline`에, 측정 실패는 `{"token": ..., "code": ..., "message": ...}`를
+    /// `/error`에 POST한다. `code`는 GeolocationPositionError의 코드이고 0은 Geolocation API가 없다는 뜻이다.
+    pub fn page_template(mut self, html: impl Into<String>) -> Self {
+        self.config.template = Some(Template::Html(html.into()));
+        self
+    }
+
+    /// `page_template`과 같지만 서버를 열 때마다 이 파일에서 읽는다. 읽지 못하면 내장 페이지를 쓴다.
+    pub fn page_template_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config.template = Some(Template::File(path.into()));
+        self
+    }
+
+    /// 콘솔 출력 형식 (기본값 `Output::Text`)
+    ///
+    /// `Output::Json`이면 받은 위치마다 JSON 한 줄을 stdout에 쓰고 안내는 stderr로 보내므로 스크립트에서 읽기 좋다.
+    /// 서버를 열 때 프로세스 전체의 출력에 적용된다.
+    pub fn output(mut self, output: Output) -> Self {
+        self.config.output = output;
+        self
+    }
+
+    /// 콘솔에 직접 출력할 안내의 양 (기본값 `Verbosity::Info`)
+    ///
+    /// 안내는 이 설정과 상관없이 `log` 이벤트로도 나간다. 서버를 열 때 프로세스 전체의 출력에 적용된다.
+    pub fn verbosity(mut self, verbosity: Verbosity) -> Self {
+        self.config.verbosity = verbosity;
+        self
+    }
+
+    /// 콘솔 안내, 오류 메시지, 브라우저 페이지의 언어 (기본값 `Language::Auto` = 시스템 로케일)
+    ///
+    /// 서버를 열 때 프로세스 전체에 적용된다.
+    pub fn language(mut self, language: Language) -> Self {
+        self.config.language = language;
+        self
+    }
+
+    pub fn build(self) -> Weim {
+        Weim { config: self.config }
+    }
+}
+
+/// 설정된 위치 추적기
+#[derive(Debug, Clone, Default)]
+pub struct Weim {
+    pub(crate) config: Config,
+}
+
+impl Weim {
+    pub fn builder() -> WeimBuilder {
+        WeimBuilder::new()
+    }
+
+    /// 서버를 열고 브라우저에서 위치를 한 번 받아온다.
+    pub fn locate(&self) -> Result<Location, WeimError> {
+        self.locate_cancellable(&CancellationToken::new())
+    }
+
+    /// `locate()`와 같지만 `token`이 취소되면 `WeimError::Cancelled`로 끝난다.
+    pub fn locate_cancellable(&self, token: &CancellationToken) -> Result<Location, WeimError> {
+        // WEIM_MOCK이 설정되어 있으면 브라우저 없이 바로 돌려준다
+        if let Some(mock) = MockProvider::from_env() {
+            let location = mock.and_then(|mut mock| mock.locate())?;
+            console::set(self.config.output, self.config.verbosity);
+            i18n::set(self.config.language);
+            console::fix(&DeviceId::default(), &location);
+            return Ok(location);
+        }
+
+        let mut fixes = self.gather(token, Mode::Single)?;
+        fixes.pop().ok_or(WeimError::ServerClosed)
+    }
+
+    /// 브라우저에서 위치를 `count`개 모아 이상치를 걸러낸 결과를 돌려준다. 0은 1로 취급한다.
+    pub fn locate_samples(&self, count: usize) -> Result<SampleSet, WeimError> {
+        let count = count.max(1);
+        if let Some(mock) = MockProvider::from_env() {
+            let mut mock = mock?;
+            let samples: Vec<Location> = (0..count).map(|_| mock.locate()).collect::<Result<_, _>>()?;
+            console::set(self.config.output, self.config.verbosity);
+            i18n::set(self.config.language);
+            for location in &samples {
+                console::fix(&DeviceId::default(), location);
+            }
+            return Ok(SampleSet::new(samples));
+        }
+        self.gather(&CancellationToken::new(), Mode::Samples(count)).map(SampleSet::new)
+    }
+
+    /// 멈출 때까지 받는 모드로 `duration` 동안 받은 위치를 모두 모은다. 한 곳을 오래 재서 보정하거나 측량할 때 쓴다.
+    ///
+    /// 돌려받은 `SampleSet`의 `centroid()`, `spread()`, `best_accuracy()`로 요약한다.
+    /// 그동안 하나도 받지 못하면 `WeimError::Timeout`으로 끝난다.
+    pub fn collect_for(&self, duration: Duration) -> Result<SampleSet, WeimError> {
+        self.collect(Some(duration), usize::MAX)
+    }
+
+    /// `collect_for()`와 같지만 시간 대신 위치를 `count`개 받으면 끝낸다. 0은 1로 취급한다.
+    ///
+    /// `timeout`을 설정했으면 위치 사이의 최대 간격으로 쓰인다.
+    pub fn collect_count(&self, count: usize) -> Result<SampleSet, WeimError> {
+        self.collect(None, count.max(1))
+    }
+
+    fn collect(&self, duration: Option<Duration>, count: usize) -> Result<SampleSet, WeimError> {
+        if let Some(mock) = MockProvider::from_env() {
+            let mut mock = mock?;
+            // 흉내 낸 위치는 기다려도 바뀌지 않으므로 시간만 정했으면 하나만 받는다
+            let count = if duration.is_some() { 1 } else { count };
+            let samples: Vec<Location> = (0..count).map(|_| mock.locate()).collect::<Result<_, _>>()?;
+            console::set(self.config.output, self.config.verbosity);
+            i18n::set(self.config.language);
+            for location in &samples {
+                console::fix(&DeviceId::default(), location);
+            }
+            return Ok(SampleSet::new(samples));
+        }
+
+        // 시간이 다 되면 `token`을 취소한다. 먼저 끝나면 `finished`로 알린다
+        let (token, finished) = (CancellationToken::new(), CancellationToken::new());
+        if let Some(end) = duration.and_then(|duration| Instant::now().checked_add(duration)) {
+            let (token, finished) = (token.clone(), finished.clone());
+            thread::spawn(move || {
+                while Instant::now() < end && !finished.is_cancelled() {
+                    thread::sleep(POLL_INTERVAL);
+                }
+                token.cancel();
+            });
+        }
+
+        let (server, _browser) = self.open()?;
+        let mut fixes = Vec::new();
+        let result = self.run(&server, &token, Mode::Watch, |_, location| {
+            fixes.push(location);
+            fixes.len() < count
+        });
+        finished.cancel();
+        match result {
+            Ok(()) => {}
+            // 시간이 다 됐거나 다음 위치가 오지 않으면 그때까지 모은 것을 돌려준다
+            Err(WeimError::Cancelled | WeimError::Timeout) if !fixes.is_empty() => {}
+            Err(WeimError::Cancelled) => return Err(WeimError::Timeout),
+            Err(e) => return Err(e),
+        }
+
+        if self.config.close_strategy == CloseStrategy::KillProcess {
+            handler::kill_browser();
+        }
+        Ok(SampleSet::new(fixes))
+    }
+
+    // 서버를 열고 모드에 맞는 개수의 위치가 모일 때까지 요청을 처리한다
+    fn gather(&self, token: &CancellationToken, mode: Mode) -> Result<Vec<Location>, WeimError> {
+        let count = match mode {
+            Mode::Single | Mode::Watch | Mode::Session => 1,
+            Mode::Samples(count) => count,
+        };
+
+        let (server, _browser) = self.open()?;
+        let mut fixes = Vec::with_capacity(count);
+        self.run(&server, token, mode, |_, location| {
+            fixes.push(location);
+            fixes.len() < count
+        })?;
+
+        if self.config.close_strategy == CloseStrategy::KillProcess {
+            handler::kill_browser();
+        }
+        Ok(fixes)
+    }
+
+    /// 배너를 출력하고 서버를 연 뒤 브라우저를 연다. 돌려받은 `Browser`는 서버와 함께 버린다.
+    pub(crate) fn open(&self) -> Result<(Server, Browser), WeimError> {
+        console::set(self.config.output, self.config.verbosity);
+        i18n::set(self.config.language);
+        print_banner();
+
+        let server = self.bind()?;
+        let addr = server.server_addr().to_ip().ok_or(WeimError::ServerClosed)?;
+
+        // 서버가 열린 뒤에 브라우저 열기
+        let browser = self.launch(addr)?;
+        Ok((server, browser))
+    }
+
+    /// 요청을 처리하면서 받은 위치를 `on_fix`에 넘긴다. `on_fix`가 false를 돌려주면 끝난다.
+    ///
+    /// `Mode::Watch`에서는 제한 시간이 위치를 받을 때마다 다시 시작된다.
+    pub(crate) fn run(
+        &self,
+        server: &Server,
+        token: &CancellationToken,
+        mode: Mode,
+        mut on_fix: impl FnMut(DeviceId, Location) -> bool,
+    ) -> Result<(), WeimError> {
+        let handler = Arc::new(Handler::new(&self.config, mode));
+        // 웹소켓 연결 스레드가 처리한 요청의 결과
+        #[cfg(feature = "websocket")]
+        let (upgrades, outcomes) = mpsc::channel();
+        let recorder = Recorder::open(&self.config)?;
+        let enricher = Enricher::new(&self.config);
+        #[cfg(feature = "notifications")]
+        let mut notifier = Notifier::new(self.config.notifications.as_ref());
+        let mut deadline = self.config.timeout.map(|timeout| Instant::now() + timeout);
+        let opened = Instant::now();
+        // 첫 위치를 받은 뒤에는 브라우저가 떠났는지 따지지 않는다
+        let mut received = false;
+
+        // 요청은 일꾼 스레드들이 처리하고, 받은 위치는 이 스레드에서 들어온 순서대로 넘긴다
+        let (results, finished) = mpsc::channel();
+        let (next, stop) = (Mutex::new(0), AtomicBool::new(false));
+        thread::scope(|scope| {
+            // 돌아갈 때 일꾼을 멈춘다. 처리 중인 요청은 끝날 때까지 기다린다
+            let _stop = StopOnDrop(&stop);
+            for _ in 0..self.config.workers.max(1) {
+                let (handler, next, stop, results) = (&handler, &next, &stop, results.clone());
+                #[cfg(feature = "websocket")]
+                let upgrades = &upgrades;
+                scope.spawn(move || {
+                    pool::work(
+                        server,
+                        handler,
+                        next,
+                        stop,
+                        results,
+                        #[cfg(feature = "websocket")]
+                        upgrades,
+                    )
+                });
+            }
+            // 일꾼이 모두 끝나면 채널이 끊긴다
+            drop(results);
+            let mut reorder = Reorder::default();
+
+            loop {
+                if token.is_cancelled() {
+                    return Err(WeimError::Cancelled);
+                }
+                if !received && let Some(e) = handler.abandoned(opened) {
+                    return Err(e);
+                }
+
+                let wait = match deadline {
+                    Some(deadline) => {
+                        let remaining = deadline.saturating_duration_since(Instant::now());
+                        if remaining.is_zero() {
+                            return Err(WeimError::Timeout);
+                        }
+                        remaining.min(POLL_INTERVAL)
+                    }
+                    None => POLL_INTERVAL,
+                };
+
+                // 웹소켓으로 받은 위치를 먼저 처리한다
+                #[cfg(feature = "websocket")]
+                let upgraded = outcomes.try_recv().ok();
+                #[cfg(not(feature = "websocket"))]
+                let upgraded = None;
+                let ready = match upgraded {
+                    Some(outcome) => vec![outcome],
+                    None => match finished.recv_timeout(wait) {
+                        Ok(numbered) => {
+                            reorder.push(numbered);
+                            reorder.ready()
+                        }
+                        // 늦은 요청을 기다리던 결과가 있으면 이제 넘길 수도 있다
+                        Err(RecvTimeoutError::Timeout) => reorder.ready(),
+                        Err(RecvTimeoutError::Disconnected) => return Err(WeimError::ServerClosed),
+                    },
+                };
+
+                for outcome in ready {
+                    match outcome {
+                        Outcome::Continue | Outcome::Subscribe => {}
+                        #[cfg(feature = "websocket")]
+                        Outcome::Upgrade => {}
+                        Outcome::Failed(e) => return Err(e),
+                        Outcome::Fix(device, mut location) => {
+                            received = true;
+                            enricher.apply(&mut location);
+                            recorder.record(&device, &location);
+                            handler.publish(&device, &location);
+                            #[cfg(feature = "notifications")]
+                            if let Some(notifier) = notifier.as_mut() {
+                                notifier.on_fix(&location);
+                            }
+                            if !on_fix(device, location) {
+                                return Ok(());
+                            }
+                            if mode == Mode::Watch {
+                                deadline = self.config.timeout.map(|timeout| Instant::now() + timeout);
+                            }
+                        }
+                    }
+                }
+            }
+        })
+    }
+
+    // 설정한 포트로 열고, 실패하면 허용된 경우 빈 포트로 다시 시도한다
+    fn bind(&self) -> Result<Server, WeimError> {
+        let addr = SocketAddr::new(self.config.bind_address, self.config.port);
+
+        // 인증서는 포트를 다시 시도하더라도 한 번만 준비한다
+        #[cfg(feature = "tls")]
+        let ssl = match &self.config.tls {
+            Some(tls) => {
+                let host = if addr.ip().is_unspecified() { lan_ip() } else { Some(addr.ip()) };
+                Some(tls.ssl_config(host)?)
+            }
+            None => None,
+        };
+        let listen = |addr: SocketAddr| {
+            #[cfg(feature = "tls")]
+            if let Some(ssl) = &ssl {
+                return Server::https(addr, ssl.clone());
+            }
+            Server::http(addr)
+        };
+
+        match listen(addr) {
+            Ok(server) => Ok(server),
+            Err(_) if self.config.port_fallback && self.config.port != 0 => {
+                warning!("{}", tr!("⚠️ {} 포트를 사용할 수 없어 빈 포트로 엽니다.", "⚠️ Port {} is unavailable, opening a free port instead.", self.config.port));
+                listen(SocketAddr::new(self.config.bind_address, 0))
+                    .map_err(|source| WeimError::Bind { addr: addr.to_string(), source })
+            }
+            Err(source) => Err(WeimError::Bind { addr: addr.to_string(), source }),
+        }
+    }
+
+    /// 다른 기기에서 접속할 때 쓸 페이지 주소 (끝에 `/` 없음)
+    pub(crate) fn base_url(&self, addr: SocketAddr) -> String {
+        let lan_url = if self.config.lan { lan_url(self.scheme(), addr) } else { None };
+        lan_url.unwrap_or_else(|| page_url(self.scheme(), addr))
+    }
+
+    pub(crate) fn scheme(&self) -> &'static str {
+        #[cfg(feature = "tls")]
+        if self.config.tls.is_some() {
+            return "https";
+        }
+        "http"
+    }
+
+    /// 브라우저를 열거나, 열지 않도록 설정했으면 주소를 안내한다.
+    pub(crate) fn launch(&self, addr: SocketAddr) -> Result<Browser, WeimError> {
+        let url = page_url(self.scheme(), addr);
+        if self.config.lan {
+            match lan_url(self.scheme(), addr) {
+                Some(lan_url) => info!("{}", tr!("📱 휴대폰에서 {} 을(를) 열어 주세요.", "📱 Open {} on your phone.", lan_url)),
+                None => warning!(
+                    "{}",
+                    tr!(
+                        "⚠️ LAN 주소를 찾지 못했습니다. 이 컴퓨터의 IP와 {} 포트로 접속해 주세요.",
+                        "⚠️ Could not find a LAN address. Connect to this computer's IP on port {}.",
+                        addr.port()
+                    )
+                ),
+            }
+        }
+        #[cfg(feature = "qr")]
+        if self.config.print_qr {
+            crate::qr::print_qr(&self.base_url(addr));
+        }
+        #[cfg(feature = "tls")]
+        if matches!(self.config.tls, Some(Tls::SelfSigned)) {
+            tls::print_untrusted_notice();
+        }
+        if self.config.open_browser {
+            self.open_page(&url)
+        } else {
+            if !self.config.lan {
+                info!("{}", tr!("🌐 브라우저에서 {} 을(를) 열어 주세요.", "🌐 Open {} in your browser.", url));
+            }
+            Ok(Browser::default())
+        }
+    }
+
+    /// `url`을 브라우저 창이나, `headless(true)`면 창 없는 Chrome으로 연다.
+    pub(crate) fn open_page(&self, url: &str) -> Result<Browser, WeimError> {
+        #[cfg(feature = "headless")]
+        if self.config.headless {
+            #[cfg(feature = "tls")]
+            let insecure = matches!(self.config.tls, Some(Tls::SelfSigned));
+            #[cfg(not(feature = "tls"))]
+            let insecure = false;
+            match Headless::launch(url, insecure) {
+                Ok(headless) => {
+                    info!("{}", pick("🕶️ 창 없는 Chrome으로 위치를 받습니다.", "🕶️ Getting the location through headless Chrome."));
+                    return Ok(Browser { _headless: Some(headless) });
+                }
+                Err(e) => warning!(
+                    "{}",
+                    tr!("⚠️ 헤드리스 Chrome을 쓸 수 없어 브라우저 창을 엽니다: {}", "⚠️ Headless Chrome is unavailable, opening a browser window: {}", e)
+                ),
+            }
+        }
+        open_browser(url).map(|_| Browser::default())
+    }
+}
+
+/// 서버를 닫을 때까지 들고 있을 브라우저. 버리면 weim이 띄운 헤드리스 Chrome을 끝낸다.
+#[derive(Default)]
+pub(crate) struct Browser {
+    #[cfg(feature = "headless")]
+    _headless: Option<Headless>,
+}
+
+// 요청 하나에 답한다. 스트림이나 웹소켓으로 넘긴 연결은 별도 스레드에서 계속 쓴다.
+pub(crate) fn serve_request(
+    handler: &Arc<Handler>,
+    mut request: Request,
+    #[cfg(feature = "websocket")] upgrades: &Sender<Outcome>,
+) -> Outcome {
+    let started = Instant::now();
+    // 업그레이드 요청의 본문은 연결 전체라서 끝나지 않으므로 GET은 본문을 읽지 않는다
+    let mut body = String::new();
+    if *request.method() != Method::Get {
+        request.as_reader().read_to_string(&mut body).ok();
+    }
+
+    let client = request.remote_addr().map(|addr| addr.ip());
+    let (reply, outcome) = handler.handle(&Incoming {
+        client,
+        origin: header(&request, "Origin"),
+        method: request.method().as_str(),
+        path: request.url(),
+        body: &body,
+    });
+    log_access(handler, &request, &reply, &outcome, started);
+    if let Outcome::Subscribe = outcome {
+        // 스트림은 서버가 닫혀 채널이 끊길 때까지 별도 스레드에서 쓴다
+        let (rx, head) = (handler.events().channel(), events::stream_head(&reply));
+        let writer = request.into_writer();
+        thread::spawn(move || events::stream(rx, head, writer));
+        return Outcome::Continue;
+    }
+    #[cfg(feature = "websocket")]
+    if let Outcome::Upgrade = outcome {
+        let key = request
+            .headers()
+            .iter()
+            .find(|header| header.field.equiv("Sec-WebSocket-Key"))
+            .map(|header| ws::accept_key(header.value.as_str()));
+        if let Some(accept) = key {
+            let response = Response::empty(101).with_header(Header::from_bytes("Sec-WebSocket-Accept", accept).unwrap());
+            let stream = request.upgrade("websocket", response);
+            let (handler, upgrades) = (Arc::downgrade(handler), upgrades.clone());
+            thread::spawn(move || ws::serve(stream, client, handler, upgrades));
+            return Outcome::Continue;
+        }
+    }
+    let mut response = Response::from_string(reply.body).with_status_code(reply.status);
+    for (name, value) in reply.headers {
+        response.add_header(Header::from_bytes(name, value).unwrap());
+    }
+    request.respond(response).ok();
+    outcome
+}
+
+// 스트림과 웹소켓은 연결을 넘기기 전에 남긴다. 응답의 상태와 크기는 보내기 전에 정해져 있다
+#[cfg_attr(not(feature = "websocket"), allow(unused_variables))]
+fn log_access(handler: &Handler, request: &Request, reply: &Reply, outcome: &Outcome, started: Instant) {
+    #[allow(unused_mut)]
+    let mut status = reply.status;
+    #[cfg(feature = "websocket")]
+    if let Outcome::Upgrade = outcome && header(request, "Sec-WebSocket-Key").is_some() {
+        status = 101;
+    }
+    handler.access_log().record(&Access {
+        client: request.remote_addr().map(|addr| addr.ip()),
+        method: request.method().as_str(),
+        path: request.url(),
+        version: &format!("HTTP/{}", request.http_version()),
+        status,
+        bytes: reply.body.len(),
+        latency: started.elapsed(),
+        referer: header(request, "Referer"),
+        user_agent: header(request, "User-Agent"),
+    });
+}
+
+fn header<'a>(request: &'a Request, name: &'static str) -> Option<&'a str> {
+    request.headers().iter().find(|header| header.field.equiv(name)).map(|header| header.value.as_str())
+}
+
+pub(crate) fn print_banner() {
+    info!("{}", pick("\n🚀 위치 추적 시스템 시작!", "\n🚀 Location tracking started!"));
+    info!("{}", pick("🔍 위치 정보를 수집합니다...\n", "🔍 Collecting location...\n"));
+    info!("{}", "=".repeat(60));
+}
+
+// 루프백이나 전체 주소로 열었으면 localhost로 접속
+pub(crate) fn page_url(scheme: &str, addr: SocketAddr) -> String {
+    let ip = addr.ip();
+    if ip.is_loopback() || ip.is_unspecified() {
+        format!("{}://localhost:{}", scheme, addr.port())
+    } else {
+        format!("{}://{}", scheme, addr)
+    }
+}
+
+// 전체 주소로 열었으면 다른 기기에서 접속할 수 있는 주소를 만든다
+fn lan_url(scheme: &str, addr: SocketAddr) -> Option<String> {
+    let ip = if addr.ip().is_unspecified() { lan_ip()? } else { addr.ip() };
+    if ip.is_loopback() {
+        return None;
+    }
+    Some(format!("{}://{}", scheme, SocketAddr::new(ip, addr.port())))
+}
+
+// 외부로 나가는 경로의 출발 주소. UDP connect는 패킷을 보내지 않는다.
+pub(crate) fn lan_ip() -> Option<IpAddr> {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).ok()?;
+    socket.connect((Ipv4Addr::new(192, 0, 2, 1), 9)).ok()?;
+    let ip = socket.local_addr().ok()?.ip();
+    (!ip.is_unspecified()).then_some(ip)
+}
+
+fn open_browser(url: &str) -> Result<(), WeimError> {
+    #[cfg(target_os = "windows")]
+    let result = Command::new("cmd").args(["/C", "start", url]).spawn();
+
+    #[cfg(target_os = "macos")]
+    let result = Command::new("open").arg(url).spawn();
+
+    #[cfg(target_os = "linux")]
+    let result = Command::new("xdg-open").arg(url).spawn();
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+    let result: std::io::Result<std::process::Child> =
+        Err(std::io::Error::new(std::io::ErrorKind::Unsupported, url));
+
+    result.map(|_| ()).map_err(WeimError::BrowserLaunch)
+}
@@ -0,0 +1,943 @@
+//! 셸 스크립트에서 weim을 쓰기 위한 명령줄 도구 (`cli` 기능 필요)
+
+mod service;
+
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::str::FromStr;
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+use clap::{ArgGroup, Args, Parser, Subcommand, ValueEnum};
+#[cfg(feature = "heatmap")]
+use weim::heatmap::{ColorRamp, Heatmap};
+use weim::notation::CoordinateFormat;
+use weim::privacy::Coarsening;
+use weim::places::{self, Place, PlaceDetector};
+use weim::trips::{self, Trip, TripDetector};
+#[cfg(feature = "encryption")]
+use weim::HistoryKey;
+use service::Service;
+use weim::{geo, DeviceId, History, HistoryQuery, IpRange, RateLimit, Retention, Schedule, Language, Location, MotionChange, MotionDetector, MotionState, Output, SampleSet, Verbosity, Weim};
+
+// `--map`으로 그리는 점자 지도의 크기 (글자)
+#[cfg(feature = "static-map")]
+const MAP_COLUMNS: u32 = 60;
+#[cfg(feature = "static-map")]
+const MAP_ROWS: u32 = 15;
+
+// 설정 파일의 `history_passphrase`와 같은 환경 변수. `history export`는 설정을 읽지 않아서 직접 본다
+#[cfg(feature = "encryption")]
+const PASSPHRASE_ENV: &str = "WEIM_HISTORY_PASSPHRASE";
+
+#[derive(Parser)]
+#[command(name = "weim", version, about = "브라우저에서 내 위치를 받아 온다")]
+struct Cli {
+    #[command(flatten)]
+    options: Options,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Args)]
+struct Options {
+    /// 결과를 JSON으로 출력
+    #[arg(long, global = true)]
+    json: bool,
+    /// 결과 말고는 아무것도 출력하지 않음
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+    /// 요청 하나하나까지 안내
+    #[arg(short, long, global = true)]
+    verbose: bool,
+    /// 로컬 서버 포트 (기본값 3030)
+    #[arg(long, global = true)]
+    port: Option<u16>,
+    /// 위치를 기다릴 최대 시간(초)
+    #[arg(long, global = true, value_name = "SECONDS")]
+    timeout: Option<u64>,
+    /// 브라우저를 열지 않고 주소만 안내
+    #[arg(long, global = true)]
+    no_browser: bool,
+    /// 같은 네트워크의 휴대폰에서도 접속할 수 있게 연다
+    #[arg(long, global = true)]
+    lan: bool,
+    /// 이 주소나 CIDR 대역에서 온 요청만 받는다 (여러 번 줄 수 있다)
+    #[arg(long, global = true, value_name = "CIDR")]
+    allow: Vec<IpRange>,
+    /// 이 주소나 CIDR 대역에서 온 요청은 받지 않는다 (여러 번 줄 수 있다)
+    #[arg(long, global = true, value_name = "CIDR")]
+    deny: Vec<IpRange>,
+    /// LAN 클라이언트가 위치를 보낼 수 있는 초당 요청 수 (기본값 5, 0이면 끈다)
+    #[arg(long, global = true, value_name = "PER_SECOND")]
+    rate_limit: Option<f64>,
+    /// 다른 출처의 페이지가 `/api`를 읽을 수 있게 허용할 출처. 주지 않으면 같은 출처만, `*`이면 모두 (여러 번 줄 수 있다)
+    #[arg(long, global = true, value_name = "ORIGIN")]
+    allowed_origin: Vec<String>,
+    /// 요청을 동시에 처리할 스레드 수 (기본값 4)
+    #[arg(long, global = true, value_name = "N")]
+    workers: Option<usize>,
+    /// 서버가 답한 요청을 이 파일에 combined 형식으로 남긴다
+    #[arg(long, global = true, value_name = "PATH")]
+    access_log: Option<PathBuf>,
+    /// 이 정확도(m) 안에 들어올 때까지 측정을 계속한다
+    #[arg(long, global = true, value_name = "METERS")]
+    accuracy: Option<f64>,
+    /// 좌표를 한 변이 이 길이(m)인 격자의 가운데로 뭉개서 출력하고 기록한다
+    #[arg(long, global = true, value_name = "METERS")]
+    coarse: Option<f64>,
+    /// 안내와 페이지의 언어 (기본값 auto)
+    #[arg(long, global = true, value_enum)]
+    lang: Option<Lang>,
+    /// 받은 위치 주변 지도를 점자로 함께 그린다 (OpenStreetMap 타일을 받아 온다)
+    #[cfg(feature = "static-map")]
+    #[arg(long, global = true)]
+    map: bool,
+}
+
+impl Options {
+    // 옵션으로 정하지 않았으면 시스템 로케일을 따른다
+    fn language(&self) -> Language {
+        self.lang.map(Language::from).unwrap_or_default()
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Lang {
+    Ko,
+    En,
+    Auto,
+}
+
+impl From<Lang> for Language {
+    fn from(lang: Lang) -> Self {
+        match lang {
+            Lang::Ko => Language::Korean,
+            Lang::En => Language::English,
+            Lang::Auto => Language::Auto,
+        }
+    }
+}
+
+// 언어에 맞는 문구를 고른다. 옵션을 읽기 전(인자 해석)에는 `Language::Auto`로 부른다.
+fn pick<'a>(language: impl Into<Language>, korean: &'a str, english: &'a str) -> &'a str {
+    if language.into().resolve() == Language::English { english } else { korean }
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// 위치를 한 번 받아 출력한다
+    Locate,
+    /// 멈출 때까지 받은 위치를 한 줄씩 출력한다
+    Watch {
+        /// 이 개수만큼 받으면 끝낸다
+        #[arg(long)]
+        count: Option<usize>,
+        /// 멈춤, 걷기, 차로 이동 상태가 바뀔 때도 한 줄씩 출력한다
+        #[arg(long)]
+        motion: bool,
+        /// Prometheus가 긁어 갈 `/metrics`를 연다
+        #[arg(long)]
+        metrics: bool,
+    },
+    /// 한 곳에서 정해 둔 시간이나 개수만큼 위치를 모아 중심, 퍼짐, 최고 정확도를 보여 준다
+    Collect {
+        /// 이 시간(초) 동안 모은다
+        #[arg(long = "for", value_name = "SECONDS", required_unless_present = "count", conflicts_with = "count")]
+        seconds: Option<u64>,
+        /// 이 개수만큼 모은다
+        #[arg(long)]
+        count: Option<usize>,
+    },
+    /// 서버를 계속 띄워 두고 다른 프로세스의 질의에 답한다
+    Serve {
+        /// 받은 위치를 기록할 SQLite 파일
+        #[arg(long)]
+        history: Option<PathBuf>,
+        /// 기록을 이 열쇠 파일로 암호화한다 (암호는 `WEIM_HISTORY_PASSPHRASE`로 준다)
+        #[cfg(feature = "encryption")]
+        #[arg(long, value_name = "PATH")]
+        key_file: Option<PathBuf>,
+        /// 질의를 받을 Unix 소켓 경로 (Windows에서는 이름 있는 파이프)
+        #[cfg(any(unix, windows))]
+        #[arg(long)]
+        socket: Option<PathBuf>,
+        /// Prometheus가 긁어 갈 `/metrics`를 연다
+        #[arg(long)]
+        metrics: bool,
+        /// 페이지를 계속 열어 두지 않고 정해 둔 때마다 위치를 받는다 (예: "every 10m 08:00-20:00", "*/10 8-19 * * *")
+        #[arg(long)]
+        schedule: Option<Schedule>,
+        /// tiny_http 대신 axum 서버로 연다 (keep-alive, HTTP/2)
+        #[cfg(feature = "axum")]
+        #[arg(long)]
+        axum: bool,
+    },
+    /// 위치 기록 다루기
+    History {
+        #[command(subcommand)]
+        command: HistoryCommand,
+    },
+    /// `serve`를 부팅할 때 시작되는 서비스(systemd, launchd, Windows 서비스)로 다루기
+    Service {
+        #[command(subcommand)]
+        command: ServiceCommand,
+    },
+    /// 두 좌표(위도,경도, 도·분·초 또는 지도 링크) 사이의 거리와 방위각
+    Distance {
+        #[arg(allow_hyphen_values = true)]
+        from: Point,
+        #[arg(allow_hyphen_values = true)]
+        to: Point,
+        /// 타원체 기준 Vincenty 공식으로 계산
+        #[arg(long)]
+        vincenty: bool,
+    },
+}
+
+#[derive(Args)]
+struct ServiceTarget {
+    /// 서비스 이름
+    #[arg(long, default_value = "weim")]
+    name: String,
+    /// 로그인한 사용자 대신 시스템 전체에 둔다 (systemd, launchd. 관리자 권한 필요)
+    #[arg(long)]
+    system: bool,
+}
+
+#[derive(Subcommand)]
+enum ServiceCommand {
+    /// 서비스를 만들어 등록하고 시작한다. 비정상으로 끝나면 5초 뒤 다시 시작한다
+    Install {
+        #[command(flatten)]
+        target: ServiceTarget,
+        /// 출력을 저널이나 기본 로그 파일 대신 이 파일에 덧붙인다
+        #[arg(long, value_name = "PATH")]
+        log: Option<PathBuf>,
+        /// 서비스의 `weim serve`가 읽을 설정 파일 (기본값은 지금의 `WEIM_CONFIG`)
+        #[arg(long, value_name = "PATH")]
+        config: Option<PathBuf>,
+        /// 등록하지 않고 만들 내용만 출력한다
+        #[arg(long)]
+        dry_run: bool,
+        /// `weim serve`에 넘길 인자 (`--` 뒤에 쓴다. 예: `-- --no-browser --lan`)
+        #[arg(last = true, value_name = "SERVE_ARGS")]
+        args: Vec<String>,
+    },
+    /// 서비스를 멈추고 지운다
+    Uninstall {
+        #[command(flatten)]
+        target: ServiceTarget,
+    },
+    /// 서비스가 돌고 있는지 출력한다
+    Status {
+        #[command(flatten)]
+        target: ServiceTarget,
+    },
+    /// 서비스 관리자가 부르는 진입점
+    #[cfg(windows)]
+    #[command(hide = true)]
+    Run {
+        #[arg(long)]
+        name: String,
+        #[arg(long)]
+        dir: PathBuf,
+        #[arg(long)]
+        log: Option<PathBuf>,
+        #[arg(long)]
+        config: Option<PathBuf>,
+        #[arg(last = true)]
+        args: Vec<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum HistoryCommand {
+    /// 기록을 GPX, KML, GeoJSON 파일로 내보낸다
+    Export {
+        /// SQLite 기록 파일
+        database: PathBuf,
+        /// 암호화한 기록의 열쇠 파일 (암호는 `WEIM_HISTORY_PASSPHRASE`로 준다)
+        #[cfg(feature = "encryption")]
+        #[arg(long, value_name = "PATH")]
+        key_file: Option<PathBuf>,
+        /// 쓸 파일. 형식을 정하지 않으면 확장자로 고른다
+        output: PathBuf,
+        #[arg(long, value_enum)]
+        format: Option<Format>,
+        /// 이 시각 이후 (Unix epoch ms 또는 RFC 3339)
+        #[arg(long, value_parser = parse_time)]
+        from: Option<DateTime<Utc>>,
+        /// 이 시각 이전 (Unix epoch ms 또는 RFC 3339)
+        #[arg(long, value_parser = parse_time)]
+        to: Option<DateTime<Utc>>,
+        /// 이 기기의 기록만
+        #[arg(long)]
+        device: Option<String>,
+        /// GPX 구간을 나눌 위치 사이의 최대 간격(분)
+        #[arg(long, default_value_t = 10)]
+        split_gap: u64,
+        /// 트랙 모양에서 이 거리(m) 안쪽으로만 벗어나는 위치는 뺀다 (Douglas–Peucker)
+        #[arg(long, value_name = "METERS")]
+        simplify: Option<f64>,
+    },
+    /// 기록에서 오래된 위치를 지운다. 기준을 여럿 주면 하나라도 넘은 위치를 지운다
+    #[command(group(ArgGroup::new("criteria").required(true).multiple(true).args(["before", "max_age", "max_rows"])))]
+    Purge {
+        /// SQLite 기록 파일
+        database: PathBuf,
+        /// 암호화한 기록의 열쇠 파일 (암호는 `WEIM_HISTORY_PASSPHRASE`로 준다)
+        #[cfg(feature = "encryption")]
+        #[arg(long, value_name = "PATH")]
+        key_file: Option<PathBuf>,
+        /// 이 시각 이전의 위치 (Unix epoch ms 또는 RFC 3339)
+        #[arg(long, value_parser = parse_time)]
+        before: Option<DateTime<Utc>>,
+        /// 이 기간(일)보다 오래된 위치
+        #[arg(long, value_name = "DAYS")]
+        max_age: Option<u64>,
+        /// 최근 이 개수만 남긴다
+        #[arg(long)]
+        max_rows: Option<u64>,
+        /// 이 기기의 위치만
+        #[arg(long)]
+        device: Option<String>,
+        /// 지우기 전에 지울 위치를 이 파일로 내보낸다
+        #[arg(long, value_name = "PATH")]
+        export: Option<PathBuf>,
+        #[arg(long, value_enum, requires = "export")]
+        format: Option<Format>,
+    },
+    /// 기록을 머문 곳 사이의 이동 구간으로 나눠 거리, 시간, 속력을 보여 준다
+    Trips {
+        /// SQLite 기록 파일
+        database: PathBuf,
+        /// 암호화한 기록의 열쇠 파일 (암호는 `WEIM_HISTORY_PASSPHRASE`로 준다)
+        #[cfg(feature = "encryption")]
+        #[arg(long, value_name = "PATH")]
+        key_file: Option<PathBuf>,
+        /// 이 시각 이후 (Unix epoch ms 또는 RFC 3339)
+        #[arg(long, value_parser = parse_time)]
+        from: Option<DateTime<Utc>>,
+        /// 이 시각 이전 (Unix epoch ms 또는 RFC 3339)
+        #[arg(long, value_parser = parse_time)]
+        to: Option<DateTime<Utc>>,
+        /// 이 기기의 기록만
+        #[arg(long)]
+        device: Option<String>,
+        /// 이 반경(m) 안에 머물면 멈춘 것으로 본다
+        #[arg(long, default_value_t = 100.0)]
+        stop_radius: f64,
+        /// 이만큼(분) 머물면 멈춘 것으로 본다
+        #[arg(long, default_value_t = 5)]
+        min_stop: u64,
+        /// 떠난 곳과 도착한 곳의 주소를 OpenStreetMap Nominatim에서 찾는다
+        #[cfg(feature = "geocoding")]
+        #[arg(long)]
+        addresses: bool,
+        #[arg(long, value_enum, default_value_t = SummaryFormat::Table)]
+        format: SummaryFormat,
+        /// 표준 출력 대신 이 파일에 쓴다
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+    /// 오래 머문 곳을 칠한 열지도를 PNG나 Leaflet.heat JSON으로 쓴다
+    #[cfg(feature = "heatmap")]
+    Heatmap {
+        /// SQLite 기록 파일
+        database: PathBuf,
+        /// 암호화한 기록의 열쇠 파일 (암호는 `WEIM_HISTORY_PASSPHRASE`로 준다)
+        #[cfg(feature = "encryption")]
+        #[arg(long, value_name = "PATH")]
+        key_file: Option<PathBuf>,
+        /// 쓸 파일. `.json`이면 Leaflet.heat 배열, 아니면 PNG
+        output: PathBuf,
+        /// 이 시각 이후 (Unix epoch ms 또는 RFC 3339)
+        #[arg(long, value_parser = parse_time)]
+        from: Option<DateTime<Utc>>,
+        /// 이 시각 이전 (Unix epoch ms 또는 RFC 3339)
+        #[arg(long, value_parser = parse_time)]
+        to: Option<DateTime<Utc>>,
+        /// 이 기기의 기록만
+        #[arg(long)]
+        device: Option<String>,
+        /// 격자 한 칸의 크기(m)
+        #[arg(long, default_value_t = 50.0)]
+        cell: f64,
+        /// 이웃한 이만큼의 칸까지 번지게 그린다
+        #[arg(long, default_value_t = 1)]
+        blur: u32,
+        /// 쉼표로 나눈 `#rrggbb` 색 띠 (기본값 파랑 → 빨강)
+        #[arg(long)]
+        ramp: Option<ColorRamp>,
+        /// 머문 시간 대신 위치 수를 센다
+        #[arg(long)]
+        count: bool,
+    },
+    /// 기록에서 자주 머문 곳을 찾아 방문 횟수와 머문 시간을 보여 준다
+    Places {
+        /// SQLite 기록 파일
+        database: PathBuf,
+        /// 암호화한 기록의 열쇠 파일 (암호는 `WEIM_HISTORY_PASSPHRASE`로 준다)
+        #[cfg(feature = "encryption")]
+        #[arg(long, value_name = "PATH")]
+        key_file: Option<PathBuf>,
+        /// 이 시각 이후 (Unix epoch ms 또는 RFC 3339)
+        #[arg(long, value_parser = parse_time)]
+        from: Option<DateTime<Utc>>,
+        /// 이 시각 이전 (Unix epoch ms 또는 RFC 3339)
+        #[arg(long, value_parser = parse_time)]
+        to: Option<DateTime<Utc>>,
+        /// 이 기기의 기록만
+        #[arg(long)]
+        device: Option<String>,
+        /// 이만큼(분) 머물러야 방문으로 센다
+        #[arg(long, default_value_t = 10)]
+        min_stay: u64,
+        /// 방문끼리 이 거리(m) 안에 있으면 같은 곳으로 본다
+        #[arg(long, default_value_t = 150.0)]
+        distance: f64,
+        /// 이만큼 가야 장소로 센다
+        #[arg(long, default_value_t = 2)]
+        min_visits: usize,
+        #[arg(long, value_enum, default_value_t = SummaryFormat::Table)]
+        format: SummaryFormat,
+        /// 표준 출력 대신 이 파일에 쓴다
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+enum SummaryFormat {
+    Table,
+    Json,
+    Csv,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    Gpx,
+    Kml,
+    #[cfg(feature = "geojson")]
+    Geojson,
+}
+
+// "위도,경도". `37°33′59″N 126°58′41″E` 같은 도·분·초 표기와 지도 링크도 받는다.
+#[derive(Clone, Copy)]
+struct Point(f64, f64);
+
+impl FromStr for Point {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match Location::parse(value) {
+            Ok(location) => Ok(Point(location.latitude, location.longitude)),
+            Err(_) => Err(format!("{}: {}", pick(Language::Auto, "'위도,경도' 형식이어야 합니다", "expected 'latitude,longitude'"), value)),
+        }
+    }
+}
+
+fn parse_time(value: &str) -> Result<DateTime<Utc>, String> {
+    let time = match value.parse() {
+        Ok(millis) => DateTime::from_timestamp_millis(millis),
+        Err(_) => DateTime::parse_from_rfc3339(value).ok().map(|time| time.to_utc()),
+    };
+    time.ok_or_else(|| {
+        let message = pick(Language::Auto, "Unix epoch(ms)나 RFC 3339 시각이어야 합니다", "expected Unix epoch (ms) or an RFC 3339 time");
+        format!("{}: {}", message, value)
+    })
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(&cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(cli: &Cli) -> Result<(), Box<dyn Error>> {
+    let options = &cli.options;
+    match &cli.command {
+        Command::Locate => {
+            let location = weim(options)?.build().locate()?;
+            print_location(options, &location);
+        }
+        Command::Collect { seconds, count } => {
+            let weim = weim(options)?.build();
+            let samples = match (seconds, count) {
+                (Some(seconds), _) => weim.collect_for(Duration::from_secs(*seconds))?,
+                (None, count) => weim.collect_count(count.unwrap_or(1))?,
+            };
+            print_collection(options, &samples);
+        }
+        Command::Watch { count, motion, metrics } => {
+            let mut builder = weim(options)?;
+            // 설정 파일에서 켠 것은 그대로 둔다
+            if *metrics {
+                builder = builder.metrics(true);
+            }
+            let watch = builder.build().watch()?;
+            let mut detector = MotionDetector::new();
+            for location in watch.take(count.unwrap_or(usize::MAX)) {
+                let location = location?;
+                print_location(options, &location);
+                if *motion && let Some(change) = detector.update(&location) {
+                    print_motion(options, &change);
+                }
+            }
+        }
+        Command::Serve { history, #[cfg(feature = "encryption")] key_file, #[cfg(any(unix, windows))] socket, metrics, schedule, #[cfg(feature = "axum")] axum } => {
+            let mut builder = weim(options)?;
+            if let Some(path) = history {
+                builder = builder.history(path);
+            }
+            #[cfg(feature = "encryption")]
+            if let Some(path) = key_file {
+                builder = builder.history_key(HistoryKey::File(path.clone()));
+            }
+            #[cfg(any(unix, windows))]
+            if let Some(path) = socket {
+                builder = builder.daemon_socket(path);
+            }
+            if *metrics {
+                builder = builder.metrics(true);
+            }
+            if let Some(schedule) = schedule {
+                builder = builder.schedule(schedule.clone());
+            }
+            #[cfg(feature = "axum")]
+            if *axum {
+                builder = builder.axum(true);
+            }
+            builder.build().serve()?.wait()?;
+        }
+        Command::Service { command } => match command {
+            ServiceCommand::Install { target, log, config, dry_run, args } => {
+                let service = Service { log: log.clone(), config: config.clone(), args: args.clone(), ..service_of(options, target) };
+                service::install(&service, *dry_run)?;
+            }
+            ServiceCommand::Uninstall { target } => service::uninstall(&service_of(options, target))?,
+            ServiceCommand::Status { target } => service::status(&service_of(options, target))?,
+            #[cfg(windows)]
+            ServiceCommand::Run { name, dir, log, config, args } => {
+                let service = Service { name: name.clone(), system: true, log: log.clone(), config: config.clone(), args: args.clone(), language: options.language() };
+                service::run(service, dir.clone())?;
+            }
+        },
+        Command::History { command: HistoryCommand::Export { database, #[cfg(feature = "encryption")] key_file, output, format, from, to, device, split_gap, simplify } } => {
+            let mut query = HistoryQuery::new();
+            if let Some(from) = from {
+                query = query.since(*from);
+            }
+            if let Some(to) = to {
+                query = query.until(*to);
+            }
+            if let Some(device) = device {
+                query = query.device(DeviceId::new(device.as_str()));
+            }
+            let history = open_history(database, #[cfg(feature = "encryption")] key_file.as_deref())?;
+            export(options, &history, &query, output, *format, Duration::from_secs(split_gap * 60), simplify.unwrap_or(0.0))?;
+        }
+        Command::History {
+            command:
+                HistoryCommand::Purge { database, #[cfg(feature = "encryption")] key_file, before, max_age, max_rows, device, export: output, format },
+        } => {
+            let history = open_history(database, #[cfg(feature = "encryption")] key_file.as_deref())?;
+            let mut retention = Retention::new();
+            if let Some(days) = max_age {
+                retention = retention.max_age(Duration::from_secs(days * 24 * 60 * 60));
+            }
+            if let Some(rows) = max_rows {
+                retention = retention.max_rows(*rows);
+            }
+            // 두 기준 가운데 더 늦은 시각 이전을 모두 지운다
+            let cutoff = history.retention_cutoff(&retention)?.max(*before);
+            let Some(cutoff) = cutoff else {
+                if !options.quiet {
+                    println!("{}", pick(options.language(), "🧹 지울 위치가 없습니다.", "🧹 Nothing to purge."));
+                }
+                return Ok(());
+            };
+            let mut query = HistoryQuery::new().until(cutoff);
+            if let Some(device) = device {
+                query = query.device(DeviceId::new(device.as_str()));
+            }
+            if let Some(output) = output {
+                // 지울 위치를 남겨 두는 것이라 줄이지 않는다
+                export(options, &history, &query, output, *format, Duration::from_secs(10 * 60), 0.0)?;
+            }
+            let count = history.delete(&query)?;
+            if !options.quiet {
+                match options.language().resolve() {
+                    Language::English => println!("🧹 Purged {} locations.", count),
+                    _ => println!("🧹 위치 {}개를 지웠습니다.", count),
+                }
+            }
+        }
+        Command::History {
+            command:
+                HistoryCommand::Trips {
+                    database,
+                    #[cfg(feature = "encryption")]
+                    key_file,
+                    from,
+                    to,
+                    device,
+                    stop_radius,
+                    min_stop,
+                    #[cfg(feature = "geocoding")]
+                    addresses,
+                    format,
+                    output,
+                },
+        } => {
+            let mut query = HistoryQuery::new();
+            if let Some(from) = from {
+                query = query.since(*from);
+            }
+            if let Some(to) = to {
+                query = query.until(*to);
+            }
+            if let Some(device) = device {
+                query = query.device(DeviceId::new(device.as_str()));
+            }
+            let history = open_history(database, #[cfg(feature = "encryption")] key_file.as_deref())?;
+            let detector = TripDetector::new().stop_radius(*stop_radius).min_stop(Duration::from_secs(min_stop * 60));
+            #[allow(unused_mut)]
+            let mut found = history.trips(&query, &detector)?;
+            #[cfg(feature = "geocoding")]
+            if *addresses {
+                trips::annotate(&mut found, &weim::geocode::Nominatim::new());
+            }
+            let mut out = summary_output(output.as_deref())?;
+            match format {
+                SummaryFormat::Table => print_trips(options, &mut out, &found)?,
+                SummaryFormat::Json => {
+                    trips::write_json(&mut out, &found)?;
+                    writeln!(out)?;
+                }
+                SummaryFormat::Csv => trips::write_csv(&mut out, &found)?,
+            }
+            out.flush()?;
+        }
+        #[cfg(feature = "heatmap")]
+        Command::History {
+            command:
+                HistoryCommand::Heatmap { database, #[cfg(feature = "encryption")] key_file, output, from, to, device, cell, blur, ramp, count },
+        } => {
+            let mut query = HistoryQuery::new();
+            if let Some(from) = from {
+                query = query.since(*from);
+            }
+            if let Some(to) = to {
+                query = query.until(*to);
+            }
+            if let Some(device) = device {
+                query = query.device(DeviceId::new(device.as_str()));
+            }
+            let history = open_history(database, #[cfg(feature = "encryption")] key_file.as_deref())?;
+            let mut heatmap = Heatmap::new().cell_size(*cell).blur(*blur).time_weighted(!count);
+            if let Some(ramp) = ramp {
+                heatmap = heatmap.ramp(ramp.clone());
+            }
+            let json = output.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("json"));
+            if json {
+                let points = history.heatmap_points(&query, &heatmap)?;
+                std::fs::write(output, serde_json::to_string(&points)?)?;
+            } else {
+                let image = history.heatmap(&query, &heatmap)?;
+                std::fs::write(output, &image.png)?;
+                if !options.quiet {
+                    // `L.imageOverlay(url, [[south, west], [north, east]])`로 겹친다
+                    println!("🔥 {}×{} [[{}, {}], [{}, {}]]", image.width, image.height, image.south, image.west, image.north, image.east);
+                }
+            }
+            if !options.quiet {
+                match options.language().resolve() {
+                    Language::English => println!("💾 Exported to {}.", output.display()),
+                    _ => println!("💾 {} 에 내보냈습니다.", output.display()),
+                }
+            }
+        }
+        Command::History {
+            command:
+                HistoryCommand::Places {
+                    database,
+                    #[cfg(feature = "encryption")]
+                    key_file,
+                    from,
+                    to,
+                    device,
+                    min_stay,
+                    distance,
+                    min_visits,
+                    format,
+                    output,
+                },
+        } => {
+            let mut query = HistoryQuery::new();
+            if let Some(from) = from {
+                query = query.since(*from);
+            }
+            if let Some(to) = to {
+                query = query.until(*to);
+            }
+            if let Some(device) = device {
+                query = query.device(DeviceId::new(device.as_str()));
+            }
+            let history = open_history(database, #[cfg(feature = "encryption")] key_file.as_deref())?;
+            let detector = PlaceDetector::new().min_stay(Duration::from_secs(min_stay * 60)).distance(*distance).min_visits(*min_visits);
+            let found = history.places(&query, &detector)?;
+            let mut out = summary_output(output.as_deref())?;
+            match format {
+                SummaryFormat::Table => print_places(options, &mut out, &found)?,
+                SummaryFormat::Json => {
+                    places::write_json(&mut out, &found)?;
+                    writeln!(out)?;
+                }
+                SummaryFormat::Csv => places::write_csv(&mut out, &found)?,
+            }
+            out.flush()?;
+        }
+        Command::Distance { from, to, vincenty } => {
+            let (from, to) = ((from.0, from.1), (to.0, to.1));
+            let distance = if *vincenty { geo::vincenty(from, to) } else { Some(geo::haversine(from, to)) };
+            let Some(distance) = distance else {
+                let message = pick(
+                    options.language(),
+                    "Vincenty 공식이 수렴하지 않았습니다 (거의 정반대 지점)",
+                    "the Vincenty formula did not converge (nearly antipodal points)",
+                );
+                return Err(message.into());
+            };
+            let bearing = geo::initial_bearing(from, to);
+            if options.json {
+                println!("{}", serde_json::json!({ "distance": distance, "bearing": bearing }));
+            } else if options.quiet {
+                println!("{:.3}", distance);
+            } else {
+                let (distance_label, bearing_label) = (pick(options.language(), "거리", "distance"), pick(options.language(), "방위각", "bearing"));
+                println!("📏 {}: {:.3}m, {}: {:.1}°", distance_label, distance, bearing_label, bearing);
+            }
+        }
+    }
+    Ok(())
+}
+
+// `weim.toml`과 `WEIM_*` 환경 변수 위에 명령줄 옵션을 얹는다
+fn weim(options: &Options) -> Result<weim::WeimBuilder, weim::WeimError> {
+    let output = if options.json { Output::Json } else { Output::Text };
+    let verbosity = match (options.quiet, options.verbose) {
+        (true, _) => Verbosity::Quiet,
+        (_, true) => Verbosity::Debug,
+        _ => Verbosity::Info,
+    };
+    let mut builder = Weim::configured()?.output(output).verbosity(verbosity);
+    if let Some(port) = options.port {
+        builder = builder.port(port);
+    }
+    if options.no_browser {
+        builder = builder.open_browser(false);
+    }
+    if options.lan {
+        builder = builder.lan(true);
+    }
+    for range in &options.allow {
+        builder = builder.allow(*range);
+    }
+    for range in &options.deny {
+        builder = builder.deny(*range);
+    }
+    if let Some(per_second) = options.rate_limit {
+        builder = builder.rate_limit((per_second > 0.0).then(|| RateLimit::new(per_second)));
+    }
+    if !options.allowed_origin.is_empty() {
+        builder = builder.allowed_origins(options.allowed_origin.iter().cloned());
+    }
+    if let Some(workers) = options.workers {
+        builder = builder.workers(workers);
+    }
+    if let Some(path) = &options.access_log {
+        builder = builder.access_log(path);
+    }
+    if let Some(lang) = options.lang {
+        builder = builder.language(lang.into());
+    }
+    if let Some(seconds) = options.timeout {
+        builder = builder.timeout(Duration::from_secs(seconds));
+    }
+    if let Some(meters) = options.accuracy {
+        builder = builder.accuracy_threshold(meters);
+    }
+    if let Some(meters) = options.coarse {
+        builder = builder.coarse_location(Coarsening::Grid(meters));
+    }
+    Ok(builder)
+}
+
+fn service_of(options: &Options, target: &ServiceTarget) -> Service {
+    Service { name: target.name.clone(), system: target.system, log: None, config: None, args: Vec::new(), language: options.language() }
+}
+
+// `--key-file`이나 `WEIM_HISTORY_PASSPHRASE`가 있으면 암호화한 기록으로 연다
+fn open_history(database: &std::path::Path, #[cfg(feature = "encryption")] key_file: Option<&std::path::Path>) -> Result<History, Box<dyn Error>> {
+    #[cfg(feature = "encryption")]
+    {
+        let key = key_file.map(|path| HistoryKey::File(path.to_path_buf())).or_else(|| std::env::var(PASSPHRASE_ENV).ok().map(HistoryKey::Passphrase));
+        if let Some(key) = key {
+            return Ok(History::open_encrypted(database, &key)?);
+        }
+    }
+    Ok(History::open(database)?)
+}
+
+// 형식을 정하지 않았으면 확장자로 고른다
+fn export(options: &Options, history: &History, query: &HistoryQuery, output: &std::path::Path, format: Option<Format>, split_gap: Duration, tolerance: f64) -> Result<(), Box<dyn Error>> {
+    let format = match format.or_else(|| format_of(output)) {
+        Some(format) => format,
+        None => {
+            let message = pick(options.language(), "의 형식을 알 수 없습니다. --format으로 정해 주세요", ": unknown format, choose one with --format");
+            return Err(format!("{}{}", output.display(), message).into());
+        }
+    };
+    match format {
+        Format::Gpx => history.export_gpx(output, query, split_gap, tolerance)?,
+        Format::Kml => history.export_kml(output, query, tolerance)?,
+        #[cfg(feature = "geojson")]
+        Format::Geojson => history.export_geojson(output, query, tolerance)?,
+    }
+    if !options.quiet {
+        match options.language().resolve() {
+            Language::English => println!("💾 Exported to {}.", output.display()),
+            _ => println!("💾 {} 에 내보냈습니다.", output.display()),
+        }
+    }
+    Ok(())
+}
+
+fn format_of(path: &std::path::Path) -> Option<Format> {
+    match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+        "gpx" => Some(Format::Gpx),
+        "kml" => Some(Format::Kml),
+        #[cfg(feature = "geojson")]
+        "geojson" | "json" => Some(Format::Geojson),
+        _ => None,
+    }
+}
+
+// `--output`이 없으면 표준 출력
+fn summary_output(path: Option<&std::path::Path>) -> std::io::Result<Box<dyn Write>> {
+    Ok(match path {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(std::io::stdout().lock()),
+    })
+}
+
+// 장소마다 한 줄. 머문 시간이 긴 곳부터다.
+fn print_places(options: &Options, out: &mut dyn Write, found: &[Place]) -> std::io::Result<()> {
+    if found.is_empty() && !options.quiet {
+        writeln!(out, "{}", pick(options.language(), "📌 자주 머문 곳이 없습니다.", "📌 No places found."))?;
+    }
+    for place in found {
+        let hours = place.dwell().as_secs_f64() / 3600.0;
+        let location = Location { latitude: place.latitude, longitude: place.longitude, ..Location::default() };
+        let summary = match options.language().resolve() {
+            Language::English => format!("{} visits, {:.1}h in total", place.visits.len(), hours),
+            _ => format!("{}번 방문, 모두 {:.1}시간", place.visits.len(), hours),
+        };
+        writeln!(out, "📌 #{} {} (±{:.0}m): {}", place.id, location.format(CoordinateFormat::default()), place.radius, summary)?;
+    }
+    Ok(())
+}
+
+// 구간마다 한 줄. 거리는 km, 속력은 km/h로 보여 준다.
+fn print_trips(options: &Options, out: &mut dyn Write, found: &[Trip]) -> std::io::Result<()> {
+    if found.is_empty() && !options.quiet {
+        writeln!(out, "{}", pick(options.language(), "🚶 이동 구간이 없습니다.", "🚶 No trips found."))?;
+    }
+    let place = |location: &Location| location.address.clone().unwrap_or_else(|| location.format(CoordinateFormat::default()));
+    for trip in found {
+        let start = trip.start.local_timestamp();
+        let minutes = trip.duration.as_secs().div_ceil(60);
+        let (average, max) = (trip.average_speed() * 3.6, trip.max_speed * 3.6);
+        let summary = match options.language().resolve() {
+            Language::English => format!("{:.1}km in {}min, avg {:.0}km/h, max {:.0}km/h", trip.distance / 1000.0, minutes, average, max),
+            _ => format!("{:.1}km, {}분, 평균 {:.0}km/h, 최고 {:.0}km/h", trip.distance / 1000.0, minutes, average, max),
+        };
+        writeln!(out, "🚗 {} [{}] {} → {}: {}", start.format("%Y-%m-%d %H:%M"), trip.device, place(&trip.start), place(&trip.end), summary)?;
+    }
+    Ok(())
+}
+
+fn print_motion(options: &Options, change: &MotionChange) {
+    if options.json {
+        println!("{}", serde_json::json!({ "motion": change.to, "speed": change.speed, "timestamp": change.location.timestamp }));
+    } else if options.quiet {
+        println!("{:?}", change.to);
+    } else {
+        let icon = match change.to {
+            MotionState::Stationary => "🧍",
+            MotionState::Walking => "🚶",
+            MotionState::Driving => "🚗",
+        };
+        println!("{} {} ({:.1}km/h)", icon, change.to, change.speed * 3.6);
+    }
+}
+
+fn print_collection(options: &Options, samples: &SampleSet) {
+    let (Some((latitude, longitude)), Some(spread), Some(best)) = (samples.centroid(), samples.spread(), samples.best_accuracy()) else {
+        return;
+    };
+    let outliers = samples.outliers().count();
+    if options.json {
+        let summary = serde_json::json!({
+            "samples": samples.samples.len(),
+            "outliers": outliers,
+            "centroid": { "latitude": latitude, "longitude": longitude },
+            "spread": spread,
+            "best_accuracy": best,
+            "span": samples.span().as_secs_f64(),
+            "weighted_average": samples.weighted_average(),
+        });
+        println!("{}", summary);
+    } else if options.quiet {
+        println!("{},{}", latitude, longitude);
+    } else {
+        let language = options.language();
+        let seconds = samples.span().as_secs_f64();
+        match language.resolve() {
+            Language::English => println!("📐 {} fixes over {:.0}s ({} outliers left out)", samples.samples.len(), seconds, outliers),
+            _ => println!("📐 {:.0}초 동안 {}개 측정 (이상치 {}개 제외)", seconds, samples.samples.len(), outliers),
+        }
+        let centroid = Location { latitude, longitude, ..Location::default() };
+        println!("  {}: {}", pick(language, "중심", "centroid"), centroid.format(CoordinateFormat::default()));
+        println!("  {}: {:.2}m", pick(language, "퍼짐(DRMS)", "spread (DRMS)"), spread);
+        println!("  {}: {:.2}m", pick(language, "최고 정확도", "best accuracy"), best);
+    }
+}
+
+fn print_location(options: &Options, location: &Location) {
+    // JSON 모드에서는 라이브러리가 위치마다 한 줄씩 이미 썼다
+    if options.json {
+        return;
+    }
+    if options.quiet {
+        println!("{},{}", location.latitude, location.longitude);
+    } else {
+        println!("📍 {} (±{:.1}m)", location.format(CoordinateFormat::default()), location.accuracy);
+        #[cfg(feature = "static-map")]
+        if options.map {
+            match location.terminal_map(MAP_COLUMNS, MAP_ROWS) {
+                Ok(map) => print!("{}\n© OpenStreetMap contributors\n", map),
+                Err(e) => eprintln!("⚠️ {}: {}", pick(options.language(), "지도를 그리지 못했습니다", "could not draw the map"), e),
+            }
+        }
+    }
+}
@@ -0,0 +1,177 @@
+//! 지도 서비스 링크
+//!
+//! 위치를 Google, OpenStreetMap, Apple, 네이버, 카카오 지도에서 여는 URL을 만든다.
+
+use std::fmt::Write;
+
+use serde::{Deserialize, Serialize};
+
+use crate::query::param;
+use crate::geo::Coordinate;
+use crate::i18n::{pick, tr};
+use crate::notation;
+use crate::{Location, WeimError};
+
+/// 기본 확대 수준. 건물 몇 채가 보이는 정도다.
+pub const DEFAULT_ZOOM: u8 = 16;
+
+/// 링크를 만들 수 있는 지도 서비스. 설정 파일에서는 `google`, `openstreetmap`(`osm`), `apple`, `naver`, `kakao`로 쓴다.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MapProvider {
+    Google,
+    #[serde(alias = "osm")]
+    OpenStreetMap,
+    Apple,
+    Naver,
+    Kakao,
+}
+
+impl MapProvider {
+    /// 요약에 찍는 이름
+    pub fn name(&self) -> &'static str {
+        match self {
+            MapProvider::Google => "Google Maps",
+            MapProvider::OpenStreetMap => "OpenStreetMap",
+            MapProvider::Apple => "Apple Maps",
+            MapProvider::Naver => pick("네이버 지도", "Naver Map"),
+            MapProvider::Kakao => pick("카카오맵", "Kakao Map"),
+        }
+    }
+}
+
+/// 확대 수준과 표식 이름을 정해서 지도 링크를 만든다.
+///
+/// 서비스가 받지 않는 값은 빠진다. OpenStreetMap과 네이버는 표식 이름을, 카카오는 확대 수준을 받지 않는다.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MapUrl {
+    provider: MapProvider,
+    zoom: u8,
+    label: Option<String>,
+}
+
+impl MapUrl {
+    pub fn new(provider: MapProvider) -> Self {
+        MapUrl { provider, zoom: DEFAULT_ZOOM, label: None }
+    }
+
+    /// 확대 수준 1~20 (기본값 16)
+    pub fn zoom(mut self, zoom: u8) -> Self {
+        self.zoom = zoom.clamp(1, 20);
+        self
+    }
+
+    /// 표식에 붙일 이름 (기본값 없음)
+    pub fn label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    /// 좌표를 가리키는 URL
+    pub fn url(&self, coordinate: impl Coordinate) -> String {
+        let (latitude, longitude, zoom) = (coordinate.latitude(), coordinate.longitude(), self.zoom);
+        // 소수점 아래 7자리면 1cm 정도라 충분하다
+        let (lat, lon) = (format!("{:.7}", latitude), format!("{:.7}", longitude));
+        let label = self.label.as_deref().map(encode);
+        match self.provider {
+            MapProvider::Google => match label {
+                Some(label) => format!("https://www.google.com/maps?q={},{}({})&z={}", lat, lon, label, zoom),
+                None => format!("https://www.google.com/maps?q={},{}&z={}", lat, lon, zoom),
+            },
+            MapProvider::OpenStreetMap => format!("https://www.openstreetmap.org/?mlat={0}&mlon={1}#map={2}/{0}/{1}", lat, lon, zoom),
+            MapProvider::Apple => {
+                format!("https://maps.apple.com/?ll={},{}&q={}&z={}", lat, lon, label.unwrap_or_else(|| encode(pick("위치", "Location"))), zoom)
+            }
+            MapProvider::Naver => format!("https://map.naver.com/p?c={},{},{},0,0,0,dh", lon, lat, zoom),
+            // 카카오 링크는 이름이 꼭 있어야 한다
+            MapProvider::Kakao => {
+                format!("https://map.kakao.com/link/map/{},{},{}", label.unwrap_or_else(|| encode(pick("위치", "Location"))), lat, lon)
+            }
+        }
+    }
+}
+
+// URL 안에 그대로 넣을 수 있게 RFC 3986의 예약되지 않은 글자 말고는 모두 %로 바꾼다
+fn encode(text: &str) -> String {
+    let mut encoded = String::with_capacity(text.len());
+    for byte in text.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => encoded.push(byte as char),
+            _ => {
+                write!(encoded, "%{:02X}", byte).ok();
+            }
+        }
+    }
+    encoded
+}
+
+/// 지도 링크가 가리키는 `(위도, 경도)`
+///
+/// 이 모듈이 만드는 링크와 Google 지도의 장소(`!3d…!4d…`), 화면 중심(`@위도,경도,16z`), 검색(`?q=`) 링크를 읽는다.
+/// 주소로 검색하는 링크나 짧은 공유 링크(`maps.app.goo.gl`)에는 좌표가 없어 읽지 못한다.
+pub fn parse_url(url: &str) -> Result<(f64, f64), WeimError> {
+    let (rest, fragment) = url.split_once('#').unwrap_or((url, ""));
+    let (path, query) = rest.split_once('?').unwrap_or((rest, ""));
+    // 장소 핀, 검색어, 화면 중심 순으로 믿을 만하다
+    let place = || {
+        let (_, latitude) = path.split_once("!3d")?;
+        let (latitude, longitude) = latitude.split_once("!4d")?;
+        let longitude = longitude.split('!').next()?;
+        Some((latitude.parse().ok()?, longitude.parse().ok()?))
+    };
+    let search = || {
+        ["q", "query", "ll", "center", "destination", "daddr"].iter().find_map(|name| {
+            let value = param(query, name)?;
+            // Google은 `q=loc:37.5,127(이름)`처럼 이름을 붙이기도 한다
+            let value = value.strip_prefix("loc:").unwrap_or(&value);
+            notation::parse(value.split('(').next()?).ok()
+        })
+    };
+    let marker = || Some((param(query, "mlat")?.parse().ok()?, param(query, "mlon")?.parse().ok()?));
+    // 네이버는 `c=경도,위도,확대 수준,…`
+    let naver = || {
+        let value = param(query, "c").filter(|_| path.contains("naver."))?;
+        let mut parts = value.split(',').map(|part| part.parse::<f64>().ok());
+        let (longitude, latitude) = (parts.next()??, parts.next()??);
+        Some((latitude, longitude))
+    };
+    // 카카오는 `/link/map/이름,위도,경도`
+    let kakao = || {
+        let (_, link) = path.split_once("/link/").filter(|_| path.contains("kakao."))?;
+        let mut parts = link.rsplit(',').map(|part| part.parse::<f64>().ok());
+        let (longitude, latitude) = (parts.next()??, parts.next()??);
+        Some((latitude, longitude))
+    };
+    let view = || {
+        let (_, center) = path.split_once('@')?;
+        let mut parts = center.split(',').map(|part| part.parse::<f64>().ok());
+        Some((parts.next()??, parts.next()??))
+    };
+    // OpenStreetMap은 `#map=확대 수준/위도/경도`
+    let fragment = || {
+        let mut parts = fragment.strip_prefix("map=")?.split('/').skip(1).map(|part| part.parse::<f64>().ok());
+        Some((parts.next()??, parts.next()??))
+    };
+    place()
+        .or_else(search)
+        .or_else(marker)
+        .or_else(naver)
+        .or_else(kakao)
+        .or_else(view)
+        .or_else(fragment)
+        .filter(|(latitude, longitude): &(f64, f64)| latitude.abs() <= 90.0 && longitude.abs() <= 180.0)
+        .ok_or_else(|| WeimError::InvalidPayload(tr!("지도 링크에서 좌표를 찾지 못했습니다: {}", "no coordinates in map link: {}", url)))
+}
+
+impl Location {
+    /// 이 위치를 `provider` 지도에서 여는 URL. 확대 수준이나 표식 이름을 정하려면 `MapUrl`을 쓴다.
+    pub fn map_url(&self, provider: MapProvider) -> String {
+        MapUrl::new(provider).url(self)
+    }
+
+    /// `map::parse_url()`로 읽은 지도 링크의 위치. 정확도는 알 수 없어 0으로 둔다.
+    pub fn from_map_url(url: &str) -> Result<Location, WeimError> {
+        let (latitude, longitude) = parse_url(url)?;
+        Ok(Location { latitude, longitude, ..Location::default() })
+    }
+}
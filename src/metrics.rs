@@ -0,0 +1,159 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use crate::handler::Reply;
+use crate::locator::Config;
+#[cfg(feature = "webhook")]
+use crate::Webhook;
+use crate::{DeviceId, Location};
+
+// 페이지를 받아 간 뒤 첫 위치가 오기까지 걸린 시간(초)의 히스토그램 구간
+const TIME_TO_FIX_BUCKETS: [f64; 9] = [0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 120.0, 300.0];
+
+#[derive(Default)]
+struct Counters {
+    devices: BTreeMap<DeviceId, DeviceStats>,
+    errors: u64,
+    rejected: BTreeMap<&'static str, u64>,
+    // 기기별로 페이지를 받아 간 시각. 그 기기의 첫 위치가 오면 지운다
+    served: HashMap<DeviceId, Instant>,
+    time_to_fix: Histogram,
+}
+
+struct DeviceStats {
+    fixes: u64,
+    accuracy: f64,
+    received: Instant,
+}
+
+#[derive(Default)]
+struct Histogram {
+    // 구간마다 그 값 이하인 관측 수. 누적은 출력할 때 한다
+    buckets: [u64; TIME_TO_FIX_BUCKETS.len()],
+    sum: f64,
+    count: u64,
+}
+
+impl Histogram {
+    fn observe(&mut self, value: f64) {
+        if let Some(index) = TIME_TO_FIX_BUCKETS.iter().position(|bound| value <= *bound) {
+            self.buckets[index] += 1;
+        }
+        self.sum += value;
+        self.count += 1;
+    }
+}
+
+/// 계속 받는 모드의 `/metrics`. Prometheus 텍스트 형식으로 답한다.
+///
+/// - `weim_fixes_total{device}`: 받은 위치 수
+/// - `weim_errors_total`: 브라우저가 알려 온 실패와 읽을 수 없는 요청 수
+/// - `weim_rejected_requests_total{reason}`: 세션 토큰이 틀리거나(`token`), 허용하지 않은 주소이거나(`ip`), 너무 자주 보내서(`rate_limit`) 거부한 요청 수
+/// - `weim_webhook_failures_total`, `weim_webhook_pending`: 실패한 웹훅 전송 시도와 남은 전송 수 (`webhook` 기능 필요)
+/// - `weim_last_accuracy_meters{device}`, `weim_seconds_since_last_fix{device}`: 마지막 위치의 정확도와 지난 시간
+/// - `weim_time_to_fix_seconds`: 페이지를 받아 간 뒤 그 기기의 첫 위치가 오기까지 걸린 시간
+pub(crate) struct Metrics {
+    counters: Mutex<Counters>,
+    #[cfg(feature = "webhook")]
+    webhooks: Vec<Webhook>,
+}
+
+impl Metrics {
+    #[cfg_attr(not(feature = "webhook"), allow(unused_variables))]
+    pub(crate) fn new(config: &Config) -> Self {
+        Metrics {
+            counters: Mutex::default(),
+            #[cfg(feature = "webhook")]
+            webhooks: config.webhooks.clone(),
+        }
+    }
+
+    /// 페이지를 받아 간 때. 같은 기기가 위치 없이 다시 받아 가면 새로 잰다
+    pub(crate) fn served(&self, device: DeviceId) {
+        self.counters.lock().unwrap().served.insert(device, Instant::now());
+    }
+
+    pub(crate) fn fix(&self, device: &DeviceId, location: &Location) {
+        let mut counters = self.counters.lock().unwrap();
+        if let Some(served) = counters.served.remove(device) {
+            counters.time_to_fix.observe(served.elapsed().as_secs_f64());
+        }
+        let stats = counters
+            .devices
+            .entry(device.clone())
+            .or_insert(DeviceStats { fixes: 0, accuracy: location.accuracy, received: Instant::now() });
+        stats.fixes += 1;
+        stats.accuracy = location.accuracy;
+        stats.received = Instant::now();
+    }
+
+    pub(crate) fn error(&self) {
+        self.counters.lock().unwrap().errors += 1;
+    }
+
+    /// `reason`은 `token`, `ip`, `rate_limit` 중 하나
+    pub(crate) fn rejected(&self, reason: &'static str) {
+        *self.counters.lock().unwrap().rejected.entry(reason).or_default() += 1;
+    }
+
+    pub(crate) fn reply(&self) -> Reply {
+        Reply::new(200, self.render()).header("Content-Type", "text/plain; version=0.0.4; charset=utf-8")
+    }
+
+    fn render(&self) -> String {
+        let counters = self.counters.lock().unwrap();
+        let mut out = String::new();
+
+        family(&mut out, "weim_fixes_total", "counter", "Locations received.");
+        for (device, stats) in &counters.devices {
+            writeln!(out, "weim_fixes_total{{device=\"{}\"}} {}", label(device.as_str()), stats.fixes).ok();
+        }
+        family(&mut out, "weim_errors_total", "counter", "Errors reported by browsers or unreadable requests.");
+        writeln!(out, "weim_errors_total {}", counters.errors).ok();
+        family(&mut out, "weim_rejected_requests_total", "counter", "Requests rejected for a wrong session token, a blocked address or the rate limit.");
+        for (reason, count) in &counters.rejected {
+            writeln!(out, "weim_rejected_requests_total{{reason=\"{}\"}} {}", reason, count).ok();
+        }
+
+        #[cfg(feature = "webhook")]
+        {
+            family(&mut out, "weim_webhook_failures_total", "counter", "Failed webhook delivery attempts.");
+            writeln!(out, "weim_webhook_failures_total {}", self.webhooks.iter().map(Webhook::failures).sum::<u64>()).ok();
+            family(&mut out, "weim_webhook_pending", "gauge", "Webhook deliveries not yet sent.");
+            writeln!(out, "weim_webhook_pending {}", self.webhooks.iter().map(Webhook::pending).sum::<usize>()).ok();
+        }
+
+        family(&mut out, "weim_last_accuracy_meters", "gauge", "Accuracy of the last location.");
+        for (device, stats) in &counters.devices {
+            writeln!(out, "weim_last_accuracy_meters{{device=\"{}\"}} {}", label(device.as_str()), stats.accuracy).ok();
+        }
+        family(&mut out, "weim_seconds_since_last_fix", "gauge", "Seconds since the last location was received.");
+        for (device, stats) in &counters.devices {
+            writeln!(out, "weim_seconds_since_last_fix{{device=\"{}\"}} {:.3}", label(device.as_str()), stats.received.elapsed().as_secs_f64()).ok();
+        }
+
+        let histogram = &counters.time_to_fix;
+        family(&mut out, "weim_time_to_fix_seconds", "histogram", "Time from serving the page to the first location from that device.");
+        let mut cumulative = 0;
+        for (bound, count) in TIME_TO_FIX_BUCKETS.iter().zip(histogram.buckets) {
+            cumulative += count;
+            writeln!(out, "weim_time_to_fix_seconds_bucket{{le=\"{}\"}} {}", bound, cumulative).ok();
+        }
+        writeln!(out, "weim_time_to_fix_seconds_bucket{{le=\"+Inf\"}} {}", histogram.count).ok();
+        writeln!(out, "weim_time_to_fix_seconds_sum {:.3}", histogram.sum).ok();
+        writeln!(out, "weim_time_to_fix_seconds_count {}", histogram.count).ok();
+        out
+    }
+}
+
+fn family(out: &mut String, name: &str, kind: &str, help: &str) {
+    writeln!(out, "# HELP {} {}", name, help).ok();
+    writeln!(out, "# TYPE {} {}", name, kind).ok();
+}
+
+// 라벨 값에서는 역슬래시, 큰따옴표, 줄바꿈을 이스케이프한다
+fn label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
@@ -0,0 +1,189 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::time::Duration;
+use chrono::TimeDelta;
+use serde::{Deserialize, Serialize};
+
+use crate::geo::haversine;
+use crate::i18n::pick;
+use crate::{Location, WeimError};
+
+// 느린 상태로 돌아가려면 기준 속력의 이 비율 아래로 떨어져야 한다. 기준 근처에서 상태가 번갈아 바뀌지 않게 한다.
+const HYSTERESIS: f64 = 0.75;
+
+/// 지금 움직이는 모양
+///
+/// 느린 것부터 차례로 놓여 있어 크기를 비교할 수 있다.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum MotionState {
+    Stationary,
+    Walking,
+    Driving,
+}
+
+impl fmt::Display for MotionState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MotionState::Stationary => f.write_str(pick("멈춤", "stationary")),
+            MotionState::Walking => f.write_str(pick("걷는 중", "walking")),
+            MotionState::Driving => f.write_str(pick("차로 이동 중", "driving")),
+        }
+    }
+}
+
+/// 움직이는 모양이 바뀐 사건
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MotionChange {
+    /// 처음 판단한 것이면 `None`
+    pub from: Option<MotionState>,
+    pub to: MotionState,
+    /// 창 안에서 잰 속력 (m/s)
+    pub speed: f64,
+    /// 바뀐 것을 알아챈 위치
+    pub location: Location,
+}
+
+/// `WithMotion`이 돌려주는 것. 위치마다 `Fix`가 나오고, 상태가 바뀌면 바로 뒤에 `Change`가 나온다.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum MotionEvent {
+    Fix(Location),
+    Change(MotionChange),
+}
+
+/// 최근 위치들의 속력으로 멈춤, 걷기, 차로 이동을 가른다.
+///
+/// 창(`window`) 안 첫 위치와 마지막 위치 사이의 거리에서 두 위치의 정확도만큼을 빼고 시간으로 나눈다.
+/// 브라우저가 속력을 알려 주면 창 안 속력의 평균을 대신 쓴다. 흔들리는 위치가 움직임으로 잡히지 않게 하려는 것이다.
+/// 느린 상태로 돌아갈 때는 기준 속력의 75% 아래로 떨어져야 한다.
+#[derive(Debug, Clone)]
+pub struct MotionDetector {
+    window: Duration,
+    walking_speed: f64,
+    driving_speed: f64,
+    recent: VecDeque<Location>,
+    state: Option<MotionState>,
+}
+
+impl Default for MotionDetector {
+    fn default() -> Self {
+        MotionDetector {
+            window: Duration::from_secs(60),
+            walking_speed: 0.5,
+            driving_speed: 4.0,
+            recent: VecDeque::new(),
+            state: None,
+        }
+    }
+}
+
+impl MotionDetector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 속력을 잴 최근 기간 (기본값 60초). 길수록 늦게 바뀌지만 덜 흔들린다.
+    pub fn window(mut self, window: Duration) -> Self {
+        self.window = window;
+        self
+    }
+
+    /// 이보다 빠르면(m/s) 걷는 것으로 본다 (기본값 0.5)
+    pub fn walking_speed(mut self, speed: f64) -> Self {
+        self.walking_speed = speed;
+        self
+    }
+
+    /// 이보다 빠르면(m/s) 차로 움직이는 것으로 본다 (기본값 4.0 = 14.4km/h)
+    pub fn driving_speed(mut self, speed: f64) -> Self {
+        self.driving_speed = speed;
+        self
+    }
+
+    /// 지금 판단한 상태. 위치를 둘 이상 받기 전에는 `None`
+    pub fn state(&self) -> Option<MotionState> {
+        self.state
+    }
+
+    /// 새 위치를 넣고 상태가 바뀌었으면 그 사건을 돌려준다.
+    pub fn update(&mut self, location: &Location) -> Option<MotionChange> {
+        // 시각이 거꾸로 들어온 위치는 거리를 잴 수 없다
+        if self.recent.back().is_some_and(|last| location.timestamp < last.timestamp) {
+            return None;
+        }
+        self.recent.push_back(location.clone());
+        let window = TimeDelta::from_std(self.window).unwrap_or(TimeDelta::MAX);
+        while self.recent.len() > 2 && location.timestamp - self.recent[1].timestamp >= window {
+            self.recent.pop_front();
+        }
+
+        let speed = self.speed()?;
+        // 지금 상태 이상의 기준은 그대로, 아래 기준은 낮춰서 비교한다
+        let threshold = |limit: f64, state: MotionState| match self.state {
+            Some(current) if current >= state => limit * HYSTERESIS,
+            _ => limit,
+        };
+        let state = if speed >= threshold(self.driving_speed, MotionState::Driving) {
+            MotionState::Driving
+        } else if speed >= threshold(self.walking_speed, MotionState::Walking) {
+            MotionState::Walking
+        } else {
+            MotionState::Stationary
+        };
+        if self.state == Some(state) {
+            return None;
+        }
+        let from = self.state.replace(state);
+        Some(MotionChange { from, to: state, speed, location: location.clone() })
+    }
+
+    // 창 안의 속력 (m/s). 시간이 흐르지 않았으면 `None`
+    fn speed(&self) -> Option<f64> {
+        let (first, last) = (self.recent.front()?, self.recent.back()?);
+        let seconds = (last.timestamp - first.timestamp).as_seconds_f64();
+        if seconds <= 0.0 {
+            return None;
+        }
+        let reported: Vec<f64> = self.recent.iter().filter_map(|location| location.speed).collect();
+        if !reported.is_empty() {
+            return Some(reported.iter().sum::<f64>() / reported.len() as f64);
+        }
+        let noise = (first.accuracy.max(0.0) + last.accuracy.max(0.0)) / 2.0;
+        Some((haversine(first, last) - noise).max(0.0) / seconds)
+    }
+}
+
+/// 위치 스트림에 움직임 상태가 바뀐 사건을 끼워 넣는 반복자
+#[derive(Debug)]
+pub struct WithMotion<I> {
+    inner: I,
+    detector: MotionDetector,
+    pending: Option<MotionChange>,
+}
+
+impl<I> WithMotion<I> {
+    /// 어떤 위치 스트림에든 움직임 판단을 붙인다.
+    pub fn new(inner: I, detector: MotionDetector) -> Self {
+        WithMotion { inner, detector, pending: None }
+    }
+
+    pub fn detector(&self) -> &MotionDetector {
+        &self.detector
+    }
+}
+
+impl<I> Iterator for WithMotion<I>
+where
+    I: Iterator<Item = Result<Location, WeimError>>,
+{
+    type Item = Result<MotionEvent, WeimError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(change) = self.pending.take() {
+            return Some(Ok(MotionEvent::Change(change)));
+        }
+        Some(self.inner.next()?.map(|location| {
+            self.pending = self.detector.update(&location);
+            MotionEvent::Fix(location)
+        }))
+    }
+}
@@ -0,0 +1,211 @@
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use rumqttc::{Client, MqttOptions, QoS, TlsConfiguration, Transport};
+use serde::Serialize;
+
+use crate::console::{info, warning};
+use crate::i18n::tr;
+use crate::{DeviceId, Location};
+
+const DEFAULT_PORT: u16 = 1883;
+const DEFAULT_TLS_PORT: u16 = 8883;
+const DEFAULT_TOPIC: &str = "owntracks/weim/{device}";
+const KEEP_ALIVE: Duration = Duration::from_secs(30);
+// 연결이 끊긴 동안 쌓아 둘 메시지 수
+const QUEUE_CAPACITY: usize = 100;
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+const METERS_PER_SECOND_TO_KMH: f64 = 3.6;
+
+// OwnTracks 위치 메시지 (https://owntracks.org/booklet/tech/json/)
+#[derive(Debug, Serialize)]
+struct OwnTracks<'a> {
+    #[serde(rename = "_type")]
+    kind: &'static str,
+    lat: f64,
+    lon: f64,
+    acc: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    alt: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    vac: Option<i64>,
+    // km/h
+    #[serde(skip_serializing_if = "Option::is_none")]
+    vel: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cog: Option<i64>,
+    // 초 단위
+    tst: i64,
+    tid: &'a str,
+}
+
+impl<'a> OwnTracks<'a> {
+    fn new(location: &Location, tracker_id: &'a str) -> Self {
+        OwnTracks {
+            kind: "location",
+            lat: location.latitude,
+            lon: location.longitude,
+            acc: location.accuracy.round() as i64,
+            alt: location.altitude.map(|altitude| altitude.round() as i64),
+            vac: location.altitude_accuracy.map(|accuracy| accuracy.round() as i64),
+            vel: location.speed.map(|speed| (speed * METERS_PER_SECOND_TO_KMH).round() as i64),
+            cog: location.heading.map(|heading| heading.round() as i64),
+            tst: location.timestamp.timestamp(),
+            tid: tracker_id,
+        }
+    }
+}
+
+/// 받은 위치를 OwnTracks 형식으로 MQTT 브로커에 발행하는 전송기 (`mqtt` 기능 필요)
+///
+/// Home Assistant의 OwnTracks 연동 같은 소비자가 그대로 읽을 수 있다.
+/// 첫 위치를 보낼 때 연결하고, 연결이 끊기면 백그라운드에서 다시 연결한다.
+/// 복제한 값은 연결을 함께 쓴다.
+#[derive(Clone)]
+pub struct Mqtt {
+    host: String,
+    port: Option<u16>,
+    client_id: String,
+    credentials: Option<(String, String)>,
+    tls: bool,
+    topic: String,
+    tracker_id: String,
+    retain: bool,
+    client: Arc<Mutex<Option<Client>>>,
+}
+
+impl fmt::Debug for Mqtt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // 비밀번호는 찍지 않는다
+        f.debug_struct("Mqtt")
+            .field("host", &self.host)
+            .field("port", &self.port)
+            .field("client_id", &self.client_id)
+            .field("username", &self.credentials.as_ref().map(|(username, _)| username))
+            .field("tls", &self.tls)
+            .field("topic", &self.topic)
+            .finish()
+    }
+}
+
+impl Mqtt {
+    /// 브로커 호스트 이름이나 주소
+    pub fn new(host: impl Into<String>) -> Self {
+        Mqtt {
+            host: host.into(),
+            port: None,
+            client_id: format!("weim-{}", std::process::id()),
+            credentials: None,
+            tls: false,
+            topic: DEFAULT_TOPIC.to_string(),
+            tracker_id: "wm".to_string(),
+            retain: true,
+            client: Arc::default(),
+        }
+    }
+
+    /// 브로커 포트 (기본값 1883, TLS면 8883)
+    pub fn port(mut self, port: u16) -> Self {
+        self.port = Some(port);
+        self
+    }
+
+    /// MQTT 클라이언트 ID (기본값 weim-<프로세스 ID>)
+    pub fn client_id(mut self, id: impl Into<String>) -> Self {
+        self.client_id = id.into();
+        self
+    }
+
+    /// 사용자 이름과 비밀번호 (기본값 없음)
+    pub fn credentials(mut self, username: impl Into<String>, password: impl Into<String>) -> Self {
+        self.credentials = Some((username.into(), password.into()));
+        self
+    }
+
+    /// TLS로 연결할지 여부 (기본값 false). 인증서는 webpki 루트 인증서로 확인한다.
+    pub fn tls(mut self, enabled: bool) -> Self {
+        self.tls = enabled;
+        self
+    }
+
+    /// 발행할 토픽 (기본값 "owntracks/weim/{device}"). `{device}`는 기기 이름으로 바뀐다.
+    pub fn topic(mut self, topic: impl Into<String>) -> Self {
+        self.topic = topic.into();
+        self
+    }
+
+    /// OwnTracks 지도에 표시할 두 글자 트래커 ID (기본값 "wm")
+    pub fn tracker_id(mut self, id: impl Into<String>) -> Self {
+        self.tracker_id = id.into();
+        self
+    }
+
+    /// 마지막 위치를 브로커에 남길지 여부 (기본값 true, OwnTracks 앱과 같음)
+    pub fn retain(mut self, enabled: bool) -> Self {
+        self.retain = enabled;
+        self
+    }
+
+    /// 위치를 발행 대기열에 넣는다. 연결을 기다리지 않고 바로 돌아온다.
+    pub fn publish(&self, device: &DeviceId, location: &Location) {
+        let Ok(payload) = serde_json::to_vec(&OwnTracks::new(location, &self.tracker_id)) else {
+            return;
+        };
+        let topic = self.topic.replace("{device}", device.as_str());
+
+        let mut client = self.client.lock().unwrap();
+        let client = client.get_or_insert_with(|| self.connect());
+        if let Err(e) = client.try_publish(topic, QoS::AtLeastOnce, self.retain, payload) {
+            warning!("{}", tr!("⚠️ MQTT로 위치를 보내지 못했습니다: {}", "⚠️ Could not publish the location over MQTT: {}", e));
+        }
+    }
+
+    fn connect(&self) -> Client {
+        let port = self.port.unwrap_or(if self.tls { DEFAULT_TLS_PORT } else { DEFAULT_PORT });
+        let mut options = MqttOptions::new(&self.client_id, &self.host, port);
+        options.set_keep_alive(KEEP_ALIVE);
+        if let Some((username, password)) = &self.credentials {
+            options.set_credentials(username, password);
+        }
+        if self.tls {
+            options.set_transport(Transport::tls_with_config(TlsConfiguration::Rustls(Arc::new(tls_config()))));
+        }
+
+        let (client, mut connection) = Client::new(options, QUEUE_CAPACITY);
+        let broker = format!("{}:{}", self.host, port);
+        // 이벤트 루프를 돌려야 메시지가 실제로 나간다
+        thread::spawn(move || {
+            let mut connected = false;
+            for event in connection.iter() {
+                match event {
+                    Ok(_) if !connected => {
+                        connected = true;
+                        info!("{}", tr!("📡 MQTT 브로커 {}에 연결했습니다.", "📡 Connected to MQTT broker {}.", broker));
+                    }
+                    Ok(_) => {}
+                    Err(e) => {
+                        if connected {
+                            warning!("{}", tr!("⚠️ MQTT 연결이 끊겼습니다 ({}): {}", "⚠️ MQTT connection lost ({}): {}", broker, e));
+                        } else {
+                            warning!("{}", tr!("⚠️ MQTT 브로커 {}에 연결할 수 없습니다: {}", "⚠️ Cannot connect to MQTT broker {}: {}", broker, e));
+                        }
+                        connected = false;
+                        thread::sleep(RECONNECT_DELAY);
+                    }
+                }
+            }
+        });
+        client
+    }
+}
+
+// ring 구현과 webpki 루트 인증서를 쓴다
+fn tls_config() -> rustls::ClientConfig {
+    let roots = rustls::RootCertStore { roots: webpki_roots::TLS_SERVER_ROOTS.to_vec() };
+    rustls::ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .expect("ring은 기본 TLS 버전을 지원한다")
+        .with_root_certificates(roots)
+        .with_no_client_auth()
+}
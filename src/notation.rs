@@ -0,0 +1,369 @@
+//! 좌표 표기 (십진 도, 도·분, 도·분·초)
+//!
+//! `37.566500, 126.978000`, `37.566500°N 126.978000°E`, `37°33.990′N`, `37°33′59.4″N` 같은 표기를 만들고 읽는다.
+
+use crate::geo::Coordinate;
+use crate::i18n::tr;
+use crate::{Location, WeimError};
+
+/// 좌표 표기 방식
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Style {
+    /// 부호 있는 십진 도: `-33.856800` (기본값)
+    #[default]
+    Decimal,
+    /// 반구 글자를 붙인 십진 도: `33.856800°S`
+    Hemisphere,
+    /// 도와 십진 분: `33°51.408′S`
+    DegreesMinutes,
+    /// 도, 분, 초: `33°51′24.5″S`
+    DegreesMinutesSeconds,
+}
+
+impl Style {
+    // 마지막 자리(도, 분, 초)의 기본 소수점 자리 수. 어느 쪽이든 10cm 안팎이다.
+    fn default_precision(&self) -> usize {
+        match self {
+            Style::Decimal | Style::Hemisphere => 6,
+            Style::DegreesMinutes => 4,
+            Style::DegreesMinutesSeconds => 2,
+        }
+    }
+}
+
+/// 좌표를 글자로 바꾸는 방식과 자리 수
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct CoordinateFormat {
+    style: Style,
+    precision: Option<usize>,
+}
+
+impl CoordinateFormat {
+    pub fn new(style: Style) -> Self {
+        CoordinateFormat { style, precision: None }
+    }
+
+    /// 마지막 자리(도, 분 또는 초)의 소수점 아래 자리 수 (기본값 십진 도 6, 분 4, 초 2)
+    pub fn precision(mut self, digits: usize) -> Self {
+        self.precision = Some(digits.min(12));
+        self
+    }
+
+    /// 위도 하나. 반구 글자는 N/S다.
+    pub fn latitude(&self, latitude: f64) -> String {
+        self.angle(latitude, ['N', 'S'])
+    }
+
+    /// 경도 하나. 반구 글자는 E/W다.
+    pub fn longitude(&self, longitude: f64) -> String {
+        self.angle(longitude, ['E', 'W'])
+    }
+
+    /// `위도, 경도`. 반구 글자를 붙이는 표기는 쉼표 없이 띄어 쓴다.
+    pub fn format(&self, coordinate: impl Coordinate) -> String {
+        let separator = if self.style == Style::Decimal { ", " } else { " " };
+        format!("{}{}{}", self.latitude(coordinate.latitude()), separator, self.longitude(coordinate.longitude()))
+    }
+
+    fn angle(&self, value: f64, [positive, negative]: [char; 2]) -> String {
+        let precision = self.precision.unwrap_or_else(|| self.style.default_precision());
+        let hemisphere = if value < 0.0 { negative } else { positive };
+        let degrees = value.abs();
+        match self.style {
+            Style::Decimal => format!("{:.*}", precision, value),
+            Style::Hemisphere => format!("{:.*}°{}", precision, degrees, hemisphere),
+            Style::DegreesMinutes => {
+                let (whole, minutes) = carry(degrees, 60.0, precision);
+                format!("{}°{:0width$.*}′{}", whole, precision, minutes, hemisphere, width = width(precision))
+            }
+            Style::DegreesMinutesSeconds => {
+                let (whole, minutes) = carry(degrees, 3600.0, precision);
+                let (minutes, seconds) = ((minutes / 60.0).floor(), minutes % 60.0);
+                format!("{}°{:02}′{:0width$.*}″{}", whole, minutes, precision, seconds, hemisphere, width = width(precision))
+            }
+        }
+    }
+}
+
+// 도와 나머지(분 또는 초)로 나눈다. 반올림해서 60분(60초)이 되면 윗자리로 올린다.
+fn carry(degrees: f64, parts: f64, precision: usize) -> (u32, f64) {
+    let scale = 10f64.powi(precision as i32);
+    let total = (degrees * parts * scale).round() / scale;
+    let whole = (total / parts).floor();
+    (whole as u32, ((total - whole * parts) * scale).round() / scale)
+}
+
+// 분과 초를 두 자리로 맞출 때의 전체 너비
+fn width(precision: usize) -> usize {
+    if precision == 0 { 2 } else { 3 + precision }
+}
+
+/// `위도, 경도` 한 쌍을 읽는다. `CoordinateFormat`이 만드는 모든 표기를 받는다.
+///
+/// 두 값은 쉼표, 세미콜론, 반구 글자, `°` 또는 공백으로 나눈다. 반구 글자는 앞(`N37.5`)이나 뒤(`37.5N`)에 올 수 있고,
+/// 글자가 있으면 경도를 먼저 써도 된다. 분과 초는 `′`, `'`, `″`, `"`로 표시하거나 공백으로 나눠 쓴다.
+pub fn parse(text: &str) -> Result<(f64, f64), WeimError> {
+    let invalid = || WeimError::InvalidPayload(tr!("좌표를 읽을 수 없습니다: {:?}", "cannot read coordinates: {:?}", text));
+    let mut angles = group(&tokenize(text).ok_or_else(invalid)?).ok_or_else(invalid)?;
+    // 아무 표시 없이 숫자만 늘어놓았으면 반씩 나눈다 (37.5 126.9, 37 33 59.4 126 58 40.8)
+    if let [only] = angles.as_mut_slice()
+        && only.hemisphere.is_none()
+        && only.numbers.len() % 2 == 0
+        && only.numbers.iter().all(|&(_, unit)| unit == Unit::Unmarked)
+    {
+        let second = only.numbers.split_off(only.numbers.len() / 2);
+        angles.push(Angle { numbers: second, hemisphere: None });
+    }
+    let [first, second] = angles.as_slice() else {
+        return Err(invalid());
+    };
+    let (first, second) = (first.resolve().ok_or_else(invalid)?, second.resolve().ok_or_else(invalid)?);
+    // 반구 글자가 있으면 그 축을 따른다
+    let (latitude, longitude) = match (first.1, second.1) {
+        (Some(axis), Some(other)) if axis == other => return Err(invalid()),
+        (Some(Axis::Longitude), _) | (None, Some(Axis::Latitude)) => (second.0, first.0),
+        _ => (first.0, second.0),
+    };
+    if latitude.abs() > 90.0 || longitude.abs() > 180.0 {
+        return Err(invalid());
+    }
+    Ok((latitude, longitude))
+}
+
+/// 위도나 경도 하나를 읽는다. S와 W는 음수가 된다.
+pub fn parse_angle(text: &str) -> Result<f64, WeimError> {
+    let invalid = || WeimError::InvalidPayload(tr!("각도를 읽을 수 없습니다: {:?}", "cannot read angle: {:?}", text));
+    let angles = group(&tokenize(text).ok_or_else(invalid)?).ok_or_else(invalid)?;
+    match angles.as_slice() {
+        [angle] => angle.resolve().map(|(value, _)| value).filter(|value| value.abs() <= 180.0).ok_or_else(invalid),
+        _ => Err(invalid()),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Token {
+    Number(f64, Unit),
+    Hemisphere(char),
+    Separator,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Unit {
+    Unmarked,
+    Degrees,
+    Minutes,
+    Seconds,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Axis {
+    Latitude,
+    Longitude,
+}
+
+#[derive(Debug, Default)]
+struct Angle {
+    numbers: Vec<(f64, Unit)>,
+    hemisphere: Option<char>,
+}
+
+impl Angle {
+    // 값과 반구 글자로 정해진 축
+    fn resolve(&self) -> Option<(f64, Option<Axis>)> {
+        let (&(degrees, _), rest) = self.numbers.split_first()?;
+        if rest.len() > 2 || rest.iter().any(|&(value, _)| !(0.0..60.0).contains(&value)) || degrees.fract() != 0.0 && !rest.is_empty() {
+            return None;
+        }
+        let minutes = rest.first().map_or(0.0, |&(minutes, _)| minutes);
+        let seconds = rest.get(1).map_or(0.0, |&(seconds, _)| seconds);
+        let mut value = degrees.abs() + minutes / 60.0 + seconds / 3600.0;
+        if degrees.is_sign_negative() {
+            value = -value;
+        }
+        let axis = match self.hemisphere {
+            Some('N') => Some(Axis::Latitude),
+            Some('S') => {
+                value = -value.abs();
+                Some(Axis::Latitude)
+            }
+            Some('E') => Some(Axis::Longitude),
+            Some('W') => {
+                value = -value.abs();
+                Some(Axis::Longitude)
+            }
+            _ => None,
+        };
+        (self.hemisphere.is_none() || degrees >= 0.0).then_some((value, axis))
+    }
+}
+
+fn tokenize(text: &str) -> Option<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            '0'..='9' | '.' | '-' | '+' => {
+                let mut number = String::new();
+                while let Some(&c) = chars.peek().filter(|c| c.is_ascii_digit() || matches!(c, '.' | '-' | '+')) {
+                    number.push(c);
+                    chars.next();
+                }
+                while chars.peek().is_some_and(|c| *c == ' ') {
+                    chars.next();
+                }
+                let unit = match chars.peek() {
+                    Some('°' | 'º' | '˚') => Unit::Degrees,
+                    Some('\'' | '′' | '’') => Unit::Minutes,
+                    Some('"' | '″' | '”') => Unit::Seconds,
+                    _ => Unit::Unmarked,
+                };
+                if unit != Unit::Unmarked {
+                    chars.next();
+                }
+                // 따옴표 두 개를 초로 쓰기도 한다
+                if unit == Unit::Minutes && chars.peek() == Some(&'\'') {
+                    chars.next();
+                    tokens.push(Token::Number(number.parse().ok()?, Unit::Seconds));
+                    continue;
+                }
+                tokens.push(Token::Number(number.parse().ok()?, unit));
+            }
+            'N' | 'S' | 'E' | 'W' | 'n' | 's' | 'e' | 'w' => {
+                tokens.push(Token::Hemisphere(c.to_ascii_uppercase()));
+                chars.next();
+            }
+            ',' | ';' => {
+                tokens.push(Token::Separator);
+                chars.next();
+            }
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            _ => return None,
+        }
+    }
+    tokens.iter().all(|token| match token {
+        Token::Number(value, _) => value.is_finite(),
+        _ => true,
+    }).then_some(tokens)
+}
+
+// 토큰을 각도별로 묶는다. 반구 글자가 앞에 오면 글자가, 뒤에 오면 글자 다음이, 그 밖에는 쉼표와 `°`가 새 각도를 시작한다.
+fn group(tokens: &[Token]) -> Option<Vec<Angle>> {
+    let prefix = matches!(tokens.first(), Some(Token::Hemisphere(_)));
+    let mut angles = Vec::new();
+    let mut current = Angle::default();
+    for token in tokens {
+        match *token {
+            Token::Hemisphere(hemisphere) if prefix => {
+                if !current.numbers.is_empty() {
+                    angles.push(std::mem::take(&mut current));
+                }
+                current.hemisphere = Some(hemisphere);
+            }
+            Token::Hemisphere(hemisphere) => {
+                if current.numbers.is_empty() {
+                    return None;
+                }
+                current.hemisphere = Some(hemisphere);
+                angles.push(std::mem::take(&mut current));
+            }
+            Token::Separator => {
+                if !current.numbers.is_empty() {
+                    angles.push(std::mem::take(&mut current));
+                }
+            }
+            Token::Number(value, unit) => {
+                if unit == Unit::Degrees && !current.numbers.is_empty() {
+                    angles.push(std::mem::take(&mut current));
+                }
+                current.numbers.push((value, unit));
+            }
+        }
+    }
+    if !current.numbers.is_empty() {
+        angles.push(current);
+    }
+    Some(angles)
+}
+
+impl Location {
+    /// `format`으로 쓴 `위도, 경도`
+    pub fn format(&self, format: CoordinateFormat) -> String {
+        format.format(self)
+    }
+
+    /// 붙여 넣은 좌표의 위치. 정확도는 알 수 없어 0으로 둔다.
+    ///
+    /// `notation::parse()`가 읽는 모든 표기와 `map::parse_url()`이 읽는 지도 링크를 받는다.
+    pub fn parse(text: &str) -> Result<Location, WeimError> {
+        let text = text.trim();
+        if text.starts_with("http://") || text.starts_with("https://") {
+            return Location::from_map_url(text);
+        }
+        let (latitude, longitude) = parse(text)?;
+        Ok(Location { latitude, longitude, ..Location::default() })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 서울시청
+    const POINT: (f64, f64) = (37.5665, 126.978);
+
+    fn close(a: (f64, f64), b: (f64, f64)) -> bool {
+        (a.0 - b.0).abs() < 1e-6 && (a.1 - b.1).abs() < 1e-6
+    }
+
+    #[test]
+    fn formats_every_style() {
+        assert_eq!(CoordinateFormat::new(Style::Decimal).format(POINT), "37.566500, 126.978000");
+        assert_eq!(CoordinateFormat::new(Style::Hemisphere).format(POINT), "37.566500°N 126.978000°E");
+        assert_eq!(CoordinateFormat::new(Style::DegreesMinutes).format(POINT), "37°33.9900′N 126°58.6800′E");
+        assert_eq!(CoordinateFormat::new(Style::DegreesMinutesSeconds).format(POINT), "37°33′59.40″N 126°58′40.80″E");
+        assert_eq!(CoordinateFormat::new(Style::DegreesMinutesSeconds).precision(0).latitude(-33.8568), "33°51′24″S");
+        assert_eq!(CoordinateFormat::new(Style::Hemisphere).precision(2).longitude(-0.1276), "0.13°W");
+    }
+
+    #[test]
+    fn carries_rounded_seconds() {
+        let format = CoordinateFormat::new(Style::DegreesMinutesSeconds);
+        assert_eq!(format.latitude(0.99999999), "1°00′00.00″N");
+        assert_eq!(CoordinateFormat::new(Style::DegreesMinutes).precision(1).latitude(12.999999), "13°00.0′N");
+    }
+
+    #[test]
+    fn parses_dms() {
+        assert!(close(parse("37°33′59.4″N 126°58′40.8″E").unwrap(), POINT));
+        assert!(close(parse("37°33'59.4\"N, 126°58'40.8\"E").unwrap(), POINT));
+        assert!(close(parse("126°58'40.8\"E 37°33'59.4\"N").unwrap(), POINT));
+        assert!(close(parse("N37 33 59.4 E126 58 40.8").unwrap(), POINT));
+        assert!(close(parse("37 33 59.4 126 58 40.8").unwrap(), POINT));
+        assert!(close(parse("37°33.99′N 126°58.68′E").unwrap(), POINT));
+        assert!(close(parse("37.5665, 126.978").unwrap(), POINT));
+        assert!((parse_angle("33°51′24.48″S").unwrap() + 33.8568).abs() < 1e-6);
+    }
+
+    #[test]
+    fn reads_what_it_writes() {
+        for style in [Style::Decimal, Style::Hemisphere, Style::DegreesMinutes, Style::DegreesMinutesSeconds] {
+            for point in [POINT, (-33.8568, 151.2153), (40.7128, -74.006)] {
+                let text = CoordinateFormat::new(style).precision(6).format(point);
+                assert!(close(parse(&text).unwrap(), point), "{}", text);
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_coordinates() {
+        assert!(parse("").is_err());
+        assert!(parse("37.5665").is_err());
+        assert!(parse("91, 126.978").is_err());
+        assert!(parse("37°N 126°N").is_err());
+        assert!(parse("37°61′N 126°E").is_err());
+        assert!(parse("서울").is_err());
+        assert!(parse_angle("-37°S").is_err());
+    }
+}
@@ -0,0 +1,90 @@
+use notify_rust::Notification;
+
+use crate::console::warning;
+use crate::i18n::{pick, tr};
+use crate::notation::CoordinateFormat;
+use crate::{GeofenceEvent, Location};
+
+const APP_NAME: &str = "weim";
+
+/// 데스크톱 알림을 보낼 상황 (`notifications` 기능 필요)
+#[derive(Debug, Clone)]
+pub struct Notifications {
+    fix: bool,
+    accuracy_threshold: Option<f64>,
+}
+
+impl Default for Notifications {
+    fn default() -> Self {
+        Notifications { fix: true, accuracy_threshold: None }
+    }
+}
+
+impl Notifications {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 첫 위치를 받았을 때 알릴지 여부 (기본값 true)
+    pub fn fix(mut self, enabled: bool) -> Self {
+        self.fix = enabled;
+        self
+    }
+
+    /// 정확도가 이 값(m)보다 나빠지면 알린다. 다시 좋아질 때도 한 번 알린다. (기본값 없음)
+    pub fn accuracy_threshold(mut self, meters: f64) -> Self {
+        self.accuracy_threshold = Some(meters);
+        self
+    }
+}
+
+/// 서버를 한 번 여는 동안의 알림 상태
+pub(crate) struct Notifier {
+    settings: Notifications,
+    acquired: bool,
+    degraded: bool,
+}
+
+impl Notifier {
+    pub(crate) fn new(settings: Option<&Notifications>) -> Option<Self> {
+        Some(Notifier { settings: settings?.clone(), acquired: false, degraded: false })
+    }
+
+    pub(crate) fn on_fix(&mut self, location: &Location) {
+        if self.settings.fix && !self.acquired {
+            show(
+                pick("📍 위치를 받았습니다", "📍 Location received"),
+                &format!("{} (±{:.0}m)", location.format(CoordinateFormat::default()), location.accuracy),
+            );
+        }
+        self.acquired = true;
+
+        if let Some(threshold) = self.settings.accuracy_threshold {
+            let degraded = location.accuracy > threshold;
+            if degraded != self.degraded {
+                let summary = if degraded {
+                    pick("⚠️ 위치 정확도가 떨어졌습니다", "⚠️ Location accuracy dropped")
+                } else {
+                    pick("✅ 위치 정확도가 회복되었습니다", "✅ Location accuracy recovered")
+                };
+                show(summary, &tr!("정확도 {:.0}m (기준 {:.0}m)", "Accuracy {:.0}m (threshold {:.0}m)", location.accuracy, threshold));
+            }
+            self.degraded = degraded;
+        }
+    }
+}
+
+/// 지오펜스 이벤트를 데스크톱 알림으로 보낸다.
+pub(crate) fn geofence(event: &GeofenceEvent) {
+    show(
+        &tr!("🗺️ 지오펜스 {}", "🗺️ Geofence {}", event.kind),
+        &format!("{} ({})", event.fence, event.location.format(CoordinateFormat::default())),
+    );
+}
+
+// 알림 데몬이 없어도 위치 처리는 계속한다
+fn show(summary: &str, body: &str) {
+    if let Err(e) = Notification::new().appname(APP_NAME).summary(summary).body(body).show() {
+        warning!("{}", tr!("⚠️ 데스크톱 알림을 보내지 못했습니다: {}", "⚠️ Could not show the desktop notification: {}", e));
+    }
+}
@@ -0,0 +1,454 @@
+use std::path::PathBuf;
+
+use crate::console::warning;
+use crate::i18n::{self, tr};
+use crate::locator::{CloseStrategy, Config, Mode};
+
+const HTML_TEMPLATE: &str = r#"
+<!DOCTYPE html> 
+<html lang="{{LANG}}">
+<head>
+    <meta charset="UTF-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1.0">
+    <title>{{TITLE}}</title>
+    <style>
+        body {
+            margin: 0;
+            padding: 20px;
+            font-family: monospace;
+            background: #000;
+            color: #0f0;
+        }
+        #status { font-size: 14px; }
+        #consent { max-width: 480px; line-height: 1.5; }
+        #consent button { font-family: inherit; margin-right: 8px; padding: 6px 12px; }
+        #map { height: 60vh; margin-top: 12px; }
+        #manual input, #manual button { font-family: inherit; margin: 4px 8px 4px 0; padding: 4px; }
+    </style>
+</head>
+<body>
+    <div id="consent" hidden>
+        <p>{{CONSENT_TEXT}}</p>
+        <button id="share">{{SHARE}}</button>
+        <button id="decline">{{DECLINE}}</button>
+    </div>
+    <div id="status">{{TRACKING}}</div>
+    <div id="done" hidden>{{DONE}}</div>
+    <form id="manual" hidden>
+        <p id="manual-text"></p>
+        <input id="manual-latitude" inputmode="decimal">
+        <input id="manual-longitude" inputmode="decimal">
+        <input id="manual-accuracy" inputmode="decimal">
+        <button id="manual-submit" type="submit"></button>
+    </form>
+    <div id="map" hidden></div>
+    <script>
+        // 현재 언어의 문구. {이름} 자리를 값으로 채운다
+        const text = {{TEXT}};
+        const say = (template, values = {}) => template.replace(/\{(\w+)\}/g, (_, name) => values[name]);
+
+        window.addEventListener('DOMContentLoaded', () => {
+            // 정확도가 기준 안으로 들어오거나 횟수를 다 쓸 때까지 계속 측정
+            const threshold = {{ACCURACY_THRESHOLD}};
+            const maxAttempts = {{MAX_ATTEMPTS}};
+            // 1보다 크면 측정값을 이 개수만큼 모두 보낸다 (Infinity = 멈출 때까지). 주소에 ?once가 있으면 하나만
+            const samples = new URLSearchParams(window.location.search).has('once') ? 1 : {{SAMPLES}};
+            // 이 페이지를 받은 브라우저임을 증명하는 세션 토큰
+            const token = '{{TOKEN}}';
+            // 주소의 ?device= 값, 없으면 이 브라우저에 기억해 둔 임의의 ID
+            const device = new URLSearchParams(window.location.search).get('device') || (() => {
+                const random = Math.random().toString(36).slice(2, 10);
+                try {
+                    const saved = localStorage.getItem('weim-device');
+                    if (saved) return saved;
+                    localStorage.setItem('weim-device', random);
+                } catch (e) {}
+                return random;
+            })();
+            let attempts = 0;
+            let sent = 0;
+            let best = null;
+            let finished = false;
+
+            // 실시간 전송은 웹소켓 하나로 보내고, 연결이 없을 때만 요청을 따로 보낸다
+            let socket = null;
+            if ({{WEBSOCKET}}) {
+                const url = (window.location.protocol === 'https:' ? 'wss://' : 'ws://') + window.location.host + '/ws';
+                let retry = 1000;
+                let lastPong = 0;
+                const connect = () => {
+                    const ws = new WebSocket(url);
+                    ws.onopen = () => {
+                        socket = ws;
+                        retry = 1000;
+                        lastPong = Date.now();
+                    };
+                    ws.onmessage = (event) => {
+                        if (event.data === 'pong') lastPong = Date.now();
+                    };
+                    // 끊기면 1초부터 두 배씩, 최대 30초 간격으로 다시 연결한다
+                    ws.onclose = () => {
+                        if (socket === ws) socket = null;
+                        if (finished) return;
+                        setTimeout(connect, retry);
+                        retry = Math.min(retry * 2, 30000);
+                    };
+                };
+                connect();
+                // 15초마다 살아 있는지 묻고, 45초 동안 답이 없으면 다시 연결한다
+                setInterval(() => {
+                    if (!socket) return;
+                    if (Date.now() - lastPong > 45000) {
+                        socket.close();
+                    } else {
+                        socket.send('ping');
+                    }
+                }, 15000);
+            }
+
+            // 지도를 켰으면 Leaflet을 불러와 받은 위치와 정확도 원을 그린다
+            const showMap = {{MAP}};
+            const leaflet = showMap ? new Promise((resolve) => {
+                const css = document.createElement('link');
+                css.rel = 'stylesheet';
+                css.href = 'https://unpkg.com/leaflet@1.9.4/dist/leaflet.css';
+                css.integrity = 'sha256-p4NxAoJBhIIN+hmNHrzRCf9tD/miZyoHS5obTRR9BMY=';
+                css.crossOrigin = '';
+                document.head.appendChild(css);
+                const script = document.createElement('script');
+                script.src = 'https://unpkg.com/leaflet@1.9.4/dist/leaflet.js';
+                script.integrity = 'sha256-20nQCchB9co0qIjJZRGuk2/Z9VM+kNiyxNV1lvTlZBo=';
+                script.crossOrigin = '';
+                // 불러오지 못해도 위치 전송은 계속한다
+                script.onload = script.onerror = resolve;
+                document.head.appendChild(script);
+            }) : null;
+            let view = null;
+            const plot = async (data) => {
+                if (!leaflet) return;
+                await leaflet;
+                if (!window.L) return;
+                const point = [data.latitude, data.longitude];
+                if (!view) {
+                    const container = document.getElementById('map');
+                    container.hidden = false;
+                    const map = L.map(container);
+                    L.tileLayer('https://tile.openstreetmap.org/{z}/{x}/{y}.png', {
+                        maxZoom: 19,
+                        attribution: '&copy; OpenStreetMap contributors'
+                    }).addTo(map);
+                    view = { map, marker: L.marker(point).addTo(map), circle: L.circle(point, { radius: data.accuracy }).addTo(map) };
+                    map.fitBounds(view.circle.getBounds(), { maxZoom: 17 });
+                } else {
+                    view.marker.setLatLng(point);
+                    view.circle.setLatLng(point).setRadius(data.accuracy);
+                    view.map.panTo(point);
+                }
+            };
+
+            // 기기가 향한 나침반 방위. 움직여야 알 수 있는 진행 방향과 달리 멈춰 있어도 잰다
+            let compass = null;
+            const listenCompass = () => {
+                window.addEventListener('deviceorientationabsolute', (event) => {
+                    if (event.alpha !== null) compass = (360 - event.alpha) % 360;
+                });
+                window.addEventListener('deviceorientation', (event) => {
+                    // iOS Safari는 absolute 이벤트 대신 webkitCompassHeading을 준다
+                    if (typeof event.webkitCompassHeading === 'number') {
+                        compass = event.webkitCompassHeading;
+                    } else if (event.absolute && event.alpha !== null) {
+                        compass = (360 - event.alpha) % 360;
+                    }
+                });
+            };
+            // iOS는 사용자가 화면을 누를 때에만 방향 센서 권한을 물을 수 있다
+            if (window.DeviceOrientationEvent && typeof DeviceOrientationEvent.requestPermission === 'function') {
+                let asked = false;
+                const ask = () => {
+                    if (asked) return;
+                    asked = true;
+                    DeviceOrientationEvent.requestPermission()
+                        .then((state) => { if (state === 'granted') listenCompass(); })
+                        .catch(() => {});
+                };
+                document.addEventListener('click', ask);
+                document.addEventListener('touchend', ask);
+            } else if (window.DeviceOrientationEvent) {
+                listenCompass();
+            }
+
+            // 여러 기기를 구분할 수 있게 위치마다 함께 보낸다. 배터리는 Battery Status API가 있는 브라우저에서만 읽는다
+            const battery = navigator.getBattery ? navigator.getBattery().catch(() => null) : Promise.resolve(null);
+            const deviceInfo = async () => {
+                const status = await battery;
+                return {
+                    userAgent: navigator.userAgent,
+                    platform: navigator.userAgentData?.platform || navigator.platform || null,
+                    screenWidth: screen.width,
+                    screenHeight: screen.height,
+                    battery: status ? status.level : null,
+                    charging: status ? status.charging : null
+                };
+            };
+
+            // source: 'measured'는 브라우저가 잰 위치, 'manual'은 사용자가 입력한 위치
+            const send = async (position, source = 'measured') => {
+                const data = {
+                    latitude: position.coords.latitude,
+                    longitude: position.coords.longitude,
+                    accuracy: position.coords.accuracy,
+                    altitude: position.coords.altitude,
+                    altitudeAccuracy: position.coords.altitudeAccuracy,
+                    heading: position.coords.heading,
+                    speed: position.coords.speed,
+                    compass: source === 'measured' ? compass : null,
+                    // 수동 입력에는 측정 시각이 없다
+                    timestamp: position.timestamp ?? Date.now()
+                };
+                document.getElementById('status').textContent = say(text.position, {
+                    latitude: data.latitude.toFixed(6),
+                    longitude: data.longitude.toFixed(6),
+                    accuracy: data.accuracy.toFixed(2)
+                });
+                plot(data);
+
+                // 서버가 이 기기의 시계가 얼마나 틀렸는지 어림하는 데 쓴다
+                const body = JSON.stringify({ ...data, deviceInfo: await deviceInfo(), sentAt: Date.now(), token, device, source });
+                if (socket && socket.readyState === WebSocket.OPEN) {
+                    socket.send(body);
+                    return;
+                }
+                await fetch('{{UPDATE_URL}}', {
+                    method: 'POST',
+                    headers: { 'Content-Type': 'application/json' },
+                    body
+                });
+            };
+
+            let watchId = null;
+            const done = () => {
+                finished = true;
+                if (watchId !== null) navigator.geolocation.clearWatch(watchId);
+                document.getElementById('done').hidden = false;
+
+                // 자동으로 창 닫기. 지도를 켰으면 볼 수 있게 둔다
+                if ({{CLOSE_TAB}} && !showMap) {
+                    setTimeout(() => window.close(), 1500);
+                }
+            };
+
+            const finish = async (position) => {
+                finished = true;
+                await send(position);
+                done();
+            };
+
+            const start = () => watchId = navigator.geolocation.watchPosition(
+                async (position) => {
+                    if (finished) return;
+                    if (samples > 1) {
+                        const count = ++sent;
+                        if (count >= samples) finished = true;
+                        await send(position);
+                        document.getElementById('status').textContent = samples === Infinity
+                            ? say(text.streaming, { count })
+                            : say(text.sampling, { count, samples });
+                        if (count >= samples) done();
+                        return;
+                    }
+                    attempts++;
+                    if (!best || position.coords.accuracy < best.coords.accuracy) {
+                        best = position;
+                    }
+                    if (threshold === null || best.coords.accuracy <= threshold || attempts >= maxAttempts) {
+                        finish(best);
+                    } else {
+                        document.getElementById('status').textContent = say(text.improving, {
+                            attempts,
+                            maxAttempts,
+                            accuracy: best.coords.accuracy.toFixed(2)
+                        });
+                    }
+                },
+                (error) => {
+                    if (finished) return;
+                    // 이미 받은 위치가 있으면 그중 가장 좋은 것을 보낸다
+                    if (best) {
+                        finish(best);
+                        return;
+                    }
+                    document.getElementById('status').textContent = text.error + error.message;
+                    // 이미 보낸 위치가 있으면 다음 측정을 기다린다
+                    if (sent > 0) return;
+                    if ({{MANUAL}}) {
+                        manual();
+                    } else {
+                        fail(error.code, error.message);
+                    }
+                },
+                { enableHighAccuracy: {{HIGH_ACCURACY}}, timeout: {{POSITION_TIMEOUT}}, maximumAge: {{MAXIMUM_AGE}} }
+            );
+
+            // 위치를 얻지 못하면 켜 둔 경우에 한해 좌표를 직접 입력받는다
+            const manual = () => {
+                const form = document.getElementById('manual');
+                if (!{{MANUAL}} || !form.hidden) return;
+                document.getElementById('manual-text').textContent = text.manual;
+                document.getElementById('manual-latitude').placeholder = text.latitude;
+                document.getElementById('manual-longitude').placeholder = text.longitude;
+                document.getElementById('manual-accuracy').placeholder = text.accuracy;
+                document.getElementById('manual-submit').textContent = text.submit;
+                form.hidden = false;
+                form.addEventListener('submit', async (event) => {
+                    event.preventDefault();
+                    const value = (id) => parseFloat(document.getElementById(id).value);
+                    const [latitude, longitude, accuracy] = [value('manual-latitude'), value('manual-longitude'), value('manual-accuracy')];
+                    if (!(Math.abs(latitude) <= 90 && Math.abs(longitude) <= 180)) {
+                        document.getElementById('status').textContent = text.invalid;
+                        return;
+                    }
+                    if (finished) return;
+                    finished = true;
+                    form.hidden = true;
+                    const coords = {
+                        latitude,
+                        longitude,
+                        accuracy: accuracy >= 0 ? accuracy : 0,
+                        altitude: null,
+                        altitudeAccuracy: null,
+                        heading: null,
+                        speed: null
+                    };
+                    await send({ coords }, 'manual');
+                    done();
+                });
+            };
+
+            // 위치를 얻지 못한 까닭을 Rust 쪽에 알린다 (0 = Geolocation API 없음)
+            const fail = async (code, message) => {
+                finished = true;
+                if (watchId !== null) navigator.geolocation.clearWatch(watchId);
+                await fetch('/error', {
+                    method: 'POST',
+                    headers: { 'Content-Type': 'application/json' },
+                    body: JSON.stringify({ token, code, message })
+                });
+            };
+
+            // 아무것도 보내지 않고 탭을 닫으면 Rust 쪽이 더 기다리지 않게 알린다
+            window.addEventListener('pagehide', () => {
+                if (sent === 0 && !finished) navigator.sendBeacon('/leave', JSON.stringify({ token }));
+            });
+            // 뒤로 가기 캐시에서 돌아오면 페이지를 다시 연 것으로 알린다
+            window.addEventListener('pageshow', (event) => {
+                if (event.persisted) fetch('/', { cache: 'no-store' });
+            });
+
+            if (!navigator.geolocation) {
+                document.getElementById('status').textContent = text.unsupported;
+                if ({{MANUAL}}) {
+                    manual();
+                } else {
+                    fail(0, text.unsupported);
+                }
+                return;
+            }
+
+            // 동의 화면을 켰으면 사용자가 공유를 누른 뒤에야 위치를 묻는다
+            if ({{CONSENT}}) {
+                const consent = document.getElementById('consent');
+                const status = document.getElementById('status');
+                consent.hidden = false;
+                status.hidden = true;
+                document.getElementById('share').addEventListener('click', () => {
+                    consent.hidden = true;
+                    status.hidden = false;
+                    start();
+                });
+                // 거절하면 Rust 쪽에 PermissionDenied로 알린다
+                document.getElementById('decline').addEventListener('click', async () => {
+                    finished = true;
+                    consent.hidden = true;
+                    status.hidden = false;
+                    status.textContent = text.declined;
+                    await fetch('/decline', {
+                        method: 'POST',
+                        headers: { 'Content-Type': 'application/json' },
+                        body: JSON.stringify({ token })
+                    });
+                });
+            } else {
+                start();
+            }
+        });
+    </script>
+</body>
+</html>
+"#;
+
+/// 기본 페이지 대신 쓸 HTML 템플릿
+#[derive(Debug, Clone)]
+pub(crate) enum Template {
+    Html(String),
+    // 서버를 열 때마다 다시 읽는다
+    File(PathBuf),
+}
+
+// 템플릿 파일을 읽지 못하면 기본 페이지로 연다
+fn template(config: &Config) -> String {
+    match &config.template {
+        None => HTML_TEMPLATE.to_string(),
+        Some(Template::Html(html)) => html.clone(),
+        Some(Template::File(path)) => std::fs::read_to_string(path).unwrap_or_else(|e| {
+            warning!(
+                "{}",
+                tr!(
+                    "⚠️ {} 템플릿을 읽지 못해 기본 페이지를 씁니다: {}",
+                    "⚠️ Could not read the template {}, using the built-in page: {}",
+                    path.display(),
+                    e
+                )
+            );
+            HTML_TEMPLATE.to_string()
+        }),
+    }
+}
+
+/// 설정값과 세션 토큰을 페이지 템플릿에 채워 넣는다.
+pub(crate) fn render(config: &Config, mode: Mode, token: &str) -> String {
+    let samples = match mode {
+        Mode::Single => "1".to_string(),
+        Mode::Samples(count) => count.to_string(),
+        Mode::Watch | Mode::Session => "Infinity".to_string(),
+    };
+    let close_tab = config.close_strategy == CloseStrategy::TabCloseScript;
+    // 웹소켓은 멈출 때까지 보내는 실시간 추적에서만 쓴다
+    let websocket = cfg!(feature = "websocket") && matches!(mode, Mode::Watch | Mode::Session);
+    let text = i18n::page_text();
+    let json = serde_json::to_string(text).unwrap_or_else(|_| "{}".to_string());
+    template(config)
+        .replace("{{LANG}}", text.lang)
+        .replace("{{TITLE}}", text.title)
+        .replace("{{TRACKING}}", text.tracking)
+        .replace("{{DONE}}", text.done)
+        .replace("{{CONSENT_TEXT}}", text.consent)
+        .replace("{{SHARE}}", text.share)
+        .replace("{{DECLINE}}", text.decline)
+        .replace("{{CONSENT}}", &config.consent.to_string())
+        .replace("{{MAP}}", &config.show_map.to_string())
+        .replace("{{MANUAL}}", &config.manual_entry.to_string())
+        .replace("{{TEXT}}", &json)
+        .replace("{{HIGH_ACCURACY}}", &config.high_accuracy.to_string())
+        .replace("{{POSITION_TIMEOUT}}", &config.position_timeout.as_millis().to_string())
+        .replace("{{MAXIMUM_AGE}}", &config.maximum_age.as_millis().to_string())
+        .replace("{{CLOSE_TAB}}", &close_tab.to_string())
+        .replace("{{WEBSOCKET}}", &websocket.to_string())
+        .replace(
+            "{{ACCURACY_THRESHOLD}}",
+            &config.accuracy_threshold.map_or("null".to_string(), |meters| meters.to_string()),
+        )
+        .replace("{{MAX_ATTEMPTS}}", &config.max_attempts.max(1).to_string())
+        .replace("{{SAMPLES}}", &samples)
+        .replace("{{UPDATE_URL}}", "/update")
+        .replace("{{TOKEN}}", token)
+}
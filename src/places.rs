@@ -0,0 +1,272 @@
+//! 자주 가는 곳 찾기
+//!
+//! 기록에서 한곳에 머문 구간(방문)을 찾고, 가까운 방문끼리 DBSCAN으로 묶어 집, 회사, 운동하는 곳 같은 장소로 만든다.
+//! 장소마다 방문 횟수와 머문 시간의 합, 대표 좌표를 센다.
+
+use std::collections::VecDeque;
+use std::io::{self, Write};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde_json::json;
+
+use crate::csv_log::escape_csv;
+use crate::export::rfc3339;
+use crate::geo::{haversine, METERS_PER_DEGREE};
+use crate::trips::stays;
+use crate::{DeviceId, Location};
+
+const CSV_HEADER: &str = "place,latitude,longitude,radius_m,visits,dwell_s,first_visit,last_visit\n";
+
+/// 한곳에 머문 구간 하나
+#[derive(Debug, Clone, PartialEq)]
+pub struct Visit {
+    pub device: DeviceId,
+    /// 머문 위치들의 평균
+    pub latitude: f64,
+    pub longitude: f64,
+    /// 도착한 시각
+    pub arrived: DateTime<Utc>,
+    /// 떠난 시각. 기록이 끝날 때까지 머물렀으면 마지막 위치의 시각이다.
+    pub departed: DateTime<Utc>,
+}
+
+impl Visit {
+    pub fn dwell(&self) -> Duration {
+        (self.departed - self.arrived).to_std().unwrap_or_default()
+    }
+}
+
+/// 방문을 묶은 장소 하나
+#[derive(Debug, Clone, PartialEq)]
+pub struct Place {
+    /// 머문 시간이 긴 장소부터 0, 1, 2, ...
+    pub id: usize,
+    /// 대표 좌표. 방문 위치들을 머문 시간으로 가중 평균한 곳이다.
+    pub latitude: f64,
+    pub longitude: f64,
+    /// 대표 좌표에서 가장 먼 방문까지의 거리 (m)
+    pub radius: f64,
+    /// 도착한 시각순 방문
+    pub visits: Vec<Visit>,
+}
+
+impl Place {
+    /// 모든 방문에서 머문 시간의 합
+    pub fn dwell(&self) -> Duration {
+        self.visits.iter().map(Visit::dwell).sum()
+    }
+}
+
+/// 방문과 장소를 가르는 기준
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PlaceDetector {
+    stay_radius: f64,
+    min_stay: Duration,
+    max_gap: Duration,
+    distance: f64,
+    min_visits: usize,
+}
+
+impl Default for PlaceDetector {
+    fn default() -> Self {
+        PlaceDetector {
+            stay_radius: 100.0,
+            min_stay: Duration::from_secs(10 * 60),
+            max_gap: Duration::from_secs(30 * 60),
+            distance: 150.0,
+            min_visits: 2,
+        }
+    }
+}
+
+impl PlaceDetector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 이 반경(m) 안에서 움직이면 머문 것으로 본다 (기본값 100m)
+    pub fn stay_radius(mut self, meters: f64) -> Self {
+        self.stay_radius = meters;
+        self
+    }
+
+    /// 이만큼 머물러야 방문으로 센다 (기본값 10분)
+    pub fn min_stay(mut self, duration: Duration) -> Self {
+        self.min_stay = duration;
+        self
+    }
+
+    /// 위치 사이가 이보다 비면 방문을 끊는다 (기본값 30분)
+    pub fn max_gap(mut self, gap: Duration) -> Self {
+        self.max_gap = gap;
+        self
+    }
+
+    /// 방문끼리 이 거리(m) 안에 있으면 이웃으로 본다. DBSCAN의 ε (기본값 150m)
+    pub fn distance(mut self, meters: f64) -> Self {
+        self.distance = meters;
+        self
+    }
+
+    /// 이웃(자신 포함)이 이만큼 있어야 장소가 된다. 한 번만 간 곳은 빼려면 2 이상으로 둔다 (기본값 2)
+    pub fn min_visits(mut self, count: usize) -> Self {
+        self.min_visits = count.max(1);
+        self
+    }
+
+    /// 한 기기의 시간순 위치에서 방문을 찾는다.
+    pub fn visits(&self, device: &DeviceId, points: &[Location]) -> Vec<Visit> {
+        stays(points, self.stay_radius, self.min_stay, self.max_gap)
+            .into_iter()
+            .map(|stay| {
+                let stay = &points[stay];
+                let count = stay.len() as f64;
+                Visit {
+                    device: device.clone(),
+                    latitude: stay.iter().map(|point| point.latitude).sum::<f64>() / count,
+                    longitude: stay.iter().map(|point| point.longitude).sum::<f64>() / count,
+                    arrived: stay[0].timestamp,
+                    departed: stay[stay.len() - 1].timestamp,
+                }
+            })
+            .collect()
+    }
+
+    /// 방문을 DBSCAN으로 묶는다. 어느 장소에도 들지 못한 방문은 버린다.
+    ///
+    /// 모든 방문 쌍의 거리를 재므로 방문 수의 제곱만큼 걸린다. 방문은 위치보다 훨씬 적어서 몇 년 치 기록도 충분히 빠르다.
+    pub fn cluster(&self, visits: Vec<Visit>) -> Vec<Place> {
+        let neighbors: Vec<Vec<usize>> = (0..visits.len())
+            .map(|i| (0..visits.len()).filter(|&j| distance(&visits[i], &visits[j]) <= self.distance).collect())
+            .collect();
+        let core = |i: usize| neighbors[i].len() >= self.min_visits;
+
+        // 핵심 방문에서 닿는 방문을 모두 같은 장소로 모은다
+        let mut label = vec![None; visits.len()];
+        let mut groups: Vec<Vec<usize>> = Vec::new();
+        for start in 0..visits.len() {
+            if label[start].is_some() || !core(start) {
+                continue;
+            }
+            let mut group = Vec::new();
+            let mut queue = VecDeque::from([start]);
+            label[start] = Some(groups.len());
+            while let Some(i) = queue.pop_front() {
+                group.push(i);
+                if !core(i) {
+                    continue;
+                }
+                for &j in &neighbors[i] {
+                    if label[j].is_none() {
+                        label[j] = Some(groups.len());
+                        queue.push_back(j);
+                    }
+                }
+            }
+            groups.push(group);
+        }
+
+        let mut visits: Vec<Option<Visit>> = visits.into_iter().map(Some).collect();
+        let mut places: Vec<Place> = groups
+            .into_iter()
+            .map(|group| {
+                let mut members: Vec<Visit> = group.into_iter().filter_map(|i| visits[i].take()).collect();
+                members.sort_by_key(|visit| visit.arrived);
+                place(members)
+            })
+            .collect();
+        places.sort_by_key(|place| std::cmp::Reverse(place.dwell()));
+        for (id, place) in places.iter_mut().enumerate() {
+            place.id = id;
+        }
+        places
+    }
+}
+
+// 방문 위치를 머문 시간으로 가중 평균한다. 짧은 거리라 첫 방문 둘레를 평면으로 본다.
+fn place(visits: Vec<Visit>) -> Place {
+    let origin = (visits[0].latitude, visits[0].longitude);
+    let scale = origin.0.to_radians().cos();
+    // 머문 시간이 0인 방문만 있어도 평균을 낼 수 있게 1ms를 더한다
+    let weight = |visit: &Visit| visit.dwell().as_millis() as f64 + 1.0;
+    let total: f64 = visits.iter().map(weight).sum();
+    let (mut east, mut north) = (0.0, 0.0);
+    for visit in &visits {
+        east += (visit.longitude - origin.1) * METERS_PER_DEGREE * scale * weight(visit);
+        north += (visit.latitude - origin.0) * METERS_PER_DEGREE * weight(visit);
+    }
+    let latitude = origin.0 + north / total / METERS_PER_DEGREE;
+    let longitude = origin.1 + east / total / (METERS_PER_DEGREE * scale);
+    let radius = visits.iter().map(|visit| haversine((latitude, longitude), (visit.latitude, visit.longitude))).fold(0.0, f64::max);
+    Place { id: 0, latitude, longitude, radius, visits }
+}
+
+fn distance(a: &Visit, b: &Visit) -> f64 {
+    haversine((a.latitude, a.longitude), (b.latitude, b.longitude))
+}
+
+/// 장소 요약을 CSV로 쓴다. 시각은 RFC 3339, 머문 시간은 초다.
+pub fn write_csv<W: Write>(mut out: W, places: &[Place]) -> io::Result<()> {
+    out.write_all(CSV_HEADER.as_bytes())?;
+    for place in places {
+        let (first, last) = (place.visits.first(), place.visits.last());
+        writeln!(
+            out,
+            "{},{},{},{:.0},{},{:.0},{},{}",
+            place.id,
+            place.latitude,
+            place.longitude,
+            place.radius,
+            place.visits.len(),
+            place.dwell().as_secs_f64(),
+            escape_csv(&first.map(|visit| rfc3339(visit.arrived)).unwrap_or_default()),
+            escape_csv(&last.map(|visit| rfc3339(visit.departed)).unwrap_or_default()),
+        )?;
+    }
+    Ok(())
+}
+
+/// 장소와 방문을 JSON 배열로 쓴다.
+pub fn write_json<W: Write>(out: W, places: &[Place]) -> io::Result<()> {
+    let summary: Vec<_> = places
+        .iter()
+        .map(|place| {
+            let visits: Vec<_> = place
+                .visits
+                .iter()
+                .map(|visit| {
+                    json!({
+                        "device": visit.device,
+                        "latitude": visit.latitude,
+                        "longitude": visit.longitude,
+                        "arrived": rfc3339(visit.arrived),
+                        "departed": rfc3339(visit.departed),
+                        "dwell_s": visit.dwell().as_secs_f64(),
+                    })
+                })
+                .collect();
+            json!({
+                "place": place.id,
+                "latitude": place.latitude,
+                "longitude": place.longitude,
+                "radius_m": place.radius,
+                "dwell_s": place.dwell().as_secs_f64(),
+                "visits": visits,
+            })
+        })
+        .collect();
+    serde_json::to_writer_pretty(out, &summary).map_err(io::Error::from)
+}
+
+#[cfg(feature = "history")]
+impl crate::History {
+    /// 조건에 맞는 위치에서 기기별로 방문을 찾고, 모든 기기의 방문을 함께 장소로 묶는다.
+    pub fn places(&self, query: &crate::HistoryQuery, detector: &PlaceDetector) -> Result<Vec<Place>, crate::WeimError> {
+        let mut visits = Vec::new();
+        for track in self.tracks(query)? {
+            visits.extend(detector.visits(&DeviceId(track.name), &track.points));
+        }
+        Ok(detector.cluster(visits))
+    }
+}
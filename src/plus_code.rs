@@ -0,0 +1,320 @@
+//! Open Location Code(Plus Code) 인코딩과 디코딩
+//!
+//! `8Q98HXGC+Q4`처럼 주소가 없는 곳도 가리킬 수 있는 코드다. 현재 위치 근처라면
+//! `HXGC+Q4`처럼 앞자리를 뺀 짧은 코드로 나누고, 받은 쪽은 자기 위치를 기준으로 되살린다.
+
+use crate::geo::{normalize_longitude, Coordinate, METERS_PER_DEGREE};
+use crate::i18n::tr;
+use crate::{Location, WeimError};
+
+/// 가장 긴 자릿수 (구분자 제외)
+pub const MAX_DIGITS: usize = 15;
+
+const ALPHABET: &[u8; 20] = b"23456789CFGHJMPQRVWX";
+const SEPARATOR: char = '+';
+const SEPARATOR_POSITION: usize = 8;
+const PADDING: char = '0';
+// 10자리까지는 위도, 경도를 20진수로 번갈아 쓰고, 그 뒤로는 5행 4열 격자를 한 자리씩 쓴다
+const PAIR_LENGTH: usize = 10;
+const PAIR_PRECISION: f64 = 8000.0;
+const PAIR_FIRST_PLACE: i64 = 160_000;
+const GRID_ROWS: i64 = 5;
+const GRID_COLUMNS: i64 = 4;
+const GRID_LAT_FIRST_PLACE: i64 = 625;
+const GRID_LNG_FIRST_PLACE: i64 = 256;
+const FINAL_LAT_PRECISION: f64 = 25_000_000.0;
+const FINAL_LNG_PRECISION: f64 = 8_192_000.0;
+// 이만큼 줄일 수 있는 칸 크기(°)와 뺄 앞자리 수
+const SHORTEN_STEPS: [(f64, usize); 3] = [(0.0025, 8), (0.05, 6), (1.0, 4)];
+const MIN_TRIMMABLE_LENGTH: usize = 6;
+
+/// Plus Code 하나가 가리키는 영역 (°)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CodeArea {
+    pub south: f64,
+    pub west: f64,
+    pub north: f64,
+    pub east: f64,
+    /// 구분자와 채움 글자를 뺀 자릿수
+    pub length: usize,
+}
+
+impl CodeArea {
+    /// 영역의 중심 `(위도, 경도)`
+    pub fn center(&self) -> (f64, f64) {
+        (((self.south + self.north) / 2.0).min(90.0), ((self.west + self.east) / 2.0).min(180.0))
+    }
+}
+
+/// 좌표를 `length`자리 코드로 바꾼다. 10자리는 약 14m, 11자리는 약 3m 영역이다.
+///
+/// 자릿수는 2~15로 맞추고, 10자리보다 짧으면 짝수로 내린다.
+pub fn encode(coordinate: impl Coordinate, length: usize) -> String {
+    let mut length = length.clamp(2, MAX_DIGITS);
+    if length < PAIR_LENGTH {
+        length -= length % 2;
+    }
+    let mut latitude = coordinate.latitude().clamp(-90.0, 90.0);
+    let longitude = normalize_longitude(coordinate.longitude());
+    // 북극은 가장 북쪽 칸에 넣는다
+    if latitude == 90.0 {
+        latitude -= latitude_precision(length);
+    }
+
+    // 부동소수점 오차로 한 칸 아래로 떨어지지 않게 반올림한 뒤 내린다
+    let mut lat = (((latitude + 90.0) * FINAL_LAT_PRECISION * 1e6).round() / 1e6).floor() as i64;
+    let mut lng = (((longitude + 180.0) * FINAL_LNG_PRECISION * 1e6).round() / 1e6).floor() as i64;
+
+    let mut digits = Vec::with_capacity(MAX_DIGITS);
+    if length > PAIR_LENGTH {
+        for _ in 0..MAX_DIGITS - PAIR_LENGTH {
+            digits.push(ALPHABET[((lat % GRID_ROWS) * GRID_COLUMNS + lng % GRID_COLUMNS) as usize]);
+            lat /= GRID_ROWS;
+            lng /= GRID_COLUMNS;
+        }
+    } else {
+        lat /= GRID_ROWS.pow(5);
+        lng /= GRID_COLUMNS.pow(5);
+    }
+    for _ in 0..PAIR_LENGTH / 2 {
+        digits.push(ALPHABET[(lng % 20) as usize]);
+        digits.push(ALPHABET[(lat % 20) as usize]);
+        lat /= 20;
+        lng /= 20;
+    }
+    digits.reverse();
+
+    let mut code: String = digits.iter().take(length).map(|&b| b as char).collect();
+    if length < SEPARATOR_POSITION {
+        code.extend(std::iter::repeat_n(PADDING, SEPARATOR_POSITION - length));
+    }
+    code.insert(SEPARATOR_POSITION, SEPARATOR);
+    code
+}
+
+/// 전체 코드가 가리키는 영역. 짧은 코드는 `recover()`로 먼저 되살린다.
+pub fn decode(code: &str) -> Result<CodeArea, WeimError> {
+    if !is_full(code) {
+        return Err(invalid(code));
+    }
+    let digits: Vec<usize> = code
+        .chars()
+        .filter(|&c| c != SEPARATOR && c != PADDING)
+        .take(MAX_DIGITS)
+        .filter_map(digit)
+        .collect();
+
+    let (mut lat, mut lng) = (-90 * PAIR_PRECISION as i64, -180 * PAIR_PRECISION as i64);
+    let pairs = digits.len().min(PAIR_LENGTH);
+    let mut place = PAIR_FIRST_PLACE;
+    for i in (0..pairs).step_by(2) {
+        lat += digits[i] as i64 * place;
+        lng += digits[i + 1] as i64 * place;
+        if i < pairs - 2 {
+            place /= 20;
+        }
+    }
+    let mut lat_size = place as f64 / PAIR_PRECISION;
+    let mut lng_size = place as f64 / PAIR_PRECISION;
+
+    let (mut grid_lat, mut grid_lng) = (0, 0);
+    if digits.len() > PAIR_LENGTH {
+        let (mut row_place, mut column_place) = (GRID_LAT_FIRST_PLACE, GRID_LNG_FIRST_PLACE);
+        for (i, &digit) in digits.iter().enumerate().skip(PAIR_LENGTH) {
+            grid_lat += digit as i64 / GRID_COLUMNS * row_place;
+            grid_lng += digit as i64 % GRID_COLUMNS * column_place;
+            if i < digits.len() - 1 {
+                row_place /= GRID_ROWS;
+                column_place /= GRID_COLUMNS;
+            }
+        }
+        lat_size = row_place as f64 / FINAL_LAT_PRECISION;
+        lng_size = column_place as f64 / FINAL_LNG_PRECISION;
+    }
+
+    let south = lat as f64 / PAIR_PRECISION + grid_lat as f64 / FINAL_LAT_PRECISION;
+    let west = lng as f64 / PAIR_PRECISION + grid_lng as f64 / FINAL_LNG_PRECISION;
+    Ok(CodeArea { south, west, north: south + lat_size, east: west + lng_size, length: digits.len() })
+}
+
+/// `reference` 근처라면 앞자리를 뺀 짧은 코드. 너무 멀거나 채움 글자가 있는 코드는 그대로 돌려준다.
+pub fn shorten(code: &str, reference: impl Coordinate) -> Result<String, WeimError> {
+    let area = decode(code)?;
+    let code = code.to_ascii_uppercase();
+    if code.contains(PADDING) || area.length < MIN_TRIMMABLE_LENGTH {
+        return Ok(code);
+    }
+    let (latitude, longitude) = area.center();
+    let range = (latitude - reference.latitude().clamp(-90.0, 90.0))
+        .abs()
+        .max((longitude - normalize_longitude(reference.longitude())).abs());
+    // 받는 쪽의 기준 위치가 조금 달라도 같은 코드로 되살아나도록 칸 크기의 30% 안에서만 줄인다
+    for (size, removed) in SHORTEN_STEPS {
+        if range < size * 0.3 {
+            return Ok(code[removed..].to_string());
+        }
+    }
+    Ok(code)
+}
+
+/// 짧은 코드를 `reference`에서 가장 가까운 전체 코드로 되살린다. 전체 코드는 그대로 돌려준다.
+pub fn recover(code: &str, reference: impl Coordinate) -> Result<String, WeimError> {
+    if is_full(code) {
+        return Ok(code.to_ascii_uppercase());
+    }
+    let separator = match code.find(SEPARATOR) {
+        Some(separator) if is_valid(code) && separator < SEPARATOR_POSITION && !code.contains(PADDING) => separator,
+        _ => return Err(invalid(code)),
+    };
+    let latitude = reference.latitude().clamp(-90.0, 90.0);
+    let longitude = normalize_longitude(reference.longitude());
+
+    // 빠진 앞자리는 기준 위치의 코드에서 가져온다
+    let missing = SEPARATOR_POSITION - separator;
+    let resolution = 20f64.powi(2 - (missing / 2) as i32);
+    let half = resolution / 2.0;
+    let prefix = encode((latitude, longitude), PAIR_LENGTH);
+    let area = decode(&format!("{}{}", &prefix[..missing], code.to_ascii_uppercase()))?;
+
+    // 기준 위치에서 반 칸보다 멀면 옆 칸이 더 가깝다
+    let (mut center_lat, mut center_lng) = area.center();
+    if latitude + half < center_lat && center_lat - resolution >= -90.0 {
+        center_lat -= resolution;
+    } else if latitude - half > center_lat && center_lat + resolution <= 90.0 {
+        center_lat += resolution;
+    }
+    if longitude + half < center_lng {
+        center_lng -= resolution;
+    } else if longitude - half > center_lng {
+        center_lng += resolution;
+    }
+    Ok(encode((center_lat, center_lng), area.length))
+}
+
+/// 문법에 맞는 코드인지 여부 (전체 코드와 짧은 코드 모두)
+pub fn is_valid(code: &str) -> bool {
+    let Some(separator) = code.find(SEPARATOR) else {
+        return false;
+    };
+    if code.rfind(SEPARATOR) != Some(separator) || separator > SEPARATOR_POSITION || separator % 2 == 1 {
+        return false;
+    }
+    // 채움 글자는 구분자 바로 앞까지 짝수 위치에서 시작하고, 구분자 뒤에는 아무것도 없어야 한다
+    if let Some(padding) = code.find(PADDING) {
+        if separator < SEPARATOR_POSITION || padding == 0 || padding % 2 == 1 {
+            return false;
+        }
+        if !code[padding..separator].chars().all(|c| c == PADDING) || code.len() > separator + 1 {
+            return false;
+        }
+    }
+    // 자리가 하나도 없거나 구분자 뒤에 한 글자만 오면 안 된다
+    if code.len() < 2 || code.len() - separator - 1 == 1 {
+        return false;
+    }
+    code.chars().all(|c| c == SEPARATOR || c == PADDING || digit(c).is_some())
+}
+
+/// 앞자리를 빼지 않은 코드인지 여부
+pub fn is_full(code: &str) -> bool {
+    if !is_valid(code) || code.find(SEPARATOR) != Some(SEPARATOR_POSITION) {
+        return false;
+    }
+    // 첫 두 자리가 위도 ±90°, 경도 ±180° 안에 들어야 한다
+    let mut chars = code.chars();
+    let first_lat = chars.next().and_then(digit).unwrap_or(0);
+    let first_lng = chars.next().and_then(digit).unwrap_or(0);
+    first_lat * 20 < 180 && first_lng * 20 < 360
+}
+
+fn digit(c: char) -> Option<usize> {
+    ALPHABET.iter().position(|&b| b as char == c.to_ascii_uppercase())
+}
+
+fn latitude_precision(length: usize) -> f64 {
+    if length <= PAIR_LENGTH {
+        20f64.powi(2 - (length / 2) as i32)
+    } else {
+        20f64.powi(-3) / (GRID_ROWS as f64).powi((length - PAIR_LENGTH) as i32)
+    }
+}
+
+fn invalid(code: &str) -> WeimError {
+    WeimError::InvalidPayload(tr!("올바른 Plus Code가 아닙니다: {:?}", "not a valid Plus Code: {:?}", code))
+}
+
+impl Location {
+    /// 이 위치의 `length`자리 Plus Code
+    pub fn plus_code(&self, length: usize) -> String {
+        encode(self, length)
+    }
+
+    /// `reference` 근처에서 나눌 짧은 10자리 Plus Code
+    pub fn short_plus_code(&self, reference: impl Coordinate) -> String {
+        let code = encode(self, PAIR_LENGTH);
+        shorten(&code, reference).unwrap_or(code)
+    }
+
+    /// 전체 Plus Code 영역의 중심 위치. 정확도는 영역 높이와 너비 중 긴 쪽의 절반이다.
+    ///
+    /// 짧은 코드는 `plus_code::recover()`로 먼저 되살린다.
+    pub fn from_plus_code(code: &str) -> Result<Location, WeimError> {
+        let area = decode(code)?;
+        let (latitude, longitude) = area.center();
+        let height = (area.north - area.south) * METERS_PER_DEGREE;
+        let width = (area.east - area.west) * METERS_PER_DEGREE * latitude.to_radians().cos();
+        Ok(Location { latitude, longitude, accuracy: height.max(width) / 2.0, ..Location::default() })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn decodes_reference_code() {
+        let area = decode("8FVC9G8F+6X").unwrap();
+        assert_eq!(area.length, 10);
+        assert!(close(area.south, 47.3655) && close(area.north, 47.365625));
+        assert!(close(area.west, 8.524875) && close(area.east, 8.525));
+    }
+
+    #[test]
+    fn encodes_reference_code() {
+        assert_eq!(encode((47.3655625, 8.5249375), 10), "8FVC9G8F+6X");
+        assert_eq!(encode((47.3655625, 8.5249375), 4), "8FVC0000+");
+        // 공식 시험 자료 (encoding.csv)
+        assert_eq!(encode((20.3701135, 2.78223535156), 11), "7FG49QCJ+2VX");
+    }
+
+    // 공식 시험 자료 (shortCodeTests.csv)
+    #[test]
+    fn shortens_and_recovers() {
+        let code = "9C3W9QCJ+2VX";
+        for (reference, short) in [
+            ((51.3701125, -1.217765625), "+2VX"),
+            ((51.3708675, -1.217765625), "CJ+2VX"),
+            ((51.3693575, -1.217765625), "CJ+2VX"),
+            ((51.3701125, -1.217010625), "CJ+2VX"),
+            ((51.3701125, -1.218520625), "CJ+2VX"),
+        ] {
+            assert_eq!(shorten(code, reference).unwrap(), short);
+            assert_eq!(recover(short, reference).unwrap(), code);
+        }
+        assert_eq!(recover("9G8F+6X", (47.4, 8.6)).unwrap(), "8FVC9G8F+6X");
+    }
+
+    #[test]
+    fn validates_codes() {
+        assert!(is_full("8FVC9G8F+6X"));
+        assert!(is_valid("9G8F+6X") && !is_full("9G8F+6X"));
+        assert!(!is_valid("8FVC9G8F6X"));
+        assert!(!is_valid("8FVC9G8F+6"));
+        assert!(!is_valid("8FVC9G8F+6XA"));
+        assert!(decode("9G8F+6X").is_err());
+    }
+}
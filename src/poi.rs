@@ -0,0 +1,193 @@
+//! 현재 위치 주변의 장소(POI) 찾기 (`poi` 기능 필요)
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::OnceLock;
+use serde::{Deserialize, Serialize};
+
+use crate::geo::{haversine, initial_bearing};
+use crate::i18n::pick;
+use crate::{http, Location, WeimError};
+
+const DEFAULT_ENDPOINT: &str = "https://overpass-api.de/api/interpreter";
+// Overpass 서버에서 쿼리를 실행할 최대 시간 (초)
+const QUERY_TIMEOUT: u32 = 25;
+
+/// 찾을 장소 종류
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Category {
+    Cafe,
+    Restaurant,
+    Pharmacy,
+    Hospital,
+    Toilets,
+    Atm,
+    FuelStation,
+    Parking,
+    /// 버스 정류장, 지하철·기차역, 트램 정류장
+    TransitStop,
+    /// 직접 지정한 OSM 태그 (예: `("shop", "convenience")`)
+    Tag(String, String),
+}
+
+impl Category {
+    // 장소를 고르는 OSM 태그들. 하나라도 맞으면 된다.
+    fn selectors(&self) -> Vec<(&str, &str)> {
+        match self {
+            Category::Cafe => vec![("amenity", "cafe")],
+            Category::Restaurant => vec![("amenity", "restaurant")],
+            Category::Pharmacy => vec![("amenity", "pharmacy")],
+            Category::Hospital => vec![("amenity", "hospital")],
+            Category::Toilets => vec![("amenity", "toilets")],
+            Category::Atm => vec![("amenity", "atm")],
+            Category::FuelStation => vec![("amenity", "fuel")],
+            Category::Parking => vec![("amenity", "parking")],
+            Category::TransitStop => vec![
+                ("highway", "bus_stop"),
+                ("railway", "station"),
+                ("railway", "tram_stop"),
+                ("public_transport", "station"),
+            ],
+            Category::Tag(key, value) => vec![(key.as_str(), value.as_str())],
+        }
+    }
+}
+
+impl fmt::Display for Category {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Category::Cafe => f.write_str(pick("카페", "cafe")),
+            Category::Restaurant => f.write_str(pick("식당", "restaurant")),
+            Category::Pharmacy => f.write_str(pick("약국", "pharmacy")),
+            Category::Hospital => f.write_str(pick("병원", "hospital")),
+            Category::Toilets => f.write_str(pick("화장실", "toilets")),
+            Category::Atm => write!(f, "ATM"),
+            Category::FuelStation => f.write_str(pick("주유소", "fuel station")),
+            Category::Parking => f.write_str(pick("주차장", "parking")),
+            Category::TransitStop => f.write_str(pick("대중교통 정류장", "transit stop")),
+            Category::Tag(key, value) => write!(f, "{}={}", key, value),
+        }
+    }
+}
+
+/// 주변에서 찾은 장소
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Poi {
+    /// OSM 요소 이름 (예: "node/123")
+    pub id: String,
+    /// 이름. 이름이 없는 장소도 있다.
+    pub name: Option<String>,
+    /// 위도 (°). 건물 같은 영역은 중심점
+    pub latitude: f64,
+    /// 경도 (°)
+    pub longitude: f64,
+    /// 기준 위치에서의 거리 (m)
+    pub distance: f64,
+    /// 기준 위치에서 본 방위각 (°, 북쪽 기준 시계 방향)
+    pub bearing: f64,
+    /// OSM 태그 전체
+    pub tags: HashMap<String, String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OverpassResponse {
+    #[serde(default)]
+    elements: Vec<Element>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Element {
+    #[serde(rename = "type")]
+    kind: String,
+    id: i64,
+    lat: Option<f64>,
+    lon: Option<f64>,
+    // way와 relation은 `out center`로 중심점을 받는다
+    center: Option<Center>,
+    #[serde(default)]
+    tags: HashMap<String, String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Center {
+    lat: f64,
+    lon: f64,
+}
+
+/// OpenStreetMap Overpass API 클라이언트
+#[derive(Debug, Clone)]
+pub struct Overpass {
+    endpoint: String,
+}
+
+impl Default for Overpass {
+    fn default() -> Self {
+        Overpass { endpoint: DEFAULT_ENDPOINT.to_string() }
+    }
+}
+
+impl Overpass {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Overpass 서버 주소 (기본값 overpass-api.de)
+    pub fn endpoint(mut self, url: impl Into<String>) -> Self {
+        self.endpoint = url.into();
+        self
+    }
+
+    /// `location`에서 `radius_m` 미터 안의 `category` 장소들. 가까운 순서로 돌려준다.
+    pub fn nearby(&self, location: &Location, radius_m: f64, category: &Category) -> Result<Vec<Poi>, WeimError> {
+        let around = format!("(around:{:.0},{},{})", radius_m, location.latitude, location.longitude);
+        let filters: String = category
+            .selectors()
+            .iter()
+            .map(|(key, value)| format!("nwr[\"{}\"=\"{}\"]{};", escape(key), escape(value), around))
+            .collect();
+        let query = format!("[out:json][timeout:{}];({});out center tags;", QUERY_TIMEOUT, filters);
+
+        let response: OverpassResponse = http::get_json(&self.endpoint, &[("data", query)])?;
+        let mut places: Vec<Poi> = response
+            .elements
+            .into_iter()
+            .filter_map(|element| element.into_poi(location))
+            // 영역의 중심은 반경 밖에 있을 수 있다
+            .filter(|poi| poi.distance <= radius_m)
+            .collect();
+        places.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+        Ok(places)
+    }
+}
+
+impl Element {
+    fn into_poi(self, origin: &Location) -> Option<Poi> {
+        let (latitude, longitude) = match (self.lat, self.lon, self.center) {
+            (Some(lat), Some(lon), _) => (lat, lon),
+            (_, _, Some(center)) => (center.lat, center.lon),
+            _ => return None,
+        };
+        Some(Poi {
+            id: format!("{}/{}", self.kind, self.id),
+            name: self.tags.get("name").cloned(),
+            latitude,
+            longitude,
+            distance: haversine(origin, (latitude, longitude)),
+            bearing: initial_bearing(origin, (latitude, longitude)),
+            tags: self.tags,
+        })
+    }
+}
+
+// Overpass QL 문자열 안에서 따옴표와 역슬래시를 이스케이프한다
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+impl Location {
+    /// 이 위치에서 `radius_m` 미터 안의 장소를 가까운 순서로 찾는다. 기본 Overpass 서버를 쓴다. (`poi` 기능 필요)
+    pub fn nearby(&self, radius_m: f64, category: Category) -> Result<Vec<Poi>, WeimError> {
+        static OVERPASS: OnceLock<Overpass> = OnceLock::new();
+        OVERPASS.get_or_init(Overpass::default).nearby(self, radius_m, &category)
+    }
+}
@@ -0,0 +1,96 @@
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tiny_http::Server;
+
+use crate::handler::{Handler, Outcome};
+use crate::locator::{serve_request, POLL_INTERVAL};
+
+// 앞선 요청이 끝나기를 기다려 주는 최대 시간. 넘으면 느린 요청을 건너뛰고 뒤의 결과를 먼저 넘긴다
+const REORDER_WINDOW: Duration = Duration::from_secs(1);
+
+/// 요청을 받은 순서대로 매긴 번호와 처리 결과
+pub(crate) type Numbered = (u64, Outcome);
+
+/// 요청 하나를 받아 번호를 매기고 처리하는 일을 `stop`이 켜질 때까지 되풀이한다.
+///
+/// 요청을 꺼내고 번호를 매기는 동안만 잠그므로, 본문을 늦게 보내는 클라이언트가 있어도 다른 스레드는 계속 받는다.
+/// 서버가 닫히면 끝나고, 모든 스레드가 끝나면 `results`가 끊긴다.
+pub(crate) fn work(
+    server: &Server,
+    handler: &Arc<Handler>,
+    next: &Mutex<u64>,
+    stop: &AtomicBool,
+    results: Sender<Numbered>,
+    #[cfg(feature = "websocket")] upgrades: &Sender<Outcome>,
+) {
+    while !stop.load(Ordering::Relaxed) {
+        let (sequence, request) = {
+            let mut next = next.lock().unwrap();
+            match server.recv_timeout(POLL_INTERVAL) {
+                Ok(Some(request)) => {
+                    *next += 1;
+                    (*next - 1, request)
+                }
+                Ok(None) => continue,
+                Err(_) => return,
+            }
+        };
+        let outcome = serve_request(
+            handler,
+            request,
+            #[cfg(feature = "websocket")]
+            upgrades,
+        );
+        if results.send((sequence, outcome)).is_err() {
+            return;
+        }
+    }
+}
+
+/// 돌아가며 끝난 요청의 결과를 받은 순서대로 되돌린다.
+///
+/// 휴대폰 한 대가 위치를 잇달아 보내도 기록과 구독자에게는 보낸 순서대로 간다.
+/// 앞선 요청이 `REORDER_WINDOW`보다 오래 걸리면 기다리지 않고, 그 결과는 끝나는 대로 넘긴다.
+#[derive(Default)]
+pub(crate) struct Reorder {
+    next: u64,
+    waiting: BTreeMap<u64, (Instant, Outcome)>,
+}
+
+impl Reorder {
+    pub(crate) fn push(&mut self, (sequence, outcome): Numbered) {
+        self.waiting.insert(sequence, (Instant::now(), outcome));
+    }
+
+    /// 이제 넘겨도 되는 결과들
+    pub(crate) fn ready(&mut self) -> Vec<Outcome> {
+        let mut ready = Vec::new();
+        while let Some(mut entry) = self.waiting.first_entry() {
+            let sequence = *entry.key();
+            // 건너뛰었던 느린 요청이 이제 끝났다
+            let late = sequence < self.next;
+            let gave_up = entry.get_mut().0.elapsed() >= REORDER_WINDOW;
+            if !late && sequence != self.next && !gave_up {
+                break;
+            }
+            let (_, outcome) = entry.remove();
+            if !late {
+                self.next = sequence + 1;
+            }
+            ready.push(outcome);
+        }
+        ready
+    }
+}
+
+/// 버릴 때 일꾼들을 멈추게 한다. `thread::scope`가 기다리기 전에 불리도록 범위 안에 둔다.
+pub(crate) struct StopOnDrop<'a>(pub(crate) &'a AtomicBool);
+
+impl Drop for StopOnDrop<'_> {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,69 @@
+//! 좌표 뭉개기
+//!
+//! 대략적인 위치만 필요한 프로그램을 위해, 받은 좌표를 격자나 소수점 자리에 맞춰 거칠게 만든 뒤에 돌려주고 기록한다.
+
+use crate::geo::METERS_PER_DEGREE;
+use crate::Location;
+
+// 부동소수점 오차로 한 칸 아래로 내려가지 않게 더하는 값
+const EPSILON: f64 = 1e-9;
+
+/// 좌표를 얼마나 거칠게 만들지
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Coarsening {
+    /// 한 변이 이 길이(m)인 격자의 가운데로 옮긴다. 같은 칸 안의 위치는 모두 같은 좌표가 된다.
+    Grid(f64),
+    /// 위도와 경도를 소수점 아래 이 자리까지만 남기고 버린다. 3자리면 100m 안팎이다.
+    Decimals(u8),
+}
+
+impl Coarsening {
+    /// `location`의 좌표를 거칠게 만든다. 정확도는 적어도 옮겨질 수 있는 거리만큼 늘린다.
+    ///
+    /// 이미 거칠게 만든 위치에 다시 적용해도 달라지지 않는다.
+    pub fn apply(&self, location: &mut Location) {
+        let (latitude, longitude, error) = match *self {
+            Coarsening::Grid(meters) => grid(location.latitude, location.longitude, meters),
+            Coarsening::Decimals(digits) => decimals(location.latitude, location.longitude, digits),
+        };
+        location.latitude = latitude;
+        location.longitude = longitude;
+        location.accuracy = location.accuracy.max(error);
+    }
+}
+
+// 격자 칸의 가운데. 경도 방향 칸은 위도에 맞춰 넓혀서 정사각형에 가깝게 한다.
+// 칸이 위도 180°와 경도 360°를 똑같이 나누도록 조금 줄여서, 끝에 자투리 칸이 생기지 않게 한다.
+fn grid(latitude: f64, longitude: f64, meters: f64) -> (f64, f64, f64) {
+    if meters.is_nan() || meters <= 0.0 {
+        return (latitude, longitude, 0.0);
+    }
+    let snap = |value: f64, range: f64, step: f64| {
+        let cells = (range / step).floor().max(1.0);
+        let step = range / cells;
+        let cell = ((value + range / 2.0) / step + EPSILON).floor().clamp(0.0, cells - 1.0);
+        (cell + 0.5) * step - range / 2.0
+    };
+    let latitude = snap(latitude, 180.0, meters / METERS_PER_DEGREE);
+    let longitude = snap((longitude + 180.0).rem_euclid(360.0) - 180.0, 360.0, meters / (METERS_PER_DEGREE * latitude.to_radians().cos()));
+    (latitude, longitude, meters / std::f64::consts::SQRT_2)
+}
+
+// 0 쪽으로 버린다. 옮겨질 수 있는 거리는 한 칸의 대각선이다.
+fn decimals(latitude: f64, longitude: f64, digits: u8) -> (f64, f64, f64) {
+    let scale = 10f64.powi(digits.min(12) as i32);
+    // `+ 0.0`은 -0을 0으로 바꾼다
+    let truncate = |value: f64| (value.abs() * scale + EPSILON).floor().copysign(value) / scale + 0.0;
+    let (latitude, longitude) = (truncate(latitude), truncate(longitude));
+    let meters = METERS_PER_DEGREE / scale;
+    (latitude, longitude, meters.hypot(meters * latitude.to_radians().cos()))
+}
+
+impl Location {
+    /// `coarsening`으로 거칠게 만든 위치
+    pub fn coarsened(&self, coarsening: Coarsening) -> Location {
+        let mut location = self.clone();
+        coarsening.apply(&mut location);
+        location
+    }
+}
@@ -0,0 +1,123 @@
+// 횡메르카토르 투영과 타원체. UTM과 한국 좌표계가 같이 쓴다.
+
+/// 지구 타원체 (장반경 m, 편평률)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Ellipsoid {
+    pub a: f64,
+    pub f: f64,
+}
+
+pub(crate) const WGS84: Ellipsoid = Ellipsoid { a: 6_378_137.0, f: 1.0 / 298.257_223_563 };
+pub(crate) const GRS80: Ellipsoid = Ellipsoid { a: 6_378_137.0, f: 1.0 / 298.257_222_101 };
+pub(crate) const BESSEL: Ellipsoid = Ellipsoid { a: 6_377_397.155, f: 1.0 / 299.152_812_8 };
+
+impl Ellipsoid {
+    fn e2(&self) -> f64 {
+        self.f * (2.0 - self.f)
+    }
+
+    /// 타원체 면 위의 위도, 경도(°)를 지구 중심 직교 좌표(m)로
+    pub(crate) fn cartesian(&self, latitude: f64, longitude: f64) -> [f64; 3] {
+        let (phi, lambda) = (latitude.to_radians(), longitude.to_radians());
+        let n = self.a / (1.0 - self.e2() * phi.sin().powi(2)).sqrt();
+        [n * phi.cos() * lambda.cos(), n * phi.cos() * lambda.sin(), n * (1.0 - self.e2()) * phi.sin()]
+    }
+
+    /// 지구 중심 직교 좌표(m)를 위도, 경도(°)로. 높이는 버린다.
+    pub(crate) fn geodetic(&self, [x, y, z]: [f64; 3]) -> (f64, f64) {
+        let p = x.hypot(y);
+        let mut phi = z.atan2(p * (1.0 - self.e2()));
+        // 몇 번이면 mm 아래로 수렴한다
+        for _ in 0..10 {
+            let n = self.a / (1.0 - self.e2() * phi.sin().powi(2)).sqrt();
+            let height = p / phi.cos() - n;
+            phi = z.atan2(p * (1.0 - self.e2() * n / (n + height)));
+        }
+        (phi.to_degrees(), y.atan2(x).to_degrees())
+    }
+}
+
+/// 횡메르카토르 투영 (Krüger 급수, n의 3차까지라 mm 정도의 오차)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct TransverseMercator {
+    pub ellipsoid: Ellipsoid,
+    /// 원점 위도 (°)
+    pub origin_latitude: f64,
+    /// 중앙 자오선 (°)
+    pub central_meridian: f64,
+    pub scale: f64,
+    pub false_easting: f64,
+    pub false_northing: f64,
+}
+
+impl TransverseMercator {
+    /// 위도, 경도(°)를 `(동향, 북향)`(m)으로
+    pub(crate) fn forward(&self, latitude: f64, longitude: f64) -> (f64, f64) {
+        let (easting, northing) = self.raw(latitude, longitude - self.central_meridian);
+        (self.false_easting + easting, self.false_northing + northing - self.raw(self.origin_latitude, 0.0).1)
+    }
+
+    /// `(동향, 북향)`(m)을 위도, 경도(°)로
+    pub(crate) fn inverse(&self, easting: f64, northing: f64) -> (f64, f64) {
+        let series = Series::new(self.ellipsoid);
+        let radius = self.scale * series.a;
+        let xi = (northing - self.false_northing + self.raw(self.origin_latitude, 0.0).1) / radius;
+        let eta = (easting - self.false_easting) / radius;
+
+        let (mut xi_prime, mut eta_prime) = (xi, eta);
+        for (j, beta) in series.beta.iter().enumerate() {
+            let k = 2.0 * (j + 1) as f64;
+            xi_prime -= beta * (k * xi).sin() * (k * eta).cosh();
+            eta_prime -= beta * (k * xi).cos() * (k * eta).sinh();
+        }
+        let chi = (xi_prime.sin() / eta_prime.cosh()).asin();
+        let mut phi = chi;
+        for (j, delta) in series.delta.iter().enumerate() {
+            phi += delta * (2.0 * (j + 1) as f64 * chi).sin();
+        }
+        let lambda = (eta_prime.sinh() / xi_prime.cos()).atan();
+        (phi.to_degrees(), self.central_meridian + lambda.to_degrees())
+    }
+
+    // 원점 위도와 가산값을 빼기 전의 투영 (중앙 자오선에서 잰 경도)
+    fn raw(&self, latitude: f64, longitude: f64) -> (f64, f64) {
+        let series = Series::new(self.ellipsoid);
+        let (phi, lambda) = (latitude.to_radians(), longitude.to_radians());
+
+        let e = 2.0 * series.n.sqrt() / (1.0 + series.n);
+        let t = (phi.sin().atanh() - e * (e * phi.sin()).atanh()).sinh();
+        let xi = (t / lambda.cos()).atan();
+        let eta = (lambda.sin() / (1.0 + t * t).sqrt()).atanh();
+
+        let (mut easting, mut northing) = (eta, xi);
+        for (j, alpha) in series.alpha.iter().enumerate() {
+            let k = 2.0 * (j + 1) as f64;
+            easting += alpha * (k * xi).cos() * (k * eta).sinh();
+            northing += alpha * (k * xi).sin() * (k * eta).cosh();
+        }
+        (self.scale * series.a * easting, self.scale * series.a * northing)
+    }
+}
+
+// 전개 계수
+struct Series {
+    n: f64,
+    a: f64,
+    alpha: [f64; 3],
+    beta: [f64; 3],
+    delta: [f64; 3],
+}
+
+impl Series {
+    fn new(ellipsoid: Ellipsoid) -> Self {
+        let n = ellipsoid.f / (2.0 - ellipsoid.f);
+        let (n2, n3) = (n * n, n * n * n);
+        Series {
+            n,
+            a: ellipsoid.a / (1.0 + n) * (1.0 + n2 / 4.0 + n2 * n2 / 64.0),
+            alpha: [n / 2.0 - 2.0 * n2 / 3.0 + 5.0 * n3 / 16.0, 13.0 * n2 / 48.0 - 3.0 * n3 / 5.0, 61.0 * n3 / 240.0],
+            beta: [n / 2.0 - 2.0 * n2 / 3.0 + 37.0 * n3 / 96.0, n2 / 48.0 + n3 / 15.0, 17.0 * n3 / 480.0],
+            delta: [2.0 * n - 2.0 * n2 / 3.0 - 2.0 * n3, 7.0 * n2 / 3.0 - 8.0 * n3 / 5.0, 56.0 * n3 / 15.0],
+        }
+    }
+}
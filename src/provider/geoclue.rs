@@ -0,0 +1,173 @@
+use std::thread;
+use std::time::{Duration, Instant};
+use chrono::DateTime;
+use zbus::blocking::{proxy, Connection, Proxy};
+use zbus::proxy::CacheProperties;
+use zbus::zvariant::OwnedObjectPath;
+
+use crate::i18n::pick;
+use crate::locator::POLL_INTERVAL;
+use crate::provider::LocationProvider;
+use crate::{Location, WeimError};
+
+const SERVICE: &str = "org.freedesktop.GeoClue2";
+const MANAGER_PATH: &str = "/org/freedesktop/GeoClue2/Manager";
+
+/// GeoClue2에 요청할 정확도 단계
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccuracyLevel {
+    Country,
+    City,
+    Neighborhood,
+    Street,
+    Exact,
+}
+
+impl AccuracyLevel {
+    // GClueAccuracyLevel 값
+    fn as_u32(self) -> u32 {
+        match self {
+            AccuracyLevel::Country => 1,
+            AccuracyLevel::City => 4,
+            AccuracyLevel::Neighborhood => 5,
+            AccuracyLevel::Street => 6,
+            AccuracyLevel::Exact => 8,
+        }
+    }
+}
+
+/// 리눅스 데스크톱의 GeoClue2 서비스를 D-Bus로 부르는 공급자 (`geoclue` 기능 필요)
+///
+/// GNOME/KDE처럼 위치 에이전트가 떠 있는 환경에서 브라우저 없이 위치를 받는다.
+#[derive(Debug, Clone)]
+pub struct GeoClueProvider {
+    desktop_id: String,
+    accuracy_level: AccuracyLevel,
+    timeout: Duration,
+}
+
+impl Default for GeoClueProvider {
+    fn default() -> Self {
+        GeoClueProvider {
+            desktop_id: "weim".to_string(),
+            accuracy_level: AccuracyLevel::Exact,
+            timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+impl GeoClueProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// GeoClue에 알릴 앱 ID. 에이전트의 권한 창과 설정에 이 이름이 쓰인다. (기본값 "weim")
+    pub fn desktop_id(mut self, id: impl Into<String>) -> Self {
+        self.desktop_id = id.into();
+        self
+    }
+
+    /// 요청할 정확도 (기본값 `AccuracyLevel::Exact`)
+    pub fn accuracy_level(mut self, level: AccuracyLevel) -> Self {
+        self.accuracy_level = level;
+        self
+    }
+
+    /// 첫 위치를 기다리는 최대 시간 (기본값 30초)
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    fn proxy<'a>(&self, conn: &Connection, path: &'a str, interface: &'a str) -> Result<Proxy<'a>, WeimError> {
+        proxy::Builder::new(conn)
+            .destination(SERVICE)
+            .and_then(|builder| builder.path(path))
+            .and_then(|builder| builder.interface(interface))
+            .map(|builder| builder.cache_properties(CacheProperties::No))
+            .and_then(|builder| builder.build())
+            .map_err(dbus_error)
+    }
+}
+
+impl LocationProvider for GeoClueProvider {
+    fn name(&self) -> &str {
+        "geoclue"
+    }
+
+    fn locate(&mut self) -> Result<Location, WeimError> {
+        let conn = Connection::system().map_err(dbus_error)?;
+
+        let manager = self.proxy(&conn, MANAGER_PATH, "org.freedesktop.GeoClue2.Manager")?;
+        let client_path: OwnedObjectPath = manager.call("GetClient", &()).map_err(dbus_error)?;
+
+        let client = self.proxy(&conn, client_path.as_str(), "org.freedesktop.GeoClue2.Client")?;
+        client
+            .set_property("DesktopId", self.desktop_id.as_str())
+            .map_err(|e| dbus_error(e.into()))?;
+        client
+            .set_property("RequestedAccuracyLevel", self.accuracy_level.as_u32())
+            .map_err(|e| dbus_error(e.into()))?;
+        client.call::<_, _, ()>("Start", &()).map_err(dbus_error)?;
+
+        // 위치가 잡히면 Location 속성이 "/" 대신 위치 객체 경로를 가리킨다
+        let started = Instant::now();
+        let result = loop {
+            let path: OwnedObjectPath = match client.get_property("Location") {
+                Ok(path) => path,
+                Err(e) => break Err(dbus_error(e)),
+            };
+            if path.as_str() != "/" {
+                break self.read_location(&conn, path.as_str());
+            }
+            if started.elapsed() >= self.timeout {
+                break Err(WeimError::Timeout);
+            }
+            thread::sleep(POLL_INTERVAL);
+        };
+
+        client.call::<_, _, ()>("Stop", &()).ok();
+        result
+    }
+}
+
+impl GeoClueProvider {
+    fn read_location(&self, conn: &Connection, path: &str) -> Result<Location, WeimError> {
+        let location = self.proxy(conn, path, "org.freedesktop.GeoClue2.Location")?;
+        let get = |name: &str| location.get_property::<f64>(name).map_err(dbus_error);
+        let (seconds, micros): (u64, u64) = location.get_property("Timestamp").map_err(dbus_error)?;
+
+        Ok(Location {
+            latitude: get("Latitude")?,
+            longitude: get("Longitude")?,
+            accuracy: get("Accuracy")?,
+            timestamp: DateTime::from_timestamp_micros((seconds * 1_000_000 + micros) as i64).unwrap_or_default(),
+            ..Location::default()
+        })
+    }
+}
+
+// 에이전트가 없거나 사용자가 거부한 경우를 구분해서 돌려준다
+fn dbus_error(e: zbus::Error) -> WeimError {
+    match &e {
+        zbus::Error::MethodError(name, message, _) if name.as_str() == "org.freedesktop.DBus.Error.AccessDenied" => {
+            let message = message.clone().unwrap_or_default();
+            if message.contains("agent") {
+                WeimError::AgentUnavailable(message)
+            } else {
+                WeimError::PermissionDenied
+            }
+        }
+        zbus::Error::MethodError(name, _, _) if name.as_str() == "org.freedesktop.DBus.Error.ServiceUnknown" => {
+            WeimError::Platform(pick("GeoClue2 서비스가 설치되어 있지 않습니다", "the GeoClue2 service is not installed").to_string())
+        }
+        zbus::Error::FDO(fdo) => match fdo.as_ref() {
+            zbus::fdo::Error::AccessDenied(message) if message.contains("agent") => {
+                WeimError::AgentUnavailable(message.clone())
+            }
+            zbus::fdo::Error::AccessDenied(_) => WeimError::PermissionDenied,
+            _ => WeimError::Platform(e.to_string()),
+        },
+        _ => WeimError::Platform(e.to_string()),
+    }
+}
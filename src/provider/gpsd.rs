@@ -0,0 +1,206 @@
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::time::{Duration, Instant};
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::Deserialize;
+
+use crate::provider::LocationProvider;
+use crate::{Location, Source, WeimError};
+
+const DEFAULT_ADDR: &str = "127.0.0.1:2947";
+const WATCH_COMMAND: &[u8] = b"?WATCH={\"enable\":true,\"json\":true}\n";
+
+// 오차 추정값을 주지 않는 수신기에 붙일 정확도 (m)
+const UNKNOWN_ACCURACY: f64 = 100.0;
+
+// gpsd가 보내는 보고서 중 TPV(시간-위치-속도)에서 쓰는 필드만
+#[derive(Debug, Deserialize)]
+struct Report {
+    class: String,
+    #[serde(default)]
+    mode: u8,
+    lat: Option<f64>,
+    lon: Option<f64>,
+    #[serde(rename = "altHAE")]
+    alt_hae: Option<f64>,
+    alt: Option<f64>,
+    speed: Option<f64>,
+    track: Option<f64>,
+    time: Option<String>,
+    eph: Option<f64>,
+    epx: Option<f64>,
+    epy: Option<f64>,
+    epv: Option<f64>,
+}
+
+// 보고서 한 줄을 읽은 결과
+enum Line {
+    // 줄을 다 읽었다. 읽을 수 없는 줄이면 None
+    Complete(Option<Box<Report>>),
+    // 아직 줄 끝이 오지 않았다
+    Incomplete,
+}
+
+impl Report {
+    // mode 2 = 2D, 3 = 3D 측위
+    fn into_location(self) -> Option<Location> {
+        if self.class != "TPV" || self.mode < 2 {
+            return None;
+        }
+
+        let accuracy = self
+            .eph
+            .or_else(|| self.epx.zip(self.epy).map(|(x, y)| x.hypot(y)))
+            .unwrap_or(UNKNOWN_ACCURACY);
+        let timestamp = self
+            .time
+            .as_deref()
+            .and_then(|time| DateTime::parse_from_rfc3339(time).ok())
+            .map(|time| time.to_utc())
+            .unwrap_or_else(Utc::now);
+
+        Some(Location {
+            latitude: self.lat?,
+            longitude: self.lon?,
+            accuracy,
+            altitude: if self.mode >= 3 { self.alt_hae.or(self.alt) } else { None },
+            altitude_accuracy: if self.mode >= 3 { self.epv } else { None },
+            speed: self.speed,
+            heading: self.track,
+            timestamp,
+            device_timestamp: None,
+            address: None,
+            compass: None,
+            weather: None,
+            device_info: None,
+            source: Source::Measured,
+        })
+    }
+}
+
+/// gpsd에 연결해서 실제 GPS 수신기의 위치를 받는 공급자
+///
+/// 연결은 호출 사이에 유지된다. `locate()`는 그동안 쌓인 보고서 중 가장 최근 측위를 돌려주고,
+/// `max_age()`보다 오래된 측위는 버리고 새 측위를 기다린다.
+#[derive(Debug)]
+pub struct GpsdProvider {
+    addr: String,
+    timeout: Duration,
+    max_age: Duration,
+    reader: Option<BufReader<TcpStream>>,
+    // 아직 줄바꿈이 오지 않은 보고서의 앞부분
+    line: Vec<u8>,
+}
+
+impl Default for GpsdProvider {
+    fn default() -> Self {
+        GpsdProvider {
+            addr: DEFAULT_ADDR.to_string(),
+            timeout: Duration::from_secs(30),
+            max_age: Duration::from_secs(10),
+            reader: None,
+            line: Vec::new(),
+        }
+    }
+}
+
+impl GpsdProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// gpsd 주소 (기본값 127.0.0.1:2947)
+    pub fn addr(mut self, addr: impl Into<String>) -> Self {
+        self.addr = addr.into();
+        self
+    }
+
+    /// 측위 하나를 기다리는 최대 시간 (기본값 30초)
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// 돌려줄 측위의 최대 나이. 수신기가 잰 시각부터 잰다 (기본값 10초)
+    pub fn max_age(mut self, age: Duration) -> Self {
+        self.max_age = age;
+        self
+    }
+
+    fn connect(&mut self) -> Result<(), WeimError> {
+        if self.reader.is_none() {
+            self.line.clear();
+            let mut stream = TcpStream::connect(&self.addr)?;
+            stream.write_all(WATCH_COMMAND)?;
+            self.reader = Some(BufReader::new(stream));
+        }
+        Ok(())
+    }
+
+    // 보고서 한 줄을 읽는다. `wait`가 None이면 기다리지 않는다. 읽다 만 줄은 다음에 이어 읽는다
+    fn read_line(&mut self, wait: Option<Duration>) -> Result<Line, WeimError> {
+        self.connect()?;
+        let reader = self.reader.as_mut().unwrap();
+        let stream = reader.get_ref();
+        stream.set_nonblocking(wait.is_none())?;
+        if let Some(wait) = wait {
+            stream.set_read_timeout(Some(wait))?;
+        }
+        match reader.read_until(b'\n', &mut self.line) {
+            Ok(0) => {
+                self.reader = None;
+                Err(WeimError::Io(io::ErrorKind::UnexpectedEof.into()))
+            }
+            Ok(_) if self.line.ends_with(b"\n") => {
+                let report = serde_json::from_slice(&self.line).ok().map(Box::new);
+                self.line.clear();
+                Ok(Line::Complete(report))
+            }
+            Ok(_) => Ok(Line::Incomplete),
+            Err(e) if wait.is_none() && e.kind() == io::ErrorKind::WouldBlock => Ok(Line::Incomplete),
+            Err(e) if matches!(e.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut) => Err(WeimError::Timeout),
+            Err(e) => {
+                self.reader = None;
+                Err(e.into())
+            }
+        }
+    }
+
+    fn fresh(&self, location: &Location) -> bool {
+        TimeDelta::from_std(self.max_age).is_ok_and(|max_age| Utc::now() - location.timestamp <= max_age)
+    }
+}
+
+impl LocationProvider for GpsdProvider {
+    fn name(&self) -> &str {
+        "gpsd"
+    }
+
+    fn locate(&mut self) -> Result<Location, WeimError> {
+        // 지난 호출 뒤로 쌓인 보고서를 모두 읽고 가장 최근 측위만 남긴다.
+        // VERSION, DEVICES, SKY 같은 다른 보고서는 건너뛴다
+        let mut newest = None;
+        while let Line::Complete(report) = self.read_line(None)? {
+            if let Some(location) = report.and_then(|report| report.into_location()) {
+                newest = Some(location);
+            }
+        }
+        if let Some(location) = newest.filter(|location| self.fresh(location)) {
+            return Ok(location);
+        }
+
+        let deadline = Instant::now() + self.timeout;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Err(WeimError::Timeout);
+            }
+            if let Line::Complete(Some(report)) = self.read_line(Some(remaining))?
+                && let Some(location) = report.into_location()
+                && self.fresh(&location)
+            {
+                return Ok(location);
+            }
+        }
+    }
+}
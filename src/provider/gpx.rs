@@ -0,0 +1,202 @@
+use std::fs;
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant};
+use chrono::{DateTime, Utc};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::{Reader, XmlVersion};
+
+use crate::i18n::{pick, tr};
+use crate::provider::LocationProvider;
+use crate::{Location, WeimError};
+
+// 시각 정보가 없는 포인트 사이의 간격
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(1);
+// hdop이 없을 때 붙일 정확도 (m)
+const DEFAULT_ACCURACY: f64 = 5.0;
+const METERS_PER_HDOP: f64 = 5.0;
+
+#[derive(Debug, Clone)]
+struct TrackPoint {
+    location: Location,
+    time: Option<DateTime<Utc>>,
+}
+
+/// GPX 파일의 트랙 포인트를 실제 움직이는 것처럼 차례로 돌려주는 공급자 (`gpx` 기능 필요)
+///
+/// 포인트 사이의 시간 간격만큼 기다렸다가 돌려주므로, 움직이지 않고도
+/// 지오펜스나 추적 로직을 시험해 볼 수 있다.
+#[derive(Debug, Clone)]
+pub struct GpxProvider {
+    points: Vec<TrackPoint>,
+    next: usize,
+    speed: f64,
+    repeat: bool,
+    original_timestamps: bool,
+    // 마지막으로 돌려준 시점과 그 포인트의 기록 시각
+    last: Option<(Instant, Option<DateTime<Utc>>)>,
+}
+
+impl GpxProvider {
+    /// GPX 파일을 읽는다.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, WeimError> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    /// GPX 문서 문자열을 읽는다. `<trkpt>`, `<rtept>`를 문서 순서대로 재생한다.
+    pub fn parse(gpx: &str) -> Result<Self, WeimError> {
+        let points = parse_points(gpx)?;
+        if points.is_empty() {
+            return Err(WeimError::InvalidPayload(pick("GPX에 트랙 포인트가 없습니다", "the GPX has no track points").to_string()));
+        }
+        Ok(GpxProvider {
+            points,
+            next: 0,
+            speed: 1.0,
+            repeat: false,
+            original_timestamps: false,
+            last: None,
+        })
+    }
+
+    /// 재생 배속 (기본값 1.0 = 실제 시간). 0 이하나 무한대면 기다리지 않고 바로 돌려준다.
+    pub fn speed(mut self, multiplier: f64) -> Self {
+        self.speed = multiplier;
+        self
+    }
+
+    /// 끝까지 재생한 뒤 처음부터 다시 재생할지 여부 (기본값 false)
+    pub fn repeat(mut self, enabled: bool) -> Self {
+        self.repeat = enabled;
+        self
+    }
+
+    /// 돌려줄 위치에 GPX에 기록된 시각을 그대로 쓸지 여부 (기본값 false = 현재 시각)
+    pub fn original_timestamps(mut self, enabled: bool) -> Self {
+        self.original_timestamps = enabled;
+        self
+    }
+
+    /// 남은 포인트 수
+    pub fn remaining(&self) -> usize {
+        self.points.len() - self.next
+    }
+
+    fn wait_for(&self, point: &TrackPoint) {
+        let Some((emitted_at, previous)) = self.last else {
+            return;
+        };
+        if self.speed <= 0.0 || !self.speed.is_finite() {
+            return;
+        }
+
+        let gap = match (previous, point.time) {
+            (Some(previous), Some(time)) => (time - previous).to_std().unwrap_or_default(),
+            _ => DEFAULT_INTERVAL,
+        };
+        let due = emitted_at + gap.div_f64(self.speed);
+        thread::sleep(due.saturating_duration_since(Instant::now()));
+    }
+}
+
+impl LocationProvider for GpxProvider {
+    fn name(&self) -> &str {
+        "gpx"
+    }
+
+    fn locate(&mut self) -> Result<Location, WeimError> {
+        if self.next >= self.points.len() {
+            if !self.repeat {
+                return Err(WeimError::Exhausted);
+            }
+            self.next = 0;
+            self.last = None;
+        }
+
+        let point = self.points[self.next].clone();
+        self.wait_for(&point);
+        self.next += 1;
+        self.last = Some((Instant::now(), point.time));
+
+        let mut location = point.location;
+        location.timestamp = match point.time {
+            Some(time) if self.original_timestamps => time,
+            _ => Utc::now(),
+        };
+        Ok(location)
+    }
+}
+
+fn parse_points(gpx: &str) -> Result<Vec<TrackPoint>, WeimError> {
+    let mut reader = Reader::from_str(gpx);
+    reader.config_mut().trim_text(true);
+
+    let mut points = Vec::new();
+    let mut current: Option<TrackPoint> = None;
+    let mut field: Option<String> = None;
+
+    loop {
+        let event = reader
+            .read_event()
+            .map_err(|e| WeimError::InvalidPayload(tr!("GPX 해석 실패: {}", "cannot parse the GPX: {}", e)))?;
+        match event {
+            Event::Start(e) if is_point(&e) => current = Some(start_point(&e)?),
+            Event::Empty(e) if is_point(&e) => points.push(start_point(&e)?),
+            Event::Start(e) => field = Some(e.local_name().as_ref().to_string()),
+            Event::Text(text) => {
+                if let (Some(point), Some(name)) = (current.as_mut(), field.as_deref()) {
+                    let text = text.xml10_content();
+                    match name {
+                        "ele" => point.location.altitude = text.trim().parse().ok(),
+                        "time" => {
+                            point.time = DateTime::parse_from_rfc3339(text.trim())
+                                .ok()
+                                .map(|time| time.with_timezone(&Utc))
+                        }
+                        "hdop" => {
+                            if let Ok(hdop) = text.trim().parse::<f64>() {
+                                point.location.accuracy = hdop * METERS_PER_HDOP;
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            Event::End(e) => {
+                let name = e.local_name();
+                if matches!(name.as_ref(), "trkpt" | "rtept") {
+                    points.extend(current.take());
+                }
+                field = None;
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(points)
+}
+
+fn is_point(e: &BytesStart) -> bool {
+    matches!(e.local_name().as_ref(), "trkpt" | "rtept")
+}
+
+fn start_point(e: &BytesStart) -> Result<TrackPoint, WeimError> {
+    let coordinate = |name: &str| -> Result<f64, WeimError> {
+        e.try_get_attribute(name)
+            .ok()
+            .flatten()
+            .and_then(|attr| attr.normalized_value(XmlVersion::Implicit1_0).ok()?.trim().parse().ok())
+            .ok_or_else(|| WeimError::InvalidPayload(tr!("GPX 포인트에 {} 값이 없습니다", "a GPX point has no {} value", name)))
+    };
+
+    Ok(TrackPoint {
+        location: Location {
+            latitude: coordinate("lat")?,
+            longitude: coordinate("lon")?,
+            accuracy: DEFAULT_ACCURACY,
+            ..Location::default()
+        },
+        time: None,
+    })
+}
@@ -0,0 +1,68 @@
+use serde::Deserialize;
+
+use crate::provider::LocationProvider;
+use crate::{http, Location, WeimError};
+
+const DEFAULT_ENDPOINT: &str = "http://ip-api.com/json/";
+
+// 대부분의 IP 위치 서비스는 lat/lon 이나 latitude/longitude 를 돌려준다
+#[derive(Debug, Deserialize)]
+struct IpResponse {
+    #[serde(alias = "lat")]
+    latitude: f64,
+    #[serde(alias = "lon")]
+    longitude: f64,
+}
+
+/// 공인 IP로 대략적인 위치를 조회하는 공급자 (`ip-lookup` 기능 필요)
+///
+/// 도시 단위 정확도이므로 다른 공급자가 실패했을 때의 대안으로 쓰는 것이 좋다.
+#[derive(Debug, Clone)]
+pub struct IpProvider {
+    endpoint: String,
+    accuracy: f64,
+}
+
+impl Default for IpProvider {
+    fn default() -> Self {
+        IpProvider {
+            endpoint: DEFAULT_ENDPOINT.to_string(),
+            accuracy: 5000.0,
+        }
+    }
+}
+
+impl IpProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 조회할 서비스 주소 (기본값 ip-api.com)
+    pub fn endpoint(mut self, url: impl Into<String>) -> Self {
+        self.endpoint = url.into();
+        self
+    }
+
+    /// 결과에 붙일 정확도 (m, 기본값 5000)
+    pub fn accuracy(mut self, meters: f64) -> Self {
+        self.accuracy = meters;
+        self
+    }
+}
+
+impl LocationProvider for IpProvider {
+    fn name(&self) -> &str {
+        "ip"
+    }
+
+    fn locate(&mut self) -> Result<Location, WeimError> {
+        let response: IpResponse = http::get_json(&self.endpoint, &[])?;
+        Ok(Location {
+            latitude: response.latitude,
+            longitude: response.longitude,
+            accuracy: self.accuracy,
+            timestamp: chrono::Utc::now(),
+            ..Location::default()
+        })
+    }
+}
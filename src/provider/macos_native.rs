@@ -0,0 +1,100 @@
+use std::time::{Duration, Instant};
+use chrono::DateTime;
+use objc2_core_location::{
+    kCLLocationAccuracyBest, kCLLocationAccuracyHundredMeters, CLAuthorizationStatus, CLLocationManager,
+};
+use objc2_foundation::{NSDate, NSRunLoop};
+
+use crate::i18n::pick;
+use crate::provider::LocationProvider;
+use crate::{Location, WeimError};
+
+/// macOS CoreLocation을 쓰는 공급자 (`macos-native` 기능 필요)
+///
+/// 처음 실행하면 시스템 권한 창이 뜬다. 거부하면 `WeimError::PermissionDenied`를 돌려준다.
+#[derive(Debug, Clone)]
+pub struct MacOsProvider {
+    high_accuracy: bool,
+    timeout: Duration,
+}
+
+impl Default for MacOsProvider {
+    fn default() -> Self {
+        MacOsProvider {
+            high_accuracy: true,
+            timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+impl MacOsProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 최고 정밀도를 요청할지 여부 (기본값 true)
+    pub fn high_accuracy(mut self, enabled: bool) -> Self {
+        self.high_accuracy = enabled;
+        self
+    }
+
+    /// 권한 응답과 첫 위치를 기다리는 최대 시간 (기본값 30초)
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+impl LocationProvider for MacOsProvider {
+    fn name(&self) -> &str {
+        "macos"
+    }
+
+    fn locate(&mut self) -> Result<Location, WeimError> {
+        // SAFETY: 매니저는 이 스레드에서만 만들고 쓰며, 런 루프도 같은 스레드에서 돌린다.
+        unsafe {
+            if !CLLocationManager::locationServicesEnabled_class() {
+                return Err(WeimError::Platform(pick("위치 서비스가 꺼져 있습니다", "location services are turned off").to_string()));
+            }
+
+            let manager = CLLocationManager::new();
+            let accuracy = if self.high_accuracy {
+                kCLLocationAccuracyBest
+            } else {
+                kCLLocationAccuracyHundredMeters
+            };
+            manager.setDesiredAccuracy(accuracy);
+            manager.requestWhenInUseAuthorization();
+            manager.startUpdatingLocation();
+
+            // 델리게이트 대신 런 루프를 조금씩 돌리면서 상태를 확인한다
+            let run_loop = NSRunLoop::currentRunLoop();
+            let started = Instant::now();
+            let result = loop {
+                let status = manager.authorizationStatus();
+                if status == CLAuthorizationStatus::Denied || status == CLAuthorizationStatus::Restricted {
+                    break Err(WeimError::PermissionDenied);
+                }
+
+                if let Some(location) = manager.location() {
+                    let coordinate = location.coordinate();
+                    break Ok(Location {
+                        latitude: coordinate.latitude,
+                        longitude: coordinate.longitude,
+                        accuracy: location.horizontalAccuracy(),
+                        timestamp: DateTime::from_timestamp_millis((location.timestamp().timeIntervalSince1970() * 1000.0) as i64).unwrap_or_default(),
+                        ..Location::default()
+                    });
+                }
+
+                if started.elapsed() >= self.timeout {
+                    break Err(WeimError::Timeout);
+                }
+                run_loop.runUntilDate(&NSDate::dateWithTimeIntervalSinceNow(0.1));
+            };
+
+            manager.stopUpdatingLocation();
+            result
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use std::env;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::i18n::tr;
+use crate::provider::LocationProvider;
+use crate::{Location, WeimError};
+
+/// 이 환경 변수에 "위도,경도[,정확도]"를 넣으면 모든 `locate()`가 브라우저 대신 그 위치를 돌려준다.
+pub const MOCK_ENV: &str = "WEIM_MOCK";
+
+enum Script {
+    Fixed(Box<Location>),
+    Sequence { fixes: Vec<Location>, next: usize, repeat: bool },
+    Function { f: Box<dyn FnMut(Duration) -> Location + Send>, started: Option<Instant> },
+}
+
+/// 정해진 각본대로 위치를 돌려주는 공급자
+///
+/// 브라우저를 열거나 포트를 쓰지 않으므로 weim을 쓰는 코드의 테스트에 쓸 수 있다.
+pub struct MockProvider {
+    script: Script,
+}
+
+impl fmt::Debug for MockProvider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match &self.script {
+            Script::Fixed(_) => "fixed",
+            Script::Sequence { .. } => "sequence",
+            Script::Function { .. } => "function",
+        };
+        f.debug_struct("MockProvider").field("script", &kind).finish()
+    }
+}
+
+impl MockProvider {
+    /// 항상 같은 위치를 돌려준다.
+    pub fn new(location: Location) -> Self {
+        MockProvider { script: Script::Fixed(Box::new(location)) }
+    }
+
+    /// `new()`와 같다.
+    pub fn fixed(location: Location) -> Self {
+        Self::new(location)
+    }
+
+    /// 호출할 때마다 목록의 다음 위치를 돌려주고, 끝나면 `WeimError::Exhausted`를 돌려준다.
+    pub fn sequence(fixes: Vec<Location>) -> Self {
+        MockProvider {
+            script: Script::Sequence { fixes, next: 0, repeat: false },
+        }
+    }
+
+    /// 첫 호출부터 지난 시간을 받아 위치를 만든다.
+    pub fn from_fn(f: impl FnMut(Duration) -> Location + Send + 'static) -> Self {
+        MockProvider {
+            script: Script::Function { f: Box::new(f), started: None },
+        }
+    }
+
+    /// `sequence()` 목록이 끝나면 처음부터 다시 돌려줄지 여부 (기본값 false)
+    pub fn repeat(mut self, enabled: bool) -> Self {
+        if let Script::Sequence { repeat, .. } = &mut self.script {
+            *repeat = enabled;
+        }
+        self
+    }
+
+    /// `WEIM_MOCK` 환경 변수가 있으면 그 위치를 돌려주는 공급자를 만든다.
+    pub fn from_env() -> Option<Result<Self, WeimError>> {
+        let value = env::var(MOCK_ENV).ok()?;
+        Some(parse_env(&value).map(Self::new))
+    }
+}
+
+impl LocationProvider for MockProvider {
+    fn name(&self) -> &str {
+        "mock"
+    }
+
+    fn locate(&mut self) -> Result<Location, WeimError> {
+        match &mut self.script {
+            Script::Fixed(location) => Ok(Location::clone(location)),
+            Script::Sequence { fixes, next, repeat } => {
+                if *next >= fixes.len() && *repeat {
+                    *next = 0;
+                }
+                let location = fixes.get(*next).cloned().ok_or(WeimError::Exhausted)?;
+                *next += 1;
+                Ok(location)
+            }
+            Script::Function { f, started } => {
+                let started = started.get_or_insert_with(Instant::now);
+                Ok(f(started.elapsed()))
+            }
+        }
+    }
+}
+
+fn parse_env(value: &str) -> Result<Location, WeimError> {
+    let invalid = || WeimError::InvalidPayload(tr!(
+            "{} 값은 \"위도,경도[,정확도]\" 형식이어야 합니다: {}",
+            "{} must look like \"latitude,longitude[,accuracy]\": {}",
+            MOCK_ENV,
+            value
+        ));
+    let numbers = value
+        .split(',')
+        .map(|part| part.trim().parse::<f64>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| invalid())?;
+
+    match numbers.as_slice() {
+        [latitude, longitude] | [latitude, longitude, _] => Ok(Location {
+            latitude: *latitude,
+            longitude: *longitude,
+            accuracy: numbers.get(2).copied().unwrap_or(0.0),
+            timestamp: chrono::Utc::now(),
+            ..Location::default()
+        }),
+        _ => Err(invalid()),
+    }
+}
@@ -0,0 +1,171 @@
+#[cfg(all(target_os = "linux", feature = "geoclue"))]
+mod geoclue;
+mod gpsd;
+#[cfg(feature = "gpx")]
+mod gpx;
+#[cfg(feature = "ip-lookup")]
+mod ip;
+#[cfg(all(target_os = "macos", feature = "macos-native"))]
+mod macos_native;
+mod mock;
+mod nmea;
+#[cfg(all(windows, feature = "windows-native"))]
+mod windows_native;
+
+#[cfg(all(target_os = "linux", feature = "geoclue"))]
+pub use geoclue::{AccuracyLevel, GeoClueProvider};
+pub use gpsd::GpsdProvider;
+#[cfg(feature = "gpx")]
+pub use gpx::GpxProvider;
+#[cfg(feature = "ip-lookup")]
+pub use ip::IpProvider;
+#[cfg(all(target_os = "macos", feature = "macos-native"))]
+pub use macos_native::MacOsProvider;
+pub use mock::{MockProvider, MOCK_ENV};
+#[cfg(feature = "nmea")]
+pub use nmea::NmeaProvider;
+pub use nmea::NmeaParser;
+#[cfg(all(windows, feature = "windows-native"))]
+pub use windows_native::WindowsProvider;
+
+use crate::console::{info, warning};
+use crate::i18n::{pick, tr};
+use crate::privacy::Coarsening;
+use crate::{Location, Weim, WeimError};
+
+/// 위치를 구하는 방법 하나
+///
+/// 브라우저, IP 조회, 테스트용 가짜 위치 등 여러 방식을 같은 방법으로 다룰 수 있다.
+pub trait LocationProvider: Send {
+    /// 로그와 오류 메시지에 쓰일 이름
+    fn name(&self) -> &str;
+
+    fn locate(&mut self) -> Result<Location, WeimError>;
+}
+
+impl LocationProvider for Weim {
+    fn name(&self) -> &str {
+        "browser"
+    }
+
+    fn locate(&mut self) -> Result<Location, WeimError> {
+        Weim::locate(self)
+    }
+}
+
+impl<P: LocationProvider + ?Sized> LocationProvider for Box<P> {
+    fn name(&self) -> &str {
+        (**self).name()
+    }
+
+    fn locate(&mut self) -> Result<Location, WeimError> {
+        (**self).locate()
+    }
+}
+
+/// 앞의 공급자가 실패하면 다음 공급자를 차례로 시도한다.
+///
+/// 예: 브라우저 → IP 조회 → 마지막으로 받은 위치
+#[derive(Default)]
+pub struct FallbackChain {
+    providers: Vec<Box<dyn LocationProvider>>,
+    use_last_known: bool,
+    last_known: Option<Location>,
+    coarse_location: Option<Coarsening>,
+}
+
+impl FallbackChain {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 켜진 기능에 맞춰 구성한 기본 체인
+    ///
+    /// 운영체제 위치 API → 브라우저(`weim`) → IP 조회 → 마지막으로 받은 위치 순서로 시도한다.
+    /// 설정 파일에 `providers`가 있으면 그 순서를 따르고, `coarse_location`을 켰으면 모든 공급자의 위치에 적용한다.
+    pub fn recommended(weim: Weim) -> Self {
+        let coarse_location = weim.config.coarse_location;
+        #[cfg(feature = "config")]
+        if let Some(providers) = weim.config.providers.clone() {
+            return FallbackChain { coarse_location, ..crate::settings::provider_chain(&providers, weim) };
+        }
+
+        let chain = FallbackChain::new();
+
+        #[cfg(all(windows, feature = "windows-native"))]
+        let chain = chain.with(WindowsProvider::new());
+
+        #[cfg(all(target_os = "macos", feature = "macos-native"))]
+        let chain = chain.with(MacOsProvider::new());
+
+        #[cfg(all(target_os = "linux", feature = "geoclue"))]
+        let chain = chain.with(GeoClueProvider::new());
+
+        let chain = chain.with(weim);
+
+        #[cfg(feature = "ip-lookup")]
+        let chain = chain.with(IpProvider::new());
+
+        FallbackChain { coarse_location, ..chain.last_known_fallback(true) }
+    }
+
+    /// 체인 끝에 공급자를 추가한다.
+    pub fn with(mut self, provider: impl LocationProvider + 'static) -> Self {
+        self.providers.push(Box::new(provider));
+        self
+    }
+
+    /// 모두 실패했을 때 마지막으로 성공한 위치를 돌려줄지 여부 (기본값 false)
+    pub fn last_known_fallback(mut self, enabled: bool) -> Self {
+        self.use_last_known = enabled;
+        self
+    }
+
+    /// 어느 공급자가 찾았든 좌표를 거칠게 만든 뒤에 돌려준다. (기본값 없음 = 그대로)
+    pub fn coarse_location(mut self, coarsening: Coarsening) -> Self {
+        self.coarse_location = Some(coarsening);
+        self
+    }
+
+    /// 이 체인에서 마지막으로 성공한 위치
+    pub fn last_known(&self) -> Option<&Location> {
+        self.last_known.as_ref()
+    }
+}
+
+impl LocationProvider for FallbackChain {
+    fn name(&self) -> &str {
+        "chain"
+    }
+
+    fn locate(&mut self) -> Result<Location, WeimError> {
+        let mut errors = Vec::new();
+
+        for provider in &mut self.providers {
+            match provider.locate() {
+                Ok(mut location) => {
+                    if let Some(coarsening) = self.coarse_location {
+                        coarsening.apply(&mut location);
+                    }
+                    self.last_known = Some(location.clone());
+                    return Ok(location);
+                }
+                // 취소는 다음 공급자로 넘기지 않는다
+                Err(WeimError::Cancelled) => return Err(WeimError::Cancelled),
+                Err(e) => {
+                    warning!("{}", tr!("⚠️ {} 공급자 실패: {}", "⚠️ Provider {} failed: {}", provider.name(), e));
+                    errors.push((provider.name().to_string(), e));
+                }
+            }
+        }
+
+        if self.use_last_known
+            && let Some(location) = &self.last_known
+        {
+            info!("{}", pick("📦 마지막으로 받은 위치를 사용합니다.", "📦 Using the last received location."));
+            return Ok(location.clone());
+        }
+
+        Err(WeimError::AllProvidersFailed(errors))
+    }
+}
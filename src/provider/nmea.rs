@@ -0,0 +1,353 @@
+use chrono::{NaiveDate, NaiveTime, Utc};
+
+use crate::{Location, Source};
+
+// HDOP 1당 대략적인 수평 오차 (m)
+const METERS_PER_HDOP: f64 = 5.0;
+// GGA 없이 RMC만 받았을 때의 정확도 (m)
+const UNKNOWN_ACCURACY: f64 = 100.0;
+const KNOTS_TO_MPS: f64 = 0.514_444;
+
+#[derive(Debug, Clone)]
+struct Gga {
+    latitude: f64,
+    longitude: f64,
+    hdop: Option<f64>,
+    altitude: Option<f64>,
+}
+
+#[derive(Debug, Clone)]
+struct Rmc {
+    latitude: f64,
+    longitude: f64,
+    speed: Option<f64>,
+    heading: Option<f64>,
+    date: Option<NaiveDate>,
+}
+
+enum Sentence {
+    Gga(Gga),
+    Rmc(Rmc),
+}
+
+// 같은 시각에 들어온 GGA/RMC를 하나로 모은다
+#[derive(Debug)]
+struct Pending {
+    time: String,
+    gga: Option<Gga>,
+    rmc: Option<Rmc>,
+}
+
+impl Pending {
+    fn into_location(self) -> Option<Location> {
+        let time = NaiveTime::parse_from_str(&self.time, "%H%M%S%.f").ok();
+        let date = self.rmc.as_ref().and_then(|rmc| rmc.date).unwrap_or_else(|| Utc::now().date_naive());
+        let timestamp = time
+            .map(|time| date.and_time(time).and_utc())
+            .unwrap_or_else(Utc::now);
+
+        match (self.gga, self.rmc) {
+            (Some(gga), rmc) => Some(Location {
+                latitude: gga.latitude,
+                longitude: gga.longitude,
+                accuracy: gga.hdop.map(|hdop| hdop * METERS_PER_HDOP).unwrap_or(UNKNOWN_ACCURACY),
+                altitude: gga.altitude,
+                altitude_accuracy: None,
+                speed: rmc.as_ref().and_then(|rmc| rmc.speed),
+                heading: rmc.as_ref().and_then(|rmc| rmc.heading),
+                timestamp,
+                device_timestamp: None,
+                address: None,
+                compass: None,
+                weather: None,
+                device_info: None,
+                source: Source::Measured,
+            }),
+            (None, Some(rmc)) => Some(Location {
+                latitude: rmc.latitude,
+                longitude: rmc.longitude,
+                accuracy: UNKNOWN_ACCURACY,
+                altitude: None,
+                altitude_accuracy: None,
+                speed: rmc.speed,
+                heading: rmc.heading,
+                timestamp,
+                device_timestamp: None,
+                address: None,
+                compass: None,
+                weather: None,
+                device_info: None,
+                source: Source::Measured,
+            }),
+            (None, None) => None,
+        }
+    }
+}
+
+/// NMEA 0183 문장(GGA, RMC)을 읽어 `Location`으로 바꾸는 파서
+///
+/// 같은 시각의 GGA와 RMC가 모두 들어오면 바로, 한 종류만 보내는 수신기는
+/// 다음 시각의 문장이 들어올 때 위치를 내보낸다.
+#[derive(Debug, Default)]
+pub struct NmeaParser {
+    pending: Option<Pending>,
+}
+
+impl NmeaParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 한 줄을 넣고, 위치가 완성되면 돌려준다. 체크섬이 틀리거나 측위가 없는 문장은 무시한다.
+    pub fn push(&mut self, line: &str) -> Option<Location> {
+        let (time, sentence) = parse_sentence(line)?;
+
+        let mut finished = None;
+        if self.pending.as_ref().is_some_and(|pending| pending.time != time) {
+            finished = self.pending.take().and_then(Pending::into_location);
+        }
+
+        let pending = self.pending.get_or_insert(Pending { time, gga: None, rmc: None });
+        match sentence {
+            Sentence::Gga(gga) => pending.gga = Some(gga),
+            Sentence::Rmc(rmc) => pending.rmc = Some(rmc),
+        }
+
+        if pending.gga.is_some() && pending.rmc.is_some() {
+            return self.pending.take().and_then(Pending::into_location);
+        }
+        finished
+    }
+}
+
+// 문장의 UTC 시각 필드와 내용을 돌려준다
+fn parse_sentence(line: &str) -> Option<(String, Sentence)> {
+    let body = checked_body(line.trim())?;
+    let fields: Vec<&str> = body.split(',').collect();
+    // 앞의 두 글자는 GP, GN, GL 같은 송신기 종류
+    let kind = fields.first()?.get(2..)?;
+
+    match kind {
+        "GGA" if fields.len() >= 10 => {
+            let quality: u8 = fields[6].parse().ok()?;
+            if quality == 0 {
+                return None;
+            }
+            let gga = Gga {
+                latitude: coordinate(fields[2], fields[3])?,
+                longitude: coordinate(fields[4], fields[5])?,
+                hdop: fields[8].parse().ok(),
+                altitude: fields[9].parse().ok(),
+            };
+            Some((fields[1].to_string(), Sentence::Gga(gga)))
+        }
+        "RMC" if fields.len() >= 10 => {
+            if fields[2] != "A" {
+                return None;
+            }
+            let rmc = Rmc {
+                latitude: coordinate(fields[3], fields[4])?,
+                longitude: coordinate(fields[5], fields[6])?,
+                speed: fields[7].parse::<f64>().ok().map(|knots| knots * KNOTS_TO_MPS),
+                heading: fields[8].parse().ok(),
+                date: NaiveDate::parse_from_str(fields[9], "%d%m%y").ok(),
+            };
+            Some((fields[1].to_string(), Sentence::Rmc(rmc)))
+        }
+        _ => None,
+    }
+}
+
+// "$...*hh" 형식을 확인하고 '$'와 체크섬 사이의 내용을 돌려준다
+fn checked_body(line: &str) -> Option<&str> {
+    let line = line.strip_prefix('$')?;
+    let (body, checksum) = match line.split_once('*') {
+        Some((body, checksum)) => (body, Some(checksum)),
+        None => (line, None),
+    };
+    if let Some(checksum) = checksum {
+        let expected = u8::from_str_radix(checksum.get(..2)?, 16).ok()?;
+        if body.bytes().fold(0u8, |acc, b| acc ^ b) != expected {
+            return None;
+        }
+    }
+    Some(body)
+}
+
+// ddmm.mmmm / dddmm.mmmm 형식을 도 단위로 바꾼다
+fn coordinate(value: &str, hemisphere: &str) -> Option<f64> {
+    // 아래에서 바이트 위치로 자르므로 숫자와 점만 받는다
+    if !value.bytes().all(|b| b.is_ascii_digit() || b == b'.') {
+        return None;
+    }
+    let dot = value.find('.').unwrap_or(value.len());
+    if dot < 2 {
+        return None;
+    }
+    let degrees: f64 = value[..dot - 2].parse().ok()?;
+    let minutes: f64 = value[dot - 2..].parse().ok()?;
+    let decimal = degrees + minutes / 60.0;
+    match hemisphere {
+        "N" | "E" => Some(decimal),
+        "S" | "W" => Some(-decimal),
+        _ => None,
+    }
+}
+
+#[cfg(feature = "nmea")]
+pub use serial::NmeaProvider;
+
+#[cfg(feature = "nmea")]
+mod serial {
+    use std::io::{self, BufRead, BufReader};
+    use std::time::{Duration, Instant};
+    use serialport::SerialPort;
+
+    use super::NmeaParser;
+    use crate::provider::LocationProvider;
+    use crate::{Location, WeimError};
+
+    /// 시리얼 포트로 연결된 GPS 모듈에서 NMEA 문장을 읽는 공급자 (`nmea` 기능 필요)
+    ///
+    /// 포트는 호출 사이에 열린 채로 유지된다.
+    pub struct NmeaProvider {
+        path: String,
+        baud_rate: u32,
+        timeout: Duration,
+        reader: Option<BufReader<Box<dyn SerialPort>>>,
+        parser: NmeaParser,
+    }
+
+    impl NmeaProvider {
+        /// 장치 경로(예: `/dev/ttyUSB0`, `COM3`)와 보드레이트로 만든다.
+        pub fn new(path: impl Into<String>, baud_rate: u32) -> Self {
+            NmeaProvider {
+                path: path.into(),
+                baud_rate,
+                timeout: Duration::from_secs(30),
+                reader: None,
+                parser: NmeaParser::new(),
+            }
+        }
+
+        /// 측위 하나를 기다리는 최대 시간 (기본값 30초)
+        pub fn timeout(mut self, timeout: Duration) -> Self {
+            self.timeout = timeout;
+            self
+        }
+
+        fn open(&mut self) -> Result<&mut BufReader<Box<dyn SerialPort>>, WeimError> {
+            if self.reader.is_none() {
+                let port = serialport::new(&self.path, self.baud_rate)
+                    .timeout(Duration::from_secs(1))
+                    .open()
+                    .map_err(io::Error::from)?;
+                self.reader = Some(BufReader::new(port));
+            }
+            Ok(self.reader.as_mut().unwrap())
+        }
+    }
+
+    impl LocationProvider for NmeaProvider {
+        fn name(&self) -> &str {
+            "nmea"
+        }
+
+        fn locate(&mut self) -> Result<Location, WeimError> {
+            let deadline = Instant::now() + self.timeout;
+            let mut buf = Vec::new();
+
+            loop {
+                if Instant::now() >= deadline {
+                    return Err(WeimError::Timeout);
+                }
+
+                let reader = self.open()?;
+                match reader.read_until(b'\n', &mut buf) {
+                    Ok(0) => {
+                        self.reader = None;
+                        return Err(WeimError::Io(io::ErrorKind::UnexpectedEof.into()));
+                    }
+                    // 포트 읽기 제한 시간은 짧게 두고, 전체 제한 시간만 따로 본다
+                    Err(e) if e.kind() == io::ErrorKind::TimedOut => continue,
+                    Err(e) => {
+                        self.reader = None;
+                        return Err(e.into());
+                    }
+                    Ok(_) => {}
+                }
+
+                if buf.ends_with(b"\n") {
+                    let line = String::from_utf8_lossy(&buf).into_owned();
+                    buf.clear();
+                    if let Some(location) = self.parser.push(&line) {
+                        return Ok(location);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::*;
+
+    // NMEA 0183 설명에 흔히 실리는 예
+    const GGA: &str = "$GPGGA,123519,4807.038,N,01131.000,E,1,08,0.9,545.4,M,46.9,M,,*47";
+    const RMC: &str = "$GPRMC,123519,A,4807.038,N,01131.000,E,022.4,084.4,230394,003.1,W*6A";
+
+    // 체크섬을 붙인 문장
+    fn sentence(body: &str) -> String {
+        format!("${}*{:02X}", body, body.bytes().fold(0u8, |acc, b| acc ^ b))
+    }
+
+    #[test]
+    fn merges_gga_and_rmc() {
+        let mut parser = NmeaParser::new();
+        assert!(parser.push(GGA).is_none());
+        let location = parser.push(RMC).unwrap();
+        assert!((location.latitude - 48.1173).abs() < 1e-9);
+        assert!((location.longitude - (11.0 + 31.0 / 60.0)).abs() < 1e-9);
+        assert_eq!(location.accuracy, 0.9 * METERS_PER_HDOP);
+        assert_eq!(location.altitude, Some(545.4));
+        assert_eq!(location.speed, Some(22.4 * KNOTS_TO_MPS));
+        assert_eq!(location.heading, Some(84.4));
+        assert_eq!(location.timestamp, Utc.with_ymd_and_hms(1994, 3, 23, 12, 35, 19).unwrap());
+    }
+
+    #[test]
+    fn emits_lone_sentence_at_next_time() {
+        let mut parser = NmeaParser::new();
+        assert!(parser.push(GGA).is_none());
+        let next = sentence("GPGGA,123520,4807.040,S,01131.000,W,1,08,0.9,545.4,M,46.9,M,,");
+        let location = parser.push(&next).unwrap();
+        assert!((location.latitude - 48.1173).abs() < 1e-9);
+        assert!(location.speed.is_none());
+        let location = parser.push(RMC).unwrap();
+        assert!(location.latitude < 0.0 && location.longitude < 0.0);
+        assert_eq!(location.accuracy, 0.9 * METERS_PER_HDOP);
+    }
+
+    #[test]
+    fn ignores_bad_checksum_and_no_fix() {
+        let mut parser = NmeaParser::new();
+        assert!(parser.push(&GGA.replace("*47", "*48")).is_none());
+        assert!(parser.push(&RMC.replace("4807.038", "4807.039")).is_none());
+        assert!(parser.push(&sentence("GPGGA,123519,4807.038,N,01131.000,E,0,00,,,M,,M,,")).is_none());
+        assert!(parser.push(&sentence("GPRMC,123519,V,4807.038,N,01131.000,E,,,230394,,")).is_none());
+        assert!(parser.pending.is_none());
+    }
+
+    #[test]
+    fn rejects_non_ascii_input() {
+        let mut parser = NmeaParser::new();
+        assert!(parser.push(&sentence("GPGGA,123519,xé1.5,N,01131.000,E,1,08,0.9,545.4,M,46.9,M,,")).is_none());
+        assert!(parser.push("$GPGGA,123519,4é.038,N,01131.000,E,1,08,0.9,545.4,M,46.9,M,,").is_none());
+        assert!(parser.push("$GéGGA,123519").is_none());
+        assert!(parser.push("$GPGGA,123519*é7").is_none());
+        assert!(parser.pending.is_none());
+    }
+}
@@ -0,0 +1,81 @@
+use chrono::DateTime;
+use windows::Devices::Geolocation::{GeolocationAccessStatus, Geolocator, PositionAccuracy};
+
+use crate::provider::LocationProvider;
+use crate::{Location, WeimError};
+
+// 1601-01-01 기준 100ns 단위 시각을 Unix epoch 밀리초로 바꿀 때의 차이
+const EPOCH_DIFFERENCE: i64 = 116_444_736_000_000_000;
+
+/// Windows 위치 API(`Windows.Devices.Geolocation`)를 쓰는 공급자 (`windows-native` 기능 필요)
+///
+/// 브라우저 없이 바로 위치를 받는다. 설정에서 위치 서비스가 꺼져 있으면
+/// `WeimError::PermissionDenied`를 돌려준다.
+#[derive(Debug, Clone)]
+pub struct WindowsProvider {
+    high_accuracy: bool,
+}
+
+impl Default for WindowsProvider {
+    fn default() -> Self {
+        WindowsProvider { high_accuracy: true }
+    }
+}
+
+impl WindowsProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 고정밀 위치(GPS 등)를 요청할지 여부 (기본값 true)
+    pub fn high_accuracy(mut self, enabled: bool) -> Self {
+        self.high_accuracy = enabled;
+        self
+    }
+}
+
+impl LocationProvider for WindowsProvider {
+    fn name(&self) -> &str {
+        "windows"
+    }
+
+    fn locate(&mut self) -> Result<Location, WeimError> {
+        let access = Geolocator::RequestAccessAsync()
+            .and_then(|operation| operation.join())
+            .map_err(platform_error)?;
+        if access != GeolocationAccessStatus::Allowed {
+            return Err(WeimError::PermissionDenied);
+        }
+
+        let locator = Geolocator::new().map_err(platform_error)?;
+        let accuracy = if self.high_accuracy {
+            PositionAccuracy::High
+        } else {
+            PositionAccuracy::Default
+        };
+        locator.SetDesiredAccuracy(accuracy).map_err(platform_error)?;
+
+        let coordinate = locator
+            .GetGeopositionAsync()
+            .and_then(|operation| operation.join())
+            .and_then(|position| position.Coordinate())
+            .map_err(platform_error)?;
+        let position = coordinate
+            .Point()
+            .and_then(|point| point.Position())
+            .map_err(platform_error)?;
+        let timestamp = coordinate.Timestamp().map_err(platform_error)?;
+
+        Ok(Location {
+            latitude: position.Latitude,
+            longitude: position.Longitude,
+            accuracy: coordinate.Accuracy().map_err(platform_error)?,
+            timestamp: DateTime::from_timestamp_millis((timestamp.UniversalTime - EPOCH_DIFFERENCE) / 10_000).unwrap_or_default(),
+            ..Location::default()
+        })
+    }
+}
+
+fn platform_error(e: windows::core::Error) -> WeimError {
+    WeimError::Platform(e.to_string())
+}
@@ -0,0 +1,319 @@
+//! 파이썬 `weim` 모듈 (`python` 기능 필요)
+//!
+//! `maturin develop --release`나 `pip install .`로 설치한다. 위치를 기다리는 동안 GIL을 놓고,
+//! 주피터 노트북에서 중단을 누르면 서버를 닫고 `KeyboardInterrupt`를 낸다.
+//!
+//! ```python
+//! import weim
+//! here = weim.where_i_am()
+//! for fix in weim.Weim(port=0, verbosity="quiet").watch():
+//!     print(fix.latitude, fix.longitude, fix.timestamp)
+//! ```
+
+use std::path::PathBuf;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+use pyo3::exceptions::{PyPermissionError, PyTimeoutError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use crate::locator::POLL_INTERVAL;
+use crate::{CancellationToken, Language, Location, Source, Verbosity, Watch, Weim, WeimError};
+
+mod exceptions {
+    pyo3::create_exception!(weim, WeimError, pyo3::exceptions::PyRuntimeError, "weim이 위치를 받지 못했을 때");
+}
+
+/// 받은 위치 하나. 시각은 UTC `datetime`이다.
+#[pyclass(name = "Location", module = "weim", frozen, get_all)]
+struct PyLocation {
+    latitude: f64,
+    longitude: f64,
+    accuracy: f64,
+    altitude: Option<f64>,
+    altitude_accuracy: Option<f64>,
+    speed: Option<f64>,
+    heading: Option<f64>,
+    compass: Option<f64>,
+    timestamp: DateTime<Utc>,
+    address: Option<String>,
+    /// "measured" 또는 "manual"
+    source: &'static str,
+}
+
+impl From<Location> for PyLocation {
+    fn from(location: Location) -> Self {
+        PyLocation {
+            latitude: location.latitude,
+            longitude: location.longitude,
+            accuracy: location.accuracy,
+            altitude: location.altitude,
+            altitude_accuracy: location.altitude_accuracy,
+            speed: location.speed,
+            heading: location.heading,
+            compass: location.compass,
+            timestamp: location.timestamp,
+            address: location.address,
+            source: match location.source {
+                Source::Measured => "measured",
+                Source::Manual => "manual",
+            },
+        }
+    }
+}
+
+#[pymethods]
+impl PyLocation {
+    /// 속성을 담은 dict. `pandas.DataFrame([fix.to_dict() for fix in ...])`처럼 쓴다
+    fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let dict = PyDict::new(py);
+        dict.set_item("latitude", self.latitude)?;
+        dict.set_item("longitude", self.longitude)?;
+        dict.set_item("accuracy", self.accuracy)?;
+        dict.set_item("altitude", self.altitude)?;
+        dict.set_item("altitude_accuracy", self.altitude_accuracy)?;
+        dict.set_item("speed", self.speed)?;
+        dict.set_item("heading", self.heading)?;
+        dict.set_item("compass", self.compass)?;
+        dict.set_item("timestamp", self.timestamp)?;
+        dict.set_item("address", &self.address)?;
+        dict.set_item("source", self.source)?;
+        Ok(dict)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Location(latitude={:?}, longitude={:?}, accuracy={:?})", self.latitude, self.longitude, self.accuracy)
+    }
+}
+
+/// 설정한 위치 추적기. 인자는 모두 키워드로 주고, 주지 않으면 `WeimBuilder`의 기본값을 쓴다.
+///
+/// 시간은 초 단위 숫자다.
+#[pyclass(name = "Weim", module = "weim", frozen)]
+struct PyWeim {
+    weim: Weim,
+}
+
+#[pymethods]
+impl PyWeim {
+    #[new]
+    #[pyo3(signature = (
+        *,
+        port = None,
+        lan = None,
+        timeout = None,
+        high_accuracy = None,
+        position_timeout = None,
+        maximum_age = None,
+        accuracy_threshold = None,
+        max_attempts = None,
+        open_browser = None,
+        consent_screen = None,
+        show_map = None,
+        manual_entry = None,
+        cache_file = None,
+        language = None,
+        verbosity = None,
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        port: Option<u16>,
+        lan: Option<bool>,
+        timeout: Option<f64>,
+        high_accuracy: Option<bool>,
+        position_timeout: Option<f64>,
+        maximum_age: Option<f64>,
+        accuracy_threshold: Option<f64>,
+        max_attempts: Option<u32>,
+        open_browser: Option<bool>,
+        consent_screen: Option<bool>,
+        show_map: Option<bool>,
+        manual_entry: Option<bool>,
+        cache_file: Option<PathBuf>,
+        language: Option<&str>,
+        verbosity: Option<&str>,
+    ) -> PyResult<Self> {
+        let mut builder = Weim::builder();
+        if let Some(port) = port {
+            builder = builder.port(port);
+        }
+        if let Some(enabled) = lan {
+            builder = builder.lan(enabled);
+        }
+        if let Some(seconds) = timeout {
+            builder = builder.timeout(seconds_arg("timeout", seconds)?);
+        }
+        if let Some(enabled) = high_accuracy {
+            builder = builder.high_accuracy(enabled);
+        }
+        if let Some(seconds) = position_timeout {
+            builder = builder.position_timeout(seconds_arg("position_timeout", seconds)?);
+        }
+        if let Some(seconds) = maximum_age {
+            builder = builder.maximum_age(seconds_arg("maximum_age", seconds)?);
+        }
+        if let Some(meters) = accuracy_threshold {
+            builder = builder.accuracy_threshold(meters);
+        }
+        if let Some(attempts) = max_attempts {
+            builder = builder.max_attempts(attempts);
+        }
+        if let Some(enabled) = open_browser {
+            builder = builder.open_browser(enabled);
+        }
+        if let Some(enabled) = consent_screen {
+            builder = builder.consent_screen(enabled);
+        }
+        if let Some(enabled) = show_map {
+            builder = builder.show_map(enabled);
+        }
+        if let Some(enabled) = manual_entry {
+            builder = builder.manual_entry(enabled);
+        }
+        if let Some(path) = cache_file {
+            builder = builder.cache_file(path);
+        }
+        if let Some(language) = language {
+            builder = builder.language(match language {
+                "ko" => Language::Korean,
+                "en" => Language::English,
+                "auto" => Language::Auto,
+                other => return Err(PyValueError::new_err(format!("language must be 'ko', 'en' or 'auto', not {:?}", other))),
+            });
+        }
+        if let Some(verbosity) = verbosity {
+            builder = builder.verbosity(match verbosity {
+                "quiet" => Verbosity::Quiet,
+                "errors" => Verbosity::Errors,
+                "warnings" => Verbosity::Warnings,
+                "info" => Verbosity::Info,
+                "debug" => Verbosity::Debug,
+                other => {
+                    return Err(PyValueError::new_err(format!(
+                        "verbosity must be 'quiet', 'errors', 'warnings', 'info' or 'debug', not {:?}",
+                        other
+                    )));
+                }
+            });
+        }
+        Ok(PyWeim { weim: builder.build() })
+    }
+
+    /// 위치를 한 번 받는다.
+    fn locate(&self, py: Python<'_>) -> PyResult<PyLocation> {
+        locate(py, self.weim.clone())
+    }
+
+    /// 서버를 열고 위치를 받는 대로 돌려주는 반복자. `with` 문을 빠져나가거나 `stop()`하면 서버를 닫는다.
+    fn watch(&self, py: Python<'_>) -> PyResult<PyWatch> {
+        let watch = py.detach(|| self.weim.watch()).map_err(error)?;
+        let token = watch.cancellation_token();
+        Ok(PyWatch { watch: Mutex::new(watch), token })
+    }
+}
+
+/// `Weim.watch()`가 돌려주는 위치 반복자
+#[pyclass(name = "Watch", module = "weim", frozen)]
+struct PyWatch {
+    watch: Mutex<Watch>,
+    token: CancellationToken,
+}
+
+#[pymethods]
+impl PyWatch {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&self, py: Python<'_>) -> PyResult<Option<PyLocation>> {
+        if self.token.is_cancelled() {
+            return Ok(None);
+        }
+        receive(py, &self.watch, &self.token, |watch| watch.recv_timeout(POLL_INTERVAL))
+    }
+
+    /// 서버를 닫는다. 반복은 바로 끝난다.
+    fn stop(&self) {
+        self.token.cancel();
+    }
+
+    /// 페이지를 열 주소 (예: "127.0.0.1:3030")
+    #[getter]
+    fn addr(&self) -> String {
+        self.watch.lock().unwrap().addr().to_string()
+    }
+
+    fn __enter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    #[pyo3(signature = (*_args))]
+    fn __exit__(&self, _args: &Bound<'_, pyo3::types::PyTuple>) -> bool {
+        self.token.cancel();
+        false
+    }
+}
+
+/// 기본 설정으로 브라우저에서 위치를 받는다. 실패하면 `weim.WeimError`, `TimeoutError`, `PermissionError`를 낸다.
+#[pyfunction]
+fn where_i_am(py: Python<'_>) -> PyResult<PyLocation> {
+    locate(py, Weim::default())
+}
+
+// 중단 신호를 살필 수 있게 다른 스레드에서 받는다
+fn locate(py: Python<'_>, weim: Weim) -> PyResult<PyLocation> {
+    let token = CancellationToken::new();
+    let (tx, rx) = mpsc::channel();
+    let thread_token = token.clone();
+    thread::spawn(move || {
+        tx.send(weim.locate_cancellable(&thread_token)).ok();
+    });
+    receive(py, &Mutex::new(rx), &token, |rx| rx.recv_timeout(POLL_INTERVAL))?.ok_or_else(|| error(WeimError::ServerClosed))
+}
+
+// GIL을 놓고 기다리다가 틈틈이 중단 신호를 살핀다. 중단하면 `token`을 취소한다. 보낼 쪽이 끝나면 None
+fn receive<R: Send>(
+    py: Python<'_>,
+    source: &Mutex<R>,
+    token: &CancellationToken,
+    recv: impl Fn(&mut R) -> Result<Result<Location, WeimError>, RecvTimeoutError> + Sync,
+) -> PyResult<Option<PyLocation>> {
+    loop {
+        match py.detach(|| recv(&mut source.lock().unwrap())) {
+            Ok(Ok(location)) => return Ok(Some(location.into())),
+            Ok(Err(e)) => return Err(error(e)),
+            Err(RecvTimeoutError::Disconnected) => return Ok(None),
+            Err(RecvTimeoutError::Timeout) => {
+                if let Err(interrupt) = py.check_signals() {
+                    token.cancel();
+                    return Err(interrupt);
+                }
+            }
+        }
+    }
+}
+
+fn error(e: WeimError) -> PyErr {
+    match e {
+        WeimError::Timeout => PyTimeoutError::new_err(e.to_string()),
+        WeimError::PermissionDenied => PyPermissionError::new_err(e.to_string()),
+        e => exceptions::WeimError::new_err(e.to_string()),
+    }
+}
+
+fn seconds_arg(name: &str, seconds: f64) -> PyResult<Duration> {
+    Duration::try_from_secs_f64(seconds).map_err(|_| PyValueError::new_err(format!("{} must be a non-negative number of seconds", name)))
+}
+
+#[pymodule]
+fn weim(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyLocation>()?;
+    m.add_class::<PyWeim>()?;
+    m.add_class::<PyWatch>()?;
+    m.add_function(wrap_pyfunction!(where_i_am, m)?)?;
+    m.add("WeimError", m.py().get_type::<exceptions::WeimError>())?;
+    Ok(())
+}
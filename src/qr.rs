@@ -0,0 +1,21 @@
+use qrcode::render::unicode::Dense1x2;
+use qrcode::QrCode;
+
+use crate::console::{info, warning};
+use crate::i18n::tr;
+
+/// 주소를 터미널에 QR 코드로 출력한다. (`qr` 기능 필요)
+pub(crate) fn print_qr(url: &str) {
+    match QrCode::new(url) {
+        // 어두운 배경의 터미널에서도 읽히도록 색을 뒤집는다
+        Ok(code) => info!(
+            "{}",
+            code.render::<Dense1x2>()
+                .dark_color(Dense1x2::Light)
+                .light_color(Dense1x2::Dark)
+                .quiet_zone(true)
+                .build()
+        ),
+        Err(e) => warning!("{}", tr!("⚠️ QR 코드를 만들지 못했습니다: {}", "⚠️ Could not make the QR code: {}", e)),
+    }
+}
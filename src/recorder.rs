@@ -0,0 +1,87 @@
+use std::path::PathBuf;
+
+use crate::cache;
+#[cfg(feature = "history")]
+use crate::console::info;
+use crate::console::warning;
+use crate::i18n::tr;
+use crate::locator::Config;
+#[cfg(feature = "history")]
+use crate::{history, History};
+#[cfg(feature = "mqtt")]
+use crate::Mqtt;
+#[cfg(feature = "traccar")]
+use crate::Traccar;
+#[cfg(feature = "webhook")]
+use crate::Webhook;
+use crate::{CsvLog, DeviceId, Location, WeimError};
+
+/// 설정된 기록 대상(SQLite, CSV, 웹훅, MQTT, Traccar)과 마지막 위치 캐시에 받은 위치를 남긴다.
+pub(crate) struct Recorder {
+    #[cfg(feature = "history")]
+    history: Option<History>,
+    csv: Option<CsvLog>,
+    cache_file: Option<PathBuf>,
+    #[cfg(feature = "webhook")]
+    webhooks: Vec<Webhook>,
+    #[cfg(feature = "mqtt")]
+    mqtt: Option<Mqtt>,
+    #[cfg(feature = "traccar")]
+    traccar: Option<Traccar>,
+}
+
+impl Recorder {
+    pub(crate) fn open(config: &Config) -> Result<Self, WeimError> {
+        #[cfg(feature = "history")]
+        let history = config
+            .history
+            .as_ref()
+            .map(|path| history::open(path, #[cfg(feature = "encryption")] config.history_key.as_ref()))
+            .transpose()?;
+        // 보관 기준을 넘은 위치는 열 때 지운다. 실패해도 기록은 계속한다
+        #[cfg(feature = "history")]
+        if let (Some(history), Some(retention)) = (&history, &config.history_retention) {
+            match history.purge(retention) {
+                Ok(0) => {}
+                Ok(count) => info!("{}", tr!("🧹 보관 기준을 넘은 위치 {}개를 지웠습니다.", "🧹 Purged {} locations past the retention policy.", count)),
+                Err(e) => warning!("{}", tr!("⚠️ 오래된 위치를 지우지 못했습니다: {}", "⚠️ Could not purge old locations: {}", e)),
+            }
+        }
+        Ok(Recorder {
+            #[cfg(feature = "history")]
+            history,
+            csv: config.csv_log.clone(),
+            cache_file: config.cache_file.clone(),
+            #[cfg(feature = "webhook")]
+            webhooks: config.webhooks.clone(),
+            #[cfg(feature = "mqtt")]
+            mqtt: config.mqtt.clone(),
+            #[cfg(feature = "traccar")]
+            traccar: config.traccar.clone(),
+        })
+    }
+
+    /// 기록에 실패해도 받은 위치는 그대로 돌려준다.
+    pub(crate) fn record(&self, device: &DeviceId, location: &Location) {
+        cache::store(location, self.cache_file.as_deref());
+        #[cfg(feature = "history")]
+        if let Some(Err(e)) = self.history.as_ref().map(|history| history.append(device, location)) {
+            warning!("{}", tr!("⚠️ 위치를 기록하지 못했습니다: {}", "⚠️ Could not record the location: {}", e));
+        }
+        if let Some(Err(e)) = self.csv.as_ref().map(|csv| csv.append(device.as_str(), location)) {
+            warning!("{}", tr!("⚠️ CSV에 위치를 기록하지 못했습니다: {}", "⚠️ Could not write the location to CSV: {}", e));
+        }
+        #[cfg(feature = "webhook")]
+        for webhook in &self.webhooks {
+            webhook.send(device, location);
+        }
+        #[cfg(feature = "mqtt")]
+        if let Some(mqtt) = &self.mqtt {
+            mqtt.publish(device, location);
+        }
+        #[cfg(feature = "traccar")]
+        if let Some(traccar) = &self.traccar {
+            traccar.report(device, location);
+        }
+    }
+}
@@ -0,0 +1,145 @@
+use std::time::Duration;
+
+use crate::geo::haversine;
+use crate::Location;
+
+// 중앙값까지 거리가 전체 중앙값 거리의 이 배수를 넘으면 이상치 후보
+const OUTLIER_FACTOR: f64 = 3.0;
+// 이보다 적으면 이상치를 가려내지 않는다
+const MIN_SAMPLES_FOR_REJECTION: usize = 3;
+// 정확도가 0이거나 없을 때 가중치 계산에 쓸 최솟값 (m)
+const MIN_ACCURACY: f64 = 0.1;
+
+/// 여러 번 측정한 위치 묶음
+///
+/// 모든 원본 측정값을 그대로 갖고 있고, 중앙값에서 멀리 떨어진 이상치를 뺀 뒤
+/// 가장 정확한 값이나 정확도 가중 평균을 계산한다.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SampleSet {
+    /// 받은 순서대로의 원본 측정값
+    pub samples: Vec<Location>,
+    outlier: Vec<bool>,
+}
+
+impl SampleSet {
+    /// 어느 공급자에서 모았든 측정값 목록으로 만든다.
+    pub fn new(samples: Vec<Location>) -> Self {
+        let outlier = find_outliers(&samples);
+        SampleSet { samples, outlier }
+    }
+
+    /// 이상치로 걸러지지 않은 측정값
+    pub fn inliers(&self) -> impl Iterator<Item = &Location> {
+        self.samples.iter().zip(&self.outlier).filter(|(_, outlier)| !**outlier).map(|(location, _)| location)
+    }
+
+    /// 이상치로 걸러진 측정값
+    pub fn outliers(&self) -> impl Iterator<Item = &Location> {
+        self.samples.iter().zip(&self.outlier).filter(|(_, outlier)| **outlier).map(|(location, _)| location)
+    }
+
+    /// 이상치를 뺀 측정값 중 정확도가 가장 좋은 것
+    pub fn most_accurate(&self) -> Option<&Location> {
+        self.inliers().min_by(|a, b| a.accuracy.total_cmp(&b.accuracy))
+    }
+
+    /// 이상치를 뺀 측정값 중 가장 좋은 정확도(m)
+    pub fn best_accuracy(&self) -> Option<f64> {
+        self.most_accurate().map(|location| location.accuracy)
+    }
+
+    /// 이상치를 뺀 측정값의 단순 평균 (위도, 경도). 경도는 날짜 변경선을 걸쳐도 맞게 평균한다.
+    pub fn centroid(&self) -> Option<(f64, f64)> {
+        let (mut count, mut latitude, mut east, mut north) = (0, 0.0, 0.0, 0.0);
+        for location in self.inliers() {
+            count += 1;
+            latitude += location.latitude;
+            let longitude = location.longitude.to_radians();
+            east += longitude.sin();
+            north += longitude.cos();
+        }
+        (count > 0).then(|| (latitude / f64::from(count), east.atan2(north).to_degrees()))
+    }
+
+    /// 이상치를 뺀 측정값이 `centroid()`에서 떨어진 거리의 제곱평균제곱근 (DRMS, m)
+    ///
+    /// 한 곳에 가만히 두고 모았으면 측정이 얼마나 흩어지는지, 곧 `accuracy`가 실제로 얼마나 믿을 만한지를 보여 준다.
+    pub fn spread(&self) -> Option<f64> {
+        let center = self.centroid()?;
+        let (mut count, mut total) = (0, 0.0);
+        for location in self.inliers() {
+            count += 1;
+            total += haversine(center, location).powi(2);
+        }
+        Some((total / f64::from(count)).sqrt())
+    }
+
+    /// 첫 측정부터 마지막 측정까지 걸린 시간
+    pub fn span(&self) -> Duration {
+        let times = self.samples.iter().map(|location| location.timestamp);
+        times.clone().max().zip(times.min()).and_then(|(last, first)| (last - first).to_std().ok()).unwrap_or_default()
+    }
+
+    /// 이상치를 뺀 측정값을 정확도 제곱의 역수로 가중 평균한 위치
+    ///
+    /// 정확도는 합친 오차 추정값, 시각은 가장 마지막 측정값의 시각이 된다.
+    pub fn weighted_average(&self) -> Option<Location> {
+        let weight = |location: &Location| location.accuracy.max(MIN_ACCURACY).powi(-2);
+
+        let mut total = 0.0;
+        let (mut latitude, mut longitude) = (0.0, 0.0);
+        let (mut altitude, mut altitude_weight) = (0.0, 0.0);
+        let mut timestamp = None;
+        for location in self.inliers() {
+            let w = weight(location);
+            total += w;
+            latitude += location.latitude * w;
+            longitude += location.longitude * w;
+            if let Some(alt) = location.altitude {
+                altitude += alt * w;
+                altitude_weight += w;
+            }
+            timestamp = timestamp.max(Some(location.timestamp));
+        }
+
+        Some(Location {
+            latitude: latitude / total,
+            longitude: longitude / total,
+            accuracy: total.recip().sqrt(),
+            altitude: (altitude_weight > 0.0).then(|| altitude / altitude_weight),
+            timestamp: timestamp?,
+            ..Location::default()
+        })
+    }
+}
+
+// 중앙값 위치에서 멀고, 자기 정확도로도 설명되지 않는 측정값을 이상치로 본다
+fn find_outliers(samples: &[Location]) -> Vec<bool> {
+    if samples.len() < MIN_SAMPLES_FOR_REJECTION {
+        return vec![false; samples.len()];
+    }
+
+    let center_lat = median(samples.iter().map(|location| location.latitude).collect());
+    let center_lon = median(samples.iter().map(|location| location.longitude).collect());
+    let distances: Vec<f64> = samples
+        .iter()
+        .map(|location| haversine((center_lat, center_lon), location))
+        .collect();
+    let limit = median(distances.clone()) * OUTLIER_FACTOR;
+
+    samples
+        .iter()
+        .zip(distances)
+        .map(|(location, distance)| distance > limit && distance > location.accuracy)
+        .collect()
+}
+
+fn median(mut values: Vec<f64>) -> f64 {
+    values.sort_by(f64::total_cmp);
+    let mid = values.len() / 2;
+    if values.len().is_multiple_of(2) {
+        (values[mid - 1] + values[mid]) / 2.0
+    } else {
+        values[mid]
+    }
+}
@@ -0,0 +1,249 @@
+use std::str::FromStr;
+use std::time::Duration;
+use chrono::{DateTime, Datelike, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, TimeZone, Timelike};
+use serde::{Deserialize, Deserializer};
+
+use crate::i18n::tr;
+use crate::WeimError;
+
+// 창 안에 드는 cron 시각을 찾을 때 살펴볼 최대 후보 수
+const MAX_CANDIDATES: usize = 100_000;
+
+/// `serve()`로 띄운 데몬이 위치를 받을 때 (`WeimBuilder::schedule()`)
+///
+/// 시각은 모두 이 컴퓨터의 현지 시간이다.
+///
+/// - `Schedule::every(Duration::from_secs(600))`: 10분마다, 시작하자마자 한 번
+/// - `Schedule::cron("*/10 8-19 * * 1-5")`: 분 시 일 월 요일 다섯 칸의 cron 식
+/// - `between()`: 둘 다 하루 중 이 시간 안에서만 받는다 (예: 08:00–20:00)
+///
+/// 설정 파일과 명령줄에서는 `"every 10m 08:00-20:00"`이나 cron 식 문자열로 쓴다.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schedule {
+    kind: Kind,
+    window: Option<(NaiveTime, NaiveTime)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Kind {
+    Every(Duration),
+    Cron(Cron),
+}
+
+impl Schedule {
+    /// `interval`마다. 1초보다 짧으면 1초로 한다.
+    pub fn every(interval: Duration) -> Self {
+        Schedule { kind: Kind::Every(interval.max(Duration::from_secs(1))), window: None }
+    }
+
+    /// 분(0–59) 시(0–23) 일(1–31) 월(1–12) 요일(0–7, 0과 7은 일요일)의 cron 식
+    ///
+    /// 칸마다 `*`, `5`, `1-5`, `*/15`, `8-18/2`, `0,30`을 쓴다. 일과 요일을 둘 다 정하면 둘 중 하나만 맞아도 된다.
+    pub fn cron(expression: &str) -> Result<Self, WeimError> {
+        Ok(Schedule { kind: Kind::Cron(Cron::parse(expression)?), window: None })
+    }
+
+    /// 하루 중 `start`부터 `end` 전까지만 받는다. `end`가 더 이르면 자정을 넘긴다. (기본값 하루 종일)
+    pub fn between(mut self, start: NaiveTime, end: NaiveTime) -> Self {
+        self.window = Some((start, end));
+        self
+    }
+
+    /// `time` 다음에 받을 시각. 다시 오지 않으면 None (예: 2월 30일)
+    pub fn next_after(&self, time: DateTime<Local>) -> Option<DateTime<Local>> {
+        match &self.kind {
+            Kind::Every(interval) => self.within(time + TimeDelta::from_std(*interval).ok()?),
+            Kind::Cron(cron) => {
+                let mut naive = time.naive_local();
+                for _ in 0..MAX_CANDIDATES {
+                    naive = cron.next_after(naive)?;
+                    // 서머타임으로 건너뛴 시각은 없는 시각이라 다음 후보로 넘어간다
+                    if self.contains(naive.time()) && let Some(time) = Local.from_local_datetime(&naive).earliest() {
+                        return Some(time);
+                    }
+                }
+                None
+            }
+        }
+    }
+
+    /// 데몬을 띄운 `now`부터 처음 받을 시각. 간격으로 정했으면 창 안일 때 바로 받는다
+    pub(crate) fn first(&self, now: DateTime<Local>) -> Option<DateTime<Local>> {
+        match self.kind {
+            Kind::Every(_) => self.within(now),
+            Kind::Cron(_) => self.next_after(now),
+        }
+    }
+
+    fn contains(&self, time: NaiveTime) -> bool {
+        match self.window {
+            None => true,
+            Some((start, end)) if start == end => true,
+            Some((start, end)) if start < end => start <= time && time < end,
+            Some((start, end)) => time >= start || time < end,
+        }
+    }
+
+    // 창 안이면 그대로, 아니면 다음 창이 열리는 시각
+    fn within(&self, time: DateTime<Local>) -> Option<DateTime<Local>> {
+        let Some((start, _)) = self.window.filter(|_| !self.contains(time.time())) else {
+            return Some(time);
+        };
+        let mut opens = time.date_naive().and_time(start);
+        if opens <= time.naive_local() {
+            opens += TimeDelta::days(1);
+        }
+        let local = |naive: NaiveDateTime| Local.from_local_datetime(&naive).earliest();
+        local(opens).or_else(|| local(opens + TimeDelta::hours(1)))
+    }
+}
+
+impl FromStr for Schedule {
+    type Err = WeimError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let Some(rest) = s.strip_prefix("every ") else {
+            return Schedule::cron(s);
+        };
+        let invalid = || {
+            WeimError::Config(tr!(
+                "일정을 읽을 수 없습니다 (예: \"every 10m 08:00-20:00\"): {}",
+                "cannot read the schedule (e.g. \"every 10m 08:00-20:00\"): {}",
+                s
+            ))
+        };
+        let mut parts = rest.split_whitespace();
+        let schedule = Schedule::every(parts.next().and_then(interval).ok_or_else(invalid)?);
+        let schedule = match parts.next() {
+            Some(window) => {
+                let (start, end) = window.split_once('-').ok_or_else(invalid)?;
+                let time = |text: &str| NaiveTime::parse_from_str(text, "%H:%M").map_err(|_| invalid());
+                schedule.between(time(start)?, time(end)?)
+            }
+            None => schedule,
+        };
+        match parts.next() {
+            Some(_) => Err(invalid()),
+            None => Ok(schedule),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Schedule {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+// `90`, `30s`, `10m`, `2h`. 단위가 없으면 초
+fn interval(text: &str) -> Option<Duration> {
+    let (number, unit) = text.split_at(text.find(|c: char| !c.is_ascii_digit()).unwrap_or(text.len()));
+    let seconds = match unit {
+        "" | "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        _ => return None,
+    };
+    number.parse::<u64>().ok().filter(|n| *n > 0).map(|n| Duration::from_secs(n * seconds))
+}
+
+// 칸마다 맞는 값의 비트
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Cron {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    // `*`로 둔 칸은 다른 칸과 함께 따지지 않는다
+    any_day: bool,
+    any_weekday: bool,
+}
+
+impl Cron {
+    fn parse(expression: &str) -> Result<Self, WeimError> {
+        let invalid = || {
+            WeimError::Config(tr!(
+                "cron 식을 읽을 수 없습니다 (분 시 일 월 요일): {}",
+                "cannot read the cron expression (minute hour day month weekday): {}",
+                expression
+            ))
+        };
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let [minutes, hours, days, months, weekdays] = fields[..] else {
+            return Err(invalid());
+        };
+        let weekday_bits = field(weekdays, 0, 7).ok_or_else(invalid)?;
+        Ok(Cron {
+            minutes: field(minutes, 0, 59).ok_or_else(invalid)?,
+            hours: field(hours, 0, 23).ok_or_else(invalid)?,
+            days: field(days, 1, 31).ok_or_else(invalid)?,
+            months: field(months, 1, 12).ok_or_else(invalid)?,
+            // 7도 일요일
+            weekdays: (weekday_bits | weekday_bits >> 7) & 0x7f,
+            any_day: days.starts_with('*'),
+            any_weekday: weekdays.starts_with('*'),
+        })
+    }
+
+    // `time`보다 뒤의 첫 분. 맞지 않는 달, 날, 시는 통째로 건너뛴다
+    fn next_after(&self, time: NaiveDateTime) -> Option<NaiveDateTime> {
+        let mut time = time.date().and_hms_opt(time.hour(), time.minute(), 0)? + TimeDelta::minutes(1);
+        // 윤년의 2월 29일까지 찾아볼 만큼
+        let limit = time + TimeDelta::days(366 * 8);
+        while time < limit {
+            let date = time.date();
+            if !has(self.months, date.month()) {
+                let (year, month) = if date.month() == 12 { (date.year() + 1, 1) } else { (date.year(), date.month() + 1) };
+                time = NaiveDate::from_ymd_opt(year, month, 1)?.and_hms_opt(0, 0, 0)?;
+            } else if !self.day_matches(date) {
+                time = date.succ_opt()?.and_hms_opt(0, 0, 0)?;
+            } else if !has(self.hours, time.hour()) {
+                time = date.and_hms_opt(time.hour(), 0, 0)? + TimeDelta::hours(1);
+            } else if !has(self.minutes, time.minute()) {
+                time += TimeDelta::minutes(1);
+            } else {
+                return Some(time);
+            }
+        }
+        None
+    }
+
+    fn day_matches(&self, date: NaiveDate) -> bool {
+        let day = has(self.days, date.day());
+        let weekday = has(self.weekdays, date.weekday().num_days_from_sunday());
+        if self.any_day || self.any_weekday { day && weekday } else { day || weekday }
+    }
+}
+
+fn has(bits: u64, value: u32) -> bool {
+    bits >> value & 1 == 1
+}
+
+// `*`, `5`, `1-5`, `*/15`, `8-18/2`, `0,30`
+fn field(text: &str, min: u32, max: u32) -> Option<u64> {
+    let mut bits = 0;
+    for item in text.split(',') {
+        let (range, step) = match item.split_once('/') {
+            Some((range, step)) => (range, step.parse::<usize>().ok().filter(|step| *step > 0)?),
+            None => (item, 1),
+        };
+        let (start, end) = match range.split_once('-') {
+            _ if range == "*" => (min, max),
+            Some((start, end)) => (start.parse().ok()?, end.parse().ok()?),
+            // `5/10`은 5부터 끝까지
+            None => {
+                let start = range.parse().ok()?;
+                (start, if step > 1 { max } else { start })
+            }
+        };
+        if start < min || end > max || start > end {
+            return None;
+        }
+        for value in (start..=end).step_by(step) {
+            bits |= 1 << value;
+        }
+    }
+    Some(bits)
+}
@@ -0,0 +1,52 @@
+// 위치 기록 암호화. 행마다 XChaCha20-Poly1305로 봉하고, 열쇠는 암호나 열쇠 파일에서 Argon2id로 만든다.
+
+use std::fmt;
+
+use argon2::Argon2;
+use chacha20poly1305::aead::{Aead, Generate, Payload};
+use chacha20poly1305::{Key, KeyInit, XChaCha20Poly1305, XNonce};
+
+pub(crate) const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+
+pub(crate) struct Seal {
+    cipher: XChaCha20Poly1305,
+}
+
+impl Seal {
+    /// 데이터베이스마다 새로 만드는 소금
+    pub(crate) fn salt() -> [u8; SALT_LEN] {
+        Generate::generate()
+    }
+
+    /// 비밀(암호나 열쇠 파일 내용)과 소금으로 열쇠를 만든다. 일부러 느리다(수십 ms).
+    pub(crate) fn derive(secret: &[u8], salt: &[u8]) -> Option<Seal> {
+        let mut key = [0u8; 32];
+        Argon2::default().hash_password_into(secret, salt, &mut key).ok()?;
+        Some(Seal { cipher: XChaCha20Poly1305::new(&Key::from(key)) })
+    }
+
+    /// `nonce || 암호문 || 태그`. `context`는 암호화하지 않지만 바꾸면 열리지 않는다.
+    pub(crate) fn seal(&self, plaintext: &[u8], context: &[u8]) -> Vec<u8> {
+        let nonce = XNonce::generate();
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, Payload { msg: plaintext, aad: context })
+            .expect("XChaCha20-Poly1305는 이 길이의 평문을 항상 암호화한다");
+        [nonce.as_slice(), &ciphertext].concat()
+    }
+
+    /// 열쇠가 틀리거나 내용이 바뀌었으면 None
+    pub(crate) fn open(&self, sealed: &[u8], context: &[u8]) -> Option<Vec<u8>> {
+        let (nonce, ciphertext) = sealed.split_at_checked(NONCE_LEN)?;
+        let nonce = XNonce::try_from(nonce).ok()?;
+        self.cipher.decrypt(&nonce, Payload { msg: ciphertext, aad: context }).ok()
+    }
+}
+
+// 열쇠가 로그에 찍히지 않게 한다
+impl fmt::Debug for Seal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Seal(..)")
+    }
+}
@@ -0,0 +1,58 @@
+use crate::handler::Reply;
+use crate::locator::Config;
+
+/// 기본 페이지와 대시보드가 쓰는 것만 허용하는 Content-Security-Policy
+///
+/// 페이지 안의 스크립트와 스타일, unpkg의 Leaflet, OpenStreetMap 타일, 같은 서버로 보내는 요청과 웹소켓만 된다.
+pub(crate) const DEFAULT_CSP: &str = "default-src 'self'; \
+    script-src 'self' 'unsafe-inline' https://unpkg.com; \
+    style-src 'self' 'unsafe-inline' https://unpkg.com; \
+    img-src 'self' data: https://unpkg.com https://tile.openstreetmap.org; \
+    connect-src 'self'; \
+    frame-ancestors 'none'; \
+    base-uri 'none'; \
+    form-action 'none'";
+
+/// 모든 응답에 붙이는 CORS와 보안 헤더
+pub(crate) struct Headers {
+    // 비었으면 다른 출처에서는 읽지 못한다
+    origins: Vec<String>,
+    content_security_policy: Option<String>,
+}
+
+impl Headers {
+    pub(crate) fn new(config: &Config) -> Self {
+        Headers {
+            origins: config.allowed_origins.iter().map(|origin| normalize(origin)).collect(),
+            content_security_policy: config.content_security_policy.clone(),
+        }
+    }
+
+    /// `origin`은 요청의 `Origin` 헤더. 허용한 출처면 `Access-Control-Allow-Origin`으로 돌려준다
+    pub(crate) fn apply(&self, mut reply: Reply, origin: Option<&str>) -> Reply {
+        if self.origins.iter().any(|allowed| allowed == "*") {
+            reply = reply.header("Access-Control-Allow-Origin", "*");
+        } else {
+            // 출처마다 답이 다르므로 캐시가 섞지 않게 한다
+            reply = reply.header("Vary", "Origin");
+            if let Some(origin) = origin.filter(|origin| self.origins.contains(&normalize(origin))) {
+                reply = reply.header("Access-Control-Allow-Origin", origin);
+            }
+        }
+        let html = reply
+            .headers
+            .iter()
+            .any(|(name, value)| name.eq_ignore_ascii_case("Content-Type") && value.starts_with("text/html"));
+        if html && let Some(policy) = &self.content_security_policy {
+            reply = reply.header("Content-Security-Policy", policy.as_str());
+        }
+        reply
+            .header("X-Content-Type-Options", "nosniff")
+            .header("Referrer-Policy", "no-referrer")
+    }
+}
+
+// `https://Example.com/` -> `https://example.com`
+fn normalize(origin: &str) -> String {
+    origin.trim().trim_end_matches('/').to_ascii_lowercase()
+}
@@ -0,0 +1,570 @@
+//! `weim service`: 데몬 모드(`weim serve`)를 부팅할 때 시작되는 서비스로 등록한다.
+//!
+//! Linux에서는 systemd 유닛, macOS에서는 launchd plist, Windows에서는 Windows 서비스를 만든다.
+//! 비정상으로 끝나면 5초 뒤 다시 시작하고, 출력은 저널(systemd)이나 로그 파일로 보낸다.
+
+use std::error::Error;
+use std::path::{Path, PathBuf};
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+use std::process::Command;
+use std::{env, fs};
+use weim::Language;
+
+use crate::pick;
+
+// 서비스 안에서 `weim serve`가 읽을 설정 파일
+const CONFIG_ENV: &str = "WEIM_CONFIG";
+// 비정상으로 끝난 뒤 다시 시작하기까지 기다리는 시간(초)
+#[cfg_attr(not(any(target_os = "linux", target_os = "macos", windows)), allow(dead_code))]
+const RESTART_DELAY: u64 = 5;
+
+/// 등록할 서비스
+pub(crate) struct Service {
+    pub(crate) name: String,
+    /// 사용자 세션 대신 시스템 전체에 (systemd, launchd). Windows 서비스는 언제나 시스템 전체에 둔다
+    #[cfg_attr(windows, allow(dead_code))]
+    pub(crate) system: bool,
+    /// 표준 출력과 오류를 덧붙일 파일
+    pub(crate) log: Option<PathBuf>,
+    pub(crate) config: Option<PathBuf>,
+    /// `weim serve` 뒤에 붙일 인자
+    pub(crate) args: Vec<String>,
+    pub(crate) language: Language,
+}
+
+impl Service {
+    // 경로와 서비스 관리자 명령에 그대로 들어가므로 글자를 제한한다
+    fn check_name(&self) -> Result<(), Box<dyn Error>> {
+        let valid = !self.name.is_empty()
+            && self.name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+            && !self.name.starts_with('.');
+        if valid {
+            Ok(())
+        } else {
+            let message = pick(self.language, "서비스 이름에는 영문자, 숫자, '-', '_', '.'만 쓸 수 있습니다", "service names may only contain letters, digits, '-', '_' and '.'");
+            Err(format!("{}: {}", message, self.name).into())
+        }
+    }
+
+    // 서비스는 다른 디렉터리에서 시작되므로 상대 경로를 지금 기준으로 바꿔 둔다
+    fn config_path(&self) -> Result<Option<PathBuf>, Box<dyn Error>> {
+        match self.config.clone().or_else(|| env::var_os(CONFIG_ENV).map(PathBuf::from)) {
+            Some(path) => Ok(Some(fs::canonicalize(&path).map_err(|e| format!("{}: {}", path.display(), e))?)),
+            None => Ok(None),
+        }
+    }
+
+    fn log_path(&self) -> Result<Option<PathBuf>, Box<dyn Error>> {
+        Ok(match &self.log {
+            Some(path) if path.is_relative() => Some(env::current_dir()?.join(path)),
+            other => other.clone(),
+        })
+    }
+}
+
+/// 서비스 파일을 만들고 등록한 뒤 시작한다. `dry_run`이면 만들 내용만 출력한다.
+pub(crate) fn install(service: &Service, dry_run: bool) -> Result<(), Box<dyn Error>> {
+    service.check_name()?;
+    let exe = env::current_exe()?;
+    let dir = env::current_dir()?;
+    platform::install(service, &exe, &dir, dry_run)
+}
+
+/// 서비스를 멈추고 등록을 지운다.
+pub(crate) fn uninstall(service: &Service) -> Result<(), Box<dyn Error>> {
+    service.check_name()?;
+    platform::uninstall(service)
+}
+
+/// 서비스가 등록되어 있는지, 돌고 있는지 출력한다.
+pub(crate) fn status(service: &Service) -> Result<(), Box<dyn Error>> {
+    service.check_name()?;
+    platform::status(service)
+}
+
+/// 서비스 관리자가 부르는 진입점. `weim serve`를 띄우고 멈추라고 할 때까지 지켜본다. (Windows 전용)
+#[cfg(windows)]
+pub(crate) fn run(service: Service, dir: PathBuf) -> Result<(), Box<dyn Error>> {
+    platform::run(service, dir)
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::*;
+
+    fn unit_path(service: &Service) -> Result<PathBuf, Box<dyn Error>> {
+        let dir = if service.system {
+            PathBuf::from("/etc/systemd/system")
+        } else {
+            match env::var_os("XDG_CONFIG_HOME") {
+                Some(config) => PathBuf::from(config),
+                None => home(service.language)?.join(".config"),
+            }
+            .join("systemd/user")
+        };
+        Ok(dir.join(format!("{}.service", service.name)))
+    }
+
+    fn unit(service: &Service, exe: &Path, dir: &Path) -> Result<String, Box<dyn Error>> {
+        let mut command = vec![quote(&exe.to_string_lossy()), "serve".to_string()];
+        command.extend(service.args.iter().map(|arg| quote(arg)));
+
+        let mut unit = String::new();
+        unit.push_str("[Unit]\n");
+        unit.push_str("Description=weim location daemon\n");
+        unit.push_str("After=network-online.target\n");
+        unit.push_str("Wants=network-online.target\n\n");
+        unit.push_str("[Service]\n");
+        unit.push_str("Type=simple\n");
+        unit.push_str(&format!("ExecStart={}\n", command.join(" ")));
+        unit.push_str(&format!("WorkingDirectory={}\n", quote(&dir.to_string_lossy())));
+        if let Some(config) = service.config_path()? {
+            unit.push_str(&format!("Environment={}\n", quote(&format!("{}={}", CONFIG_ENV, config.display()))));
+        }
+        unit.push_str("Restart=on-failure\n");
+        unit.push_str(&format!("RestartSec={}\n", RESTART_DELAY));
+        match service.log_path()? {
+            Some(log) => {
+                unit.push_str(&format!("StandardOutput=append:{}\n", log.display()));
+                unit.push_str(&format!("StandardError=append:{}\n", log.display()));
+            }
+            None => {
+                unit.push_str("StandardOutput=journal\n");
+                unit.push_str("StandardError=journal\n");
+            }
+        }
+        unit.push_str("\n[Install]\n");
+        unit.push_str(if service.system { "WantedBy=multi-user.target\n" } else { "WantedBy=default.target\n" });
+        Ok(unit)
+    }
+
+    // systemd는 `%`와 `$`를 펼치므로 두 번 쓰고, 공백이 있어도 인자 하나가 되게 따옴표로 감싼다
+    fn quote(arg: &str) -> String {
+        let escaped = arg.replace('\\', "\\\\").replace('"', "\\\"").replace('%', "%%").replace('$', "$$");
+        format!("\"{}\"", escaped)
+    }
+
+    fn systemctl(service: &Service, args: &[&str]) -> Result<(), Box<dyn Error>> {
+        let mut command = Command::new("systemctl");
+        if !service.system {
+            command.arg("--user");
+        }
+        let status = command.args(args).status()?;
+        if !status.success() {
+            let message = pick(service.language, "systemctl이 실패했습니다", "systemctl failed");
+            return Err(format!("{} ({}): {}", message, args.join(" "), status).into());
+        }
+        Ok(())
+    }
+
+    pub(super) fn install(service: &Service, exe: &Path, dir: &Path, dry_run: bool) -> Result<(), Box<dyn Error>> {
+        let (path, unit) = (unit_path(service)?, unit(service, exe, dir)?);
+        if dry_run {
+            println!("# {}\n{}", path.display(), unit);
+            return Ok(());
+        }
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&path, unit)?;
+        systemctl(service, &["daemon-reload"])?;
+        let file = format!("{}.service", service.name);
+        systemctl(service, &["enable", "--now", &file])?;
+
+        println!("{}", pick(service.language, "✅ systemd 서비스를 등록하고 시작했습니다:", "✅ Registered and started the systemd service:"));
+        println!("   {}", path.display());
+        let journal = if service.system { format!("journalctl -u {}", file) } else { format!("journalctl --user -u {}", file) };
+        if service.log.is_none() {
+            println!("{} {}", pick(service.language, "📜 출력 보기:", "📜 View the output:"), journal);
+        }
+        if !service.system {
+            println!(
+                "{}",
+                pick(
+                    service.language,
+                    "💡 로그인하지 않아도 부팅할 때 시작하려면 `loginctl enable-linger`를 한 번 실행하세요.",
+                    "💡 Run `loginctl enable-linger` once to start it at boot without logging in."
+                )
+            );
+        }
+        Ok(())
+    }
+
+    pub(super) fn uninstall(service: &Service) -> Result<(), Box<dyn Error>> {
+        let path = unit_path(service)?;
+        if !path.exists() {
+            return Err(not_installed(service, &path));
+        }
+        let file = format!("{}.service", service.name);
+        // 이미 멈췄거나 켜 두지 않았어도 파일은 지운다
+        systemctl(service, &["disable", "--now", &file]).ok();
+        fs::remove_file(&path)?;
+        systemctl(service, &["daemon-reload"])?;
+        println!("{} {}", pick(service.language, "🗑️ 서비스를 지웠습니다:", "🗑️ Removed the service:"), path.display());
+        Ok(())
+    }
+
+    pub(super) fn status(service: &Service) -> Result<(), Box<dyn Error>> {
+        let path = unit_path(service)?;
+        if !path.exists() {
+            return Err(not_installed(service, &path));
+        }
+        println!("{} {}", pick(service.language, "📄 유닛 파일:", "📄 Unit file:"), path.display());
+        let mut command = Command::new("systemctl");
+        if !service.system {
+            command.arg("--user");
+        }
+        // 멈춘 서비스면 0이 아닌 코드로 끝나지만 오류는 아니다
+        command.args(["status", "--no-pager", &format!("{}.service", service.name)]).status()?;
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::*;
+
+    fn label(service: &Service) -> String {
+        format!("com.github.boggle200.{}", service.name)
+    }
+
+    fn plist_path(service: &Service) -> Result<PathBuf, Box<dyn Error>> {
+        let dir = if service.system {
+            PathBuf::from("/Library/LaunchDaemons")
+        } else {
+            home(service.language)?.join("Library/LaunchAgents")
+        };
+        Ok(dir.join(format!("{}.plist", label(service))))
+    }
+
+    fn plist(service: &Service, exe: &Path, dir: &Path) -> Result<String, Box<dyn Error>> {
+        let log = match service.log_path()? {
+            Some(log) => log,
+            None if service.system => PathBuf::from(format!("/Library/Logs/{}.log", service.name)),
+            None => home(service.language)?.join(format!("Library/Logs/{}.log", service.name)),
+        };
+        let string = |value: &str| format!("<string>{}</string>", escape(value));
+
+        let mut arguments = vec![string(&exe.to_string_lossy()), string("serve")];
+        arguments.extend(service.args.iter().map(|arg| string(arg)));
+        let mut plist = String::new();
+        plist.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        plist.push_str("<!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" \"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\n");
+        plist.push_str("<plist version=\"1.0\">\n<dict>\n");
+        plist.push_str(&format!("  <key>Label</key>\n  {}\n", string(&label(service))));
+        plist.push_str("  <key>ProgramArguments</key>\n  <array>\n");
+        for argument in arguments {
+            plist.push_str(&format!("    {}\n", argument));
+        }
+        plist.push_str("  </array>\n");
+        plist.push_str(&format!("  <key>WorkingDirectory</key>\n  {}\n", string(&dir.to_string_lossy())));
+        if let Some(config) = service.config_path()? {
+            plist.push_str("  <key>EnvironmentVariables</key>\n  <dict>\n");
+            plist.push_str(&format!("    <key>{}</key>\n    {}\n", CONFIG_ENV, string(&config.to_string_lossy())));
+            plist.push_str("  </dict>\n");
+        }
+        plist.push_str("  <key>RunAtLoad</key>\n  <true/>\n");
+        // 스스로 정상 종료했을 때는 다시 띄우지 않는다
+        plist.push_str("  <key>KeepAlive</key>\n  <dict>\n    <key>SuccessfulExit</key>\n    <false/>\n  </dict>\n");
+        plist.push_str(&format!("  <key>ThrottleInterval</key>\n  <integer>{}</integer>\n", RESTART_DELAY));
+        plist.push_str(&format!("  <key>StandardOutPath</key>\n  {}\n", string(&log.to_string_lossy())));
+        plist.push_str(&format!("  <key>StandardErrorPath</key>\n  {}\n", string(&log.to_string_lossy())));
+        plist.push_str("</dict>\n</plist>\n");
+        Ok(plist)
+    }
+
+    fn escape(value: &str) -> String {
+        value.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+    }
+
+    fn launchctl(service: &Service, args: &[&str]) -> Result<(), Box<dyn Error>> {
+        let status = Command::new("launchctl").args(args).status()?;
+        if !status.success() {
+            let message = pick(service.language, "launchctl이 실패했습니다", "launchctl failed");
+            return Err(format!("{} ({}): {}", message, args.join(" "), status).into());
+        }
+        Ok(())
+    }
+
+    pub(super) fn install(service: &Service, exe: &Path, dir: &Path, dry_run: bool) -> Result<(), Box<dyn Error>> {
+        let (path, plist) = (plist_path(service)?, plist(service, exe, dir)?);
+        if dry_run {
+            println!("<!-- {} -->\n{}", path.display(), plist);
+            return Ok(());
+        }
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        // 다시 설치할 때는 먼저 내린다
+        if path.exists() {
+            launchctl(service, &["unload", &path.to_string_lossy()]).ok();
+        }
+        fs::write(&path, plist)?;
+        launchctl(service, &["load", "-w", &path.to_string_lossy()])?;
+        println!("{}", pick(service.language, "✅ launchd 서비스를 등록하고 시작했습니다:", "✅ Registered and started the launchd service:"));
+        println!("   {}", path.display());
+        Ok(())
+    }
+
+    pub(super) fn uninstall(service: &Service) -> Result<(), Box<dyn Error>> {
+        let path = plist_path(service)?;
+        if !path.exists() {
+            return Err(not_installed(service, &path));
+        }
+        launchctl(service, &["unload", "-w", &path.to_string_lossy()]).ok();
+        fs::remove_file(&path)?;
+        println!("{} {}", pick(service.language, "🗑️ 서비스를 지웠습니다:", "🗑️ Removed the service:"), path.display());
+        Ok(())
+    }
+
+    pub(super) fn status(service: &Service) -> Result<(), Box<dyn Error>> {
+        let path = plist_path(service)?;
+        if !path.exists() {
+            return Err(not_installed(service, &path));
+        }
+        println!("{} {}", pick(service.language, "📄 plist 파일:", "📄 plist file:"), path.display());
+        // 돌고 있으면 PID가, 끝났으면 마지막 종료 코드가 나온다
+        Command::new("launchctl").args(["list", &label(service)]).status()?;
+        Ok(())
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use std::ffi::{OsStr, OsString};
+    use std::fs::OpenOptions;
+    use std::process::{Child, Command, Stdio};
+    use std::sync::OnceLock;
+    use std::sync::mpsc::{self, RecvTimeoutError};
+    use std::time::Duration;
+    use windows_service::service::{
+        ServiceAccess, ServiceAction, ServiceActionType, ServiceControl, ServiceControlAccept, ServiceErrorControl, ServiceExitCode,
+        ServiceFailureActions, ServiceFailureResetPeriod, ServiceInfo, ServiceStartType, ServiceState, ServiceStatus, ServiceType,
+    };
+    use windows_service::service_control_handler::{self, ServiceControlHandlerResult, ServiceStatusHandle};
+    use windows_service::service_manager::{ServiceManager, ServiceManagerAccess};
+    use windows_service::{define_windows_service, service_dispatcher};
+
+    use super::*;
+
+    // 서비스 관리자가 `weim serve`가 끝났는지 볼 간격
+    const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+    // 서비스 관리자가 부르는 진입점은 인자를 받지 못하므로 여기에 둔다
+    static LAUNCH: OnceLock<(Service, PathBuf)> = OnceLock::new();
+
+    // 서비스에는 콘솔이 없으므로 출력은 언제나 파일로 보낸다
+    fn log_path(service: &Service) -> Result<PathBuf, Box<dyn Error>> {
+        match service.log_path()? {
+            Some(log) => Ok(log),
+            None => {
+                let data = env::var_os("ProgramData").map(PathBuf::from).unwrap_or_else(|| PathBuf::from(r"C:\ProgramData"));
+                Ok(data.join("weim").join(format!("{}.log", service.name)))
+            }
+        }
+    }
+
+    // 서비스 관리자가 실행할 `weim service run ...` 인자
+    fn launch_arguments(service: &Service, dir: &Path) -> Result<Vec<OsString>, Box<dyn Error>> {
+        let mut arguments: Vec<OsString> = vec!["service".into(), "run".into(), "--name".into(), service.name.clone().into()];
+        arguments.extend(["--dir".into(), dir.as_os_str().to_owned()]);
+        arguments.extend(["--log".into(), log_path(service)?.into_os_string()]);
+        if let Some(config) = service.config_path()? {
+            arguments.extend(["--config".into(), config.into_os_string()]);
+        }
+        arguments.push("--".into());
+        arguments.extend(service.args.iter().map(OsString::from));
+        Ok(arguments)
+    }
+
+    pub(super) fn install(service: &Service, exe: &Path, dir: &Path, dry_run: bool) -> Result<(), Box<dyn Error>> {
+        let arguments = launch_arguments(service, dir)?;
+        if dry_run {
+            let line: Vec<_> = arguments.iter().map(|argument| argument.to_string_lossy()).collect();
+            println!("{} {}", exe.display(), line.join(" "));
+            return Ok(());
+        }
+        let log = log_path(service)?;
+        if let Some(parent) = log.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let manager = ServiceManager::local_computer(None::<&str>, ServiceManagerAccess::CONNECT | ServiceManagerAccess::CREATE_SERVICE)?;
+        let info = ServiceInfo {
+            name: OsString::from(&service.name),
+            display_name: OsString::from(format!("weim ({})", service.name)),
+            service_type: ServiceType::OWN_PROCESS,
+            start_type: ServiceStartType::AutoStart,
+            error_control: ServiceErrorControl::Normal,
+            executable_path: exe.to_path_buf(),
+            launch_arguments: arguments,
+            dependencies: Vec::new(),
+            // LocalSystem
+            account_name: None,
+            account_password: None,
+        };
+        let handle = manager.create_service(&info, ServiceAccess::CHANGE_CONFIG | ServiceAccess::START)?;
+        handle.set_description("weim location daemon")?;
+        let restart = ServiceAction { action_type: ServiceActionType::Restart, delay: Duration::from_secs(RESTART_DELAY) };
+        handle.update_failure_actions(ServiceFailureActions {
+            reset_period: ServiceFailureResetPeriod::After(Duration::from_secs(24 * 60 * 60)),
+            reboot_msg: None,
+            command: None,
+            actions: Some(vec![restart.clone(), restart.clone(), restart]),
+        })?;
+        // `weim serve`가 오류로 끝나 서비스가 0이 아닌 코드로 멈춰도 다시 시작한다
+        handle.set_failure_actions_on_non_crash_failures(true)?;
+        handle.start(&[] as &[&OsStr])?;
+
+        println!("{}", pick(service.language, "✅ Windows 서비스를 등록하고 시작했습니다:", "✅ Registered and started the Windows service:"));
+        println!("   {}", service.name);
+        println!("{} {}", pick(service.language, "📜 출력:", "📜 Output:"), log.display());
+        Ok(())
+    }
+
+    pub(super) fn uninstall(service: &Service) -> Result<(), Box<dyn Error>> {
+        let manager = ServiceManager::local_computer(None::<&str>, ServiceManagerAccess::CONNECT)?;
+        let handle = manager.open_service(&service.name, ServiceAccess::QUERY_STATUS | ServiceAccess::STOP | ServiceAccess::DELETE)?;
+        if handle.query_status()?.current_state != ServiceState::Stopped {
+            handle.stop()?;
+        }
+        // 열린 핸들이 모두 닫히면 지워진다
+        handle.delete()?;
+        println!("{} {}", pick(service.language, "🗑️ 서비스를 지웠습니다:", "🗑️ Removed the service:"), service.name);
+        Ok(())
+    }
+
+    pub(super) fn status(service: &Service) -> Result<(), Box<dyn Error>> {
+        let manager = ServiceManager::local_computer(None::<&str>, ServiceManagerAccess::CONNECT)?;
+        let handle = manager.open_service(&service.name, ServiceAccess::QUERY_STATUS | ServiceAccess::QUERY_CONFIG)?;
+        let (status, config) = (handle.query_status()?, handle.query_config()?);
+        println!("{} {:?}", pick(service.language, "상태:", "State:"), status.current_state);
+        if let Some(pid) = status.process_id {
+            println!("PID: {}", pid);
+        }
+        println!("{} {:?}", pick(service.language, "시작:", "Start:"), config.start_type);
+        println!("{} {}", pick(service.language, "명령:", "Command:"), config.executable_path.display());
+        Ok(())
+    }
+
+    pub(super) fn run(service: Service, dir: PathBuf) -> Result<(), Box<dyn Error>> {
+        let name = service.name.clone();
+        LAUNCH.set((service, dir)).ok();
+        service_dispatcher::start(name, ffi_service_main)?;
+        Ok(())
+    }
+
+    define_windows_service!(ffi_service_main, service_main);
+
+    fn service_main(_arguments: Vec<OsString>) {
+        if let Some((service, dir)) = LAUNCH.get() {
+            // 실행 중 오류는 `weim serve`의 출력과 같은 파일에 남긴다
+            if let Err(e) = supervise(service, dir)
+                && let Ok(log) = log_path(service)
+                && let Ok(mut file) = OpenOptions::new().create(true).append(true).open(log)
+            {
+                use std::io::Write;
+                writeln!(file, "❌ {}", e).ok();
+            }
+        }
+    }
+
+    fn set_state(handle: &ServiceStatusHandle, state: ServiceState, code: u32) -> windows_service::Result<()> {
+        handle.set_service_status(ServiceStatus {
+            service_type: ServiceType::OWN_PROCESS,
+            current_state: state,
+            controls_accepted: if state == ServiceState::Running { ServiceControlAccept::STOP | ServiceControlAccept::SHUTDOWN } else { ServiceControlAccept::empty() },
+            exit_code: if code == 0 { ServiceExitCode::Win32(0) } else { ServiceExitCode::ServiceSpecific(code) },
+            checkpoint: 0,
+            wait_hint: Duration::default(),
+            process_id: None,
+        })
+    }
+
+    // `weim serve`를 띄우고, 멈추라고 하면 끝내고, 스스로 끝나면 같은 코드로 서비스를 멈춘다
+    fn supervise(service: &Service, dir: &Path) -> Result<(), Box<dyn Error>> {
+        let (stop_tx, stop_rx) = mpsc::channel();
+        let handle = service_control_handler::register(&service.name, move |control| match control {
+            ServiceControl::Stop | ServiceControl::Shutdown => {
+                stop_tx.send(()).ok();
+                ServiceControlHandlerResult::NoError
+            }
+            ServiceControl::Interrogate => ServiceControlHandlerResult::NoError,
+            _ => ServiceControlHandlerResult::NotImplemented,
+        })?;
+
+        let mut child = match spawn(service, dir) {
+            Ok(child) => child,
+            Err(e) => {
+                set_state(&handle, ServiceState::Stopped, 1)?;
+                return Err(e);
+            }
+        };
+        set_state(&handle, ServiceState::Running, 0)?;
+        let code = loop {
+            match stop_rx.recv_timeout(CHECK_INTERVAL) {
+                Ok(()) | Err(RecvTimeoutError::Disconnected) => {
+                    child.kill().ok();
+                    child.wait().ok();
+                    break 0;
+                }
+                Err(RecvTimeoutError::Timeout) => {
+                    if let Some(status) = child.try_wait()? {
+                        // 서비스 관리자는 0이 아닌 코드를 실패로 보고 다시 시작한다
+                        break if status.success() { 0 } else { status.code().map_or(1, |code| code as u32).max(1) };
+                    }
+                }
+            }
+        };
+        set_state(&handle, ServiceState::Stopped, code)?;
+        Ok(())
+    }
+
+    fn spawn(service: &Service, dir: &Path) -> Result<Child, Box<dyn Error>> {
+        let log = log_path(service)?;
+        if let Some(parent) = log.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let stdout = OpenOptions::new().create(true).append(true).open(&log)?;
+        let stderr = stdout.try_clone()?;
+        let mut command = Command::new(env::current_exe()?);
+        command.arg("serve").args(&service.args).current_dir(dir).stdin(Stdio::null()).stdout(stdout).stderr(stderr);
+        if let Some(config) = &service.config {
+            command.env(CONFIG_ENV, config);
+        }
+        Ok(command.spawn()?)
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+mod platform {
+    use super::*;
+
+    fn unsupported(service: &Service) -> Box<dyn Error> {
+        pick(service.language, "이 운영체제에서는 서비스를 등록할 수 없습니다", "services are not supported on this operating system").into()
+    }
+
+    pub(super) fn install(service: &Service, _exe: &Path, _dir: &Path, _dry_run: bool) -> Result<(), Box<dyn Error>> {
+        Err(unsupported(service))
+    }
+
+    pub(super) fn uninstall(service: &Service) -> Result<(), Box<dyn Error>> {
+        Err(unsupported(service))
+    }
+
+    pub(super) fn status(service: &Service) -> Result<(), Box<dyn Error>> {
+        Err(unsupported(service))
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn home(language: Language) -> Result<PathBuf, Box<dyn Error>> {
+    env::var_os("HOME")
+        .map(PathBuf::from)
+        .ok_or_else(|| pick(language, "HOME 환경 변수가 없습니다", "the HOME environment variable is not set").into())
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn not_installed(service: &Service, path: &Path) -> Box<dyn Error> {
+    format!("{}: {}", pick(service.language, "설치된 서비스가 없습니다", "the service is not installed"), path.display()).into()
+}
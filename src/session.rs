@@ -0,0 +1,154 @@
+use std::fmt;
+use std::net::{SocketAddr, TcpListener};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::console::info;
+use crate::i18n::tr;
+use crate::locator::Mode;
+use crate::{CancellationToken, Location, Weim, WeimError};
+
+// 닫은 서버가 포트를 놓을 때까지 기다리는 최대 시간
+const RELEASE_TIMEOUT: Duration = Duration::from_secs(1);
+
+impl Weim {
+    /// 서버를 한 번 열어 두고 여러 번 위치를 묻는 `Locator`를 만든다. 서버는 `start()`나 첫 `locate()`에서 연다.
+    pub fn locator(&self) -> Locator {
+        Locator { weim: self.clone(), running: None }
+    }
+}
+
+/// 서버와 브라우저 탭을 열어 둔 채 `locate()`마다 위치를 돌려준다.
+///
+/// `stop()`으로 닫은 뒤 `start()`로 다시 열 수 있고, 버리면 서버를 닫는다.
+/// `timeout`은 `locate()` 한 번을 기다리는 시간으로 쓰인다.
+pub struct Locator {
+    weim: Weim,
+    running: Option<Running>,
+}
+
+struct Running {
+    addr: SocketAddr,
+    token: CancellationToken,
+    server: Arc<tiny_http::Server>,
+    rx: Receiver<Result<Location, WeimError>>,
+    // 마지막으로 받은 위치와 받은 시각
+    last: Option<(Instant, Location)>,
+    thread: JoinHandle<()>,
+}
+
+impl Locator {
+    /// 서버를 열고 브라우저를 연다. 이미 열려 있으면 그 주소를 돌려준다.
+    pub fn start(&mut self) -> Result<SocketAddr, WeimError> {
+        if let Some(running) = &self.running {
+            return Ok(running.addr);
+        }
+
+        // 제한 시간은 `locate()`에서 재고, 서버는 멈출 때까지 돈다
+        let mut weim = self.weim.clone();
+        weim.config.timeout = None;
+        let (server, browser) = weim.open()?;
+        let addr = server.server_addr().to_ip().ok_or(WeimError::ServerClosed)?;
+        let server = Arc::new(server);
+
+        let token = CancellationToken::new();
+        let (tx, rx) = mpsc::channel();
+        let (thread_server, thread_token) = (Arc::clone(&server), token.clone());
+        let thread = thread::spawn(move || {
+            let _browser = browser;
+            let result = weim.run(&thread_server, &thread_token, Mode::Session, |_, location| tx.send(Ok(location)).is_ok());
+            if let Err(e) = result {
+                tx.send(Err(e)).ok();
+            }
+        });
+
+        info!("{}", tr!("🛰️ {}에서 위치 요청을 기다립니다.", "🛰️ Waiting for location requests at {}.", addr));
+        self.running = Some(Running { addr, token, server, rx, last: None, thread });
+        Ok(addr)
+    }
+
+    /// 서버를 닫고 브라우저를 정리한다. 요청을 기다리던 서버 스레드가 끝날 때까지 기다린다.
+    pub fn stop(&mut self) {
+        if let Some(Running { addr, token, server, thread, .. }) = self.running.take() {
+            token.cancel();
+            server.unblock();
+            thread.join().ok();
+            drop(server);
+            // tiny_http는 받기 스레드가 깨어난 뒤에야 포트를 놓으므로, 바로 같은 포트로 다시 열 수 있게 기다린다
+            let deadline = Instant::now() + RELEASE_TIMEOUT;
+            while TcpListener::bind(addr).is_err() && Instant::now() < deadline {
+                thread::sleep(Duration::from_millis(10));
+            }
+        }
+    }
+
+    /// 서버가 열려 있는지 여부
+    pub fn is_running(&self) -> bool {
+        self.running.is_some()
+    }
+
+    /// 서버가 열린 주소. 닫혀 있으면 None
+    pub fn addr(&self) -> Option<SocketAddr> {
+        self.running.as_ref().map(|running| running.addr)
+    }
+
+    /// 받은 지 `max_age`가 지나지 않은 마지막 위치. `Weim::last_known()`과 같다.
+    pub fn last_known(&self, max_age: Duration) -> Option<Location> {
+        self.weim.last_known(max_age)
+    }
+
+    /// 위치를 하나 받는다. 서버가 닫혀 있으면 먼저 연다.
+    ///
+    /// `maximum_age()`보다 최근에 받은 위치가 있으면 그대로 돌려주고, 없으면 페이지가 다음 위치를 보낼 때까지 기다린다.
+    /// 서버가 오류로 멈췄으면 그 오류를 돌려주고, 다음 `locate()`가 서버를 다시 연다.
+    /// 사용자가 위치 권한을 거절하거나 브라우저가 위치를 주지 못한 때도 마찬가지다.
+    pub fn locate(&mut self) -> Result<Location, WeimError> {
+        self.start()?;
+        let result = self.receive();
+        if result.is_err() && self.running.as_ref().is_some_and(|running| running.thread.is_finished()) {
+            self.stop();
+        }
+        result
+    }
+
+    fn receive(&mut self) -> Result<Location, WeimError> {
+        let (maximum_age, timeout) = (self.weim.config.maximum_age, self.weim.config.timeout);
+        let running = self.running.as_mut().ok_or(WeimError::ServerClosed)?;
+
+        // 쌓여 있는 위치 중 마지막 것만 기억한다
+        while let Ok(received) = running.rx.try_recv() {
+            running.last = Some((Instant::now(), received?));
+        }
+        if let Some((at, location)) = &running.last
+            && at.elapsed() < maximum_age
+        {
+            return Ok(location.clone());
+        }
+
+        let received = match timeout {
+            Some(timeout) => running.rx.recv_timeout(timeout).map_err(|e| match e {
+                RecvTimeoutError::Timeout => WeimError::Timeout,
+                RecvTimeoutError::Disconnected => WeimError::ServerClosed,
+            })?,
+            None => running.rx.recv().map_err(|_| WeimError::ServerClosed)?,
+        };
+        let location = received?;
+        running.last = Some((Instant::now(), location.clone()));
+        Ok(location)
+    }
+}
+
+// tiny_http 서버는 Debug가 없다
+impl fmt::Debug for Locator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Locator").field("weim", &self.weim).field("addr", &self.addr()).finish()
+    }
+}
+
+impl Drop for Locator {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
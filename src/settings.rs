@@ -0,0 +1,559 @@
+use std::env;
+use std::fmt::Display;
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use serde::{Deserialize, Deserializer};
+use toml::{Table, Value};
+
+use crate::console::warning;
+use crate::i18n::tr;
+use crate::map::MapProvider;
+use crate::privacy::Coarsening;
+use crate::provider::FallbackChain;
+#[cfg(feature = "encryption")]
+use crate::HistoryKey;
+#[cfg(feature = "history")]
+use crate::Retention;
+use crate::{CsvLog, IpRange, Language, RateLimit, Schedule, Weim, WeimBuilder, WeimError};
+
+/// 설정 파일 경로를 정하는 환경 변수. 없으면 현재 디렉터리의 `weim.toml`을 읽는다.
+pub const CONFIG_ENV: &str = "WEIM_CONFIG";
+
+const CONFIG_FILE: &str = "weim.toml";
+
+// `WEIM_<이름>` 환경 변수로 덮어쓸 수 있는 설정. 표(`[mqtt]` 등)는 파일에서만 정한다.
+const ENV_KEYS: &[&str] = &[
+    "port",
+    "port_fallback",
+    "bind_address",
+    "lan",
+    "allow",
+    "deny",
+    "rate_limit",
+    "allowed_origins",
+    "content_security_policy",
+    "workers",
+    "https",
+    "history",
+    "history_max_age",
+    "history_max_rows",
+    "history_passphrase",
+    "history_key_file",
+    "csv_log",
+    "cache_file",
+    "access_log",
+    "api",
+    "metrics",
+    "daemon_socket",
+    "schedule",
+    "timeout",
+    "high_accuracy",
+    "position_timeout",
+    "maximum_age",
+    "accuracy_threshold",
+    "coarse_location",
+    "max_attempts",
+    "open_browser",
+    "headless",
+    "axum",
+    "browser_grace",
+    "response_grace",
+    "consent_screen",
+    "show_map",
+    "map_links",
+    "manual_entry",
+    "page_template",
+    "language",
+    "providers",
+];
+
+// `weim.toml`의 모양. 시간은 초 단위이고, 없는 항목은 빌더의 기본값을 그대로 둔다.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Settings {
+    port: Option<u16>,
+    port_fallback: Option<bool>,
+    bind_address: Option<IpAddr>,
+    lan: Option<bool>,
+    #[serde(default)]
+    allow: Vec<IpRange>,
+    #[serde(default)]
+    deny: Vec<IpRange>,
+    // 초당 요청 수. 0이면 끈다
+    rate_limit: Option<f64>,
+    allowed_origins: Option<Vec<String>>,
+    // 빈 문자열이면 붙이지 않는다
+    content_security_policy: Option<String>,
+    workers: Option<usize>,
+    https: Option<bool>,
+    history: Option<PathBuf>,
+    #[serde(default, deserialize_with = "seconds")]
+    history_max_age: Option<Duration>,
+    history_max_rows: Option<u64>,
+    history_passphrase: Option<String>,
+    history_key_file: Option<PathBuf>,
+    csv_log: Option<PathBuf>,
+    cache_file: Option<PathBuf>,
+    access_log: Option<PathBuf>,
+    api: Option<bool>,
+    metrics: Option<bool>,
+    daemon_socket: Option<PathBuf>,
+    schedule: Option<Schedule>,
+    #[serde(default, deserialize_with = "seconds")]
+    timeout: Option<Duration>,
+    high_accuracy: Option<bool>,
+    #[serde(default, deserialize_with = "seconds")]
+    position_timeout: Option<Duration>,
+    #[serde(default, deserialize_with = "seconds")]
+    maximum_age: Option<Duration>,
+    accuracy_threshold: Option<f64>,
+    // 격자 한 변(m)
+    coarse_location: Option<f64>,
+    max_attempts: Option<u32>,
+    open_browser: Option<bool>,
+    headless: Option<bool>,
+    axum: Option<bool>,
+    #[serde(default, deserialize_with = "seconds")]
+    browser_grace: Option<Duration>,
+    #[serde(default, deserialize_with = "seconds")]
+    response_grace: Option<Duration>,
+    consent_screen: Option<bool>,
+    show_map: Option<bool>,
+    map_links: Option<Vec<MapProvider>>,
+    manual_entry: Option<bool>,
+    page_template: Option<PathBuf>,
+    language: Option<LanguageName>,
+    providers: Option<Vec<ProviderKind>>,
+    #[serde(default)]
+    webhooks: Vec<WebhookSettings>,
+    mqtt: Option<MqttSettings>,
+    traccar: Option<TraccarSettings>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum LanguageName {
+    Ko,
+    En,
+    Auto,
+}
+
+impl From<LanguageName> for Language {
+    fn from(name: LanguageName) -> Self {
+        match name {
+            LanguageName::Ko => Language::Korean,
+            LanguageName::En => Language::English,
+            LanguageName::Auto => Language::Auto,
+        }
+    }
+}
+
+/// `providers = [...]`에 쓰는 위치 공급자 이름. `FallbackChain::recommended()`가 이 순서로 시도한다.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum ProviderKind {
+    /// 운영체제 위치 API (Windows, macOS, GeoClue2)
+    Native,
+    Browser,
+    Gpsd,
+    Ip,
+}
+
+// 기능을 켜지 않았어도 파일 모양은 확인한다
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[cfg_attr(not(feature = "webhook"), allow(dead_code))]
+struct WebhookSettings {
+    url: String,
+    secret: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[cfg_attr(not(feature = "mqtt"), allow(dead_code))]
+struct MqttSettings {
+    host: String,
+    port: Option<u16>,
+    topic: Option<String>,
+    client_id: Option<String>,
+    username: Option<String>,
+    password: Option<String>,
+    tls: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[cfg_attr(not(feature = "traccar"), allow(dead_code))]
+struct TraccarSettings {
+    server: String,
+    device_id: String,
+    #[serde(default, deserialize_with = "seconds")]
+    interval: Option<Duration>,
+}
+
+fn seconds<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Duration>, D::Error> {
+    let seconds = f64::deserialize(deserializer)?;
+    Duration::try_from_secs_f64(seconds).map(Some).map_err(serde::de::Error::custom)
+}
+
+impl Weim {
+    /// `weim.toml`(또는 `WEIM_CONFIG`가 가리키는 파일)과 `WEIM_*` 환경 변수로 기본값을 채운 빌더 (`config` 기능 필요)
+    ///
+    /// 파일이 없으면 환경 변수만 쓴다. 환경 변수가 파일을 이기고, 돌려받은 빌더에 직접 준 설정이 둘 다 이긴다.
+    pub fn configured() -> Result<WeimBuilder, WeimError> {
+        let path = env::var_os(CONFIG_ENV).map(PathBuf::from);
+        let mut table = match &path {
+            Some(path) => read(path)?,
+            None => match read(Path::new(CONFIG_FILE)) {
+                Err(WeimError::Io(e)) if e.kind() == ErrorKind::NotFound => Table::new(),
+                other => other?,
+            },
+        };
+        // 잘못된 값이 파일에서 왔는지 환경 변수에서 왔는지 알 수 있게 따로 먼저 읽어 본다
+        let env = env_table();
+        parse(env.clone(), Path::new("WEIM_*"))?;
+        table.extend(env);
+        let settings = parse(table, path.as_deref().unwrap_or(Path::new(CONFIG_FILE)))?;
+        Ok(Weim::builder().settings(settings))
+    }
+}
+
+impl WeimBuilder {
+    /// TOML 설정 파일의 항목을 빌더에 적용한다. (`config` 기능 필요)
+    ///
+    /// 형식은 빌더 메서드 이름과 같고, 시간은 초 단위다. 이 메서드 뒤에 부른 빌더 설정이 파일을 이긴다.
+    ///
+    /// ```toml
+    /// port = 3030
+    /// language = "en"
+    /// timeout = 30
+    /// history = "weim.db"
+    /// providers = ["native", "browser", "ip"]
+    /// map_links = ["google", "kakao"]
+    ///
+    /// [mqtt]
+    /// host = "broker.local"
+    /// ```
+    pub fn config_file(self, path: impl AsRef<Path>) -> Result<Self, WeimError> {
+        let path = path.as_ref();
+        Ok(self.settings(parse(read(path)?, path)?))
+    }
+
+    /// `WEIM_PORT`, `WEIM_LANGUAGE`, `WEIM_HISTORY`처럼 `WEIM_` 뒤에 설정 이름을 대문자로 붙인 환경 변수를 적용한다. (`config` 기능 필요)
+    ///
+    /// 값은 TOML 값으로 읽고(`true`, `30`), 그렇게 읽히지 않으면 문자열로 쓴다. `WEIM_PROVIDERS`, `WEIM_MAP_LINKS`, `WEIM_ALLOW`, `WEIM_DENY`, `WEIM_ALLOWED_ORIGINS`는 쉼표로 나눠 쓸 수 있다.
+    pub fn env(self) -> Result<Self, WeimError> {
+        Ok(self.settings(parse(env_table(), Path::new("WEIM_*"))?))
+    }
+
+    fn settings(mut self, settings: Settings) -> Self {
+        if let Some(port) = settings.port {
+            self = self.port(port);
+        }
+        if let Some(enabled) = settings.port_fallback {
+            self = self.port_fallback(enabled);
+        }
+        if let Some(addr) = settings.bind_address {
+            self = self.bind_address(addr);
+        }
+        if let Some(enabled) = settings.lan {
+            self = self.lan(enabled);
+        }
+        for range in settings.allow {
+            self = self.allow(range);
+        }
+        for range in settings.deny {
+            self = self.deny(range);
+        }
+        if let Some(per_second) = settings.rate_limit {
+            self = self.rate_limit((per_second > 0.0).then(|| RateLimit::new(per_second)));
+        }
+        if let Some(origins) = settings.allowed_origins {
+            self = self.allowed_origins(origins);
+        }
+        if let Some(policy) = settings.content_security_policy {
+            self = self.content_security_policy(Some(policy).filter(|policy| !policy.is_empty()));
+        }
+        if let Some(workers) = settings.workers {
+            self = self.workers(workers);
+        }
+        if let Some(enabled) = settings.https {
+            #[cfg(feature = "tls")]
+            {
+                self = self.https(enabled);
+            }
+            #[cfg(not(feature = "tls"))]
+            if enabled {
+                unavailable("https", "tls");
+            }
+        }
+        if let Some(path) = settings.history {
+            #[cfg(feature = "history")]
+            {
+                self = self.history(path);
+            }
+            #[cfg(not(feature = "history"))]
+            {
+                let _ = path;
+                unavailable("history", "history");
+            }
+        }
+        if settings.history_max_age.is_some() || settings.history_max_rows.is_some() {
+            #[cfg(feature = "history")]
+            {
+                let mut retention = Retention::new();
+                if let Some(age) = settings.history_max_age {
+                    retention = retention.max_age(age);
+                }
+                if let Some(rows) = settings.history_max_rows {
+                    retention = retention.max_rows(rows);
+                }
+                self = self.history_retention(retention);
+            }
+            #[cfg(not(feature = "history"))]
+            unavailable("history_max_age", "history");
+        }
+        // 둘 다 있으면 열쇠 파일을 쓴다
+        #[cfg(feature = "encryption")]
+        if let Some(key) = settings.history_key_file.map(HistoryKey::File).or(settings.history_passphrase.map(HistoryKey::Passphrase)) {
+            self = self.history_key(key);
+        }
+        // 암호화하라고 했는데 할 수 없으면 평문으로 남기지 않는다
+        #[cfg(not(feature = "encryption"))]
+        if settings.history_key_file.is_some() || settings.history_passphrase.is_some() {
+            unavailable("history_key_file", "encryption");
+            #[cfg(feature = "history")]
+            {
+                self.config.history = None;
+            }
+        }
+        if let Some(path) = settings.csv_log {
+            self = self.csv_log(CsvLog::new(path));
+        }
+        if let Some(path) = settings.cache_file {
+            self = self.cache_file(path);
+        }
+        if let Some(path) = settings.access_log {
+            self = self.access_log(path);
+        }
+        if let Some(enabled) = settings.api {
+            self = self.api(enabled);
+        }
+        if let Some(enabled) = settings.metrics {
+            self = self.metrics(enabled);
+        }
+        if let Some(path) = settings.daemon_socket {
+            #[cfg(any(unix, windows))]
+            {
+                self = self.daemon_socket(path);
+            }
+            #[cfg(not(any(unix, windows)))]
+            {
+                let _ = path;
+                warning!("{}", tr!("⚠️ `{}` 설정은 Unix와 Windows에서만 씁니다.", "⚠️ The `{}` setting is only used on Unix and Windows.", "daemon_socket"));
+            }
+        }
+        if let Some(schedule) = settings.schedule {
+            self = self.schedule(schedule);
+        }
+        if let Some(timeout) = settings.timeout {
+            self = self.timeout(timeout);
+        }
+        if let Some(enabled) = settings.high_accuracy {
+            self = self.high_accuracy(enabled);
+        }
+        if let Some(timeout) = settings.position_timeout {
+            self = self.position_timeout(timeout);
+        }
+        if let Some(age) = settings.maximum_age {
+            self = self.maximum_age(age);
+        }
+        if let Some(meters) = settings.accuracy_threshold {
+            self = self.accuracy_threshold(meters);
+        }
+        if let Some(meters) = settings.coarse_location {
+            self = self.coarse_location(Coarsening::Grid(meters));
+        }
+        if let Some(attempts) = settings.max_attempts {
+            self = self.max_attempts(attempts);
+        }
+        if let Some(enabled) = settings.open_browser {
+            self = self.open_browser(enabled);
+        }
+        if let Some(enabled) = settings.headless {
+            #[cfg(feature = "headless")]
+            {
+                self = self.headless(enabled);
+            }
+            #[cfg(not(feature = "headless"))]
+            if enabled {
+                unavailable("headless", "headless");
+            }
+        }
+        if let Some(enabled) = settings.axum {
+            #[cfg(feature = "axum")]
+            {
+                self = self.axum(enabled);
+            }
+            #[cfg(not(feature = "axum"))]
+            if enabled {
+                unavailable("axum", "axum");
+            }
+        }
+        if let Some(grace) = settings.browser_grace {
+            self = self.browser_grace(grace);
+        }
+        if let Some(grace) = settings.response_grace {
+            self = self.response_grace(grace);
+        }
+        if let Some(enabled) = settings.consent_screen {
+            self = self.consent_screen(enabled);
+        }
+        if let Some(enabled) = settings.show_map {
+            self = self.show_map(enabled);
+        }
+        if let Some(providers) = settings.map_links {
+            self = self.map_links(providers);
+        }
+        if let Some(enabled) = settings.manual_entry {
+            self = self.manual_entry(enabled);
+        }
+        if let Some(path) = settings.page_template {
+            self = self.page_template_file(path);
+        }
+        if let Some(language) = settings.language {
+            self = self.language(language.into());
+        }
+        if let Some(providers) = settings.providers {
+            self.config.providers = Some(providers);
+        }
+
+        for webhook in settings.webhooks {
+            #[cfg(feature = "webhook")]
+            {
+                let mut sink = crate::Webhook::new(webhook.url);
+                if let Some(secret) = webhook.secret {
+                    sink = sink.secret(secret);
+                }
+                self = self.webhook(sink);
+            }
+            #[cfg(not(feature = "webhook"))]
+            {
+                let _ = webhook;
+                unavailable("webhooks", "webhook");
+            }
+        }
+        if let Some(mqtt) = settings.mqtt {
+            #[cfg(feature = "mqtt")]
+            {
+                let mut sink = crate::Mqtt::new(mqtt.host);
+                if let Some(port) = mqtt.port {
+                    sink = sink.port(port);
+                }
+                if let Some(topic) = mqtt.topic {
+                    sink = sink.topic(topic);
+                }
+                if let Some(id) = mqtt.client_id {
+                    sink = sink.client_id(id);
+                }
+                if let (Some(username), Some(password)) = (mqtt.username, mqtt.password) {
+                    sink = sink.credentials(username, password);
+                }
+                if let Some(enabled) = mqtt.tls {
+                    sink = sink.tls(enabled);
+                }
+                self = self.mqtt(sink);
+            }
+            #[cfg(not(feature = "mqtt"))]
+            {
+                let _ = mqtt;
+                unavailable("mqtt", "mqtt");
+            }
+        }
+        if let Some(traccar) = settings.traccar {
+            #[cfg(feature = "traccar")]
+            {
+                let mut sink = crate::Traccar::new(traccar.server, traccar.device_id);
+                if let Some(interval) = traccar.interval {
+                    sink = sink.interval(interval);
+                }
+                self = self.traccar(sink);
+            }
+            #[cfg(not(feature = "traccar"))]
+            {
+                let _ = traccar;
+                unavailable("traccar", "traccar");
+            }
+        }
+        self
+    }
+}
+
+// 켜지 않은 기능의 설정은 경고만 남긴다
+#[cfg_attr(
+    all(feature = "tls", feature = "history", feature = "headless", feature = "axum", feature = "webhook", feature = "mqtt", feature = "traccar"),
+    allow(dead_code)
+)]
+fn unavailable(name: &str, feature: &str) {
+    warning!("{}", tr!("⚠️ `{}` 설정은 `{}` 기능이 있어야 씁니다.", "⚠️ The `{}` setting needs the `{}` feature.", name, feature));
+}
+
+fn read(path: &Path) -> Result<Table, WeimError> {
+    let text = fs::read_to_string(path).map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path.display(), e)))?;
+    text.parse::<Table>().map_err(|e| config_error(path, e))
+}
+
+fn parse(table: Table, origin: &Path) -> Result<Settings, WeimError> {
+    Value::Table(table).try_into().map_err(|e| config_error(origin, e))
+}
+
+// toml 오류 메시지는 줄바꿈으로 끝난다
+fn config_error(origin: &Path, error: impl Display) -> WeimError {
+    WeimError::Config(format!("{}: {}", origin.display(), error.to_string().trim_end()))
+}
+
+// 아는 이름의 `WEIM_*` 환경 변수만 모은다. `WEIM_CHROME` 같은 다른 변수와 섞이지 않게 한다.
+fn env_table() -> Table {
+    let mut table = Table::new();
+    for key in ENV_KEYS {
+        let name = format!("WEIM_{}", key.to_ascii_uppercase());
+        let Ok(raw) = env::var(&name) else {
+            continue;
+        };
+        let value = match format!("value = {}", raw).parse::<Table>().ok().and_then(|mut parsed| parsed.remove("value")) {
+            Some(value) => value,
+            None if matches!(*key, "providers" | "map_links" | "allow" | "deny" | "allowed_origins") => Value::Array(raw.split(',').map(|name| Value::String(name.trim().to_string())).collect()),
+            None => Value::String(raw),
+        };
+        table.insert(key.to_string(), value);
+    }
+    table
+}
+
+/// 설정 파일의 `providers` 순서대로 만든 체인. 켜지 않은 기능의 공급자는 경고하고 건너뛴다.
+pub(crate) fn provider_chain(providers: &[ProviderKind], weim: Weim) -> FallbackChain {
+    let mut chain = FallbackChain::new();
+    for kind in providers {
+        chain = match kind {
+            ProviderKind::Browser => chain.with(weim.clone()),
+            ProviderKind::Gpsd => chain.with(crate::provider::GpsdProvider::new()),
+            #[cfg(feature = "ip-lookup")]
+            ProviderKind::Ip => chain.with(crate::provider::IpProvider::new()),
+            #[cfg(all(windows, feature = "windows-native"))]
+            ProviderKind::Native => chain.with(crate::provider::WindowsProvider::new()),
+            #[cfg(all(target_os = "macos", feature = "macos-native"))]
+            ProviderKind::Native => chain.with(crate::provider::MacOsProvider::new()),
+            #[cfg(all(target_os = "linux", feature = "geoclue"))]
+            ProviderKind::Native => chain.with(crate::provider::GeoClueProvider::new()),
+            #[allow(unreachable_patterns)]
+            other => {
+                warning!("{}", tr!("⚠️ {:?} 공급자는 이 빌드에서 쓸 수 없어 건너뜁니다.", "⚠️ Skipping the {:?} provider, which this build does not include.", other).to_lowercase());
+                chain
+            }
+        };
+    }
+    chain.last_known_fallback(true)
+}
@@ -0,0 +1,88 @@
+// 기록의 공간 색인 (`spatial` 기능 필요)
+//
+// 위경도를 지구 중심 기준 3차원 좌표(m)로 바꿔 R-트리에 넣는다. 날짜변경선과 극 근처에서도 가까운 점은 가깝고,
+// 두 점 사이의 직선(현) 길이는 대원 거리와 순서가 같아서 반경 검색과 최근접 검색을 그대로 쓸 수 있다.
+
+use std::fmt;
+
+use rstar::primitives::GeomWithData;
+use rstar::RTree;
+
+use crate::geo::{Coordinate, EARTH_RADIUS};
+use crate::{History, HistoryEntry, HistoryQuery, WeimError};
+
+type Fix = GeomWithData<[f64; 3], HistoryEntry>;
+
+/// 메모리에 둔 기록 전체의 R-트리. 처음 검색할 때 만들고, 덧붙이면 넣고, 지우면 버린다.
+pub(crate) struct SpatialIndex {
+    tree: RTree<Fix>,
+}
+
+impl SpatialIndex {
+    pub(crate) fn new(entries: Vec<HistoryEntry>) -> Self {
+        let fixes = entries.into_iter().map(|entry| Fix::new(point(&entry.location), entry)).collect();
+        SpatialIndex { tree: RTree::bulk_load(fixes) }
+    }
+
+    pub(crate) fn insert(&mut self, entry: HistoryEntry) {
+        self.tree.insert(Fix::new(point(&entry.location), entry));
+    }
+}
+
+// 색인 전체가 로그에 찍히지 않게 한다
+impl fmt::Debug for SpatialIndex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SpatialIndex").field("len", &self.tree.size()).finish()
+    }
+}
+
+impl History {
+    /// `center`에서 `radius`(m) 안의 위치를 가까운 것부터 돌려준다. (`spatial` 기능 필요)
+    ///
+    /// 처음 부를 때 기록 전체로 R-트리를 만들어 두고 이후에는 그것으로 찾는다.
+    /// `query`의 기간, 기기, 범위 조건을 함께 보고, 개수 제한은 가까운 것부터 센다.
+    pub fn within(&self, center: impl Coordinate, radius: f64, query: &HistoryQuery) -> Result<Vec<HistoryEntry>, WeimError> {
+        if radius.is_nan() || radius < 0.0 {
+            return Ok(Vec::new());
+        }
+        let origin = point(&center);
+        // 반경 안의 대원 거리를 직선 거리로. 지구 반 바퀴보다 멀면 전부다
+        let chord = 2.0 * EARTH_RADIUS * (radius / (2.0 * EARTH_RADIUS)).min(std::f64::consts::FRAC_PI_2).sin();
+        self.with_index(|tree| {
+            tree.nearest_neighbor_iter_with_distance_2(origin)
+                .take_while(|(_, distance)| *distance <= chord * chord)
+                .map(|(fix, _)| &fix.data)
+                .filter(|entry| query.matches(entry))
+                .take(query.max_count())
+                .cloned()
+                .collect()
+        })
+    }
+
+    /// `center`에서 가장 가까운 위치. 조건에 맞는 위치가 없으면 `None` (`spatial` 기능 필요)
+    ///
+    /// "이번 달에 이 카페 근처에 간 적이 있는지"처럼 기기나 기간을 좁혀 물을 때는 `query`로 준다.
+    pub fn nearest(&self, center: impl Coordinate, query: &HistoryQuery) -> Result<Option<HistoryEntry>, WeimError> {
+        let origin = point(&center);
+        self.with_index(|tree| tree.nearest_neighbor_iter(origin).map(|fix| &fix.data).find(|entry| query.matches(entry)).cloned())
+    }
+
+    fn with_index<T>(&self, search: impl FnOnce(&RTree<Fix>) -> T) -> Result<T, WeimError> {
+        let mut index = self.index.borrow_mut();
+        let index = match &mut *index {
+            Some(index) => index,
+            None => index.insert(SpatialIndex::new(self.query(&HistoryQuery::new())?)),
+        };
+        Ok(search(&index.tree))
+    }
+}
+
+// 지구 중심 기준 직교 좌표 (m). 구면으로 본다.
+fn point(coordinate: &impl Coordinate) -> [f64; 3] {
+    let (phi, lambda) = (coordinate.latitude().to_radians(), coordinate.longitude().to_radians());
+    [
+        EARTH_RADIUS * phi.cos() * lambda.cos(),
+        EARTH_RADIUS * phi.cos() * lambda.sin(),
+        EARTH_RADIUS * phi.sin(),
+    ]
+}
@@ -0,0 +1,311 @@
+//! 위치를 가운데 둔 정적 지도 이미지 (`static-map` 기능 필요)
+//!
+//! OpenStreetMap 타일을 받아 이어 붙이고, 정확도 원과 표식을 그려 PNG로 만든다.
+//! 보고서나 알림에 붙일 때 쓴다. 기본 타일 서버를 쓰면 이미지 곁에 "© OpenStreetMap contributors"를 밝혀야 한다.
+
+use std::f64::consts::PI;
+use std::fs;
+use std::io::Cursor;
+use std::path::Path;
+
+use crate::i18n::tr;
+use crate::{http, Location, WeimError};
+
+const DEFAULT_TILE_URL: &str = "https://tile.openstreetmap.org/{z}/{x}/{y}.png";
+const TILE_SIZE: u32 = 256;
+// 적도에서 확대 수준 0일 때 픽셀 하나의 길이 (m)
+const METERS_PER_PIXEL: f64 = 156_543.033_92;
+const MIN_ZOOM: u8 = 3;
+const MAX_ZOOM: u8 = 18;
+// 타일을 받지 못한 자리의 색
+const BLANK: [u8; 3] = [0xdd, 0xdd, 0xdd];
+const ACCURACY: [u8; 3] = [0x33, 0x88, 0xff];
+const MARKER: [u8; 3] = [0xe5, 0x39, 0x35];
+const WHITE: [u8; 3] = [0xff, 0xff, 0xff];
+// 점자 한 글자 안의 점 위치 (x, y). 순서대로 U+2800에 더할 비트다.
+const BRAILLE_DOTS: [(u32, u32); 8] = [(0, 0), (0, 1), (0, 2), (1, 0), (1, 1), (1, 2), (0, 3), (1, 3)];
+// 이보다 어둡거나 이웃과 이만큼 밝기가 다르면 점을 찍는다
+const DARK: f64 = 110.0;
+const EDGE: f64 = 40.0;
+
+/// 정적 지도 이미지 설정
+#[derive(Debug, Clone)]
+pub struct StaticMap {
+    tile_url: String,
+    width: u32,
+    height: u32,
+    zoom: Option<u8>,
+}
+
+impl Default for StaticMap {
+    fn default() -> Self {
+        StaticMap { tile_url: DEFAULT_TILE_URL.to_string(), width: 600, height: 400, zoom: None }
+    }
+}
+
+impl StaticMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `{z}`, `{x}`, `{y}`가 들어간 PNG 타일 주소 (기본값 tile.openstreetmap.org)
+    pub fn tile_url(mut self, url: impl Into<String>) -> Self {
+        self.tile_url = url.into();
+        self
+    }
+
+    /// 이미지 크기 (픽셀, 기본값 600×400)
+    pub fn size(mut self, width: u32, height: u32) -> Self {
+        self.width = width.clamp(1, 4096);
+        self.height = height.clamp(1, 4096);
+        self
+    }
+
+    /// 확대 수준 3~18 (기본값: 정확도 원이 이미지에 들어가는 가장 큰 수준)
+    pub fn zoom(mut self, zoom: u8) -> Self {
+        self.zoom = Some(zoom.clamp(MIN_ZOOM, MAX_ZOOM));
+        self
+    }
+
+    /// `location`을 가운데 둔 지도를 PNG 바이트로 만든다.
+    ///
+    /// 받지 못한 타일은 회색으로 남기고, 하나도 받지 못하면 `WeimError::Network`
+    pub fn render(&self, location: &Location) -> Result<Vec<u8>, WeimError> {
+        let (mut image, (x, y), radius) = self.compose(location)?;
+        // 정확도 원은 반투명하게 칠하고 테두리를 두른다
+        if radius >= 1.0 {
+            image.disc(x, y, radius, ACCURACY, 0.2);
+            image.ring(x, y, radius, 2.0, ACCURACY);
+        }
+        image.disc(x, y, 8.0, WHITE, 1.0);
+        image.disc(x, y, 6.0, MARKER, 1.0);
+        image.encode()
+    }
+
+    /// `location`을 가운데 둔 지도를 `columns`×`rows` 글자 크기의 점자(⠿) 그림으로 만든다.
+    ///
+    /// 글자 하나가 2×4 점이라 지도는 `columns * 2`×`rows * 4` 픽셀로 받는다. 타일의 경계선과 어두운 곳(길 테두리, 글자)을 점으로 찍고,
+    /// 정확도 원을 점선으로, 위치를 `◉`로 표시한다. `size()`는 쓰지 않는다.
+    pub fn terminal(&self, location: &Location, columns: u32, rows: u32) -> Result<String, WeimError> {
+        let map = StaticMap { width: columns.clamp(1, 512) * 2, height: rows.clamp(1, 256) * 4, ..self.clone() };
+        let (image, (x, y), radius) = map.compose(location)?;
+        let mut dots = image.outline();
+        if radius >= 2.0 {
+            for (dot_x, dot_y, distance) in image.near(x, y, radius + 1.0) {
+                if (distance - radius).abs() <= 0.5 {
+                    dots[(dot_y * i64::from(map.width) + dot_x) as usize] = true;
+                }
+            }
+        }
+
+        let (marker_column, marker_row) = ((x / 2.0) as u32, (y / 4.0) as u32);
+        let mut text = String::with_capacity(((map.width / 2 + 1) * map.height / 4 * 3) as usize);
+        for row in 0..map.height / 4 {
+            for column in 0..map.width / 2 {
+                if (column, row) == (marker_column, marker_row) {
+                    text.push('◉');
+                    continue;
+                }
+                let mut bits = 0;
+                for (bit, (dx, dy)) in BRAILLE_DOTS.iter().enumerate() {
+                    let index = ((row * 4 + dy) * map.width + column * 2 + dx) as usize;
+                    if dots[index] {
+                        bits |= 1 << bit;
+                    }
+                }
+                text.push(char::from_u32(0x2800 + bits).unwrap_or(' '));
+            }
+            text.push('\n');
+        }
+        Ok(text)
+    }
+
+    // 타일을 이어 붙인 이미지, 그 안의 위치 (픽셀), 정확도 원의 반지름 (픽셀)
+    fn compose(&self, location: &Location) -> Result<(Image, (f64, f64), f64), WeimError> {
+        let zoom = self.zoom.unwrap_or_else(|| self.fit(location));
+        let scale = f64::from(TILE_SIZE) * 2f64.powi(i32::from(zoom));
+        let (center_x, center_y) = world_pixel(location.latitude, location.longitude, scale);
+        let (left, top) = (center_x - f64::from(self.width) / 2.0, center_y - f64::from(self.height) / 2.0);
+
+        let mut image = Image::new(self.width, self.height);
+        let tiles = 1i64 << zoom;
+        let size = i64::from(TILE_SIZE);
+        let (first_x, first_y) = ((left.floor() as i64).div_euclid(size), (top.floor() as i64).div_euclid(size));
+        let (last_x, last_y) = (((left + f64::from(self.width)).ceil() as i64 - 1).div_euclid(size), ((top + f64::from(self.height)).ceil() as i64 - 1).div_euclid(size));
+        let mut last_error = None;
+        let mut fetched = 0;
+        for tile_y in first_y..=last_y {
+            // 극지방 너머에는 타일이 없다
+            if !(0..tiles).contains(&tile_y) {
+                continue;
+            }
+            for tile_x in first_x..=last_x {
+                // 날짜 변경선을 넘으면 반대편 타일을 쓴다
+                match self.tile(zoom, tile_x.rem_euclid(tiles), tile_y) {
+                    Ok(tile) => {
+                        image.paste(&tile, (tile_x * size) as f64 - left, (tile_y * size) as f64 - top);
+                        fetched += 1;
+                    }
+                    Err(e) => last_error = Some(e),
+                }
+            }
+        }
+        if fetched == 0
+            && let Some(e) = last_error
+        {
+            return Err(e);
+        }
+
+        let meters_per_pixel = METERS_PER_PIXEL * location.latitude.to_radians().cos() / 2f64.powi(i32::from(zoom));
+        Ok((image, (center_x - left, center_y - top), location.accuracy / meters_per_pixel))
+    }
+
+    /// `render()`한 이미지를 `path`에 PNG로 저장한다.
+    pub fn save(&self, location: &Location, path: impl AsRef<Path>) -> Result<(), WeimError> {
+        fs::write(path, self.render(location)?)?;
+        Ok(())
+    }
+
+    // 정확도 원의 지름이 짧은 변의 80%를 넘지 않는 가장 큰 확대 수준
+    fn fit(&self, location: &Location) -> u8 {
+        let available = f64::from(self.width.min(self.height)) * 0.8;
+        let ground = METERS_PER_PIXEL * location.latitude.to_radians().cos();
+        (MIN_ZOOM..=MAX_ZOOM)
+            .rev()
+            .find(|&zoom| 2.0 * location.accuracy / (ground / 2f64.powi(i32::from(zoom))) <= available)
+            .unwrap_or(MIN_ZOOM)
+    }
+
+    fn tile(&self, zoom: u8, x: i64, y: i64) -> Result<Image, WeimError> {
+        let url = self.tile_url.replace("{z}", &zoom.to_string()).replace("{x}", &x.to_string()).replace("{y}", &y.to_string());
+        let bytes = http::agent().get(&url).call().map_err(http::network_error)?.body_mut().read_to_vec().map_err(http::network_error)?;
+        Image::decode(&bytes).map_err(|e| WeimError::Network(tr!("지도 타일을 읽지 못했습니다 ({}): {}", "could not read map tile ({}): {}", url, e)))
+    }
+}
+
+// 메르카토르 투영에서 세계 전체를 `scale` 픽셀로 볼 때의 좌표
+fn world_pixel(latitude: f64, longitude: f64, scale: f64) -> (f64, f64) {
+    let phi = latitude.clamp(-85.051_128_78, 85.051_128_78).to_radians();
+    let x = (longitude + 180.0) / 360.0 * scale;
+    let y = (1.0 - (phi.tan() + 1.0 / phi.cos()).ln() / PI) / 2.0 * scale;
+    (x, y)
+}
+
+// RGB 8비트 이미지
+struct Image {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+impl Image {
+    fn new(width: u32, height: u32) -> Self {
+        Image { width, height, pixels: BLANK.repeat((width * height) as usize) }
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Image, png::DecodingError> {
+        let mut decoder = png::Decoder::new(Cursor::new(bytes));
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+        let mut reader = decoder.read_info()?;
+        let mut buffer = vec![0; reader.output_buffer_size().unwrap_or_default()];
+        let info = reader.next_frame(&mut buffer)?;
+        // 회색조와 알파 채널은 RGB로 맞춘다
+        let channels = info.color_type.samples();
+        let pixels = buffer[..info.buffer_size()]
+            .chunks_exact(channels)
+            .flat_map(|pixel| match channels {
+                1 | 2 => [pixel[0]; 3],
+                _ => [pixel[0], pixel[1], pixel[2]],
+            })
+            .collect();
+        Ok(Image { width: info.width, height: info.height, pixels })
+    }
+
+    fn encode(&self) -> Result<Vec<u8>, WeimError> {
+        let mut bytes = Vec::new();
+        let mut encoder = png::Encoder::new(&mut bytes, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder
+            .write_header()
+            .and_then(|mut writer| writer.write_image_data(&self.pixels))
+            .map_err(|e| WeimError::Io(std::io::Error::other(e)))?;
+        Ok(bytes)
+    }
+
+    // `other`의 왼쪽 위를 (left, top)에 맞춰 붙인다. 밖으로 나간 부분은 버린다.
+    fn paste(&mut self, other: &Image, left: f64, top: f64) {
+        let (left, top) = (left.round() as i64, top.round() as i64);
+        for y in 0..i64::from(other.height) {
+            for x in 0..i64::from(other.width) {
+                let source = ((y * i64::from(other.width) + x) * 3) as usize;
+                self.blend(left + x, top + y, [other.pixels[source], other.pixels[source + 1], other.pixels[source + 2]], 1.0);
+            }
+        }
+    }
+
+    // 점을 찍을 픽셀. 밝기가 급하게 바뀌는 곳과 어두운 곳이다.
+    fn outline(&self) -> Vec<bool> {
+        let (width, height) = (self.width as usize, self.height as usize);
+        let luminance: Vec<f64> = self
+            .pixels
+            .chunks_exact(3)
+            .map(|pixel| 0.299 * f64::from(pixel[0]) + 0.587 * f64::from(pixel[1]) + 0.114 * f64::from(pixel[2]))
+            .collect();
+        (0..width * height)
+            .map(|index| {
+                let (x, y) = (index % width, index / width);
+                let right = if x + 1 < width { luminance[index + 1] } else { luminance[index] };
+                let below = if y + 1 < height { luminance[index + width] } else { luminance[index] };
+                luminance[index] < DARK || (luminance[index] - right).abs() + (luminance[index] - below).abs() > EDGE
+            })
+            .collect()
+    }
+
+    fn disc(&mut self, cx: f64, cy: f64, radius: f64, color: [u8; 3], opacity: f64) {
+        for (x, y, distance) in self.near(cx, cy, radius) {
+            if distance <= radius {
+                self.blend(x, y, color, opacity);
+            }
+        }
+    }
+
+    fn ring(&mut self, cx: f64, cy: f64, radius: f64, thickness: f64, color: [u8; 3]) {
+        for (x, y, distance) in self.near(cx, cy, radius + thickness) {
+            if (distance - radius).abs() <= thickness / 2.0 {
+                self.blend(x, y, color, 1.0);
+            }
+        }
+    }
+
+    // 원을 둘러싼 사각형 중 이미지 안에 있는 픽셀과 중심까지의 거리
+    fn near(&self, cx: f64, cy: f64, radius: f64) -> impl Iterator<Item = (i64, i64, f64)> + use<> {
+        let x_range = ((cx - radius).floor().max(0.0) as i64)..=((cx + radius).ceil().min(f64::from(self.width) - 1.0) as i64);
+        let y_range = ((cy - radius).floor().max(0.0) as i64)..=((cy + radius).ceil().min(f64::from(self.height) - 1.0) as i64);
+        y_range.flat_map(move |y| x_range.clone().map(move |x| (x, y, (x as f64 + 0.5 - cx).hypot(y as f64 + 0.5 - cy))))
+    }
+
+    fn blend(&mut self, x: i64, y: i64, color: [u8; 3], opacity: f64) {
+        if x < 0 || y < 0 || x >= i64::from(self.width) || y >= i64::from(self.height) {
+            return;
+        }
+        let index = ((y * i64::from(self.width) + x) * 3) as usize;
+        for (channel, value) in self.pixels[index..index + 3].iter_mut().zip(color) {
+            *channel = (f64::from(*channel) * (1.0 - opacity) + f64::from(value) * opacity).round() as u8;
+        }
+    }
+}
+
+impl Location {
+    /// 이 위치를 가운데 둔 600×400 지도를 `path`에 PNG로 저장한다. 기본 OpenStreetMap 타일 서버를 쓴다. (`static-map` 기능 필요)
+    ///
+    /// 크기, 확대 수준, 타일 서버를 바꾸려면 `StaticMap`을 쓴다.
+    pub fn save_static_map(&self, path: impl AsRef<Path>) -> Result<(), WeimError> {
+        StaticMap::default().save(self, path)
+    }
+
+    /// 이 위치를 가운데 둔 `columns`×`rows` 글자 크기의 점자 지도. 기본 OpenStreetMap 타일 서버를 쓴다. (`static-map` 기능 필요)
+    pub fn terminal_map(&self, columns: u32, rows: u32) -> Result<String, WeimError> {
+        StaticMap::default().terminal(self, columns, rows)
+    }
+}
@@ -0,0 +1,28 @@
+use std::sync::OnceLock;
+use chrono::DateTime;
+use chrono_tz::Tz;
+use tzf_rs::DefaultFinder;
+
+use crate::Location;
+
+// 경계 데이터를 푸는 데 시간이 걸리므로 처음 쓸 때 한 번만 만든다
+fn finder() -> &'static DefaultFinder {
+    static FINDER: OnceLock<DefaultFinder> = OnceLock::new();
+    FINDER.get_or_init(DefaultFinder::new)
+}
+
+impl Location {
+    /// 위치가 속한 IANA 시간대 이름 (예: "Asia/Seoul", `timezone` 기능 필요)
+    ///
+    /// 내장된 경계 데이터로 찾으므로 네트워크를 쓰지 않는다. 찾지 못하면 `None`.
+    pub fn timezone(&self) -> Option<&'static str> {
+        let name = finder().get_tz_name(self.longitude, self.latitude);
+        (!name.is_empty()).then_some(name)
+    }
+
+    /// 위치를 받은 시각을 그 지점의 현지 시각으로 바꾼다. (`timezone` 기능 필요)
+    pub fn local_time(&self) -> Option<DateTime<Tz>> {
+        let tz: Tz = self.timezone()?.parse().ok()?;
+        Some(self.timestamp.with_timezone(&tz))
+    }
+}
@@ -0,0 +1,47 @@
+use std::fs;
+use std::net::IpAddr;
+use std::path::PathBuf;
+use tiny_http::SslConfig;
+
+use crate::console::info;
+use crate::WeimError;
+use crate::i18n::pick;
+
+/// HTTPS 인증서를 어디서 가져올지 (`tls` 기능 필요)
+#[derive(Debug, Clone)]
+pub(crate) enum Tls {
+    // 실행할 때마다 새로 만드는 자체 서명 인증서
+    SelfSigned,
+    // PEM 파일
+    Files { certificate: PathBuf, private_key: PathBuf },
+}
+
+impl Tls {
+    /// `host`는 인증서에 넣을 이 컴퓨터의 주소 (LAN IP 등)
+    pub(crate) fn ssl_config(&self, host: Option<IpAddr>) -> Result<SslConfig, WeimError> {
+        match self {
+            Tls::SelfSigned => {
+                let mut names = vec!["localhost".to_string(), "127.0.0.1".to_string(), "::1".to_string()];
+                names.extend(host.map(|ip| ip.to_string()));
+                let generated = rcgen::generate_simple_self_signed(names)
+                    .map_err(|e| WeimError::Tls(e.to_string()))?;
+                Ok(SslConfig {
+                    certificate: generated.cert.pem().into_bytes(),
+                    private_key: generated.signing_key.serialize_pem().into_bytes(),
+                })
+            }
+            Tls::Files { certificate, private_key } => Ok(SslConfig {
+                certificate: fs::read(certificate)?,
+                private_key: fs::read(private_key)?,
+            }),
+        }
+    }
+}
+
+/// 자체 서명 인증서 경고를 넘기는 방법을 안내한다.
+pub(crate) fn print_untrusted_notice() {
+    info!("{}", pick("🔒 자체 서명 인증서를 사용합니다. 브라우저에 보안 경고가 나오면:", "🔒 Using a self-signed certificate. If the browser shows a security warning:"));
+    info!("{}", pick("   - Chrome: '고급' → '(안전하지 않음)으로 이동'", "   - Chrome: 'Advanced' → 'Proceed (unsafe)'"));
+    info!("{}", pick("   - Safari: '세부사항 보기' → '이 웹 사이트 방문'", "   - Safari: 'Show Details' → 'visit this website'"));
+    info!("{}", pick("   - Firefox: '고급' → '위험을 감수하고 계속'", "   - Firefox: 'Advanced' → 'Accept the Risk and Continue'"));
+}
@@ -0,0 +1,171 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::console::warning;
+use crate::i18n::{pick, tr};
+use crate::{http, DeviceId, Location, WeimError};
+
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(5);
+const DEFAULT_BATCH_SIZE: usize = 50;
+const CAPACITY: usize = 1000;
+const METERS_PER_SECOND_TO_KNOTS: f64 = 1.943_844;
+
+#[derive(Debug)]
+struct Report {
+    device: String,
+    location: Location,
+}
+
+#[derive(Debug, Default)]
+struct Queue {
+    reports: VecDeque<Report>,
+    // 보내는 중인 묶음 크기
+    in_flight: usize,
+    worker: bool,
+}
+
+#[derive(Debug, Default)]
+struct Shared {
+    queue: Mutex<Queue>,
+    changed: Condvar,
+}
+
+/// 받은 위치를 Traccar 서버에 OsmAnd 프로토콜로 보내는 클라이언트 (`traccar` 기능 필요)
+///
+/// `interval`마다 그동안 쌓인 위치를 최대 `batch_size`개씩 차례로 보낸다.
+/// 서버에 닿지 않으면 대기열에 남겨 두었다가 다음 차례에 다시 보낸다.
+/// 복제한 값은 대기열을 함께 쓴다.
+#[derive(Debug, Clone)]
+pub struct Traccar {
+    server: String,
+    device_id: String,
+    interval: Duration,
+    batch_size: usize,
+    shared: Arc<Shared>,
+}
+
+impl Traccar {
+    /// OsmAnd 포트를 포함한 서버 주소(예: "http://demo.traccar.org:5055")와 Traccar 기기 식별자
+    ///
+    /// 식별자의 `{device}`는 weim 기기 이름으로 바뀌므로 여러 기기를 따로 추적할 수 있다.
+    pub fn new(server: impl Into<String>, device_id: impl Into<String>) -> Self {
+        Traccar {
+            server: server.into(),
+            device_id: device_id.into(),
+            interval: DEFAULT_INTERVAL,
+            batch_size: DEFAULT_BATCH_SIZE,
+            shared: Arc::default(),
+        }
+    }
+
+    /// 보고 사이의 간격 (기본값 5초). 0이면 받는 대로 보낸다.
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// 한 번에 보낼 최대 위치 수 (기본값 50)
+    pub fn batch_size(mut self, size: usize) -> Self {
+        self.batch_size = size.max(1);
+        self
+    }
+
+    /// 아직 보내지 못한 위치 수
+    pub fn pending(&self) -> usize {
+        let queue = self.shared.queue.lock().unwrap();
+        queue.reports.len() + queue.in_flight
+    }
+
+    /// 대기열이 빌 때까지 최대 `timeout` 동안 기다린다. 다 보냈으면 true
+    pub fn flush(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        let mut queue = self.shared.queue.lock().unwrap();
+        while !queue.reports.is_empty() || queue.in_flight > 0 {
+            let left = deadline.saturating_duration_since(Instant::now());
+            if left.is_zero() {
+                return false;
+            }
+            queue = self.shared.changed.wait_timeout(queue, left).unwrap().0;
+        }
+        true
+    }
+
+    /// 위치를 대기열에 넣는다. 바로 돌아온다.
+    pub fn report(&self, device: &DeviceId, location: &Location) {
+        let mut queue = self.shared.queue.lock().unwrap();
+        if queue.reports.len() >= CAPACITY {
+            queue.reports.pop_front();
+            warning!("{}", pick("⚠️ Traccar 대기열이 가득 차서 가장 오래된 위치를 버렸습니다.", "⚠️ Traccar queue is full, dropped the oldest location."));
+        }
+        queue.reports.push_back(Report {
+            device: self.device_id.replace("{device}", device.as_str()),
+            location: location.clone(),
+        });
+        if !queue.worker {
+            queue.worker = true;
+            let traccar = self.clone();
+            thread::spawn(move || traccar.work());
+        }
+        self.shared.changed.notify_all();
+    }
+
+    fn work(&self) {
+        loop {
+            let batch: Vec<Report> = {
+                let mut queue = self.shared.queue.lock().unwrap();
+                while queue.reports.is_empty() {
+                    queue = self.shared.changed.wait(queue).unwrap();
+                }
+                let count = queue.reports.len().min(self.batch_size);
+                queue.in_flight = count;
+                queue.reports.drain(..count).collect()
+            };
+
+            let mut unsent = batch.into_iter();
+            for report in unsent.by_ref() {
+                if let Err(e) = self.send(&report) {
+                    warning!("{}", tr!("⚠️ Traccar 서버에 위치를 보내지 못했습니다: {}", "⚠️ Could not send the location to the Traccar server: {}", e));
+                    // 순서를 지키도록 남은 것과 함께 앞에 되돌린다
+                    let mut queue = self.shared.queue.lock().unwrap();
+                    let rest: Vec<Report> = std::iter::once(report).chain(unsent.by_ref()).collect();
+                    for report in rest.into_iter().rev() {
+                        queue.reports.push_front(report);
+                    }
+                    queue.reports.truncate(CAPACITY);
+                    break;
+                }
+            }
+
+            self.shared.queue.lock().unwrap().in_flight = 0;
+            self.shared.changed.notify_all();
+            thread::sleep(self.interval);
+        }
+    }
+
+    fn send(&self, report: &Report) -> Result<(), WeimError> {
+        let location = &report.location;
+        let mut query = vec![
+            ("id", report.device.clone()),
+            ("lat", location.latitude.to_string()),
+            ("lon", location.longitude.to_string()),
+            // OsmAnd 프로토콜은 초 단위 Unix 시각을 받는다
+            ("timestamp", location.timestamp.timestamp().to_string()),
+            ("accuracy", location.accuracy.to_string()),
+        ];
+        let optional = [
+            ("altitude", location.altitude),
+            ("speed", location.speed.map(|speed| speed * METERS_PER_SECOND_TO_KNOTS)),
+            ("bearing", location.heading),
+        ];
+        query.extend(optional.into_iter().filter_map(|(key, value)| Some((key, value?.to_string()))));
+
+        http::agent()
+            .post(&self.server)
+            .query_pairs(query.iter().map(|(key, value)| (*key, value.as_str())))
+            .send_empty()
+            .map(|_| ())
+            .map_err(http::network_error)
+    }
+}
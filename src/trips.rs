@@ -0,0 +1,271 @@
+//! 이동 구간(트립) 찾기
+//!
+//! 시간순 위치를 한곳에 머문 구간으로 나눠 이동 구간마다 거리, 걸린 시간, 평균/최고 속력을 센다.
+//! 반경 `stop_radius` 안에 `min_stop` 이상 머물면 멈춘 것으로 보고, `max_gap`보다 긴 공백도 멈춘 것처럼 나눈다.
+
+use std::io::{self, Write};
+use std::ops::Range;
+use std::time::Duration;
+
+use serde_json::json;
+
+use crate::csv_log::escape_csv;
+use crate::export::rfc3339;
+#[cfg(feature = "geocoding")]
+use crate::geocode::Geocoder;
+use crate::geo::haversine;
+use crate::{DeviceId, Location};
+
+const CSV_HEADER: &str =
+    "device,start,end,duration_s,distance_m,average_speed_mps,max_speed_mps,points,start_latitude,start_longitude,end_latitude,end_longitude,start_address,end_address\n";
+
+/// 이동 구간 하나
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trip {
+    pub device: DeviceId,
+    /// 떠난 곳. 앞서 머문 곳의 마지막 위치다. `annotate()`를 거치면 `address`에 주소가 들어간다.
+    pub start: Location,
+    /// 도착한 곳. 다음에 머문 곳의 첫 위치다.
+    pub end: Location,
+    /// 위치를 이은 거리 (m)
+    pub distance: f64,
+    pub duration: Duration,
+    /// 가장 빨랐던 속력 (m/s). 기기가 알려 준 속력이 있으면 그것을, 없으면 앞 위치와의 거리와 시간으로 잰다.
+    pub max_speed: f64,
+    /// 구간 안의 위치 수 (떠난 곳과 도착한 곳 포함)
+    pub points: usize,
+}
+
+impl Trip {
+    /// 평균 속력 (m/s)
+    pub fn average_speed(&self) -> f64 {
+        match self.duration.as_secs_f64() {
+            0.0 => 0.0,
+            seconds => self.distance / seconds,
+        }
+    }
+}
+
+/// 멈춤과 이동을 가르는 기준
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TripDetector {
+    stop_radius: f64,
+    min_stop: Duration,
+    max_gap: Duration,
+    min_distance: f64,
+}
+
+impl Default for TripDetector {
+    fn default() -> Self {
+        TripDetector {
+            stop_radius: 100.0,
+            min_stop: Duration::from_secs(5 * 60),
+            max_gap: Duration::from_secs(30 * 60),
+            min_distance: 200.0,
+        }
+    }
+}
+
+impl TripDetector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 이 반경(m) 안에서 움직이면 머문 것으로 본다 (기본값 100m)
+    pub fn stop_radius(mut self, meters: f64) -> Self {
+        self.stop_radius = meters;
+        self
+    }
+
+    /// 이만큼 머물면 멈춘 것이다 (기본값 5분)
+    pub fn min_stop(mut self, duration: Duration) -> Self {
+        self.min_stop = duration;
+        self
+    }
+
+    /// 위치 사이가 이보다 비면 기록이 끊긴 것으로 보고 구간을 나눈다 (기본값 30분)
+    pub fn max_gap(mut self, gap: Duration) -> Self {
+        self.max_gap = gap;
+        self
+    }
+
+    /// 이보다 짧은 구간(m)은 GPS 흔들림으로 보고 버린다 (기본값 200m)
+    pub fn min_distance(mut self, meters: f64) -> Self {
+        self.min_distance = meters;
+        self
+    }
+
+    /// 한 기기의 시간순 위치에서 이동 구간을 찾는다.
+    pub fn detect(&self, points: &[Location]) -> Vec<Trip> {
+        let stationary = self.stationary(points);
+        let mut trips = Vec::new();
+        // 지금 모으는 구간과, 그 안에 움직인 위치가 있는지
+        let mut current: Vec<&Location> = Vec::new();
+        let mut moving = false;
+        for (i, point) in points.iter().enumerate() {
+            if i > 0 && self.gap(&points[i - 1], point) > self.max_gap {
+                self.finish(&mut trips, &current, moving);
+                current.clear();
+                moving = false;
+            }
+            if stationary[i] {
+                // 머문 곳에 닿으면 구간을 끝내고, 여기서 다시 떠날 준비를 한다
+                if moving {
+                    current.push(point);
+                    self.finish(&mut trips, &current, moving);
+                }
+                current.clear();
+                current.push(point);
+                moving = false;
+            } else {
+                current.push(point);
+                moving = true;
+            }
+        }
+        self.finish(&mut trips, &current, moving);
+        trips
+    }
+
+    // 반경 안에 `min_stop` 이상 이어진 위치들
+    fn stationary(&self, points: &[Location]) -> Vec<bool> {
+        let mut stationary = vec![false; points.len()];
+        for stay in stays(points, self.stop_radius, self.min_stop, self.max_gap) {
+            stationary[stay].fill(true);
+        }
+        stationary
+    }
+
+    fn gap(&self, from: &Location, to: &Location) -> Duration {
+        (to.timestamp - from.timestamp).to_std().unwrap_or_default()
+    }
+
+    fn finish(&self, trips: &mut Vec<Trip>, points: &[&Location], moving: bool) {
+        let (Some(&start), Some(&end)) = (points.first(), points.last()) else {
+            return;
+        };
+        if !moving || points.len() < 2 {
+            return;
+        }
+        let mut distance = 0.0;
+        let mut max_speed: f64 = 0.0;
+        for pair in points.windows(2) {
+            let step = haversine(pair[0], pair[1]);
+            distance += step;
+            let seconds = self.gap(pair[0], pair[1]).as_secs_f64();
+            let speed = pair[1].speed.unwrap_or(if seconds > 0.0 { step / seconds } else { 0.0 });
+            max_speed = max_speed.max(speed);
+        }
+        if distance < self.min_distance {
+            return;
+        }
+        trips.push(Trip {
+            device: DeviceId::default(),
+            start: start.clone(),
+            end: end.clone(),
+            distance,
+            duration: self.gap(start, end),
+            max_speed,
+            points: points.len(),
+        });
+    }
+}
+
+/// 첫 위치에서 `radius`(m) 안에 `min_stay` 이상 머문 구간들. 위치 사이가 `max_gap`보다 비면 거기서 끊는다.
+pub(crate) fn stays(points: &[Location], radius: f64, min_stay: Duration, max_gap: Duration) -> Vec<Range<usize>> {
+    let elapsed = |from: &Location, to: &Location| (to.timestamp - from.timestamp).to_std().unwrap_or_default();
+    let mut stays = Vec::new();
+    let mut i = 0;
+    while i < points.len() {
+        let mut j = i;
+        while j + 1 < points.len() && haversine(&points[i], &points[j + 1]) <= radius && elapsed(&points[j], &points[j + 1]) <= max_gap {
+            j += 1;
+        }
+        if elapsed(&points[i], &points[j]) >= min_stay {
+            stays.push(i..j + 1);
+            i = j + 1;
+        } else {
+            i += 1;
+        }
+    }
+    stays
+}
+
+/// 구간마다 떠난 곳과 도착한 곳의 주소를 채운다. 찾지 못한 곳은 비워 둔다. (`geocoding` 기능 필요)
+#[cfg(feature = "geocoding")]
+pub fn annotate(trips: &mut [Trip], geocoder: &dyn Geocoder) {
+    for trip in trips {
+        for place in [&mut trip.start, &mut trip.end] {
+            if place.address.is_none() {
+                place.address = geocoder.reverse(place).ok().flatten();
+            }
+        }
+    }
+}
+
+/// 구간 요약을 CSV로 쓴다. 시각은 RFC 3339, 시간은 초, 거리는 m, 속력은 m/s다.
+pub fn write_csv<W: Write>(mut out: W, trips: &[Trip]) -> io::Result<()> {
+    out.write_all(CSV_HEADER.as_bytes())?;
+    for trip in trips {
+        writeln!(
+            out,
+            "{},{},{},{:.0},{:.1},{:.2},{:.2},{},{},{},{},{},{},{}",
+            escape_csv(trip.device.as_str()),
+            rfc3339(trip.start.timestamp),
+            rfc3339(trip.end.timestamp),
+            trip.duration.as_secs_f64(),
+            trip.distance,
+            trip.average_speed(),
+            trip.max_speed,
+            trip.points,
+            trip.start.latitude,
+            trip.start.longitude,
+            trip.end.latitude,
+            trip.end.longitude,
+            escape_csv(trip.start.address.as_deref().unwrap_or_default()),
+            escape_csv(trip.end.address.as_deref().unwrap_or_default()),
+        )?;
+    }
+    Ok(())
+}
+
+/// 구간 요약을 JSON 배열로 쓴다. 열 이름과 단위는 `write_csv()`와 같다.
+pub fn write_json<W: Write>(out: W, trips: &[Trip]) -> io::Result<()> {
+    let place = |location: &Location| {
+        json!({
+            "time": rfc3339(location.timestamp),
+            "latitude": location.latitude,
+            "longitude": location.longitude,
+            "address": location.address,
+        })
+    };
+    let summary: Vec<_> = trips
+        .iter()
+        .map(|trip| {
+            json!({
+                "device": trip.device,
+                "start": place(&trip.start),
+                "end": place(&trip.end),
+                "duration_s": trip.duration.as_secs_f64(),
+                "distance_m": trip.distance,
+                "average_speed_mps": trip.average_speed(),
+                "max_speed_mps": trip.max_speed,
+                "points": trip.points,
+            })
+        })
+        .collect();
+    serde_json::to_writer_pretty(out, &summary).map_err(io::Error::from)
+}
+
+#[cfg(feature = "history")]
+impl crate::History {
+    /// 조건에 맞는 위치에서 기기별로 이동 구간을 찾아 떠난 시각순으로 돌려준다.
+    pub fn trips(&self, query: &crate::HistoryQuery, detector: &TripDetector) -> Result<Vec<Trip>, crate::WeimError> {
+        let mut trips = Vec::new();
+        for track in self.tracks(query)? {
+            let device = DeviceId(track.name);
+            trips.extend(detector.detect(&track.points).into_iter().map(|trip| Trip { device: device.clone(), ..trip }));
+        }
+        trips.sort_by_key(|trip| trip.start.timestamp);
+        Ok(trips)
+    }
+}
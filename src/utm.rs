@@ -0,0 +1,292 @@
+//! UTM과 MGRS 좌표 변환
+//!
+//! WGS84 위도/경도를 UTM 구역, 동향(easting), 북향(northing)으로 바꾸고 되돌린다.
+//! MGRS는 UTM 위에 100km 격자 글자를 붙인 표기로, 측량이나 수색 구조에서 위치를 부를 때 쓴다.
+//! UTM이 다루는 위도 -80°~84° 밖(극지방, UPS)은 지원하지 않는다.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::geo::{normalize_longitude, Coordinate};
+use crate::i18n::tr;
+use crate::projection::{TransverseMercator, WGS84};
+use crate::{Location, WeimError};
+
+const SCALE: f64 = 0.9996;
+const FALSE_EASTING: f64 = 500_000.0;
+const FALSE_NORTHING: f64 = 10_000_000.0;
+const MIN_LATITUDE: f64 = -80.0;
+const MAX_LATITUDE: f64 = 84.0;
+
+// 위도 8°마다 하나씩인 MGRS 위도대. X는 72°~84°로 12°다.
+const BANDS: &[u8; 20] = b"CDEFGHJKLMNPQRSTUVWX";
+// 100km 격자 글자. 열은 구역마다 세 묶음을 돌아가며 쓰고, 행은 짝수 구역에서 다섯 칸 밀린다.
+const COLUMNS: [&[u8; 8]; 3] = [b"ABCDEFGH", b"JKLMNPQR", b"STUVWXYZ"];
+const ROWS: &[u8; 20] = b"ABCDEFGHJKLMNPQRSTUV";
+const SQUARE: f64 = 100_000.0;
+/// MGRS 숫자 자리 수의 최댓값. 5자리는 1m 칸이다.
+pub const MAX_MGRS_PRECISION: usize = 5;
+
+/// 적도 기준 반구
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hemisphere {
+    North,
+    South,
+}
+
+/// UTM 좌표. 남반구의 북향에는 10,000km가 더해져 있다.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Utm {
+    /// 구역 번호 (1~60)
+    pub zone: u8,
+    pub hemisphere: Hemisphere,
+    /// 동향 (m)
+    pub easting: f64,
+    /// 북향 (m)
+    pub northing: f64,
+}
+
+impl Utm {
+    /// 좌표가 속한 구역의 UTM 좌표. 노르웨이와 스발바르의 예외 구역을 따른다.
+    ///
+    /// 위도가 -80°~84° 밖이면 `WeimError::InvalidPayload`
+    pub fn from_coordinate(coordinate: impl Coordinate) -> Result<Utm, WeimError> {
+        let (latitude, longitude) = (coordinate.latitude(), normalize_longitude(coordinate.longitude()));
+        if !(MIN_LATITUDE..=MAX_LATITUDE).contains(&latitude) || !longitude.is_finite() {
+            return Err(WeimError::InvalidPayload(tr!(
+                "UTM은 위도 {}°~{}°만 다룹니다: {}",
+                "UTM only covers latitudes {}° to {}°: {}",
+                MIN_LATITUDE,
+                MAX_LATITUDE,
+                latitude
+            )));
+        }
+        Ok(Utm::in_zone(latitude, longitude, zone(latitude, longitude)))
+    }
+
+    fn in_zone(latitude: f64, longitude: f64, zone: u8) -> Utm {
+        let hemisphere = if latitude < 0.0 { Hemisphere::South } else { Hemisphere::North };
+        let (easting, northing) = projection(zone, hemisphere).forward(latitude, longitude);
+        Utm { zone, hemisphere, easting, northing }
+    }
+
+    /// WGS84 `(위도, 경도)`로 되돌린다.
+    pub fn coordinate(&self) -> (f64, f64) {
+        let (latitude, longitude) = projection(self.zone, self.hemisphere).inverse(self.easting, self.northing);
+        (latitude, normalize_longitude(longitude))
+    }
+
+    /// `precision`자리(0~5) 숫자로 된 MGRS 표기. 예: `52S CG 21996 49401`
+    ///
+    /// 숫자는 반올림하지 않고 잘라서, 표기가 가리키는 칸 안에 원래 위치가 들어 있다.
+    pub fn to_mgrs(&self, precision: usize) -> String {
+        let precision = precision.min(MAX_MGRS_PRECISION);
+        let (latitude, _) = self.coordinate();
+        let column = (self.easting / SQUARE).floor() as usize;
+        let row = (self.northing / SQUARE).floor() as usize;
+        let letters = [
+            COLUMNS[(self.zone as usize - 1) % 3][(column.clamp(1, 8) - 1) % 8] as char,
+            ROWS[(row + row_offset(self.zone)) % 20] as char,
+        ];
+
+        let mut mgrs = format!("{}{} {}{}", self.zone, band(latitude), letters[0], letters[1]);
+        if precision > 0 {
+            let divisor = 10f64.powi((MAX_MGRS_PRECISION - precision) as i32);
+            let easting = ((self.easting % SQUARE) / divisor).floor() as u32;
+            let northing = ((self.northing % SQUARE) / divisor).floor() as u32;
+            mgrs.push_str(&format!(" {:0width$} {:0width$}", easting, northing, width = precision));
+        }
+        mgrs
+    }
+
+    /// MGRS 표기가 가리키는 칸의 중심. 띄어쓰기와 대소문자는 가리지 않는다.
+    pub fn from_mgrs(mgrs: &str) -> Result<Utm, WeimError> {
+        square(mgrs).map(|(utm, _)| utm)
+    }
+}
+
+// MGRS 칸의 중심과 한 변의 길이 (m)
+fn square(mgrs: &str) -> Result<(Utm, f64), WeimError> {
+    let invalid = || WeimError::InvalidPayload(tr!("올바른 MGRS 좌표가 아닙니다: {:?}", "not a valid MGRS coordinate: {:?}", mgrs));
+    let compact: String = mgrs.chars().filter(|c| !c.is_whitespace()).collect::<String>().to_ascii_uppercase();
+
+    let digits = compact.chars().take_while(char::is_ascii_digit).count();
+    let zone: u8 = compact[..digits].parse().map_err(|_| invalid())?;
+    let rest = &compact.as_bytes()[digits..];
+    if !(1..=60).contains(&zone) || rest.len() < 3 {
+        return Err(invalid());
+    }
+    let band = BANDS.iter().position(|&b| b == rest[0]).ok_or_else(invalid)?;
+    let column = COLUMNS[(zone as usize - 1) % 3].iter().position(|&b| b == rest[1]).ok_or_else(invalid)?;
+    let row = ROWS.iter().position(|&b| b == rest[2]).ok_or_else(invalid)?;
+
+    // 남은 숫자는 동향과 북향을 같은 자리 수로 반씩 나눠 쓴다
+    let numbers = &compact[digits + 3..];
+    if numbers.len() % 2 == 1 || numbers.len() > 2 * MAX_MGRS_PRECISION || !numbers.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(invalid());
+    }
+    let precision = numbers.len() / 2;
+    let size = 10f64.powi((MAX_MGRS_PRECISION - precision) as i32);
+    let offset = |digits: &str| digits.parse::<f64>().unwrap_or(0.0) * size + size / 2.0;
+    let (easting_offset, northing_offset) = (offset(&numbers[..precision]), offset(&numbers[precision..]));
+
+    // 행 글자는 2,000km마다 되풀이되므로, 위도대 아래쪽 끝보다 북쪽인 가장 가까운 칸을 고른다
+    let hemisphere = if band < 10 { Hemisphere::South } else { Hemisphere::North };
+    let bottom = MIN_LATITUDE + 8.0 * band as f64;
+    let band_northing = (Utm::in_zone(bottom, central_meridian(zone), zone).northing / SQUARE).floor() * SQUARE;
+    let mut northing = ((row + 20 - row_offset(zone)) % 20) as f64 * SQUARE;
+    while northing < band_northing {
+        northing += 20.0 * SQUARE;
+    }
+
+    Ok((Utm { zone, hemisphere, easting: (column + 1) as f64 * SQUARE + easting_offset, northing: northing + northing_offset }, size))
+}
+
+/// `52N 321996 4149401`처럼 구역, 반구(N/S), 동향, 북향을 m 단위로 쓴다.
+impl fmt::Display for Utm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let hemisphere = if self.hemisphere == Hemisphere::North { 'N' } else { 'S' };
+        write!(f, "{}{} {:.0} {:.0}", self.zone, hemisphere, self.easting, self.northing)
+    }
+}
+
+/// `Display`와 같은 형식을 읽는다. 구역과 반구 사이는 띄어 써도 된다.
+impl FromStr for Utm {
+    type Err = WeimError;
+
+    fn from_str(s: &str) -> Result<Utm, WeimError> {
+        let invalid = || WeimError::InvalidPayload(tr!("올바른 UTM 좌표가 아닙니다: {:?}", "not a valid UTM coordinate: {:?}", s));
+        let parts: Vec<&str> = s.split_whitespace().collect();
+        let (zone, numbers) = match parts.as_slice() {
+            [zone, easting, northing] => (zone.to_string(), [*easting, *northing]),
+            [zone, hemisphere, easting, northing] => (format!("{}{}", zone, hemisphere), [*easting, *northing]),
+            _ => return Err(invalid()),
+        };
+        let hemisphere = match zone.chars().last().map(|c| c.to_ascii_uppercase()) {
+            Some('N') => Hemisphere::North,
+            Some('S') => Hemisphere::South,
+            _ => return Err(invalid()),
+        };
+        let zone: u8 = zone[..zone.len() - 1].parse().map_err(|_| invalid())?;
+        let easting: f64 = numbers[0].parse().map_err(|_| invalid())?;
+        let northing: f64 = numbers[1].parse().map_err(|_| invalid())?;
+        if !(1..=60).contains(&zone) || !easting.is_finite() || !northing.is_finite() {
+            return Err(invalid());
+        }
+        Ok(Utm { zone, hemisphere, easting, northing })
+    }
+}
+
+// 구역의 중앙 자오선을 기준으로 한 투영
+fn projection(zone: u8, hemisphere: Hemisphere) -> TransverseMercator {
+    TransverseMercator {
+        ellipsoid: WGS84,
+        origin_latitude: 0.0,
+        central_meridian: central_meridian(zone),
+        scale: SCALE,
+        false_easting: FALSE_EASTING,
+        false_northing: if hemisphere == Hemisphere::South { FALSE_NORTHING } else { 0.0 },
+    }
+}
+
+fn zone(latitude: f64, longitude: f64) -> u8 {
+    let zone = (((longitude + 180.0) / 6.0).floor() as u8).min(59) + 1;
+    // 노르웨이 남서부는 32구역이 넓어진다
+    if (56.0..64.0).contains(&latitude) && (3.0..12.0).contains(&longitude) {
+        return 32;
+    }
+    // 스발바르는 31, 33, 35, 37구역만 쓴다
+    if latitude >= 72.0 && (0.0..42.0).contains(&longitude) {
+        return match longitude {
+            l if l < 9.0 => 31,
+            l if l < 21.0 => 33,
+            l if l < 33.0 => 35,
+            _ => 37,
+        };
+    }
+    zone
+}
+
+fn central_meridian(zone: u8) -> f64 {
+    zone as f64 * 6.0 - 183.0
+}
+
+fn band(latitude: f64) -> char {
+    BANDS[(((latitude - MIN_LATITUDE) / 8.0).floor() as usize).min(BANDS.len() - 1)] as char
+}
+
+fn row_offset(zone: u8) -> usize {
+    if zone.is_multiple_of(2) { 5 } else { 0 }
+}
+
+impl Location {
+    /// 이 위치의 UTM 좌표
+    pub fn utm(&self) -> Result<Utm, WeimError> {
+        Utm::from_coordinate(self)
+    }
+
+    /// 이 위치의 `precision`자리 MGRS 표기. 5자리는 1m, 4자리는 10m 칸이다.
+    pub fn mgrs(&self, precision: usize) -> Result<String, WeimError> {
+        Ok(self.utm()?.to_mgrs(precision))
+    }
+
+    /// UTM 좌표의 위치. 정확도는 알 수 없어 0으로 둔다.
+    pub fn from_utm(utm: &Utm) -> Location {
+        let (latitude, longitude) = utm.coordinate();
+        Location { latitude, longitude, ..Location::default() }
+    }
+
+    /// MGRS 칸의 중심 위치. 정확도는 중심에서 칸 모서리까지의 거리다.
+    pub fn from_mgrs(mgrs: &str) -> Result<Location, WeimError> {
+        let (utm, size) = square(mgrs)?;
+        Ok(Location { accuracy: size * std::f64::consts::FRAC_1_SQRT_2, ..Location::from_utm(&utm) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geo::haversine;
+
+    const EIFFEL_TOWER: (f64, f64) = (48.8583, 2.2945);
+
+    #[test]
+    fn projects_eiffel_tower() {
+        let utm = Utm::from_coordinate(EIFFEL_TOWER).unwrap();
+        assert_eq!((utm.zone, utm.hemisphere), (31, Hemisphere::North));
+        assert!((utm.easting - 448_251.0).abs() < 1.0, "{}", utm.easting);
+        assert!((utm.northing - 5_411_943.0).abs() < 1.0, "{}", utm.northing);
+        assert!(haversine(utm.coordinate(), EIFFEL_TOWER) < 0.01);
+    }
+
+    #[test]
+    fn round_trips_eiffel_tower_through_mgrs() {
+        let utm = Utm::from_coordinate(EIFFEL_TOWER).unwrap();
+        assert_eq!(utm.to_mgrs(5), "31U DQ 48251 11943");
+        assert_eq!(utm.to_mgrs(1), "31U DQ 4 1");
+        assert_eq!(utm.to_mgrs(0), "31U DQ");
+        let square = Utm::from_mgrs("31udq4825111943").unwrap();
+        assert_eq!(square.zone, 31);
+        assert!(haversine(square.coordinate(), EIFFEL_TOWER) < 1.5);
+    }
+
+    #[test]
+    fn formats_and_parses_utm() {
+        let utm = Utm::from_coordinate((-33.8568, 151.2153)).unwrap();
+        assert_eq!(utm.hemisphere, Hemisphere::South);
+        let parsed: Utm = utm.to_string().parse().unwrap();
+        assert_eq!((parsed.zone, parsed.hemisphere), (utm.zone, utm.hemisphere));
+        assert!((parsed.easting - utm.easting).abs() <= 0.5 && (parsed.northing - utm.northing).abs() <= 0.5);
+        assert_eq!("31 N 448251 5411943".parse::<Utm>().unwrap().zone, 31);
+        assert!("61N 448251 5411943".parse::<Utm>().is_err());
+    }
+
+    #[test]
+    fn follows_zone_exceptions_and_limits() {
+        // 베르겐은 31구역이 아니라 32구역
+        assert_eq!(Utm::from_coordinate((60.39, 5.32)).unwrap().zone, 32);
+        assert_eq!(Utm::from_coordinate((78.22, 15.65)).unwrap().zone, 33);
+        assert!(Utm::from_coordinate((85.0, 0.0)).is_err());
+        assert!(Utm::from_mgrs("31I DQ 48251 11943").is_err());
+    }
+}
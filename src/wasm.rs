@@ -0,0 +1,104 @@
+use std::time::Duration;
+use chrono::DateTime;
+use js_sys::{Function, Promise};
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Position, PositionError, PositionOptions};
+
+use crate::{Location, WeimError};
+
+/// wasm32로 빌드해 브라우저 안에서 돌 때 `navigator.geolocation`으로 바로 위치를 받는다.
+///
+/// 서버를 열거나 브라우저를 띄우지 않고, 이 페이지에서 사용자에게 위치 권한을 묻는다.
+/// 같은 시계로 재므로 `Location::device_timestamp`는 없다.
+#[derive(Debug, Clone)]
+pub struct Geolocation {
+    high_accuracy: bool,
+    position_timeout: Duration,
+    maximum_age: Duration,
+}
+
+impl Default for Geolocation {
+    fn default() -> Self {
+        Geolocation { high_accuracy: true, position_timeout: Duration::from_secs(5), maximum_age: Duration::ZERO }
+    }
+}
+
+impl Geolocation {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 고정밀 위치를 요청할지 여부 (기본값 true)
+    pub fn high_accuracy(mut self, enabled: bool) -> Self {
+        self.high_accuracy = enabled;
+        self
+    }
+
+    /// 측정 한 번에 쓸 최대 시간, Geolocation API의 `timeout` (기본값 5초)
+    pub fn position_timeout(mut self, timeout: Duration) -> Self {
+        self.position_timeout = timeout;
+        self
+    }
+
+    /// 이 시간 안에 잰 위치가 있으면 새로 재지 않고 쓴다, Geolocation API의 `maximumAge` (기본값 0 = 항상 새로 잰다)
+    pub fn maximum_age(mut self, age: Duration) -> Self {
+        self.maximum_age = age;
+        self
+    }
+
+    /// 위치를 한 번 잰다. 권한을 거절하면 `WeimError::PermissionDenied`
+    pub async fn locate(&self) -> Result<Location, WeimError> {
+        let geolocation = web_sys::window()
+            .and_then(|window| window.navigator().geolocation().ok())
+            .ok_or(WeimError::Unsupported)?;
+        let options = PositionOptions::new();
+        options.set_enable_high_accuracy(self.high_accuracy);
+        options.set_timeout(millis(self.position_timeout));
+        options.set_maximum_age(millis(self.maximum_age));
+        // 성공하면 Position, 실패하면 PositionError로 끝나는 Promise
+        let promise = Promise::new(&mut |resolve: Function, reject: Function| {
+            if let Err(e) = geolocation.get_current_position_with_error_callback_and_options(&resolve, Some(&reject), &options) {
+                reject.call1(&JsValue::NULL, &e).ok();
+            }
+        });
+        match JsFuture::from(promise).await {
+            Ok(position) => Ok(location(position.unchecked_into())),
+            Err(error) => Err(position_error(error)),
+        }
+    }
+}
+
+// 브라우저 클래스 이름은 GeolocationPosition이라 형 검사 없이 속성만 읽는다
+fn location(position: Position) -> Location {
+    let coords = position.coords();
+    Location {
+        latitude: coords.latitude(),
+        longitude: coords.longitude(),
+        accuracy: coords.accuracy(),
+        altitude: coords.altitude(),
+        altitude_accuracy: coords.altitude_accuracy(),
+        speed: coords.speed(),
+        heading: coords.heading(),
+        timestamp: DateTime::from_timestamp_millis(position.timestamp() as i64).unwrap_or_default(),
+        ..Location::default()
+    }
+}
+
+// 페이지가 `/error`로 보내는 것과 같은 코드. 숫자 코드가 없으면 API를 부르지도 못한 것이다
+fn position_error(error: JsValue) -> WeimError {
+    let code = js_sys::Reflect::get(&error, &JsValue::from_str("code")).ok().and_then(|code| code.as_f64());
+    let Some(code) = code else {
+        return WeimError::PositionUnavailable(error.as_string().unwrap_or_else(|| format!("{:?}", error)));
+    };
+    let error: PositionError = error.unchecked_into();
+    match code as u16 {
+        1 => WeimError::PermissionDenied,
+        3 => WeimError::Timeout,
+        _ => WeimError::PositionUnavailable(error.message()),
+    }
+}
+
+fn millis(duration: Duration) -> u32 {
+    duration.as_millis().min(u32::MAX as u128) as u32
+}
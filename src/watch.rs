@@ -0,0 +1,130 @@
+use std::net::SocketAddr;
+use std::sync::mpsc::{self, Receiver};
+#[cfg(feature = "python")]
+use std::sync::mpsc::RecvTimeoutError;
+use std::thread;
+#[cfg(feature = "python")]
+use std::time::Duration;
+
+use crate::filter::{FilteredLocation, KalmanFilter};
+use crate::locator::Mode;
+use crate::{CancellationToken, DeviceId, Geofenced, Geofences, Location, MotionDetector, Weim, WeimError, WithMotion};
+
+impl Weim {
+    /// 서버를 열고 브라우저가 보내는 위치를 계속 받는다.
+    ///
+    /// 서버는 백그라운드 스레드에서 돌고, 돌려받은 `Watch`를 버리면 멈춘다.
+    /// `timeout`을 설정했으면 위치 사이의 최대 간격으로 쓰인다.
+    pub fn watch(&self) -> Result<Watch, WeimError> {
+        let (rx, token, addr) = self.spawn_watch(|_, location| location)?;
+        Ok(Watch { rx, token, addr })
+    }
+
+    /// 서버를 열고 백그라운드 스레드에서 받은 위치를 `map`으로 바꿔 채널로 보낸다.
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn spawn_watch<T: Send + 'static>(
+        &self,
+        mut map: impl FnMut(DeviceId, Location) -> T + Send + 'static,
+    ) -> Result<(Receiver<Result<T, WeimError>>, CancellationToken, SocketAddr), WeimError> {
+        let (server, browser) = self.open()?;
+        let addr = server.server_addr().to_ip().ok_or(WeimError::ServerClosed)?;
+
+        let token = CancellationToken::new();
+        let (tx, rx) = mpsc::channel();
+        let weim = self.clone();
+        let thread_token = token.clone();
+        thread::spawn(move || {
+            let _browser = browser;
+            let result = weim.run(&server, &thread_token, Mode::Watch, |device, location| {
+                tx.send(Ok(map(device, location))).is_ok()
+            });
+            if let Err(e) = result {
+                tx.send(Err(e)).ok();
+            }
+        });
+
+        Ok((rx, token, addr))
+    }
+}
+
+/// `Weim::watch()`가 돌려주는 위치 스트림
+///
+/// 오류가 나면 그 오류를 마지막으로 돌려주고 끝난다.
+#[derive(Debug)]
+pub struct Watch {
+    rx: Receiver<Result<Location, WeimError>>,
+    token: CancellationToken,
+    addr: SocketAddr,
+}
+
+impl Watch {
+    /// 서버가 열린 주소
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// 서버를 멈추는 토큰. 다른 스레드에서 스트림을 끝낼 때 쓴다.
+    pub fn cancellation_token(&self) -> CancellationToken {
+        self.token.clone()
+    }
+
+    /// 칼만 필터로 다듬은 위치를 돌려주는 스트림으로 바꾼다.
+    pub fn filtered(self, filter: KalmanFilter) -> Filtered<Self> {
+        Filtered::new(self, filter)
+    }
+
+    /// 위치 대신 지오펜스 진입, 이탈, 머무름 이벤트를 돌려주는 스트림으로 바꾼다.
+    pub fn geofenced(self, fences: Geofences) -> Geofenced<Self> {
+        Geofenced::new(self, fences)
+    }
+
+    /// 위치와 함께 멈춤, 걷기, 차로 이동 상태가 바뀐 사건을 돌려주는 스트림으로 바꾼다.
+    pub fn with_motion(self, detector: MotionDetector) -> WithMotion<Self> {
+        WithMotion::new(self, detector)
+    }
+
+    // 기다리는 틈틈이 다른 일(파이썬의 중단 신호 확인 등)을 할 수 있게 `timeout`까지만 기다린다
+    #[cfg(feature = "python")]
+    pub(crate) fn recv_timeout(&mut self, timeout: Duration) -> Result<Result<Location, WeimError>, RecvTimeoutError> {
+        self.rx.recv_timeout(timeout)
+    }
+}
+
+impl Iterator for Watch {
+    type Item = Result<Location, WeimError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.rx.recv().ok()
+    }
+}
+
+impl Drop for Watch {
+    fn drop(&mut self) {
+        self.token.cancel();
+    }
+}
+
+/// 위치 스트림의 각 위치를 칼만 필터에 통과시키는 반복자
+#[derive(Debug)]
+pub struct Filtered<I> {
+    inner: I,
+    filter: KalmanFilter,
+}
+
+impl<I> Filtered<I> {
+    /// 어떤 위치 스트림에든 필터를 붙인다.
+    pub fn new(inner: I, filter: KalmanFilter) -> Self {
+        Filtered { inner, filter }
+    }
+}
+
+impl<I> Iterator for Filtered<I>
+where
+    I: Iterator<Item = Result<Location, WeimError>>,
+{
+    type Item = Result<FilteredLocation, WeimError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.inner.next()?.map(|location| self.filter.update(&location)))
+    }
+}
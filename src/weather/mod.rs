@@ -0,0 +1,92 @@
+//! 위치의 현재 날씨
+//!
+//! 날씨를 가져오는 `WeatherSource`와 `OpenMeteo`는 `weather` 기능이 필요하다.
+
+#[cfg(feature = "weather")]
+mod open_meteo;
+
+#[cfg(feature = "weather")]
+pub use open_meteo::OpenMeteo;
+
+#[cfg(feature = "weather")]
+use std::fmt;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "weather")]
+use crate::console::{info, warning};
+use crate::i18n::pick;
+#[cfg(feature = "weather")]
+use crate::i18n::tr;
+#[cfg(feature = "weather")]
+use crate::{Location, WeimError};
+
+/// 관측한 현재 날씨
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Weather {
+    /// 기온 (°C)
+    pub temperature: f64,
+    /// 상대 습도 (%)
+    pub humidity: Option<f64>,
+    /// 풍속 (m/s)
+    pub wind_speed: f64,
+    /// 바람이 불어오는 방향 (°, 북쪽 기준 시계 방향)
+    pub wind_direction: Option<f64>,
+    /// WMO 날씨 코드
+    pub code: u8,
+    /// 날씨 코드의 설명 (예: "맑음")
+    pub conditions: String,
+    /// 관측 시각
+    #[serde(deserialize_with = "crate::location::timestamp")]
+    pub observed_at: DateTime<Utc>,
+}
+
+/// WMO 날씨 코드를 현재 언어로 설명한다.
+pub fn describe(code: u8) -> &'static str {
+    let (korean, english) = match code {
+        0 => ("맑음", "clear sky"),
+        1 => ("대체로 맑음", "mainly clear"),
+        2 => ("구름 조금", "partly cloudy"),
+        3 => ("흐림", "overcast"),
+        45 | 48 => ("안개", "fog"),
+        51 | 53 | 55 => ("이슬비", "drizzle"),
+        56 | 57 => ("어는 이슬비", "freezing drizzle"),
+        61 | 63 | 65 => ("비", "rain"),
+        66 | 67 => ("어는 비", "freezing rain"),
+        71 | 73 | 75 | 77 => ("눈", "snow"),
+        80..=82 => ("소나기", "rain showers"),
+        85 | 86 => ("소낙눈", "snow showers"),
+        95 => ("뇌우", "thunderstorm"),
+        96 | 99 => ("우박을 동반한 뇌우", "thunderstorm with hail"),
+        _ => ("알 수 없음", "unknown"),
+    };
+    pick(korean, english)
+}
+
+/// 날씨 서비스. 다른 서비스를 쓰려면 이 트레이트를 구현한다. (`weather` 기능 필요)
+#[cfg(feature = "weather")]
+pub trait WeatherSource: Send + Sync + fmt::Debug {
+    /// 좌표의 현재 날씨
+    fn current(&self, latitude: f64, longitude: f64) -> Result<Weather, WeimError>;
+}
+
+/// 받은 위치에 날씨를 붙이고 출력한다. 실패해도 위치는 그대로 둔다.
+#[cfg(feature = "weather")]
+pub(crate) fn annotate(source: &dyn WeatherSource, location: &mut Location) {
+    match source.current(location.latitude, location.longitude) {
+        Ok(weather) => {
+            info!(
+                "{}",
+                tr!(
+                    "🌤️ 날씨: {} {:.1}°C, 바람 {:.1}m/s",
+                    "🌤️ Weather: {} {:.1}°C, wind {:.1}m/s",
+                    weather.conditions,
+                    weather.temperature,
+                    weather.wind_speed
+                )
+            );
+            location.weather = Some(weather);
+        }
+        Err(e) => warning!("{}", tr!("⚠️ 날씨를 가져오지 못했습니다: {}", "⚠️ Could not fetch the weather: {}", e)),
+    }
+}
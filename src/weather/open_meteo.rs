@@ -0,0 +1,116 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use chrono::DateTime;
+use serde::Deserialize;
+
+use super::{describe, Weather, WeatherSource};
+use crate::geo::haversine;
+use crate::{http, WeimError};
+
+const DEFAULT_ENDPOINT: &str = "https://api.open-meteo.com/v1/forecast";
+// Open-Meteo의 현재 날씨는 15분마다 바뀐다
+const DEFAULT_MAX_AGE: Duration = Duration::from_secs(10 * 60);
+// 이 거리 안에서는 같은 날씨로 본다 (m)
+const REUSE_DISTANCE: f64 = 1000.0;
+const CURRENT: &str = "temperature_2m,relative_humidity_2m,weather_code,wind_speed_10m,wind_direction_10m";
+
+#[derive(Debug, Deserialize)]
+struct ForecastResponse {
+    current: Current,
+}
+
+#[derive(Debug, Deserialize)]
+struct Current {
+    // timeformat=unixtime (초)
+    time: i64,
+    temperature_2m: f64,
+    relative_humidity_2m: Option<f64>,
+    weather_code: u8,
+    wind_speed_10m: f64,
+    wind_direction_10m: Option<f64>,
+}
+
+impl From<Current> for Weather {
+    fn from(current: Current) -> Self {
+        Weather {
+            temperature: current.temperature_2m,
+            humidity: current.relative_humidity_2m,
+            wind_speed: current.wind_speed_10m,
+            wind_direction: current.wind_direction_10m,
+            code: current.weather_code,
+            conditions: describe(current.weather_code).to_string(),
+            observed_at: DateTime::from_timestamp(current.time, 0).unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Cached {
+    fetched_at: Instant,
+    latitude: f64,
+    longitude: f64,
+    weather: Weather,
+}
+
+/// Open-Meteo 날씨 API (API 키 필요 없음)
+///
+/// 실시간 추적처럼 위치가 자주 들어와도 가까운 곳의 최근 날씨는 다시 묻지 않는다.
+/// 복제한 값은 마지막 결과를 함께 쓴다.
+#[derive(Debug, Clone)]
+pub struct OpenMeteo {
+    endpoint: String,
+    max_age: Duration,
+    last: Arc<Mutex<Option<Cached>>>,
+}
+
+impl Default for OpenMeteo {
+    fn default() -> Self {
+        OpenMeteo {
+            endpoint: DEFAULT_ENDPOINT.to_string(),
+            max_age: DEFAULT_MAX_AGE,
+            last: Arc::default(),
+        }
+    }
+}
+
+impl OpenMeteo {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// API 주소 (기본값 api.open-meteo.com/v1/forecast)
+    pub fn endpoint(mut self, url: impl Into<String>) -> Self {
+        self.endpoint = url.into();
+        self
+    }
+
+    /// 1km 안에서 받은 날씨를 다시 쓸 시간 (기본값 10분). 0이면 매번 묻는다.
+    pub fn max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = max_age;
+        self
+    }
+}
+
+impl WeatherSource for OpenMeteo {
+    fn current(&self, latitude: f64, longitude: f64) -> Result<Weather, WeimError> {
+        let mut last = self.last.lock().unwrap();
+        if let Some(cached) = last.as_ref() {
+            let fresh = cached.fetched_at.elapsed() < self.max_age;
+            if fresh && haversine((cached.latitude, cached.longitude), (latitude, longitude)) < REUSE_DISTANCE {
+                return Ok(cached.weather.clone());
+            }
+        }
+
+        let query = [
+            ("latitude", latitude.to_string()),
+            ("longitude", longitude.to_string()),
+            ("current", CURRENT.to_string()),
+            ("wind_speed_unit", "ms".to_string()),
+            ("timeformat", "unixtime".to_string()),
+        ];
+        let response: ForecastResponse = http::get_json(&self.endpoint, &query)?;
+        let weather = Weather::from(response.current);
+        *last = Some(Cached { fetched_at: Instant::now(), latitude, longitude, weather: weather.clone() });
+        Ok(weather)
+    }
+}
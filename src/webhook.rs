@@ -0,0 +1,280 @@
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use hmac::{Hmac, KeyInit, Mac};
+use sha2::Sha256;
+
+use crate::console::warning;
+use crate::i18n::{pick, tr};
+use crate::{http, DeviceId, Location};
+
+const DEFAULT_CAPACITY: usize = 1000;
+const DEFAULT_MAX_ATTEMPTS: u32 = 8;
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(5 * 60);
+// 마지막 `Webhook`을 버린 뒤 남은 전송을 마저 보낼 시간
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(5);
+const SIGNATURE_HEADER: &str = "X-Weim-Signature";
+const DEVICE_HEADER: &str = "X-Weim-Device";
+
+// 한 주소로 보낼 위치 하나
+#[derive(Debug)]
+struct Delivery {
+    url: String,
+    device: String,
+    body: String,
+    attempts: u32,
+    due: Instant,
+}
+
+#[derive(Debug, Default)]
+struct Queue {
+    deliveries: VecDeque<Delivery>,
+    // 지금 보내는 중인 요청 수
+    in_flight: usize,
+    worker: bool,
+    // 마지막 `Webhook`을 버렸으면 이때까지만 보낸다
+    closing: Option<Instant>,
+}
+
+#[derive(Debug, Default)]
+struct Shared {
+    queue: Mutex<Queue>,
+    changed: Condvar,
+    // 실패한 전송 시도 수. 다시 시도할 것도 센다
+    failures: AtomicU64,
+}
+
+impl Shared {
+    // 남은 전송을 `grace` 동안 마저 보내게 하고 전송 스레드가 끝나기를 기다린다. 그래도 남은 것은 버린다
+    fn close(&self, grace: Duration) {
+        let deadline = Instant::now() + grace;
+        let mut queue = self.queue.lock().unwrap();
+        queue.closing = Some(deadline);
+        self.changed.notify_all();
+        while queue.worker {
+            let left = deadline.saturating_duration_since(Instant::now());
+            if left.is_zero() {
+                break;
+            }
+            queue = self.changed.wait_timeout(queue, left).unwrap().0;
+        }
+        let lost = queue.deliveries.len() + queue.in_flight;
+        if lost > 0 {
+            warning!("{}", tr!("⚠️ 보내지 못한 웹훅 전송 {}개를 버렸습니다.", "⚠️ Dropped {} webhook deliveries that were not sent.", lost));
+        }
+    }
+}
+
+// 복제한 `Webhook`들이 함께 가진다. 마지막 것이 버려지면 전송 스레드를 끝낸다
+#[derive(Debug, Default)]
+struct Handle {
+    shared: Arc<Shared>,
+}
+
+impl Drop for Handle {
+    fn drop(&mut self) {
+        self.shared.close(SHUTDOWN_GRACE);
+    }
+}
+
+// 전송 스레드가 가지는 설정. `Webhook`을 들고 있으면 마지막 값이 버려지지 않는다
+struct Worker {
+    shared: Arc<Shared>,
+    secret: Option<Vec<u8>>,
+    capacity: usize,
+    max_attempts: u32,
+}
+
+/// 받은 위치를 JSON으로 웹훅 주소에 POST하는 전송기 (`webhook` 기능 필요)
+///
+/// 전송은 백그라운드 스레드에서 하므로 위치를 받는 흐름을 막지 않는다. 실패한 전송은
+/// 지수적으로 늘어나는 간격을 두고 다시 시도하고, 대기열이 차면 가장 오래된 것부터 버린다.
+/// 서명 키를 주면 본문의 HMAC-SHA256을 `X-Weim-Signature: sha256=<hex>` 헤더로 붙인다.
+/// 복제한 값은 대기열을 함께 쓴다. 마지막 복제본을 버리면 남은 전송을 최대 5초 동안 마저 보내고
+/// 전송 스레드를 끝낸다. 그때까지 보내지 못한 것은 경고를 남기고 버린다.
+#[derive(Debug, Clone)]
+pub struct Webhook {
+    urls: Vec<String>,
+    secret: Option<Vec<u8>>,
+    capacity: usize,
+    max_attempts: u32,
+    handle: Arc<Handle>,
+}
+
+impl Webhook {
+    /// 위치를 받을 주소
+    pub fn new(url: impl Into<String>) -> Self {
+        Webhook {
+            urls: vec![url.into()],
+            secret: None,
+            capacity: DEFAULT_CAPACITY,
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            handle: Arc::default(),
+        }
+    }
+
+    /// 같은 위치를 보낼 주소를 더한다.
+    pub fn url(mut self, url: impl Into<String>) -> Self {
+        self.urls.push(url.into());
+        self
+    }
+
+    /// 본문을 서명할 HMAC 키 (기본값 없음 = 서명하지 않음)
+    pub fn secret(mut self, key: impl Into<Vec<u8>>) -> Self {
+        self.secret = Some(key.into());
+        self
+    }
+
+    /// 대기열에 둘 최대 전송 수 (기본값 1000)
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(1);
+        self
+    }
+
+    /// 한 전송을 포기하기 전까지의 최대 시도 횟수 (기본값 8)
+    pub fn max_attempts(mut self, attempts: u32) -> Self {
+        self.max_attempts = attempts.max(1);
+        self
+    }
+
+    /// 아직 보내지 못한 전송 수
+    pub fn pending(&self) -> usize {
+        let queue = self.handle.shared.queue.lock().unwrap();
+        queue.deliveries.len() + queue.in_flight
+    }
+
+    /// 지금까지 실패한 전송 시도 수. 다시 시도해서 보낸 것도 센다.
+    pub fn failures(&self) -> u64 {
+        self.handle.shared.failures.load(Ordering::Relaxed)
+    }
+
+    /// 대기열이 빌 때까지 최대 `timeout` 동안 기다린다. 다 보냈으면 true
+    pub fn flush(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        let mut queue = self.handle.shared.queue.lock().unwrap();
+        while !queue.deliveries.is_empty() || queue.in_flight > 0 {
+            let left = deadline.saturating_duration_since(Instant::now());
+            if left.is_zero() {
+                return false;
+            }
+            queue = self.handle.shared.changed.wait_timeout(queue, left).unwrap().0;
+        }
+        true
+    }
+
+    /// 위치를 대기열에 넣는다. 바로 돌아온다.
+    pub fn send(&self, device: &DeviceId, location: &Location) {
+        let Ok(body) = serde_json::to_string(location) else {
+            return;
+        };
+
+        let mut queue = self.handle.shared.queue.lock().unwrap();
+        for url in &self.urls {
+            if queue.deliveries.len() >= self.capacity {
+                queue.deliveries.pop_front();
+                warning!("{}", pick("⚠️ 웹훅 대기열이 가득 차서 가장 오래된 위치를 버렸습니다.", "⚠️ Webhook queue is full, dropped the oldest location."));
+            }
+            queue.deliveries.push_back(Delivery {
+                url: url.clone(),
+                device: device.to_string(),
+                body: body.clone(),
+                attempts: 0,
+                due: Instant::now(),
+            });
+        }
+        if !queue.worker {
+            queue.worker = true;
+            let worker = Worker {
+                shared: Arc::clone(&self.handle.shared),
+                secret: self.secret.clone(),
+                capacity: self.capacity,
+                max_attempts: self.max_attempts,
+            };
+            thread::spawn(move || worker.work());
+        }
+        self.handle.shared.changed.notify_all();
+    }
+}
+
+impl Worker {
+    // 때가 된 전송을 하나씩 꺼내 보낸다. 끝내라고 하면 대기열이 비거나 기한이 될 때까지만 보낸다
+    fn work(&self) {
+        let mut queue = self.shared.queue.lock().unwrap();
+        loop {
+            let now = Instant::now();
+            if let Some(deadline) = queue.closing
+                && (queue.deliveries.is_empty() || now >= deadline)
+            {
+                break;
+            }
+            let Some(index) = queue.deliveries.iter().position(|delivery| delivery.due <= now) else {
+                // 다음 재시도 시각까지, 또는 새 위치가 들어올 때까지 잔다
+                let mut wait = queue.deliveries.iter().map(|delivery| delivery.due - now).min();
+                if let Some(deadline) = queue.closing {
+                    wait = wait.map(|wait| wait.min(deadline - now));
+                }
+                queue = match wait {
+                    Some(wait) => self.shared.changed.wait_timeout(queue, wait).unwrap().0,
+                    None => self.shared.changed.wait(queue).unwrap(),
+                };
+                continue;
+            };
+
+            let mut delivery = queue.deliveries.remove(index).unwrap();
+            queue.in_flight += 1;
+            drop(queue);
+
+            let result = self.post(&delivery);
+
+            queue = self.shared.queue.lock().unwrap();
+            queue.in_flight -= 1;
+            if let Err(e) = result {
+                self.shared.failures.fetch_add(1, Ordering::Relaxed);
+                delivery.attempts += 1;
+                if !retryable(&e) || delivery.attempts >= self.max_attempts {
+                    warning!("{}", tr!("⚠️ 웹훅 전송을 포기했습니다 ({}): {}", "⚠️ Gave up delivering the webhook ({}): {}", delivery.url, e));
+                } else if queue.deliveries.len() < self.capacity {
+                    delivery.due = Instant::now() + backoff(delivery.attempts);
+                    queue.deliveries.push_back(delivery);
+                }
+            }
+            self.shared.changed.notify_all();
+        }
+        queue.worker = false;
+        self.shared.changed.notify_all();
+    }
+
+    fn post(&self, delivery: &Delivery) -> Result<(), ureq::Error> {
+        let mut request = http::agent()
+            .post(&delivery.url)
+            .header("Content-Type", "application/json")
+            .header(DEVICE_HEADER, &delivery.device);
+        if let Some(secret) = &self.secret {
+            request = request.header(SIGNATURE_HEADER, format!("sha256={}", sign(secret, &delivery.body)));
+        }
+        request.send(&delivery.body).map(|_| ())
+    }
+}
+
+// 1초, 2초, 4초 ... 최대 5분
+fn backoff(attempts: u32) -> Duration {
+    INITIAL_BACKOFF.saturating_mul(1 << attempts.saturating_sub(1).min(16)).min(MAX_BACKOFF)
+}
+
+// 받는 쪽 문제(4xx)는 다시 보내도 똑같이 실패한다. 429는 예외
+fn retryable(e: &ureq::Error) -> bool {
+    match e {
+        ureq::Error::StatusCode(status) => *status == 429 || *status >= 500,
+        _ => true,
+    }
+}
+
+fn sign(secret: &[u8], body: &str) -> String {
+    // HMAC은 어떤 길이의 키든 받는다
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).expect("HMAC 키 길이");
+    mac.update(body.as_bytes());
+    mac.finalize().into_bytes().iter().map(|byte| format!("{:02x}", byte)).collect()
+}
@@ -0,0 +1,53 @@
+use std::io::{Read, Write};
+use std::net::IpAddr;
+use std::sync::mpsc::Sender;
+use std::sync::Weak;
+use tungstenite::protocol::Role;
+use tungstenite::{Message, WebSocket};
+
+use crate::handler::{Handler, Incoming, Outcome};
+
+// 페이지가 연결이 살아 있는지 묻고 답하는 글
+pub(crate) const PING: &str = "ping";
+pub(crate) const PONG: &str = "pong";
+
+/// 업그레이드 응답에 넣을 `Sec-WebSocket-Accept` 값
+pub(crate) fn accept_key(key: &str) -> String {
+    tungstenite::handshake::derive_accept_key(key.as_bytes())
+}
+
+/// 업그레이드한 연결에서 페이지가 보낸 위치를 `/update`와 똑같이 처리하고,
+/// 서버 루프가 할 일은 `outcomes`로 넘긴다. 연결이 끊기거나 서버가 끝날 때까지 호출한 스레드를 막는다.
+///
+/// 서버가 끝나 `handler`가 사라지면 다음 메시지를 받을 때 연결을 닫는다.
+pub(crate) fn serve(stream: impl Read + Write, client: Option<IpAddr>, handler: Weak<Handler>, outcomes: Sender<Outcome>) {
+    let mut socket = WebSocket::from_raw_socket(stream, Role::Server, None);
+    loop {
+        // ping/close 프레임의 답은 tungstenite가 알아서 보낸다
+        let Ok(message) = socket.read() else {
+            return;
+        };
+        let Some(handler) = handler.upgrade() else {
+            break;
+        };
+        let Message::Text(text) = message else {
+            continue;
+        };
+        if text.as_str() == PING {
+            if socket.send(Message::text(PONG)).is_err() {
+                return;
+            }
+            continue;
+        }
+
+        let (reply, outcome) = handler.handle(&Incoming { client, origin: None, method: "POST", path: "/update", body: text.as_str() });
+        if socket.send(Message::text(reply.body)).is_err() {
+            return;
+        }
+        if !matches!(outcome, Outcome::Continue) && outcomes.send(outcome).is_err() {
+            break;
+        }
+    }
+    socket.close(None).ok();
+    socket.flush().ok();
+}